@@ -0,0 +1,487 @@
+//! # Analog Comparator
+//!
+//! The Analog Comparator (AC) contains a pair of comparators, each comparing
+//! the voltages of a positive and a negative input. The result is available
+//! as a [state](Comparator::state), can raise an interrupt, be routed to an
+//! output pin, or be used as an event generator.
+//!
+//! # Initializing
+//!
+//! The AC should be initialized using the [`Ac::new`] method. It will consume
+//! the AC object generated by the PAC. The individual comparators are then
+//! obtained through the [`Ac::split`] method and configured with
+//! [`Comparator::configure`].
+//!
+//! # Inputs
+//!
+//! The inputs of a comparator are selected with the [`PositiveInput`] and
+//! [`NegativeInput`] multiplexers. The `PINn` inputs correspond to the
+//! `AIN[n]` pins, which must be put in alternate function B
+//! ([`AlternateB`](crate::gpio::AlternateB)). The negative input can also be
+//! connected to ground, the internal bandgap reference, the DAC output or the
+//! VDD scaler, whose output is set with [`Comparator::set_scaler`].
+//!
+//! # Window mode
+//!
+//! The two comparators can be combined into a window comparator with
+//! [`Ac::enable_window`]. The signal to monitor is then connected to both
+//! comparators, and the upper and lower window limits to their other inputs.
+//!
+//! # Example
+//!
+//! ```
+//! let mut ac = Ac::new(peripherals.AC, &mut peripherals.PM, &ac_dig, &ac_ana);
+//! let mut comparators = ac.split();
+//!
+//! // Compare AIN[0] against VDD * 32 / 64
+//! let _ain0 = pins.pa04.into_alternate::<B>();
+//! comparators.comp0.set_scaler(31);
+//! comparators
+//!     .comp0
+//!     .configure(Config::new(PositiveInput::PIN0, NegativeInput::VSCALE));
+//! while !comparators.comp0.is_ready() {}
+//! let above = comparators.comp0.state();
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::pac::{ac, AC};
+use crate::typelevel::Sealed;
+
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+use crate::clock::{AcAnaClock, AcDigClock};
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+use crate::pac::PM;
+
+#[cfg(feature = "min-samd51g")]
+use crate::clock::AcClock;
+#[cfg(feature = "min-samd51g")]
+use crate::pac::MCLK as PM;
+
+#[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+use crate::evsys::{generators, Generator};
+
+/// Digital majority filter applied to the comparator output
+pub use ac::compctrl::FLEN_A as Filter;
+/// Condition setting the comparator interrupt flag
+pub use ac::compctrl::INTSEL_A as InterruptSelection;
+/// Negative input multiplexer selection
+pub use ac::compctrl::MUXNEG_A as NegativeInput;
+/// Positive input multiplexer selection
+pub use ac::compctrl::MUXPOS_A as PositiveInput;
+/// Comparator output routed to the `CMP[n]` pin
+pub use ac::compctrl::OUT_A as Output;
+/// Position of the input signal relative to the window
+pub use ac::statusa::WSTATE0_A as WindowState;
+/// Condition setting the window interrupt flag
+pub use ac::winctrl::WINTSEL0_A as WindowInterrupt;
+
+/// Hysteresis level
+#[cfg(feature = "min-samd51g")]
+pub use ac::compctrl::HYST_A as Hysteresis;
+/// Comparator speed (trade-off between propagation delay and power)
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+pub use ac::compctrl::SPEED_A as Speed;
+
+//==============================================================================
+// CompId
+//==============================================================================
+
+/// Type-level enum of the comparators
+pub trait CompId: Sealed {
+    /// Comparator number
+    const N: usize;
+    /// Event generator of the comparator output
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    type Generator: Generator;
+    /// Instance of the event generator
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    const GENERATOR: Self::Generator;
+}
+
+/// Comparator 0
+pub enum Comp0 {}
+
+impl Sealed for Comp0 {}
+
+impl CompId for Comp0 {
+    const N: usize = 0;
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    type Generator = generators::AcComp0;
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    const GENERATOR: Self::Generator = generators::AcComp0;
+}
+
+/// Comparator 1
+pub enum Comp1 {}
+
+impl Sealed for Comp1 {}
+
+impl CompId for Comp1 {
+    const N: usize = 1;
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    type Generator = generators::AcComp1;
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    const GENERATOR: Self::Generator = generators::AcComp1;
+}
+
+//==============================================================================
+// Config
+//==============================================================================
+
+/// Configuration of a single comparator
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// Positive input
+    pub positive: PositiveInput,
+    /// Negative input
+    pub negative: NegativeInput,
+    /// Swap the positive and negative inputs and invert the output
+    pub swap: bool,
+    /// Only perform a comparison when [started](Comparator::start), instead
+    /// of comparing continuously
+    pub single_shot: bool,
+    /// Enable the hysteresis
+    pub hysteresis: bool,
+    /// Hysteresis level, if enabled
+    #[cfg(feature = "min-samd51g")]
+    pub hysteresis_level: Hysteresis,
+    /// Comparator speed
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    pub speed: Speed,
+    /// Keep the comparator running in standby sleep mode
+    #[cfg(feature = "min-samd51g")]
+    pub run_in_standby: bool,
+    /// Output filter
+    pub filter: Filter,
+    /// Output pin routing. The `CMP[n]` pin must be configured separately.
+    pub output: Output,
+    /// Condition setting the interrupt flag (and generating an event)
+    pub interrupt: InterruptSelection,
+}
+
+impl Config {
+    /// Create a configuration comparing the given inputs continuously, with
+    /// hysteresis and filtering disabled.
+    pub const fn new(positive: PositiveInput, negative: NegativeInput) -> Self {
+        Self {
+            positive,
+            negative,
+            swap: false,
+            single_shot: false,
+            hysteresis: false,
+            #[cfg(feature = "min-samd51g")]
+            hysteresis_level: Hysteresis::HYST50,
+            #[cfg(any(feature = "samd11", feature = "samd21"))]
+            speed: Speed::HIGH,
+            #[cfg(feature = "min-samd51g")]
+            run_in_standby: false,
+            filter: Filter::OFF,
+            output: Output::OFF,
+            interrupt: InterruptSelection::TOGGLE,
+        }
+    }
+}
+
+//==============================================================================
+// Ac
+//==============================================================================
+
+/// Initialized Analog Comparator
+pub struct Ac {
+    ac: AC,
+}
+
+/// Comparators of the [`Ac`], obtained with [`Ac::split`]
+pub struct Comparators {
+    /// Comparator 0
+    pub comp0: Comparator<Comp0>,
+    /// Comparator 1
+    pub comp1: Comparator<Comp1>,
+}
+
+#[inline]
+fn sync(ac: &ac::RegisterBlock) {
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    while ac.statusb.read().syncbusy().bit_is_set() {}
+    #[cfg(feature = "min-samd51g")]
+    while ac.syncbusy.read().bits() != 0 {}
+}
+
+impl Ac {
+    /// Enable the AC bus clock, reset and enable the peripheral.
+    ///
+    /// Both the digital and analog generic clocks of the AC must be
+    /// configured.
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    pub fn new(ac: AC, pm: &mut PM, _dig_clock: &AcDigClock, _ana_clock: &AcAnaClock) -> Self {
+        pm.apbcmask.modify(|_, w| w.ac_().set_bit());
+        Self::reset(ac)
+    }
+
+    /// Enable the AC bus clock, reset and enable the peripheral.
+    ///
+    /// The bias calibration value is loaded from the NVM software calibration
+    /// area.
+    #[cfg(feature = "min-samd51g")]
+    pub fn new(ac: AC, mclk: &mut PM, _clock: &AcClock) -> Self {
+        mclk.apbcmask.modify(|_, w| w.ac_().set_bit());
+        Self::reset(ac)
+    }
+
+    fn reset(ac: AC) -> Self {
+        ac.ctrla.write(|w| w.swrst().set_bit());
+        sync(&ac);
+
+        #[cfg(feature = "min-samd51g")]
+        ac.calib
+            .write(|w| unsafe { w.bias0().bits(crate::calibration::ac_bias_cal()) });
+
+        ac.ctrla.modify(|_, w| w.enable().set_bit());
+        sync(&ac);
+        Self { ac }
+    }
+
+    /// Split the AC into its individual comparators
+    #[inline]
+    pub fn split(&mut self) -> Comparators {
+        Comparators {
+            comp0: Comparator { id: PhantomData },
+            comp1: Comparator { id: PhantomData },
+        }
+    }
+
+    /// Keep the comparators running in standby sleep mode
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    pub fn run_in_standby(&mut self, enabled: bool) {
+        self.ac.ctrla.modify(|_, w| w.runstdby().bit(enabled));
+        sync(&self.ac);
+    }
+
+    /// Combine both comparators into a window comparator
+    pub fn enable_window(&mut self, interrupt: WindowInterrupt) {
+        self.ac.winctrl.write(|w| {
+            w.wen0().set_bit();
+            w.wintsel0().variant(interrupt)
+        });
+        sync(&self.ac);
+    }
+
+    /// Disable the window comparator
+    pub fn disable_window(&mut self) {
+        self.ac.winctrl.write(|w| w.wen0().clear_bit());
+        sync(&self.ac);
+    }
+
+    /// Return the position of the input signal relative to the window, or
+    /// `None` if the window comparator is disabled.
+    pub fn window_state(&self) -> Option<WindowState> {
+        if self.ac.winctrl.read().wen0().bit_is_set() {
+            self.ac.statusa.read().wstate0().variant()
+        } else {
+            None
+        }
+    }
+
+    /// Enable the window interrupt
+    pub fn enable_window_interrupt(&mut self) {
+        self.ac.intenset.write(|w| w.win0().set_bit());
+    }
+
+    /// Disable the window interrupt
+    pub fn disable_window_interrupt(&mut self) {
+        self.ac.intenclr.write(|w| w.win0().set_bit());
+    }
+
+    /// Clear the window interrupt flag, returning whether it was set
+    pub fn check_and_clear_window_interrupt(&mut self) -> bool {
+        let set = self.ac.intflag.read().win0().bit_is_set();
+        if set {
+            self.ac.intflag.write(|w| w.win0().set_bit());
+        }
+        set
+    }
+
+    /// Enable the window event output, returning the matching generator for
+    /// use with the [`evsys`](crate::evsys) module.
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    pub fn enable_window_event(&mut self) -> generators::AcWin0 {
+        self.ac.evctrl.modify(|_, w| w.wineo0().set_bit());
+        generators::AcWin0
+    }
+
+    /// Disable the window event output
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    pub fn disable_window_event(&mut self) {
+        self.ac.evctrl.modify(|_, w| w.wineo0().clear_bit());
+    }
+
+    /// Disable the AC and return the underlying peripheral.
+    ///
+    /// **Note**: The [`Comparators`] struct is consumed by this method, so
+    /// the comparators obtained by [`split`](Ac::split) must be moved back
+    /// into it before being able to pass it into [`free`](Ac::free).
+    pub fn free(self, _comparators: Comparators) -> AC {
+        self.ac.ctrla.write(|w| w.swrst().set_bit());
+        sync(&self.ac);
+        self.ac
+    }
+}
+
+//==============================================================================
+// Comparator
+//==============================================================================
+
+/// A single comparator of the [`Ac`]
+pub struct Comparator<Id: CompId> {
+    id: PhantomData<Id>,
+}
+
+impl<Id: CompId> Comparator<Id> {
+    #[inline]
+    fn ac(&self) -> &ac::RegisterBlock {
+        // Safety: each `Comparator` only accesses its own registers, or its
+        // own bits of the shared registers. Read-modify-write operations on
+        // the shared EVCTRL register are done in a critical section.
+        unsafe { &*AC::ptr() }
+    }
+
+    #[inline]
+    fn mask(&self) -> u8 {
+        1 << Id::N
+    }
+
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    #[inline]
+    fn modify_evctrl(&mut self, bit: u16, enabled: bool) {
+        let ac = self.ac();
+        cortex_m::interrupt::free(|_| {
+            ac.evctrl.modify(|r, w| unsafe {
+                if enabled {
+                    w.bits(r.bits() | bit)
+                } else {
+                    w.bits(r.bits() & !bit)
+                }
+            })
+        });
+    }
+
+    /// Configure and enable the comparator.
+    ///
+    /// The comparator is disabled while its configuration is updated, and is
+    /// ready to compare once [`is_ready`](Comparator::is_ready) returns
+    /// `true`.
+    pub fn configure(&mut self, config: Config) {
+        self.disable();
+        self.ac().compctrl[Id::N].write(|w| {
+            w.muxpos().variant(config.positive);
+            w.muxneg().variant(config.negative);
+            w.swap().bit(config.swap);
+            w.single().bit(config.single_shot);
+            #[cfg(any(feature = "samd11", feature = "samd21"))]
+            {
+                w.hyst().bit(config.hysteresis);
+                w.speed().variant(config.speed);
+            }
+            #[cfg(feature = "min-samd51g")]
+            {
+                w.hysten().bit(config.hysteresis);
+                w.hyst().variant(config.hysteresis_level);
+                w.speed().high();
+                w.runstdby().bit(config.run_in_standby);
+            }
+            w.flen().variant(config.filter);
+            w.out().variant(config.output);
+            w.intsel().variant(config.interrupt)
+        });
+        sync(self.ac());
+        self.enable();
+    }
+
+    /// Enable the comparator
+    pub fn enable(&mut self) {
+        self.ac().compctrl[Id::N].modify(|_, w| w.enable().set_bit());
+        sync(self.ac());
+    }
+
+    /// Disable the comparator
+    pub fn disable(&mut self) {
+        self.ac().compctrl[Id::N].modify(|_, w| w.enable().clear_bit());
+        sync(self.ac());
+    }
+
+    /// Set the VDD scaler of the comparator. Its output voltage is
+    /// `VDD * (value + 1) / 64`, with `value` in `0..=63`.
+    pub fn set_scaler(&mut self, value: u8) {
+        self.ac().scaler[Id::N].write(|w| unsafe { w.value().bits(value & 0x3F) });
+    }
+
+    /// Start a single-shot comparison
+    pub fn start(&mut self) {
+        let mask = self.mask();
+        // Writing a zero to the other START bits has no effect
+        self.ac().ctrlb.write(|w| unsafe { w.bits(mask) });
+    }
+
+    /// Return whether the comparator output is valid
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.ac().statusb.read().bits() & self.mask() != 0
+    }
+
+    /// Return the comparator output: `true` when the positive input is above
+    /// the negative input (or the opposite if the inputs are swapped).
+    #[inline]
+    pub fn state(&self) -> bool {
+        self.ac().statusa.read().bits() & self.mask() != 0
+    }
+
+    /// Enable the comparator interrupt
+    pub fn enable_interrupt(&mut self) {
+        let mask = self.mask();
+        self.ac().intenset.write(|w| unsafe { w.bits(mask) });
+    }
+
+    /// Disable the comparator interrupt
+    pub fn disable_interrupt(&mut self) {
+        let mask = self.mask();
+        self.ac().intenclr.write(|w| unsafe { w.bits(mask) });
+    }
+
+    /// Clear the comparator interrupt flag, returning whether it was set
+    pub fn check_and_clear_interrupt(&mut self) -> bool {
+        let mask = self.mask();
+        let set = self.ac().intflag.read().bits() & mask != 0;
+        if set {
+            self.ac().intflag.write(|w| unsafe { w.bits(mask) });
+        }
+        set
+    }
+
+    /// Enable the comparator event output, returning the matching generator
+    /// for use with the [`evsys`](crate::evsys) module.
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    pub fn enable_event(&mut self) -> Id::Generator {
+        self.modify_evctrl(u16::from(self.mask()), true);
+        Id::GENERATOR
+    }
+
+    /// Disable the comparator event output
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    pub fn disable_event(&mut self) {
+        self.modify_evctrl(u16::from(self.mask()), false);
+    }
+
+    /// Start a single-shot comparison whenever an event is received from the
+    /// [`evsys`](crate::evsys) module.
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    pub fn enable_start_event(&mut self) {
+        self.modify_evctrl(u16::from(self.mask()) << 8, true);
+    }
+
+    /// Stop starting comparisons on incoming events
+    #[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+    pub fn disable_start_event(&mut self) {
+        self.modify_evctrl(u16::from(self.mask()) << 8, false);
+    }
+}
@@ -0,0 +1,52 @@
+//! Shared plumbing for the `async` drivers
+//!
+//! Peripheral futures register their [`Waker`] in a [`WakerCell`] before
+//! enabling the interrupt they wait on. The interrupt handler disables the
+//! interrupt and wakes the task, which then polls the future again.
+
+use core::cell::RefCell;
+use core::task::Waker;
+
+use cortex_m::interrupt::{self, Mutex};
+
+/// Storage for a single [`Waker`], shared between a task and an interrupt
+/// handler
+///
+/// Thumbv6m targets lack atomic compare-and-swap instructions, so the waker is
+/// protected by a critical section instead.
+pub(crate) struct WakerCell {
+    waker: Mutex<RefCell<Option<Waker>>>,
+}
+
+impl WakerCell {
+    /// Create an empty [`WakerCell`]
+    pub(crate) const fn new() -> Self {
+        Self {
+            waker: Mutex::new(RefCell::new(None)),
+        }
+    }
+
+    /// Register `waker` to be woken by the next call to [`wake`](Self::wake)
+    ///
+    /// A previously registered waker is dropped, unless it would wake the same
+    /// task.
+    #[inline]
+    pub(crate) fn register(&self, waker: &Waker) {
+        interrupt::free(|cs| {
+            let mut slot = self.waker.borrow(cs).borrow_mut();
+            match slot.as_ref() {
+                Some(old) if old.will_wake(waker) => (),
+                _ => *slot = Some(waker.clone()),
+            }
+        });
+    }
+
+    /// Wake the registered task, if any
+    #[inline]
+    pub(crate) fn wake(&self) {
+        let waker = interrupt::free(|cs| self.waker.borrow(cs).borrow_mut().take());
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! # Interrupt handling for `async` DMA transfers
+//!
+//! When the `async` feature is enabled, a [`Transfer`](super::Transfer) can be
+//! awaited through [`Transfer::wait_async`](super::Transfer::wait_async).
+//! The returned future enables the transfer complete and transfer error
+//! interrupts of its channel, and relies on [`on_interrupt`] being called from
+//! the DMAC interrupt handler to be woken up. A single handler serves every
+//! channel, so no per-channel ISR plumbing is required.
+//!
+//! ```no_run
+//! use atsamd_hal::dmac;
+//! use atsamd_hal::pac::interrupt;
+//!
+//! #[interrupt]
+//! fn DMAC() {
+//!     dmac::on_interrupt();
+//! }
+//! ```
+//!
+//! On SAMD51/SAME5x chips, the DMAC has five interrupt lines (`DMAC_0` to
+//! `DMAC_3`, plus `DMAC_OTHER`), and [`on_interrupt`] must be called from all
+//! of them.
+//!
+//! [`on_interrupt`] disables the interrupts of every channel that fired. It
+//! should therefore not be mixed with callback-based transfers (see
+//! [`Transfer::with_waker`](super::Transfer::with_waker)) in the same
+//! interrupt handler.
+
+use super::NUM_CHANNELS;
+use crate::async_hal::WakerCell;
+use crate::pac::DMAC;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: WakerCell = WakerCell::new();
+
+/// Wakers for tasks waiting on a transfer, indexed by channel number
+pub(super) static WAKERS: [WakerCell; NUM_CHANNELS] = [NEW_WAKER; NUM_CHANNELS];
+
+/// DMAC interrupt handler for `async` transfers
+///
+/// For every channel with a pending interrupt, disables the channel
+/// interrupts that fired, then wakes the task waiting on the channel. The
+/// interrupt flags are left untouched, so that the awaiting future can tell
+/// transfer completions and errors apart.
+#[inline]
+pub fn on_interrupt() {
+    // SAFETY: Only the channel interrupt enable registers are written, and
+    // only for channels that raised an interrupt.
+    let dmac = unsafe { &*DMAC::ptr() };
+
+    let pending = dmac.intstatus.read().bits();
+    for (id, waker) in WAKERS.iter().enumerate() {
+        if pending & (1 << id) == 0 {
+            continue;
+        }
+        disable_fired(dmac, id);
+        waker.wake();
+    }
+}
+
+/// Disable the interrupts that fired on channel `id`
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+#[inline]
+fn disable_fired(dmac: &crate::pac::dmac::RegisterBlock, id: usize) {
+    // Select the channel, then restore the previous CHID value for the
+    // preempted context. See `channel::reg::Register::with_chid`.
+    let mut old_id = 0;
+    dmac.chid.modify(|r, w| {
+        old_id = r.id().bits();
+        unsafe { w.id().bits(id as u8) }
+    });
+
+    let fired = dmac.chintflag.read().bits() & dmac.chintenset.read().bits();
+    dmac.chintenclr.write(|w| unsafe { w.bits(fired) });
+
+    dmac.chid.write(|w| unsafe { w.id().bits(old_id) });
+}
+
+/// Disable the interrupts that fired on channel `id`
+#[cfg(feature = "min-samd51g")]
+#[inline]
+fn disable_fired(dmac: &crate::pac::dmac::RegisterBlock, id: usize) {
+    let ch = &dmac.channel[id];
+    let fired = ch.chintflag.read().bits() & ch.chintenset.read().bits();
+    ch.chintenclr.write(|w| unsafe { w.bits(fired) });
+}
@@ -33,7 +33,7 @@ section 22.6.2.8 for more information."
 //! `Uninitialized` state. You will be required to call [`Channel::init`]
 //! again before being able to use it with a `Transfer`.
 
-use super::dma_controller::{ChId, PriorityLevel, TriggerAction, TriggerSource};
+use super::dma_controller::{ChId, EventAction, PriorityLevel, TriggerAction, TriggerSource};
 use crate::typelevel::{Is, Sealed};
 use core::marker::PhantomData;
 use modular_bitfield::prelude::*;
@@ -238,6 +238,36 @@ impl<Id: ChId> Channel<Id, Ready> {
             .modify(|_, w| w.burstlen().bits(burst_length as u8));
     }
 
+    /// Enable the channel's event input, performing `action` whenever an
+    /// event is received from the event system. Only the first channels of
+    /// the DMAC (0-3 on SAMD11/SAMD21, 0-7 on SAMD51/SAME5x) are event users.
+    ///
+    /// Note that [`Channel::init`] resets the event input configuration.
+    #[inline]
+    pub fn enable_event_input(&mut self, action: EventAction) {
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        self.regs.chctrlb.modify(|_, w| {
+            w.evact().variant(action);
+            w.evie().set_bit()
+        });
+
+        #[cfg(feature = "min-samd51g")]
+        self.regs.chevctrl.modify(|_, w| {
+            w.evact().variant(action);
+            w.evie().set_bit()
+        });
+    }
+
+    /// Disable the channel's event input
+    #[inline]
+    pub fn disable_event_input(&mut self) {
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        self.regs.chctrlb.modify(|_, w| w.evie().clear_bit());
+
+        #[cfg(feature = "min-samd51g")]
+        self.regs.chevctrl.modify(|_, w| w.evie().clear_bit());
+    }
+
     /// Start transfer on channel using the specified trigger source.
     ///
     /// # Return
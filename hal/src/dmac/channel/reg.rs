@@ -38,7 +38,7 @@ use channel_regs::{CHCTRLA, CHCTRLB, CHINTENCLR, CHINTENSET, CHINTFLAG, CHSTATUS
 
 #[cfg(feature = "min-samd51g")]
 use pac::dmac::{
-    channel::{chprilvl::CHPRILVL_SPEC, CHPRILVL},
+    channel::{chevctrl::CHEVCTRL_SPEC, chprilvl::CHPRILVL_SPEC, CHEVCTRL, CHPRILVL},
     CHANNEL,
 };
 
@@ -268,6 +268,8 @@ reg_proxy!(chintflag, register, rw);
 reg_proxy!(chstatus, register, r);
 #[cfg(feature = "min-samd51g")]
 reg_proxy!(chprilvl, register, rw);
+#[cfg(feature = "min-samd51g")]
+reg_proxy!(chevctrl, register, rw);
 
 reg_proxy!(intstatus, bit, r);
 reg_proxy!(busych, bit, r);
@@ -291,6 +293,8 @@ pub(super) struct RegisterBlock<Id: ChId> {
     pub swtrigctrl: SwtrigctrlProxy<Id, SWTRIGCTRL>,
     #[cfg(feature = "min-samd51g")]
     pub chprilvl: ChprilvlProxy<Id, CHPRILVL>,
+    #[cfg(feature = "min-samd51g")]
+    pub chevctrl: ChevctrlProxy<Id, CHEVCTRL>,
 }
 
 impl<Id: ChId> RegisterBlock<Id> {
@@ -308,6 +312,8 @@ impl<Id: ChId> RegisterBlock<Id> {
             swtrigctrl: SwtrigctrlProxy::new(),
             #[cfg(feature = "min-samd51g")]
             chprilvl: ChprilvlProxy::new(),
+            #[cfg(feature = "min-samd51g")]
+            chevctrl: ChevctrlProxy::new(),
         }
     }
 }
@@ -24,7 +24,8 @@ use seq_macro::seq;
 
 #[cfg(any(feature = "samd11", feature = "samd21"))]
 pub use crate::pac::dmac::chctrlb::{
-    LVL_A as PriorityLevel, TRIGACT_A as TriggerAction, TRIGSRC_A as TriggerSource,
+    EVACT_A as EventAction, LVL_A as PriorityLevel, TRIGACT_A as TriggerAction,
+    TRIGSRC_A as TriggerSource,
 };
 
 #[cfg(feature = "min-samd51g")]
@@ -33,6 +34,7 @@ pub use crate::pac::dmac::channel::{
         BURSTLEN_A as BurstLength, THRESHOLD_A as FifoThreshold, TRIGACT_A as TriggerAction,
         TRIGSRC_A as TriggerSource,
     },
+    chevctrl::EVACT_A as EventAction,
     chprilvl::PRILVL_A as PriorityLevel,
 };
 
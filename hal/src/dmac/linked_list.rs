@@ -0,0 +1,577 @@
+//! # Linked-list (multi-block) DMA transfers
+//!
+//! A [`LinkedTransfer`] chains several block transfers together through the
+//! `DESCADDR` field of the DMAC descriptors. The first block is stored in the
+//! channel's own descriptor, while every block (including the first) is also
+//! stored in a user-provided [`DescriptorList`]. The DMAC fetches the next
+//! descriptor on its own when a block completes, so there are no gaps between
+//! blocks, which is not possible with [`Transfer::recycle`].
+//!
+//! # Scatter/gather
+//!
+//! Both the source and the destination of a [`LinkedTransfer`] implement
+//! [`BlockBuffer`]. A [`BlockBuffer`] is either a single [`Buffer`], which is
+//! reused for every block, or an array of [`Buffer`]s, one per block. For
+//! example, a `[&'static mut [u8; 64]; 4]` source and a single SPI destination
+//! will gather four separate buffers into a single SPI stream.
+//!
+//! # Ping-pong buffering
+//!
+//! A circular [`LinkedTransfer`] loops back to the first block when the last
+//! block has completed. With two blocks, this provides ping-pong (double)
+//! buffering: the DMAC fills (or empties) one block while the CPU processes
+//! the other. Every descriptor is configured to raise the transfer complete
+//! interrupt flag when its block completes. Calling
+//! [`LinkedTransfer::callback`] from the DMAC interrupt handler keeps track of
+//! which block has completed, which can be retrieved with
+//! [`LinkedTransfer::last_completed_block`].
+//!
+//! ```
+//! static mut DESCRIPTORS: DescriptorList<2> = DescriptorList::new();
+//!
+//! let ping: &'static mut [u16; 256] = cortex_m::singleton!(: [u16; 256] = [0; 256]).unwrap();
+//! let pong: &'static mut [u16; 256] = cortex_m::singleton!(: [u16; 256] = [0; 256]).unwrap();
+//!
+//! chan0.enable_interrupts(InterruptFlags::new().with_tcmpl(true));
+//! let mut xfer = LinkedTransfer::new(
+//!     chan0,
+//!     unsafe { &mut DESCRIPTORS },
+//!     adc_result,
+//!     [ping, pong],
+//!     true,
+//! )
+//! .unwrap()
+//! .begin(TriggerSource::ADC_RESRDY, TriggerAction::BEAT);
+//!
+//! // In the DMAC interrupt handler
+//! xfer.callback();
+//! if let Some(block) = xfer.last_completed_block() {
+//!     // SAFETY: The DMAC is now working on the other block
+//!     let samples = unsafe { &xfer.borrow_destination()[block] };
+//!     process(samples);
+//! }
+//! ```
+//!
+//! Note that in a circular transfer, the DMAC will come back to a completed
+//! block once every other block has completed. Processing a block must
+//! therefore be done before that happens.
+
+use super::{
+    channel::{AnyChannel, Busy, CallbackStatus, Channel, ChannelId, Ready},
+    dma_controller::{ChId, TriggerAction, TriggerSource},
+    transfer::{Beat, Buffer},
+    BlockTransferControl, DmacDescriptor, Error, Result, DEFAULT_DESCRIPTOR, DESCRIPTOR_SECTION,
+};
+use core::{ptr::null_mut, sync::atomic};
+
+#[cfg(doc)]
+use super::transfer::Transfer;
+
+/// Block action which raises the transfer complete interrupt flag at the end
+/// of each block. Datasheet section 19.8.2.1
+const BLOCKACT_INT: u8 = 0x1;
+
+//==============================================================================
+// DescriptorList
+//==============================================================================
+
+/// Memory holding the descriptors of a [`LinkedTransfer`] with `N` blocks.
+///
+/// The DMAC reads the descriptors directly from this memory while the
+/// transfer is running, so it must outlive the transfer. It is usually
+/// allocated as a `static mut`.
+#[repr(C)]
+pub struct DescriptorList<const N: usize> {
+    descriptors: [DmacDescriptor; N],
+}
+
+impl<const N: usize> DescriptorList<N> {
+    /// Create a new, empty `DescriptorList`
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            descriptors: [DEFAULT_DESCRIPTOR; N],
+        }
+    }
+}
+
+impl<const N: usize> Default for DescriptorList<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//==============================================================================
+// BlockBuffer
+//==============================================================================
+
+/// Source or destination of a [`LinkedTransfer`].
+///
+/// This trait is implemented for every [`Buffer`], in which case the same
+/// buffer is used for every block, and for arrays of [`Buffer`]s, in which
+/// case each block uses its own buffer.
+///
+/// # Safety
+///
+/// This trait should only be implemented for valid DMAC sources/sinks. The
+/// same requirements as for [`Buffer`] apply to each individual block.
+pub unsafe trait BlockBuffer {
+    /// DMAC beat size
+    type Beat: Beat;
+    /// Number of blocks this buffer provides, or `None` if the same buffer
+    /// is used for every block
+    fn num_blocks(&self) -> Option<usize>;
+    /// Pointer to the buffer of the given block. See [`Buffer::dma_ptr`].
+    fn block_ptr(&mut self, block: usize) -> *mut Self::Beat;
+    /// Return whether the pointer of the given block should be incrementing
+    fn block_incrementing(&self, block: usize) -> bool;
+    /// Length of the given block in beats
+    fn block_len(&self, block: usize) -> usize;
+}
+
+unsafe impl<B: Buffer> BlockBuffer for B {
+    type Beat = B::Beat;
+
+    #[inline]
+    fn num_blocks(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn block_ptr(&mut self, _block: usize) -> *mut Self::Beat {
+        self.dma_ptr()
+    }
+
+    #[inline]
+    fn block_incrementing(&self, _block: usize) -> bool {
+        self.incrementing()
+    }
+
+    #[inline]
+    fn block_len(&self, _block: usize) -> usize {
+        self.buffer_len()
+    }
+}
+
+unsafe impl<B: Buffer, const N: usize> BlockBuffer for [B; N] {
+    type Beat = B::Beat;
+
+    #[inline]
+    fn num_blocks(&self) -> Option<usize> {
+        Some(N)
+    }
+
+    #[inline]
+    fn block_ptr(&mut self, block: usize) -> *mut Self::Beat {
+        self[block].dma_ptr()
+    }
+
+    #[inline]
+    fn block_incrementing(&self, block: usize) -> bool {
+        self[block].incrementing()
+    }
+
+    #[inline]
+    fn block_len(&self, block: usize) -> usize {
+        self[block].buffer_len()
+    }
+}
+
+//==============================================================================
+// LinkedTransfer
+//==============================================================================
+
+/// Multi-block DMA transfer, owning the resources until the transfer is
+/// done and [`LinkedTransfer::wait`] or [`LinkedTransfer::stop`] is called.
+pub struct LinkedTransfer<'a, Chan, S, D, const N: usize, W = ()>
+where
+    Chan: AnyChannel,
+    S: BlockBuffer,
+    D: BlockBuffer<Beat = S::Beat>,
+{
+    chan: Chan,
+    descriptors: &'a mut DescriptorList<N>,
+    source: S,
+    destination: D,
+    waker: Option<W>,
+    completed_blocks: usize,
+    last_block: Option<usize>,
+    complete: bool,
+}
+
+impl<C, S, D, const N: usize> LinkedTransfer<'static, C, S, D, N>
+where
+    C: AnyChannel<Status = Ready>,
+    S: BlockBuffer + 'static,
+    D: BlockBuffer<Beat = S::Beat> + 'static,
+{
+    /// Safely construct a new `LinkedTransfer` of `N` blocks. To guarantee
+    /// memory safety, the buffers and the descriptor list are required to be
+    /// `'static`.
+    ///
+    /// If `circular` is `true`, the transfer will loop back to the first
+    /// block after the last one has completed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::LengthMismatch`] if the number of blocks provided by
+    /// `source` or `destination` is not `N`, or if the source and
+    /// destination buffers of any block have mismatched lengths.
+    #[allow(clippy::new_ret_no_self)]
+    #[inline]
+    pub fn new(
+        chan: C,
+        descriptors: &'static mut DescriptorList<N>,
+        source: S,
+        destination: D,
+        circular: bool,
+    ) -> Result<Self> {
+        Self::check_block_buffers(&source, &destination)?;
+
+        // SAFETY: The safety checks are done by the function signature and the buffer
+        // length verification
+        Ok(unsafe { Self::new_unchecked(chan, descriptors, source, destination, circular) })
+    }
+}
+
+impl<'a, C, S, D, const N: usize> LinkedTransfer<'a, C, S, D, N>
+where
+    C: AnyChannel<Status = Ready>,
+    S: BlockBuffer,
+    D: BlockBuffer<Beat = S::Beat>,
+{
+    /// Construct a new `LinkedTransfer` without checking for memory safety.
+    ///
+    /// # Safety
+    ///
+    /// To guarantee the safety of creating a `LinkedTransfer` using this
+    /// method, you must uphold some invariants:
+    ///
+    /// * A `LinkedTransfer` holding a `Channel<Id, Running>` must *never* be
+    ///   dropped. It should *always* be explicitly be `wait`ed upon or
+    ///   `stop`ped.
+    ///
+    /// * `source` and `destination` must either provide exactly `N` blocks,
+    ///   or be a single buffer reused for every block.
+    ///
+    /// * The size in bytes or the source and destination buffers of each
+    ///   block should be exacly the same, unless one or both buffers are of
+    ///   length 1.
+    #[inline]
+    pub unsafe fn new_unchecked(
+        chan: C,
+        descriptors: &'a mut DescriptorList<N>,
+        mut source: S,
+        mut destination: D,
+        circular: bool,
+    ) -> Self {
+        Self::fill_descriptors(descriptors, &mut source, &mut destination, circular);
+
+        LinkedTransfer {
+            chan,
+            descriptors,
+            source,
+            destination,
+            waker: None,
+            completed_blocks: 0,
+            last_block: None,
+            complete: false,
+        }
+    }
+
+    /// Append a waker to the transfer. It will be called by
+    /// [`LinkedTransfer::callback`] every time a block completes.
+    #[inline]
+    pub fn with_waker<W: FnMut(CallbackStatus) + 'static>(
+        self,
+        waker: W,
+    ) -> LinkedTransfer<'a, C, S, D, N, W> {
+        LinkedTransfer {
+            chan: self.chan,
+            descriptors: self.descriptors,
+            source: self.source,
+            destination: self.destination,
+            waker: Some(waker),
+            completed_blocks: self.completed_blocks,
+            last_block: self.last_block,
+            complete: self.complete,
+        }
+    }
+}
+
+impl<'a, C, S, D, const N: usize, W> LinkedTransfer<'a, C, S, D, N, W>
+where
+    C: AnyChannel,
+    S: BlockBuffer,
+    D: BlockBuffer<Beat = S::Beat>,
+{
+    #[inline]
+    fn check_block_buffers(source: &S, destination: &D) -> Result<()> {
+        if N == 0 {
+            return Err(Error::LengthMismatch);
+        }
+
+        for num_blocks in [source.num_blocks(), destination.num_blocks()] {
+            if num_blocks.map_or(false, |n| n != N) {
+                return Err(Error::LengthMismatch);
+            }
+        }
+
+        for block in 0..N {
+            let src_len = source.block_len(block);
+            let dst_len = destination.block_len(block);
+
+            if src_len > 1 && dst_len > 1 && src_len != dst_len {
+                return Err(Error::LengthMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn fill_descriptors(
+        descriptors: &mut DescriptorList<N>,
+        source: &mut S,
+        destination: &mut D,
+        circular: bool,
+    ) {
+        let id = <C as AnyChannel>::Id::USIZE;
+        let list = &mut descriptors.descriptors;
+        let first: *mut DmacDescriptor = &mut list[0];
+
+        for block in 0..N {
+            // Each descriptor points to the next one in the list. The last descriptor
+            // either terminates the transaction, or loops back to the first
+            // descriptor if the transfer is circular.
+            let descaddr = if block + 1 < N {
+                &mut list[block + 1] as *mut _
+            } else if circular {
+                first
+            } else {
+                null_mut()
+            };
+
+            let src_len = source.block_len(block);
+            let dst_len = destination.block_len(block);
+            let length = core::cmp::max(src_len, dst_len);
+
+            // Raise the transfer complete flag at the end of every block, not only at the
+            // end of the transaction. The channel is still disabled when the last block
+            // completes, so Channel::xfer_complete() keeps working.
+            let btctrl = BlockTransferControl::new()
+                .with_srcinc(source.block_incrementing(block))
+                .with_dstinc(destination.block_incrementing(block))
+                .with_beatsize(S::Beat::BEATSIZE)
+                .with_blockact(BLOCKACT_INT)
+                .with_valid(true);
+
+            list[block] = DmacDescriptor {
+                descaddr,
+                srcaddr: source.block_ptr(block) as *mut _,
+                dstaddr: destination.block_ptr(block) as *mut _,
+                btcnt: length as u16,
+                btctrl,
+            };
+        }
+
+        // SAFETY this is safe as long as we ONLY write to the descriptor
+        // belonging to OUR channel. The first block is copied in the channel's
+        // descriptor, from which the DMAC will follow the rest of the list.
+        DESCRIPTOR_SECTION[id] = list[0];
+    }
+}
+
+impl<'a, C, S, D, const N: usize, W> LinkedTransfer<'a, C, S, D, N, W>
+where
+    C: AnyChannel<Status = Ready>,
+    S: BlockBuffer,
+    D: BlockBuffer<Beat = S::Beat>,
+{
+    /// Begin DMA transfer. If [TriggerSource::DISABLE](TriggerSource::DISABLE)
+    /// is used, a software trigger will be issued to the DMA channel to
+    /// launch the transfer.
+    #[inline]
+    pub fn begin(
+        mut self,
+        trig_src: TriggerSource,
+        trig_act: TriggerAction,
+    ) -> LinkedTransfer<'a, Channel<ChannelId<C>, Busy>, S, D, N, W> {
+        self.complete = false;
+        self.completed_blocks = 0;
+        self.last_block = None;
+
+        // Memory barrier to prevent the compiler/CPU from re-ordering read/write
+        // operations beyond this fence.
+        // (see https://docs.rust-embedded.org/embedonomicon/dma.html#compiler-misoptimizations)
+        atomic::fence(atomic::Ordering::Release); //  ▲
+        let chan = self.chan.into().start(trig_src, trig_act);
+
+        LinkedTransfer {
+            chan,
+            descriptors: self.descriptors,
+            source: self.source,
+            destination: self.destination,
+            waker: self.waker,
+            completed_blocks: self.completed_blocks,
+            last_block: self.last_block,
+            complete: self.complete,
+        }
+    }
+}
+
+impl<'a, C, S, D, const N: usize, W> LinkedTransfer<'a, C, S, D, N, W>
+where
+    C: AnyChannel<Status = Busy>,
+    S: BlockBuffer,
+    D: BlockBuffer<Beat = S::Beat>,
+{
+    /// Issue a software trigger request to the corresponding channel.
+    /// Note that is not guaranteed that the trigger request will register,
+    /// if a trigger request is already pending for the channel.
+    #[inline]
+    pub fn software_trigger(&mut self) {
+        self.chan.as_mut().software_trigger();
+    }
+
+    /// Unsafely and mutably borrow the source buffer
+    ///
+    /// # Safety
+    ///
+    /// The blocks of the source buffer that are being read by the DMAC
+    /// should never be accessed. For ping-pong buffering, only the block
+    /// returned by [`last_completed_block`](Self::last_completed_block) may
+    /// be accessed, until the DMAC comes back to it.
+    #[inline]
+    pub unsafe fn borrow_source(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Unsafely and mutably borrow the destination buffer
+    ///
+    /// # Safety
+    ///
+    /// The blocks of the destination buffer that are being written by the
+    /// DMAC should never be accessed. For ping-pong buffering, only the block
+    /// returned by [`last_completed_block`](Self::last_completed_block) may
+    /// be accessed, until the DMAC comes back to it.
+    #[inline]
+    pub unsafe fn borrow_destination(&mut self) -> &mut D {
+        &mut self.destination
+    }
+
+    /// Wait for the DMA transfer to complete and release all owned
+    /// resources. A circular transfer never completes.
+    ///
+    /// # Blocking: This method may block
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn wait(
+        mut self,
+    ) -> (
+        Channel<ChannelId<C>, Ready>,
+        S,
+        D,
+        &'a mut DescriptorList<N>,
+    ) {
+        while !self.complete() {}
+        self.stop()
+    }
+
+    /// Check if the whole transfer has completed
+    #[inline]
+    pub fn complete(&mut self) -> bool {
+        if !self.complete {
+            self.complete = self.chan.as_mut().xfer_complete();
+        }
+        self.complete
+    }
+
+    /// Number of blocks that have completed since the transfer began, as
+    /// counted by [`LinkedTransfer::callback`]. This counter wraps around.
+    #[inline]
+    pub fn completed_blocks(&self) -> usize {
+        self.completed_blocks
+    }
+
+    /// Index of the last block which was reported as complete by
+    /// [`LinkedTransfer::callback`], or `None` if no block has completed yet.
+    #[inline]
+    pub fn last_completed_block(&self) -> Option<usize> {
+        self.last_block
+    }
+
+    /// Count a completed block. The block index is kept modulo `N`, as the
+    /// wrapping block counter can't be reduced to an index once it wraps.
+    #[inline]
+    fn block_completed(&mut self) {
+        self.completed_blocks = self.completed_blocks.wrapping_add(1);
+        self.last_block = Some(self.last_block.map_or(0, |block| (block + 1) % N));
+        if self.chan.as_mut().xfer_complete() {
+            self.complete = true;
+        }
+    }
+
+    /// Non-blocking; Immediately stop the DMA transfer and release all owned
+    /// resources
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn stop(
+        self,
+    ) -> (
+        Channel<ChannelId<C>, Ready>,
+        S,
+        D,
+        &'a mut DescriptorList<N>,
+    ) {
+        let chan = self.chan.into().free();
+
+        // Memory barrier to prevent the compiler/CPU from re-ordering read/write
+        // operations beyond this fence.
+        // (see https://docs.rust-embedded.org/embedonomicon/dma.html#compiler-misoptimizations)
+        atomic::fence(atomic::Ordering::Acquire); // ▼
+
+        (chan, self.source, self.destination, self.descriptors)
+    }
+}
+
+impl<'a, C, S, D, const N: usize, W> LinkedTransfer<'a, C, S, D, N, W>
+where
+    C: AnyChannel<Status = Busy>,
+    S: BlockBuffer,
+    D: BlockBuffer<Beat = S::Beat>,
+    W: FnMut(CallbackStatus) + 'static,
+{
+    /// This function should be put inside the DMAC interrupt handler. It
+    /// keeps track of the completed blocks, and calls the
+    /// [`LinkedTransfer`]'s waker (if it exists).
+    #[inline]
+    pub fn callback(&mut self) {
+        let status = self.chan.as_mut().callback();
+
+        if let CallbackStatus::TransferComplete = status {
+            self.block_completed();
+        }
+
+        if let Some(w) = self.waker.as_mut() {
+            w(status)
+        }
+    }
+}
+
+impl<'a, C, S, D, const N: usize> LinkedTransfer<'a, C, S, D, N>
+where
+    C: AnyChannel<Status = Busy>,
+    S: BlockBuffer,
+    D: BlockBuffer<Beat = S::Beat>,
+{
+    /// This function should be put inside the DMAC interrupt handler. It
+    /// keeps track of the completed blocks.
+    #[inline]
+    pub fn callback(&mut self) {
+        if let CallbackStatus::TransferComplete = self.chan.as_mut().callback() {
+            self.block_completed();
+        }
+    }
+}
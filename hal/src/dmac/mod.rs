@@ -10,9 +10,10 @@
 //! functions, including memory-to-memory,
 //! memory-to-peripheral, peripheral-to-memory,
 //! and peripheral-to-peripheral transfers.
-//! One-shot and circular transfers are supported. More complex
-//! transfer configurations, including multi-buffer
-//! (linked-list descriptor) transfers, are not currently supported.
+//! One-shot and circular transfers are supported. Multi-buffer
+//! (linked-list descriptor) transfers, including scatter/gather and ping-pong
+//! buffering, are supported through [`LinkedTransfer`] (see the
+//! [`linked_list`] module).
 //!
 //! Transfers are supported for `i8`, `u8`, `i16`, `u16`, `i32`, `u32` and `f32`
 //! beat sizes.
@@ -89,6 +90,11 @@
 //! the NVIC. You will be responsible for clearing the interrupt flags in the
 //! ISR.
 //!
+//! When the `async` feature is enabled, transfers can be awaited with
+//! [`Transfer::wait_async`]. A single interrupt handler,
+//! [`on_interrupt`], dispatches completions and errors to the waiting tasks
+//! for all channels. See the [`async_api`] module for details.
+//!
 //! # About static lifetimes
 //!
 //! The safe API this driver offers requires all buffers (source and
@@ -255,8 +261,12 @@ use modular_bitfield::prelude::*;
 
 pub use channel::*;
 pub use dma_controller::*;
+pub use linked_list::*;
 pub use transfer::*;
 
+#[cfg(feature = "async")]
+pub use async_api::on_interrupt;
+
 #[derive(Debug)]
 /// Runtime errors that may occur when dealing with DMA transfers.
 pub enum Error {
@@ -393,4 +403,8 @@ static mut DESCRIPTOR_SECTION: [DmacDescriptor; NUM_CHANNELS] = [DEFAULT_DESCRIP
 
 pub mod channel;
 pub mod dma_controller;
+pub mod linked_list;
 pub mod transfer;
+
+#[cfg(feature = "async")]
+pub mod async_api;
@@ -52,6 +52,10 @@
 //! completed. When it returns, it will release the source and destination
 //! buffers, as well as the DMA channel.
 //!
+//! With the `async` feature enabled, a transfer can also be awaited through
+//! [`wait_async`](Transfer::wait_async). See the
+//! [`async_api`](super::async_api) module for the required interrupt handler.
+//!
 //! # Interrupting (stopping) a transfer
 //!
 //! A transfer can be stopped (regardless of whether it has completed or not) by
@@ -713,6 +717,72 @@ where
     }
 }
 
+#[cfg(feature = "async")]
+impl<S, D, C> Transfer<C, BufferPair<S, D>>
+where
+    S: Buffer,
+    D: Buffer<Beat = S::Beat>,
+    C: AnyChannel<Status = Busy>,
+{
+    /// Asynchronously wait for the DMA transfer to complete and release all
+    /// owned resources
+    ///
+    /// The transfer complete and transfer error interrupts of the channel are
+    /// enabled while waiting. [`dmac::on_interrupt`](super::on_interrupt)
+    /// must be called from the DMAC interrupt handler(s) for the returned
+    /// future to be woken up.
+    ///
+    /// # Return
+    ///
+    /// The channel and both buffers. They are returned in the `Err` variant
+    /// if the DMAC reported a transfer error.
+    // The `async` feature requires Rust 1.75 or later
+    #[allow(clippy::incompatible_msrv)]
+    #[allow(clippy::type_complexity)]
+    pub async fn wait_async(
+        mut self,
+    ) -> core::result::Result<
+        (Channel<ChannelId<C>, Ready>, S, D),
+        (Channel<ChannelId<C>, Ready>, S, D),
+    > {
+        let waker = &super::async_api::WAKERS[ChannelId::<C>::USIZE];
+        let flags = InterruptFlags::new().with_tcmpl(true).with_terr(true);
+
+        core::future::poll_fn(|cx| {
+            if self.complete() {
+                return core::task::Poll::Ready(());
+            }
+
+            waker.register(cx.waker());
+            // Completion is detected through the channel enable bit. Clear a
+            // stale TCMPL flag so it doesn't immediately fire the interrupt.
+            let chan = self.chan.as_mut();
+            chan.check_and_clear_interrupts(InterruptFlags::new().with_tcmpl(true));
+            chan.enable_interrupts(flags);
+
+            // The transfer could have completed before the interrupts were
+            // enabled
+            if self.complete() {
+                core::task::Poll::Ready(())
+            } else {
+                core::task::Poll::Pending
+            }
+        })
+        .await;
+
+        let chan = self.chan.as_mut();
+        chan.disable_interrupts(flags);
+        let error = chan.check_and_clear_interrupts(flags).terr();
+
+        let resources = self.stop();
+        if error {
+            Err(resources)
+        } else {
+            Ok(resources)
+        }
+    }
+}
+
 impl<S, D, C, W> Transfer<C, BufferPair<S, D>, W>
 where
     S: Buffer,
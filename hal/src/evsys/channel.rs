@@ -0,0 +1,452 @@
+//! # Abstractions over individual EVSYS channels
+//!
+//! # Connecting
+//!
+//! A [`Channel`] starts out unconnected (`Channel<Id, NoneT>`). Connecting it
+//! to an event [`Generator`] with [`Channel::connect`] or
+//! [`Channel::connect_with_clock`] returns a `Channel<Id, G>`, which tracks
+//! the generator at the type level. Any number of [`User`]s can then be
+//! attached to a connected channel with [`Channel::enable_user`].
+//!
+//! Each user can only listen to a single channel. Enabling a user on a
+//! channel will detach it from any channel it was previously attached to.
+//!
+//! # Disconnecting
+//!
+//! Calling [`Channel::disconnect`] returns the channel to its unconnected
+//! state. Users attached to the channel are not detached; they should be
+//! disabled beforehand with [`Channel::disable_user`].
+
+use super::{Generator, User};
+use crate::pac::{self, EVSYS};
+use crate::typelevel::{NoneT, Sealed};
+use core::marker::PhantomData;
+use modular_bitfield::prelude::*;
+use paste::paste;
+use seq_macro::seq;
+
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+pub use pac::evsys::channel::{EDGSEL_A as EdgeDetection, PATH_A as Path};
+
+#[cfg(feature = "min-samd51g")]
+pub use pac::evsys::channel::channel::{EDGSEL_A as EdgeDetection, PATH_A as Path};
+
+//==============================================================================
+// Channel IDs
+//==============================================================================
+
+/// Trait representing an EVSYS channel ID
+pub trait ChId {
+    const U8: u8;
+    const USIZE: usize;
+}
+
+/// Trait representing an EVSYS channel which has a generic clock, and can
+/// therefore use the synchronous and resynchronized paths.
+pub trait SyncChId: ChId {
+    /// Generic clock token of the channel
+    type Clock;
+}
+
+macro_rules! define_channels_struct {
+    ($num_channels:literal) => {
+        seq!(N in 0..$num_channels {
+            #(
+                /// Type alias for a channel number
+                pub enum Ch~N {}
+
+                impl ChId for Ch~N {
+                    const U8: u8 = N;
+                    const USIZE: usize = N;
+                }
+            )*
+
+            /// Struct generating individual handles to each EVSYS channel
+            pub struct Channels(
+                #(
+                    pub Channel<Ch~N, NoneT>,
+                )*
+            );
+
+            impl Channels {
+                #[inline]
+                pub(super) fn new() -> Self {
+                    Self(
+                        #(
+                            Channel { _id: PhantomData, _gen: PhantomData },
+                        )*
+                    )
+                }
+            }
+        });
+    };
+}
+
+#[cfg(feature = "samd11")]
+define_channels_struct!(6);
+
+#[cfg(feature = "samd21")]
+define_channels_struct!(12);
+
+#[cfg(feature = "min-samd51g")]
+define_channels_struct!(32);
+
+macro_rules! sync_channels {
+    ($($num:literal),+) => {
+        paste! {
+            $(
+                impl SyncChId for [<Ch $num>] {
+                    type Clock = crate::clock::[<Evsys $num Clock>];
+                }
+            )+
+        }
+    };
+}
+
+#[cfg(feature = "samd11")]
+sync_channels!(0, 1, 2, 3, 4, 5);
+
+#[cfg(any(feature = "samd21", feature = "min-samd51g"))]
+sync_channels!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11);
+
+//==============================================================================
+// OptionalGenerator
+//==============================================================================
+
+/// Type-level equivalent of `Option<Generator>`
+///
+/// See the [`OptionalKind`] documentation for more details on the pattern.
+///
+/// [`OptionalKind`]: crate::typelevel#optionalkind-trait-pattern
+pub trait OptionalGenerator: Sealed {}
+
+impl OptionalGenerator for NoneT {}
+
+impl<G: Generator> OptionalGenerator for G {}
+
+//==============================================================================
+// InterruptFlags
+//==============================================================================
+
+/// Interrupt sources available to an EVSYS channel
+///
+/// Interrupts are only available to channels using the synchronous or
+/// resynchronized path.
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+pub struct InterruptFlags {
+    /// Overrun: a new event was received before the previous one was
+    /// handled by all users
+    pub ovr: bool,
+    /// Event detected
+    pub evd: bool,
+    #[skip]
+    _reserved: B6,
+}
+
+impl Default for InterruptFlags {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//==============================================================================
+// Channel
+//==============================================================================
+
+/// EVSYS channel, routing the events of generator `G` to any number of users
+pub struct Channel<Id: ChId, G: OptionalGenerator> {
+    _id: PhantomData<Id>,
+    _gen: PhantomData<G>,
+}
+
+/// These methods may be used on any EVSYS channel
+impl<Id: ChId, G: OptionalGenerator> Channel<Id, G> {
+    #[inline]
+    fn evsys(&self) -> &pac::evsys::RegisterBlock {
+        // SAFETY: A channel only ever writes to the registers (or bits of shared
+        // registers) which belong to it, or to the user multiplexers of the users
+        // it was given.
+        unsafe { &*EVSYS::ptr() }
+    }
+
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    #[inline]
+    fn configure(&mut self, evgen: u8, path: Path, edge: EdgeDetection, swevt: bool) {
+        // The CHANNEL register selects the channel being written to, so it must be
+        // written in a single operation
+        self.evsys().channel.write(|w| unsafe {
+            w.channel().bits(Id::U8);
+            w.evgen().bits(evgen);
+            w.path().variant(path);
+            w.edgsel().variant(edge);
+            w.swevt().bit(swevt)
+        });
+    }
+
+    #[cfg(feature = "min-samd51g")]
+    #[inline]
+    fn configure(&mut self, evgen: u8, path: Path, edge: EdgeDetection) {
+        self.evsys().channel[Id::USIZE].channel.write(|w| unsafe {
+            w.evgen().bits(evgen);
+            w.path().variant(path);
+            w.edgsel().variant(edge)
+        });
+    }
+
+    /// Bit position of the overrun flag in the shared `INTFLAG`, `INTENSET`
+    /// and `INTENCLR` registers. The event detected flag is 8 bits higher.
+    /// The channel busy and user ready bits of `CHSTATUS` are laid out the
+    /// same way.
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    #[inline]
+    fn shared_bit() -> u32 {
+        match Id::U8 {
+            n @ 0..=7 => n as u32,
+            n => 16 + (n as u32 - 8),
+        }
+    }
+
+    #[inline]
+    fn into_generator<G2: OptionalGenerator>(self) -> Channel<Id, G2> {
+        Channel {
+            _id: PhantomData,
+            _gen: PhantomData,
+        }
+    }
+}
+
+/// These methods may only be used on an unconnected EVSYS channel
+impl<Id: ChId> Channel<Id, NoneT> {
+    /// Connect the channel to an event generator through the asynchronous
+    /// path.
+    ///
+    /// No clock is required, but edge detection and interrupts are not
+    /// available.
+    #[inline]
+    pub fn connect<G: Generator>(mut self, _generator: G) -> Channel<Id, G> {
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        self.configure(
+            G::ID,
+            Path::ASYNCHRONOUS,
+            EdgeDetection::NO_EVT_OUTPUT,
+            false,
+        );
+
+        #[cfg(feature = "min-samd51g")]
+        self.configure(G::ID, Path::ASYNCHRONOUS, EdgeDetection::NO_EVT_OUTPUT);
+
+        self.into_generator()
+    }
+}
+
+impl<Id: SyncChId> Channel<Id, NoneT> {
+    /// Connect the channel to an event generator through the specified
+    /// `path`, using the channel's generic clock.
+    ///
+    /// Edge detection selects which edges of the generator's signal are
+    /// turned into events. If `path` is [`Path::ASYNCHRONOUS`], `edge` is
+    /// ignored.
+    #[inline]
+    pub fn connect_with_clock<G: Generator>(
+        mut self,
+        _generator: G,
+        _clock: &Id::Clock,
+        path: Path,
+        edge: EdgeDetection,
+    ) -> Channel<Id, G> {
+        let edge = match path {
+            Path::ASYNCHRONOUS => EdgeDetection::NO_EVT_OUTPUT,
+            _ => edge,
+        };
+
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        self.configure(G::ID, path, edge, false);
+
+        #[cfg(feature = "min-samd51g")]
+        self.configure(G::ID, path, edge);
+
+        self.into_generator()
+    }
+}
+
+/// These methods may only be used on a connected EVSYS channel
+impl<Id: ChId, G: Generator> Channel<Id, G> {
+    /// Attach an event user to this channel
+    #[inline]
+    pub fn enable_user<U: User>(&mut self, _user: U) {
+        self.write_user::<U>(Id::U8 + 1);
+    }
+
+    /// Detach an event user from this channel
+    #[inline]
+    pub fn disable_user<U: User>(&mut self, _user: U) {
+        self.write_user::<U>(0);
+    }
+
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    #[inline]
+    fn write_user<U: User>(&mut self, channel: u8) {
+        // The USER register selects the user multiplexer being written to, so it
+        // must be written in a single operation
+        self.evsys().user.write(|w| unsafe {
+            w.user().bits(U::ID);
+            w.channel().bits(channel)
+        });
+    }
+
+    #[cfg(feature = "min-samd51g")]
+    #[inline]
+    fn write_user<U: User>(&mut self, channel: u8) {
+        self.evsys().user[U::ID as usize].write(|w| unsafe { w.channel().bits(channel) });
+    }
+
+    /// Returns `true` if an event is being propagated through the channel
+    #[inline]
+    pub fn is_busy(&self) -> bool {
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        let busy = self.evsys().chstatus.read().bits() & (1 << (Self::shared_bit() + 8)) != 0;
+
+        #[cfg(feature = "min-samd51g")]
+        let busy = self.evsys().channel[Id::USIZE]
+            .chstatus
+            .read()
+            .busych()
+            .bit_is_set();
+
+        busy
+    }
+
+    /// Returns `true` if all the users of the channel are ready to handle
+    /// a new event
+    #[inline]
+    pub fn users_ready(&self) -> bool {
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        let ready = self.evsys().chstatus.read().bits() & (1 << Self::shared_bit()) != 0;
+
+        #[cfg(feature = "min-samd51g")]
+        let ready = self.evsys().channel[Id::USIZE]
+            .chstatus
+            .read()
+            .rdyusr()
+            .bit_is_set();
+
+        ready
+    }
+
+    /// Issue a software event on the channel
+    #[inline]
+    pub fn software_event(&mut self) {
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        {
+            // Writing SWEVT requires rewriting the whole channel configuration
+            let (path, edge) = {
+                let channel = &self.evsys().channel;
+                // SAFETY: The channel configuration is read back by selecting the channel
+                // with an 8-bit write to the CHANNEL field, which leaves the rest of the
+                // configuration untouched. Datasheet section 24.8.2
+                unsafe { core::ptr::write_volatile(channel.as_ptr() as *mut u8, Id::U8) };
+                let r = channel.read();
+                (r.path().variant(), r.edgsel().variant())
+            };
+            self.configure(G::ID, path.unwrap_or(Path::ASYNCHRONOUS), edge, true);
+        }
+
+        #[cfg(feature = "min-samd51g")]
+        // SAFETY: Writing a zero to the other bits of SWEVT has no effect
+        self.evsys().swevt.write(|w| unsafe { w.bits(1 << Id::U8) });
+    }
+
+    /// Disconnect the channel from its generator
+    #[inline]
+    pub fn disconnect(mut self) -> Channel<Id, NoneT> {
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        self.configure(0, Path::SYNCHRONOUS, EdgeDetection::NO_EVT_OUTPUT, false);
+
+        #[cfg(feature = "min-samd51g")]
+        self.configure(0, Path::SYNCHRONOUS, EdgeDetection::NO_EVT_OUTPUT);
+
+        self.into_generator()
+    }
+}
+
+/// These methods may only be used on a connected EVSYS channel with a
+/// generic clock
+impl<Id: SyncChId, G: Generator> Channel<Id, G> {
+    /// Selectively enable interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: InterruptFlags) {
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        // SAFETY: Writing a zero to the other bits of INTENSET has no effect
+        self.evsys()
+            .intenset
+            .write(|w| unsafe { w.bits(Self::shared_flags(flags)) });
+
+        #[cfg(feature = "min-samd51g")]
+        // SAFETY: This is safe as InterruptFlags is only capable of writing in
+        // non-reserved bits
+        self.evsys().channel[Id::USIZE]
+            .chintenset
+            .write(|w| unsafe { w.bits(flags.into()) });
+    }
+
+    /// Selectively disable interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: InterruptFlags) {
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        // SAFETY: Writing a zero to the other bits of INTENCLR has no effect
+        self.evsys()
+            .intenclr
+            .write(|w| unsafe { w.bits(Self::shared_flags(flags)) });
+
+        #[cfg(feature = "min-samd51g")]
+        // SAFETY: This is safe as InterruptFlags is only capable of writing in
+        // non-reserved bits
+        self.evsys().channel[Id::USIZE]
+            .chintenclr
+            .write(|w| unsafe { w.bits(flags.into()) });
+    }
+
+    /// Check the specified `flags`, clear then return any that were set
+    #[inline]
+    pub fn check_and_clear_interrupts(&mut self, flags: InterruptFlags) -> InterruptFlags {
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        let cleared = {
+            let set = self.evsys().intflag.read().bits() & Self::shared_flags(flags);
+            // SAFETY: Writing a zero to the other bits of INTFLAG has no effect
+            self.evsys().intflag.write(|w| unsafe { w.bits(set) });
+            let bit = Self::shared_bit();
+            let ovr = set & (1 << bit) != 0;
+            let evd = set & (1 << (bit + 8)) != 0;
+            InterruptFlags::new().with_ovr(ovr).with_evd(evd)
+        };
+
+        #[cfg(feature = "min-samd51g")]
+        let cleared = {
+            let mut cleared = 0;
+            self.evsys().channel[Id::USIZE].chintflag.modify(|r, w| {
+                cleared = r.bits() & flags.into_bytes()[0];
+                unsafe { w.bits(cleared) }
+            });
+            InterruptFlags::from_bytes([cleared])
+        };
+
+        cleared
+    }
+
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    #[inline]
+    fn shared_flags(flags: InterruptFlags) -> u32 {
+        let bit = Self::shared_bit();
+        let mut bits = 0;
+        if flags.ovr() {
+            bits |= 1 << bit;
+        }
+        if flags.evd() {
+            bits |= 1 << (bit + 8);
+        }
+        bits
+    }
+}
@@ -0,0 +1,202 @@
+//! Event generators and users of the SAMD11 and SAMD21 chips
+//!
+//! See the EVSYS chapter of the ATSAMD11 and ATSAMD21 datasheets for the
+//! generator (EVGEN) and user multiplexer numbers.
+
+/// Event generators
+#[cfg(feature = "samd11")]
+pub mod generators {
+    impl_generators! {
+        RtcCmp0 = 0x01,
+        RtcCmp1 = 0x02,
+        RtcOvf = 0x03,
+        RtcPer0 = 0x04,
+        RtcPer1 = 0x05,
+        RtcPer2 = 0x06,
+        RtcPer3 = 0x07,
+        RtcPer4 = 0x08,
+        RtcPer5 = 0x09,
+        RtcPer6 = 0x0A,
+        RtcPer7 = 0x0B,
+        EicExtint0 = 0x0C,
+        EicExtint1 = 0x0D,
+        EicExtint2 = 0x0E,
+        EicExtint3 = 0x0F,
+        EicExtint4 = 0x10,
+        EicExtint5 = 0x11,
+        EicExtint6 = 0x12,
+        EicExtint7 = 0x13,
+        DmacCh0 = 0x14,
+        DmacCh1 = 0x15,
+        DmacCh2 = 0x16,
+        DmacCh3 = 0x17,
+        Tcc0Ovf = 0x18,
+        Tcc0Trg = 0x19,
+        Tcc0Cnt = 0x1A,
+        Tcc0Mc0 = 0x1B,
+        Tcc0Mc1 = 0x1C,
+        Tcc0Mc2 = 0x1D,
+        Tcc0Mc3 = 0x1E,
+        Tc1Ovf = 0x1F,
+        Tc1Mc0 = 0x20,
+        Tc1Mc1 = 0x21,
+        Tc2Ovf = 0x22,
+        Tc2Mc0 = 0x23,
+        Tc2Mc1 = 0x24,
+        AdcResrdy = 0x25,
+        AdcWinmon = 0x26,
+        AcComp0 = 0x27,
+        AcComp1 = 0x28,
+        AcWin0 = 0x29,
+        DacEmpty = 0x2A,
+        PtcEoc = 0x2B,
+        PtcWcomp = 0x2C,
+    }
+}
+
+/// Event users
+#[cfg(feature = "samd11")]
+pub mod users {
+    impl_users! {
+        DmacCh0 = 0x00,
+        DmacCh1 = 0x01,
+        DmacCh2 = 0x02,
+        DmacCh3 = 0x03,
+        Tcc0Ev0 = 0x04,
+        Tcc0Ev1 = 0x05,
+        Tcc0Mc0 = 0x06,
+        Tcc0Mc1 = 0x07,
+        Tcc0Mc2 = 0x08,
+        Tcc0Mc3 = 0x09,
+        Tc1Evu = 0x0A,
+        Tc2Evu = 0x0B,
+        AdcStart = 0x0C,
+        AdcSync = 0x0D,
+        AcSoc0 = 0x0E,
+        AcSoc1 = 0x0F,
+        DacStart = 0x10,
+        PtcStconv = 0x11,
+    }
+}
+
+/// Event generators
+#[cfg(feature = "samd21")]
+pub mod generators {
+    impl_generators! {
+        RtcCmp0 = 0x01,
+        RtcCmp1 = 0x02,
+        RtcOvf = 0x03,
+        RtcPer0 = 0x04,
+        RtcPer1 = 0x05,
+        RtcPer2 = 0x06,
+        RtcPer3 = 0x07,
+        RtcPer4 = 0x08,
+        RtcPer5 = 0x09,
+        RtcPer6 = 0x0A,
+        RtcPer7 = 0x0B,
+        EicExtint0 = 0x0C,
+        EicExtint1 = 0x0D,
+        EicExtint2 = 0x0E,
+        EicExtint3 = 0x0F,
+        EicExtint4 = 0x10,
+        EicExtint5 = 0x11,
+        EicExtint6 = 0x12,
+        EicExtint7 = 0x13,
+        EicExtint8 = 0x14,
+        EicExtint9 = 0x15,
+        EicExtint10 = 0x16,
+        EicExtint11 = 0x17,
+        EicExtint12 = 0x18,
+        EicExtint13 = 0x19,
+        EicExtint14 = 0x1A,
+        EicExtint15 = 0x1B,
+        DmacCh0 = 0x1C,
+        DmacCh1 = 0x1D,
+        DmacCh2 = 0x1E,
+        DmacCh3 = 0x1F,
+        Tcc0Ovf = 0x20,
+        Tcc0Trg = 0x21,
+        Tcc0Cnt = 0x22,
+        Tcc0Mc0 = 0x23,
+        Tcc0Mc1 = 0x24,
+        Tcc0Mc2 = 0x25,
+        Tcc0Mc3 = 0x26,
+        Tcc1Ovf = 0x27,
+        Tcc1Trg = 0x28,
+        Tcc1Cnt = 0x29,
+        Tcc1Mc0 = 0x2A,
+        Tcc1Mc1 = 0x2B,
+        Tcc2Ovf = 0x2C,
+        Tcc2Trg = 0x2D,
+        Tcc2Cnt = 0x2E,
+        Tcc2Mc0 = 0x2F,
+        Tcc2Mc1 = 0x30,
+        Tc3Ovf = 0x31,
+        Tc3Mc0 = 0x32,
+        Tc3Mc1 = 0x33,
+        Tc4Ovf = 0x34,
+        Tc4Mc0 = 0x35,
+        Tc4Mc1 = 0x36,
+        Tc5Ovf = 0x37,
+        Tc5Mc0 = 0x38,
+        Tc5Mc1 = 0x39,
+        #[cfg(feature = "min-samd21j")]
+        Tc6Ovf = 0x3A,
+        #[cfg(feature = "min-samd21j")]
+        Tc6Mc0 = 0x3B,
+        #[cfg(feature = "min-samd21j")]
+        Tc6Mc1 = 0x3C,
+        #[cfg(feature = "min-samd21j")]
+        Tc7Ovf = 0x3D,
+        #[cfg(feature = "min-samd21j")]
+        Tc7Mc0 = 0x3E,
+        #[cfg(feature = "min-samd21j")]
+        Tc7Mc1 = 0x3F,
+        AdcResrdy = 0x40,
+        AdcWinmon = 0x41,
+        AcComp0 = 0x42,
+        AcComp1 = 0x43,
+        AcWin0 = 0x44,
+        DacEmpty = 0x45,
+        PtcEoc = 0x46,
+        PtcWcomp = 0x47,
+    }
+}
+
+/// Event users
+#[cfg(feature = "samd21")]
+pub mod users {
+    impl_users! {
+        DmacCh0 = 0x00,
+        DmacCh1 = 0x01,
+        DmacCh2 = 0x02,
+        DmacCh3 = 0x03,
+        Tcc0Ev0 = 0x04,
+        Tcc0Ev1 = 0x05,
+        Tcc0Mc0 = 0x06,
+        Tcc0Mc1 = 0x07,
+        Tcc0Mc2 = 0x08,
+        Tcc0Mc3 = 0x09,
+        Tcc1Ev0 = 0x0A,
+        Tcc1Ev1 = 0x0B,
+        Tcc1Mc0 = 0x0C,
+        Tcc1Mc1 = 0x0D,
+        Tcc2Ev0 = 0x0E,
+        Tcc2Ev1 = 0x0F,
+        Tcc2Mc0 = 0x10,
+        Tcc2Mc1 = 0x11,
+        Tc3Evu = 0x12,
+        Tc4Evu = 0x13,
+        Tc5Evu = 0x14,
+        #[cfg(feature = "min-samd21j")]
+        Tc6Evu = 0x15,
+        #[cfg(feature = "min-samd21j")]
+        Tc7Evu = 0x16,
+        AdcStart = 0x17,
+        AdcSync = 0x18,
+        AcSoc0 = 0x19,
+        AcSoc1 = 0x1A,
+        DacStart = 0x1B,
+        PtcStconv = 0x1C,
+    }
+}
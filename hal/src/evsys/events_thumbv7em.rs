@@ -0,0 +1,235 @@
+//! Event generators and users of the SAMD51/SAME5x chips
+//!
+//! See the EVSYS chapter of the SAMD5x/E5x datasheet for the generator (EVGEN)
+//! and user multiplexer numbers.
+
+/// Event generators
+pub mod generators {
+    impl_generators! {
+        OscctrlXoscFail0 = 0x01,
+        OscctrlXoscFail1 = 0x02,
+        Osc32kctrlXosc32kFail = 0x03,
+        RtcPer0 = 0x04,
+        RtcPer1 = 0x05,
+        RtcPer2 = 0x06,
+        RtcPer3 = 0x07,
+        RtcPer4 = 0x08,
+        RtcPer5 = 0x09,
+        RtcPer6 = 0x0A,
+        RtcPer7 = 0x0B,
+        RtcCmp0 = 0x0C,
+        RtcCmp1 = 0x0D,
+        RtcCmp2 = 0x0E,
+        RtcCmp3 = 0x0F,
+        RtcTamper = 0x10,
+        RtcOvf = 0x11,
+        EicExtint0 = 0x12,
+        EicExtint1 = 0x13,
+        EicExtint2 = 0x14,
+        EicExtint3 = 0x15,
+        EicExtint4 = 0x16,
+        EicExtint5 = 0x17,
+        EicExtint6 = 0x18,
+        EicExtint7 = 0x19,
+        EicExtint8 = 0x1A,
+        EicExtint9 = 0x1B,
+        EicExtint10 = 0x1C,
+        EicExtint11 = 0x1D,
+        EicExtint12 = 0x1E,
+        EicExtint13 = 0x1F,
+        EicExtint14 = 0x20,
+        EicExtint15 = 0x21,
+        DmacCh0 = 0x22,
+        DmacCh1 = 0x23,
+        DmacCh2 = 0x24,
+        DmacCh3 = 0x25,
+        PacAccerr = 0x26,
+        Tcc0Ovf = 0x29,
+        Tcc0Trg = 0x2A,
+        Tcc0Cnt = 0x2B,
+        Tcc0Mc0 = 0x2C,
+        Tcc0Mc1 = 0x2D,
+        Tcc0Mc2 = 0x2E,
+        Tcc0Mc3 = 0x2F,
+        Tcc0Mc4 = 0x30,
+        Tcc0Mc5 = 0x31,
+        Tcc1Ovf = 0x32,
+        Tcc1Trg = 0x33,
+        Tcc1Cnt = 0x34,
+        Tcc1Mc0 = 0x35,
+        Tcc1Mc1 = 0x36,
+        Tcc1Mc2 = 0x37,
+        Tcc1Mc3 = 0x38,
+        Tcc2Ovf = 0x39,
+        Tcc2Trg = 0x3A,
+        Tcc2Cnt = 0x3B,
+        Tcc2Mc0 = 0x3C,
+        Tcc2Mc1 = 0x3D,
+        Tcc2Mc2 = 0x3E,
+        #[cfg(feature = "min-samd51j")]
+        Tcc3Ovf = 0x3F,
+        #[cfg(feature = "min-samd51j")]
+        Tcc3Trg = 0x40,
+        #[cfg(feature = "min-samd51j")]
+        Tcc3Cnt = 0x41,
+        #[cfg(feature = "min-samd51j")]
+        Tcc3Mc0 = 0x42,
+        #[cfg(feature = "min-samd51j")]
+        Tcc3Mc1 = 0x43,
+        #[cfg(feature = "min-samd51j")]
+        Tcc4Ovf = 0x44,
+        #[cfg(feature = "min-samd51j")]
+        Tcc4Trg = 0x45,
+        #[cfg(feature = "min-samd51j")]
+        Tcc4Cnt = 0x46,
+        #[cfg(feature = "min-samd51j")]
+        Tcc4Mc0 = 0x47,
+        #[cfg(feature = "min-samd51j")]
+        Tcc4Mc1 = 0x48,
+        Tc0Ovf = 0x49,
+        Tc0Mc0 = 0x4A,
+        Tc0Mc1 = 0x4B,
+        Tc1Ovf = 0x4C,
+        Tc1Mc0 = 0x4D,
+        Tc1Mc1 = 0x4E,
+        Tc2Ovf = 0x4F,
+        Tc2Mc0 = 0x50,
+        Tc2Mc1 = 0x51,
+        Tc3Ovf = 0x52,
+        Tc3Mc0 = 0x53,
+        Tc3Mc1 = 0x54,
+        #[cfg(feature = "min-samd51j")]
+        Tc4Ovf = 0x55,
+        #[cfg(feature = "min-samd51j")]
+        Tc4Mc0 = 0x56,
+        #[cfg(feature = "min-samd51j")]
+        Tc4Mc1 = 0x57,
+        #[cfg(feature = "min-samd51j")]
+        Tc5Ovf = 0x58,
+        #[cfg(feature = "min-samd51j")]
+        Tc5Mc0 = 0x59,
+        #[cfg(feature = "min-samd51j")]
+        Tc5Mc1 = 0x5A,
+        #[cfg(feature = "min-samd51n")]
+        Tc6Ovf = 0x5B,
+        #[cfg(feature = "min-samd51n")]
+        Tc6Mc0 = 0x5C,
+        #[cfg(feature = "min-samd51n")]
+        Tc6Mc1 = 0x5D,
+        #[cfg(feature = "min-samd51n")]
+        Tc7Ovf = 0x5E,
+        #[cfg(feature = "min-samd51n")]
+        Tc7Mc0 = 0x5F,
+        #[cfg(feature = "min-samd51n")]
+        Tc7Mc1 = 0x60,
+        PdecOvf = 0x61,
+        PdecErr = 0x62,
+        PdecDir = 0x63,
+        PdecVlc = 0x64,
+        PdecMc0 = 0x65,
+        PdecMc1 = 0x66,
+        Adc0Resrdy = 0x67,
+        Adc0Winmon = 0x68,
+        Adc1Resrdy = 0x69,
+        Adc1Winmon = 0x6A,
+        AcComp0 = 0x6B,
+        AcComp1 = 0x6C,
+        AcWin0 = 0x6D,
+        DacEmpty0 = 0x6E,
+        DacEmpty1 = 0x6F,
+        DacResrdy0 = 0x70,
+        DacResrdy1 = 0x71,
+        #[cfg(any(feature = "same53", feature = "same54"))]
+        GmacTsuCmp = 0x72,
+        TrngReady = 0x73,
+        CclLutout0 = 0x74,
+        CclLutout1 = 0x75,
+        CclLutout2 = 0x76,
+        CclLutout3 = 0x77,
+    }
+}
+
+/// Event users
+pub mod users {
+    impl_users! {
+        RtcTamper = 0x00,
+        PortEv0 = 0x01,
+        PortEv1 = 0x02,
+        PortEv2 = 0x03,
+        PortEv3 = 0x04,
+        DmacCh0 = 0x05,
+        DmacCh1 = 0x06,
+        DmacCh2 = 0x07,
+        DmacCh3 = 0x08,
+        DmacCh4 = 0x09,
+        DmacCh5 = 0x0A,
+        DmacCh6 = 0x0B,
+        DmacCh7 = 0x0C,
+        TalBrk = 0x0D,
+        Cm4TraceStart = 0x0E,
+        Cm4TraceStop = 0x0F,
+        Cm4TraceTrig = 0x10,
+        Tcc0Ev0 = 0x11,
+        Tcc0Ev1 = 0x12,
+        Tcc0Mc0 = 0x13,
+        Tcc0Mc1 = 0x14,
+        Tcc0Mc2 = 0x15,
+        Tcc0Mc3 = 0x16,
+        Tcc0Mc4 = 0x17,
+        Tcc0Mc5 = 0x18,
+        Tcc1Ev0 = 0x19,
+        Tcc1Ev1 = 0x1A,
+        Tcc1Mc0 = 0x1B,
+        Tcc1Mc1 = 0x1C,
+        Tcc1Mc2 = 0x1D,
+        Tcc1Mc3 = 0x1E,
+        Tcc2Ev0 = 0x1F,
+        Tcc2Ev1 = 0x20,
+        Tcc2Mc0 = 0x21,
+        Tcc2Mc1 = 0x22,
+        Tcc2Mc2 = 0x23,
+        #[cfg(feature = "min-samd51j")]
+        Tcc3Ev0 = 0x24,
+        #[cfg(feature = "min-samd51j")]
+        Tcc3Ev1 = 0x25,
+        #[cfg(feature = "min-samd51j")]
+        Tcc3Mc0 = 0x26,
+        #[cfg(feature = "min-samd51j")]
+        Tcc3Mc1 = 0x27,
+        #[cfg(feature = "min-samd51j")]
+        Tcc4Ev0 = 0x28,
+        #[cfg(feature = "min-samd51j")]
+        Tcc4Ev1 = 0x29,
+        #[cfg(feature = "min-samd51j")]
+        Tcc4Mc0 = 0x2A,
+        #[cfg(feature = "min-samd51j")]
+        Tcc4Mc1 = 0x2B,
+        Tc0Evu = 0x2C,
+        Tc1Evu = 0x2D,
+        Tc2Evu = 0x2E,
+        Tc3Evu = 0x2F,
+        #[cfg(feature = "min-samd51j")]
+        Tc4Evu = 0x30,
+        #[cfg(feature = "min-samd51j")]
+        Tc5Evu = 0x31,
+        #[cfg(feature = "min-samd51n")]
+        Tc6Evu = 0x32,
+        #[cfg(feature = "min-samd51n")]
+        Tc7Evu = 0x33,
+        PdecEvu0 = 0x34,
+        PdecEvu1 = 0x35,
+        PdecEvu2 = 0x36,
+        Adc0Start = 0x37,
+        Adc0Sync = 0x38,
+        Adc1Start = 0x39,
+        Adc1Sync = 0x3A,
+        AcSoc0 = 0x3B,
+        AcSoc1 = 0x3C,
+        DacStart0 = 0x3D,
+        DacStart1 = 0x3E,
+        CclLutin0 = 0x3F,
+        CclLutin1 = 0x40,
+        CclLutin2 = 0x41,
+        CclLutin3 = 0x42,
+    }
+}
@@ -0,0 +1,208 @@
+//! # Event System
+//!
+//! The Event System (EVSYS) routes signals, called events, from peripherals
+//! (event generators) to other peripherals (event users) without any CPU
+//! involvement. For example, a TC overflow can start an ADC conversion, and
+//! the ADC result ready event can trigger a DMA transfer, all while the CPU is
+//! asleep.
+//!
+//! # Initializing
+//!
+//! The EVSYS should be initialized using the [`EventSystem::init`] method. It
+//! will consume the EVSYS object generated by the PAC. Individual channels
+//! are then obtained through the [`EventSystem::split`] method.
+//!
+//! # Generators and users
+//!
+//! Event generators and users are represented by zero-sized marker types,
+//! defined in the [`generators`] and [`users`] modules respectively. They
+//! carry the generator and user IDs of the selected chip, so that channels
+//! can only be connected to generators and users which actually exist.
+//!
+//! Note that the generating peripheral must still be configured to output
+//! its event (for example, with `enable_event` on an EIC pin), and the
+//! receiving peripheral to accept it (for example, with
+//! [`dmac::Channel::enable_event_input`](crate::dmac::Channel::enable_event_input)).
+//!
+//! # Channel paths
+//!
+//! A channel connected with [`Channel::connect`] uses the asynchronous path.
+//! It requires no clock, but the event is passed as-is to the users, and
+//! neither edge detection nor the channel interrupts are available. The
+//! synchronous and resynchronized paths are selected with
+//! [`Channel::connect_with_clock`], which requires the generic clock of the
+//! corresponding channel to be configured through the
+//! [`GenericClockController`](crate::clock::GenericClockController).
+#![cfg_attr(
+    feature = "min-samd51g",
+    doc = "
+Only channels 0 to 11 have a generic clock on SAMD51/SAME5x chips. The other
+channels can only use the asynchronous path."
+)]
+//!
+//! # Example
+//!
+//! ```
+//! let mut peripherals = Peripherals::take().unwrap();
+//! let mut evsys = EventSystem::init(peripherals.EVSYS, &mut peripherals.PM);
+//! let channels = evsys.split();
+//!
+//! // Start an ADC conversion every time the TC3 timer overflows
+//! let mut ch0 = channels.0.connect(generators::Tc3Ovf);
+//! ch0.enable_user(users::AdcStart);
+//! ```
+
+// This is necessary until modular_bitfield fixes all their identity_op warnings
+#![allow(clippy::identity_op)]
+#![allow(unused_braces)]
+
+use crate::pac::EVSYS;
+use crate::typelevel::Sealed;
+
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+use crate::pac::PM;
+
+#[cfg(feature = "min-samd51g")]
+use crate::pac::MCLK;
+
+pub mod channel;
+pub use channel::*;
+
+//==============================================================================
+// Generator & User
+//==============================================================================
+
+/// Type-level enum of event generators
+///
+/// The implementations of this trait are the marker types in the
+/// [`generators`] module.
+pub trait Generator: Sealed {
+    /// EVGEN value selecting this generator
+    const ID: u8;
+}
+
+/// Type-level enum of event users
+///
+/// The implementations of this trait are the marker types in the [`users`]
+/// module.
+pub trait User: Sealed {
+    /// User multiplexer number
+    const ID: u8;
+}
+
+macro_rules! impl_generators {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $Type:ident = $id:literal,
+        )+
+    ) => {
+        $(
+            $( #[$cfg] )?
+            #[doc = concat!("`", stringify!($Type), "` event generator")]
+            pub struct $Type;
+
+            $( #[$cfg] )?
+            impl $crate::typelevel::Sealed for $Type {}
+
+            $( #[$cfg] )?
+            impl $crate::evsys::Generator for $Type {
+                const ID: u8 = $id;
+            }
+        )+
+    };
+}
+
+macro_rules! impl_users {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $Type:ident = $id:literal,
+        )+
+    ) => {
+        $(
+            $( #[$cfg] )?
+            #[doc = concat!("`", stringify!($Type), "` event user")]
+            pub struct $Type;
+
+            $( #[$cfg] )?
+            impl $crate::typelevel::Sealed for $Type {}
+
+            $( #[$cfg] )?
+            impl $crate::evsys::User for $Type {
+                const ID: u8 = $id;
+            }
+        )+
+    };
+}
+
+#[cfg(any(feature = "samd11", feature = "samd21"))]
+#[path = "events_thumbv6m.rs"]
+mod events;
+
+#[cfg(feature = "min-samd51g")]
+#[path = "events_thumbv7em.rs"]
+mod events;
+
+pub use events::{generators, users};
+
+//==============================================================================
+// EventSystem
+//==============================================================================
+
+/// Initialized Event System
+pub struct EventSystem {
+    evsys: EVSYS,
+}
+
+impl EventSystem {
+    /// Enable the EVSYS bus clock, reset the peripheral and return an
+    /// `EventSystem` ready to be [`split`](EventSystem::split) into channels.
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    #[inline]
+    pub fn init(evsys: EVSYS, pm: &mut PM) -> Self {
+        pm.apbcmask.modify(|_, w| w.evsys_().set_bit());
+        Self::reset(evsys)
+    }
+
+    /// Enable the EVSYS bus clock, reset the peripheral and return an
+    /// `EventSystem` ready to be [`split`](EventSystem::split) into channels.
+    #[cfg(feature = "min-samd51g")]
+    #[inline]
+    pub fn init(evsys: EVSYS, mclk: &mut MCLK) -> Self {
+        mclk.apbbmask.modify(|_, w| w.evsys_().set_bit());
+        Self::reset(evsys)
+    }
+
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    #[inline]
+    fn reset(evsys: EVSYS) -> Self {
+        evsys.ctrl.write(|w| w.swrst().set_bit());
+        Self { evsys }
+    }
+
+    #[cfg(feature = "min-samd51g")]
+    #[inline]
+    fn reset(evsys: EVSYS) -> Self {
+        evsys.ctrla.write(|w| w.swrst().set_bit());
+        while evsys.ctrla.read().swrst().bit_is_set() {}
+        Self { evsys }
+    }
+
+    /// Split the EVSYS into individual channels
+    #[inline]
+    pub fn split(&mut self) -> Channels {
+        Channels::new()
+    }
+
+    /// Release the EVSYS and return the register block.
+    ///
+    /// **Note**: The [`Channels`] struct is consumed by this method. This
+    /// means that any [`Channel`] obtained by [`split`](EventSystem::split)
+    /// must be disconnected and moved back into the [`Channels`] struct
+    /// before being able to pass it into [`free`](EventSystem::free).
+    #[inline]
+    pub fn free(self, _channels: Channels) -> EVSYS {
+        self.evsys
+    }
+}
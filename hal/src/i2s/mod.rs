@@ -0,0 +1,1034 @@
+//! # Inter-IC Sound
+//!
+//! The I2S peripheral contains two clock units, which generate (or receive)
+//! the serial clock `SCK` and frame sync `FS` signals, and two serializers,
+//! which shift the audio data in or out on an `SD` pin, clocked by one of the
+//! clock units.
+//!
+//! On SAMD21 chips, [`Serializer0`] and [`Serializer1`] can both be used as a
+//! transmitter or a receiver. On SAMD5x/E5x chips, the [`Transmitter`] drives
+//! the `SDO` pin and the [`Receiver`] samples the `SDI` pin.
+//!
+//! # Clock units
+//!
+//! A clock unit is configured with [`I2s::configure_clock_unit`], which takes
+//! the generic clock of the unit, its [`ClockPins`] and a [`ClockConfig`].
+//! The [`ClockConfig`] describes the frame format: the number and size of the
+//! slots, and the shape of the frame sync signal. In [`ClockMode::Master`],
+//! `SCK` and `FS` are divided from the generic clock to obtain the requested
+//! frame rate, and are output on the `SCK` and `FS` pins, if any. The generic
+//! clock can also be output on the `MCK` pin to clock an external codec. In
+//! [`ClockMode::Slave`], `SCK` and `FS` are inputs.
+//!
+//! # Serializers
+//!
+//! Serializers are configured with [`I2s::configure_tx`] and
+//! [`I2s::configure_rx`], which take the [`ClockUnit`] the serializer is
+//! clocked by, the `SD` pin and a [`SerializerConfig`]. Data is exchanged
+//! with the returned [`Tx`] and [`Rx`] as 32-bit words, one per slot. With
+//! the compact [`DataSize`]s, two 16-bit or four 8-bit samples are packed in
+//! each word.
+//!
+//! # TDM
+//!
+//! Up to 8 slots can be used in a frame, by setting [`ClockConfig::slots`].
+//! [`ClockConfig::tdm`] provides the usual TDM frame format, with a frame
+//! sync pulse of one `SCK` period. Slots can be ignored by a serializer with
+//! [`SerializerConfig::disabled_slots`].
+//!
+//! # PDM microphones
+//!
+//! A receiver configured with [`SerializerConfig::pdm`] samples its `SD` pin
+//! on both edges of `SCK`, which allows two PDM microphones sharing a data
+//! line to be read. The clock unit should be a master, and no `FS` pin is
+//! required. The received bitstreams must be filtered and decimated in
+//! software to obtain PCM samples.
+//!
+//! # Example
+//!
+//! ```
+//! let mut i2s = I2s::new(peripherals.I2S, &mut peripherals.PM);
+//! let clock_unit = i2s
+//!     .configure_clock_unit(
+//!         &i2s0_clock,
+//!         ClockPins::default().sck(pins.pa10).fs(pins.pa11),
+//!         ClockConfig::i2s(SlotSize::_32, ClockMode::Master { frame_rate: Hertz(48_000) }),
+//!     )
+//!     .unwrap();
+//! let mut tx = i2s.configure_tx(&clock_unit, pins.pa07, SerializerConfig::new(DataSize::_32));
+//!
+//! nb::block!(tx.write(left)).unwrap();
+//! nb::block!(tx.write(right)).unwrap();
+//! ```
+//!
+//! # Streaming with DMA
+#![cfg_attr(
+    feature = "dma",
+    doc = "
+[`Tx`] and [`Rx`] implement the DMAC [`Buffer`](crate::dmac::Buffer) trait,
+targeting the data register of the serializer, with one 32-bit beat per slot.
+Use [`Tx::dma_trigger`] or [`Rx::dma_trigger`] as the trigger source of the
+transfer, with a `BEAT` trigger action. For continuous audio streams, use a
+circular [`LinkedTransfer`](crate::dmac::LinkedTransfer) with two blocks, so
+that the CPU can process one block while the DMAC works on the other."
+)]
+
+use core::convert::Infallible;
+use core::marker::PhantomData;
+use core::ptr;
+
+use bitflags::bitflags;
+
+use crate::gpio::{AnyPin, Pin, PinId};
+use crate::pac::{i2s, I2S};
+use crate::time::Hertz;
+use crate::typelevel::{NoneT, Sealed};
+
+#[cfg(feature = "samd21")]
+use crate::pac::PM;
+
+#[cfg(feature = "min-samd51j")]
+use crate::pac::MCLK as PM;
+
+#[cfg(feature = "samd21")]
+#[path = "pads_thumbv6m.rs"]
+mod pads;
+
+#[cfg(feature = "min-samd51j")]
+#[path = "pads_thumbv7em.rs"]
+mod pads;
+
+pub use pads::I2sAlternate;
+
+/// Data delay between the frame sync edge and the first data bit
+pub use i2s::clkctrl::BITDELAY_A as DataDelay;
+/// Width of the frame sync pulse
+pub use i2s::clkctrl::FSWIDTH_A as FrameSyncWidth;
+/// Size of a slot, in bits
+pub use i2s::clkctrl::SLOTSIZE_A as SlotSize;
+
+#[cfg(feature = "samd21")]
+use i2s::serctrl as serializer;
+#[cfg(feature = "min-samd51j")]
+use i2s::txctrl as serializer;
+
+/// Number of significant bits of the data words, and their packing
+pub use serializer::DATASIZE_A as DataSize;
+/// Extension of the data words to the slot size
+pub use serializer::EXTEND_A as Extend;
+/// Alignment of the data bits within a slot
+pub use serializer::SLOTADJ_A as SlotAdjust;
+/// Line level when a transmitter slot is disabled
+pub use serializer::TXDEFAULT_A as TxDefault;
+/// Alignment of the data bits within a word
+pub use serializer::WORDADJ_A as WordAdjust;
+
+/// Largest `MCKDIV` and `MCKOUTDIV` divider
+#[cfg(feature = "samd21")]
+const MAX_DIVIDER: u32 = 32;
+/// Largest `MCKDIV` and `MCKOUTDIV` divider
+#[cfg(feature = "min-samd51j")]
+const MAX_DIVIDER: u32 = 64;
+
+// CTRLA and SYNCBUSY bits
+const SWRST: u8 = 1 << 0;
+const ENABLE: u8 = 1 << 1;
+const CKEN0: u8 = 1 << 2;
+const SEREN0: u8 = 1 << 4;
+
+// Serializer control register fields, common to SERCTRL, TXCTRL and RXCTRL
+const SERMODE_RX: u32 = 0;
+const SERMODE_TX: u32 = 1;
+const SERMODE_PDM2: u32 = 2;
+const TXDEFAULT_POS: u32 = 2;
+const TXSAME: u32 = 1 << 4;
+const CLKSEL_POS: u32 = 5;
+const SLOTADJ_POS: u32 = 7;
+const DATASIZE_POS: u32 = 8;
+const WORDADJ_POS: u32 = 12;
+const EXTEND_POS: u32 = 13;
+const BITREV: u32 = 1 << 15;
+const SLOTDIS_POS: u32 = 16;
+const MONO: u32 = 1 << 24;
+
+//==============================================================================
+// Error
+//==============================================================================
+
+/// Errors returned when configuring a clock unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The number of slots is not between 1 and 8
+    InvalidSlots,
+    /// The requested frame rate or master clock divider can't be obtained
+    /// from the generic clock frequency
+    InvalidDivider,
+}
+
+//==============================================================================
+// ClockUnitId
+//==============================================================================
+
+/// Type-level enum of the clock units
+pub trait ClockUnitId: Sealed {
+    /// Clock unit number
+    const N: usize;
+    /// Generic clock of the clock unit
+    type Clock: ClockFreq;
+}
+
+/// Frequency of a generic clock
+pub trait ClockFreq {
+    /// Return the frequency of the clock
+    fn freq(&self) -> Hertz;
+}
+
+/// Clock unit 0
+pub enum ClockUnit0 {}
+
+impl Sealed for ClockUnit0 {}
+
+impl ClockUnitId for ClockUnit0 {
+    const N: usize = 0;
+    type Clock = crate::clock::I2S0Clock;
+}
+
+/// Clock unit 1
+pub enum ClockUnit1 {}
+
+impl Sealed for ClockUnit1 {}
+
+impl ClockUnitId for ClockUnit1 {
+    const N: usize = 1;
+    type Clock = crate::clock::I2S1Clock;
+}
+
+impl ClockFreq for crate::clock::I2S0Clock {
+    #[inline]
+    fn freq(&self) -> Hertz {
+        self.freq()
+    }
+}
+
+impl ClockFreq for crate::clock::I2S1Clock {
+    #[inline]
+    fn freq(&self) -> Hertz {
+        self.freq()
+    }
+}
+
+//==============================================================================
+// SerializerId
+//==============================================================================
+
+/// Type-level enum of the serializers
+pub trait SerializerId: Sealed {
+    /// Serializer number
+    const N: usize;
+    /// Mask of the serializer bits in each group of `INTFLAG` bits
+    #[doc(hidden)]
+    const FLAGS: u16;
+}
+
+/// Serializer which can be used as a transmitter
+pub trait TxSerializer: SerializerId {}
+
+/// Serializer which can be used as a receiver
+pub trait RxSerializer: SerializerId {}
+
+/// Serializer 0
+#[cfg(feature = "samd21")]
+pub enum Serializer0 {}
+
+#[cfg(feature = "samd21")]
+impl Sealed for Serializer0 {}
+
+#[cfg(feature = "samd21")]
+impl SerializerId for Serializer0 {
+    const N: usize = 0;
+    const FLAGS: u16 = 1 << 0;
+}
+
+#[cfg(feature = "samd21")]
+impl TxSerializer for Serializer0 {}
+
+#[cfg(feature = "samd21")]
+impl RxSerializer for Serializer0 {}
+
+/// Serializer 1
+#[cfg(feature = "samd21")]
+pub enum Serializer1 {}
+
+#[cfg(feature = "samd21")]
+impl Sealed for Serializer1 {}
+
+#[cfg(feature = "samd21")]
+impl SerializerId for Serializer1 {
+    const N: usize = 1;
+    const FLAGS: u16 = 1 << 1;
+}
+
+#[cfg(feature = "samd21")]
+impl TxSerializer for Serializer1 {}
+
+#[cfg(feature = "samd21")]
+impl RxSerializer for Serializer1 {}
+
+/// Transmit serializer
+#[cfg(feature = "min-samd51j")]
+pub enum Transmitter {}
+
+#[cfg(feature = "min-samd51j")]
+impl Sealed for Transmitter {}
+
+#[cfg(feature = "min-samd51j")]
+impl SerializerId for Transmitter {
+    const N: usize = 0;
+    // The two flags of each group are set for even and odd slots
+    const FLAGS: u16 = 0b11;
+}
+
+#[cfg(feature = "min-samd51j")]
+impl TxSerializer for Transmitter {}
+
+/// Receive serializer
+#[cfg(feature = "min-samd51j")]
+pub enum Receiver {}
+
+#[cfg(feature = "min-samd51j")]
+impl Sealed for Receiver {}
+
+#[cfg(feature = "min-samd51j")]
+impl SerializerId for Receiver {
+    const N: usize = 1;
+    const FLAGS: u16 = 0b11;
+}
+
+#[cfg(feature = "min-samd51j")]
+impl RxSerializer for Receiver {}
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+/// [`PinId`] which can be used as the `SCK` pin of a clock unit
+pub trait SckId: PinId {
+    /// Clock unit of the pin
+    type ClockUnit: ClockUnitId;
+}
+
+/// [`PinId`] which can be used as the `FS` pin of a clock unit
+pub trait FsId: PinId {
+    /// Clock unit of the pin
+    type ClockUnit: ClockUnitId;
+}
+
+/// [`PinId`] which can be used as the `MCK` pin of a clock unit
+pub trait MckId: PinId {
+    /// Clock unit of the pin
+    type ClockUnit: ClockUnitId;
+}
+
+/// [`PinId`] which can be used as the `SD` pin of a serializer
+pub trait SdId: PinId {
+    /// Serializer of the pin
+    type Serializer: SerializerId;
+}
+
+/// Set of pins of a clock unit
+///
+/// Every pin is optional, and is added with the corresponding builder method.
+/// Signals without a pin are still generated (or expected) internally.
+pub struct ClockPins<U: ClockUnitId, Sck = NoneT, Fs = NoneT, Mck = NoneT> {
+    sck: Sck,
+    fs: Fs,
+    mck: Mck,
+    unit: PhantomData<U>,
+}
+
+impl<U: ClockUnitId> Default for ClockPins<U> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            sck: NoneT,
+            fs: NoneT,
+            mck: NoneT,
+            unit: PhantomData,
+        }
+    }
+}
+
+impl<U: ClockUnitId, Sck, Fs, Mck> ClockPins<U, Sck, Fs, Mck> {
+    /// Set the `SCK` pin
+    #[inline]
+    pub fn sck<I: SckId<ClockUnit = U>>(
+        self,
+        pin: impl AnyPin<Id = I>,
+    ) -> ClockPins<U, Pin<I, I2sAlternate>, Fs, Mck> {
+        ClockPins {
+            sck: pin.into().into_alternate(),
+            fs: self.fs,
+            mck: self.mck,
+            unit: PhantomData,
+        }
+    }
+
+    /// Set the `FS` pin
+    #[inline]
+    pub fn fs<I: FsId<ClockUnit = U>>(
+        self,
+        pin: impl AnyPin<Id = I>,
+    ) -> ClockPins<U, Sck, Pin<I, I2sAlternate>, Mck> {
+        ClockPins {
+            sck: self.sck,
+            fs: pin.into().into_alternate(),
+            mck: self.mck,
+            unit: PhantomData,
+        }
+    }
+
+    /// Set the `MCK` pin. The master clock is only output if
+    /// [`ClockConfig::master_clock_divider`] is set.
+    #[inline]
+    pub fn mck<I: MckId<ClockUnit = U>>(
+        self,
+        pin: impl AnyPin<Id = I>,
+    ) -> ClockPins<U, Sck, Fs, Pin<I, I2sAlternate>> {
+        ClockPins {
+            sck: self.sck,
+            fs: self.fs,
+            mck: pin.into().into_alternate(),
+            unit: PhantomData,
+        }
+    }
+
+    /// Return the `SCK`, `FS` and `MCK` pins
+    #[inline]
+    pub fn free(self) -> (Sck, Fs, Mck) {
+        (self.sck, self.fs, self.mck)
+    }
+}
+
+//==============================================================================
+// ClockConfig
+//==============================================================================
+
+/// Source of the `SCK` and `FS` signals of a clock unit
+#[derive(Clone, Copy)]
+pub enum ClockMode {
+    /// `SCK` and `FS` are divided from the generic clock, to obtain the given
+    /// frame (sample) rate
+    Master {
+        /// Number of frames per second
+        frame_rate: Hertz,
+    },
+    /// `SCK` and `FS` are received on the corresponding pins
+    Slave,
+}
+
+/// Configuration of a clock unit
+#[derive(Clone, Copy)]
+pub struct ClockConfig {
+    /// Source of the `SCK` and `FS` signals
+    pub mode: ClockMode,
+    /// Size of each slot
+    pub slot_size: SlotSize,
+    /// Number of slots in a frame, between 1 and 8
+    pub slots: u8,
+    /// Width of the frame sync pulse
+    pub frame_sync_width: FrameSyncWidth,
+    /// Delay between the frame sync edge and the first data bit
+    pub data_delay: DataDelay,
+    /// Invert the frame sync signal
+    pub invert_frame_sync: bool,
+    /// If set, output the generic clock divided by this value, between 1
+    /// and the largest divider of the chip, on the `MCK` pin
+    pub master_clock_divider: Option<u8>,
+}
+
+impl ClockConfig {
+    /// Standard I2S frame format: two slots (left and right), a frame sync
+    /// signal lasting half a frame and a one bit data delay
+    #[inline]
+    pub const fn i2s(slot_size: SlotSize, mode: ClockMode) -> Self {
+        Self {
+            mode,
+            slot_size,
+            slots: 2,
+            frame_sync_width: FrameSyncWidth::HALF,
+            data_delay: DataDelay::I2S,
+            invert_frame_sync: false,
+            master_clock_divider: None,
+        }
+    }
+
+    /// Left-justified frame format: same as [`ClockConfig::i2s`], without
+    /// data delay
+    #[inline]
+    pub const fn left_justified(slot_size: SlotSize, mode: ClockMode) -> Self {
+        Self {
+            data_delay: DataDelay::LJ,
+            ..Self::i2s(slot_size, mode)
+        }
+    }
+
+    /// TDM frame format: `slots` slots, a frame sync pulse of one bit and a
+    /// one bit data delay
+    #[inline]
+    pub const fn tdm(slot_size: SlotSize, slots: u8, mode: ClockMode) -> Self {
+        Self {
+            slots,
+            frame_sync_width: FrameSyncWidth::BIT,
+            ..Self::i2s(slot_size, mode)
+        }
+    }
+
+    /// Number of `SCK` periods in a frame
+    #[inline]
+    fn frame_len(&self) -> u32 {
+        let slot_bits = match self.slot_size {
+            SlotSize::_8 => 8,
+            SlotSize::_16 => 16,
+            SlotSize::_24 => 24,
+            SlotSize::_32 => 32,
+        };
+        slot_bits * self.slots as u32
+    }
+}
+
+//==============================================================================
+// SerializerConfig
+//==============================================================================
+
+/// Configuration of a serializer
+#[derive(Clone, Copy)]
+pub struct SerializerConfig {
+    /// Number of significant bits of the data words
+    pub data_size: DataSize,
+    /// Alignment of the data bits within a slot
+    pub slot_adjust: SlotAdjust,
+    /// Alignment of the data bits within a word
+    pub word_adjust: WordAdjust,
+    /// Extension of the data words to the slot size
+    pub extend: Extend,
+    /// Transfer the least significant bit first
+    pub lsb_first: bool,
+    /// Mono mode: a transmitter sends the data of slot 0 in slot 1, and a
+    /// receiver stores the data of slot 0 in slot 1
+    pub mono: bool,
+    /// Bitmask of the slots ignored by the serializer
+    pub disabled_slots: u8,
+    /// Line level during disabled slots (transmitters only)
+    pub tx_default: TxDefault,
+    /// On underrun, send the last word again instead of zero (transmitters
+    /// only)
+    pub repeat_on_underrun: bool,
+    /// Sample one PDM bitstream on each `SCK` edge (receivers only)
+    pub pdm: bool,
+}
+
+impl SerializerConfig {
+    /// Default configuration for the given data size. The data is
+    /// left-aligned in the slots, MSB first.
+    #[inline]
+    pub const fn new(data_size: DataSize) -> Self {
+        Self {
+            data_size,
+            slot_adjust: SlotAdjust::LEFT,
+            word_adjust: WordAdjust::RIGHT,
+            extend: Extend::ZERO,
+            lsb_first: false,
+            mono: false,
+            disabled_slots: 0,
+            tx_default: TxDefault::ZERO,
+            repeat_on_underrun: false,
+            pdm: false,
+        }
+    }
+
+    /// Configuration of a receiver sampling a pair of PDM microphones. Each
+    /// 32-bit word contains 16 bits sampled on each `SCK` edge.
+    #[inline]
+    pub const fn pdm() -> Self {
+        Self {
+            pdm: true,
+            ..Self::new(DataSize::_32)
+        }
+    }
+
+    /// Value of the serializer control register, without `SERMODE`
+    fn bits(&self, clock_unit: usize) -> u32 {
+        let mut bits = (u8::from(self.tx_default) as u32) << TXDEFAULT_POS
+            | (clock_unit as u32) << CLKSEL_POS
+            | (bool::from(self.slot_adjust) as u32) << SLOTADJ_POS
+            | (u8::from(self.data_size) as u32) << DATASIZE_POS
+            | (bool::from(self.word_adjust) as u32) << WORDADJ_POS
+            | (u8::from(self.extend) as u32) << EXTEND_POS
+            | (self.disabled_slots as u32) << SLOTDIS_POS;
+        if self.repeat_on_underrun {
+            bits |= TXSAME;
+        }
+        if self.lsb_first {
+            bits |= BITREV;
+        }
+        if self.mono {
+            bits |= MONO;
+        }
+        bits
+    }
+}
+
+//==============================================================================
+// Interrupts
+//==============================================================================
+
+bitflags! {
+    /// Interrupt bit flags for the I2S peripheral
+    ///
+    /// The binary format of the underlying bits exactly matches the `INTFLAG`
+    /// register.
+    pub struct Interrupts: u16 {
+        /// Data received by serializer 0 (or in an even slot)
+        const RXRDY0 = 1 << 0;
+        /// Data received by serializer 1 (or in an odd slot)
+        const RXRDY1 = 1 << 1;
+        /// Receive overrun on serializer 0 (or in an even slot)
+        const RXOR0 = 1 << 4;
+        /// Receive overrun on serializer 1 (or in an odd slot)
+        const RXOR1 = 1 << 5;
+        /// Serializer 0 ready to transmit (or ready for an even slot)
+        const TXRDY0 = 1 << 8;
+        /// Serializer 1 ready to transmit (or ready for an odd slot)
+        const TXRDY1 = 1 << 9;
+        /// Transmit underrun on serializer 0 (or in an even slot)
+        const TXUR0 = 1 << 12;
+        /// Transmit underrun on serializer 1 (or in an odd slot)
+        const TXUR1 = 1 << 13;
+    }
+}
+
+//==============================================================================
+// I2s
+//==============================================================================
+
+/// I2S peripheral
+///
+/// Clock units and serializers are configured individually, while the
+/// peripheral is enabled.
+pub struct I2s {
+    i2s: I2S,
+}
+
+impl I2s {
+    /// Enable the I2S bus clock, reset and enable the peripheral
+    pub fn new(i2s: I2S, pm: &mut PM) -> Self {
+        #[cfg(feature = "samd21")]
+        pm.apbcmask.modify(|_, w| w.i2s_().set_bit());
+        #[cfg(feature = "min-samd51j")]
+        pm.apbdmask.modify(|_, w| w.i2s_().set_bit());
+
+        i2s.ctrla.write(|w| w.swrst().set_bit());
+        while i2s.syncbusy.read().bits() & SWRST as u16 != 0 {}
+        i2s.ctrla.write(|w| w.enable().set_bit());
+        let i2s = Self { i2s };
+        i2s.sync(ENABLE);
+        i2s
+    }
+
+    #[inline]
+    fn sync(&self, mask: u8) {
+        while self.i2s.syncbusy.read().bits() & mask as u16 != 0 {}
+    }
+
+    #[inline]
+    fn set_ctrla(&mut self, mask: u8, enable: bool) {
+        self.i2s.ctrla.modify(|r, w| unsafe {
+            if enable {
+                w.bits(r.bits() | mask)
+            } else {
+                w.bits(r.bits() & !mask)
+            }
+        });
+        self.sync(mask);
+    }
+
+    /// Configure and enable a clock unit
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidSlots`] if [`ClockConfig::slots`] is out of
+    /// range, and [`Error::InvalidDivider`] if the master clock divider, or
+    /// in master mode the divider between the generic clock and `SCK`, is out
+    /// of range.
+    pub fn configure_clock_unit<U: ClockUnitId, Sck, Fs, Mck>(
+        &mut self,
+        clock: &U::Clock,
+        pins: ClockPins<U, Sck, Fs, Mck>,
+        config: ClockConfig,
+    ) -> Result<ClockUnit<U, Sck, Fs, Mck>, Error> {
+        if !(1..=8).contains(&config.slots) {
+            return Err(Error::InvalidSlots);
+        }
+        let sck_divider = match config.mode {
+            ClockMode::Master { frame_rate } => {
+                let divider = frame_rate
+                    .0
+                    .checked_mul(config.frame_len())
+                    .and_then(|sck| clock.freq().0.checked_add(sck / 2)?.checked_div(sck))
+                    .filter(|divider| (1..=MAX_DIVIDER).contains(divider))
+                    .ok_or(Error::InvalidDivider)?;
+                Some(divider)
+            }
+            ClockMode::Slave => None,
+        };
+        if let Some(divider) = config.master_clock_divider {
+            if !(1..=MAX_DIVIDER).contains(&(divider as u32)) {
+                return Err(Error::InvalidDivider);
+            }
+        }
+
+        self.set_ctrla(CKEN0 << U::N, false);
+        self.i2s.clkctrl[U::N].write(|w| unsafe {
+            w.slotsize().variant(config.slot_size);
+            w.nbslots().bits(config.slots - 1);
+            w.fswidth().variant(config.frame_sync_width);
+            w.bitdelay().variant(config.data_delay);
+            w.fsinv().bit(config.invert_frame_sync);
+            w.mcksel().gclk();
+            match sck_divider {
+                Some(divider) => {
+                    w.scksel().mckdiv();
+                    w.fssel().sckdiv();
+                    w.mckdiv().bits((divider - 1) as u8);
+                }
+                None => {
+                    w.scksel().sckpin();
+                    w.fssel().fspin();
+                }
+            }
+            if let Some(divider) = config.master_clock_divider {
+                w.mcken().set_bit();
+                w.mckoutdiv().bits(divider - 1);
+            }
+            w
+        });
+        self.set_ctrla(CKEN0 << U::N, true);
+
+        Ok(ClockUnit { pins })
+    }
+
+    /// Disable a clock unit and return its pins
+    pub fn disable_clock_unit<U: ClockUnitId, Sck, Fs, Mck>(
+        &mut self,
+        unit: ClockUnit<U, Sck, Fs, Mck>,
+    ) -> ClockPins<U, Sck, Fs, Mck> {
+        self.set_ctrla(CKEN0 << U::N, false);
+        unit.pins
+    }
+
+    fn configure_serializer<S: SerializerId>(&mut self, bits: u32) {
+        self.set_ctrla(SEREN0 << S::N, false);
+        write_serializer_ctrl::<S>(&self.i2s, bits);
+        let flags = S::FLAGS;
+        self.i2s
+            .intflag
+            .write(|w| unsafe { w.bits(flags | flags << 4 | flags << 8 | flags << 12) });
+        self.set_ctrla(SEREN0 << S::N, true);
+    }
+
+    /// Configure and enable a serializer as a transmitter
+    pub fn configure_tx<I, U, Sck, Fs, Mck>(
+        &mut self,
+        _unit: &ClockUnit<U, Sck, Fs, Mck>,
+        pin: impl AnyPin<Id = I>,
+        config: SerializerConfig,
+    ) -> Tx<I>
+    where
+        I: SdId,
+        I::Serializer: TxSerializer,
+        U: ClockUnitId,
+    {
+        self.configure_serializer::<I::Serializer>(config.bits(U::N) | SERMODE_TX);
+        Tx {
+            pin: pin.into().into_alternate(),
+        }
+    }
+
+    /// Configure and enable a serializer as a receiver
+    pub fn configure_rx<I, U, Sck, Fs, Mck>(
+        &mut self,
+        _unit: &ClockUnit<U, Sck, Fs, Mck>,
+        pin: impl AnyPin<Id = I>,
+        config: SerializerConfig,
+    ) -> Rx<I>
+    where
+        I: SdId,
+        I::Serializer: RxSerializer,
+        U: ClockUnitId,
+    {
+        let mode = if config.pdm { SERMODE_PDM2 } else { SERMODE_RX };
+        self.configure_serializer::<I::Serializer>(config.bits(U::N) | mode);
+        Rx {
+            pin: pin.into().into_alternate(),
+        }
+    }
+
+    /// Disable a transmitter and return its pin
+    pub fn disable_tx<I: SdId>(&mut self, tx: Tx<I>) -> Pin<I, I2sAlternate> {
+        self.set_ctrla(SEREN0 << I::Serializer::N, false);
+        tx.pin
+    }
+
+    /// Disable a receiver and return its pin
+    pub fn disable_rx<I: SdId>(&mut self, rx: Rx<I>) -> Pin<I, I2sAlternate> {
+        self.set_ctrla(SEREN0 << I::Serializer::N, false);
+        rx.pin
+    }
+
+    /// Enable the given interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, interrupts: Interrupts) {
+        self.i2s
+            .intenset
+            .write(|w| unsafe { w.bits(interrupts.bits()) });
+    }
+
+    /// Disable the given interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, interrupts: Interrupts) {
+        self.i2s
+            .intenclr
+            .write(|w| unsafe { w.bits(interrupts.bits()) });
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Interrupts {
+        Interrupts::from_bits_truncate(self.i2s.intflag.read().bits())
+    }
+
+    /// Clear the given interrupt flags. The ready flags are cleared by
+    /// accessing the data registers.
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Interrupts) {
+        self.i2s.intflag.write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the peripheral and return the underlying PAC object
+    pub fn free(mut self) -> I2S {
+        self.set_ctrla(ENABLE, false);
+        self.i2s
+    }
+}
+
+#[cfg(feature = "samd21")]
+#[inline]
+fn write_serializer_ctrl<S: SerializerId>(i2s: &i2s::RegisterBlock, bits: u32) {
+    i2s.serctrl[S::N].write(|w| unsafe { w.bits(bits) });
+}
+
+#[cfg(feature = "min-samd51j")]
+#[inline]
+fn write_serializer_ctrl<S: SerializerId>(i2s: &i2s::RegisterBlock, bits: u32) {
+    if S::N == 0 {
+        i2s.txctrl.write(|w| unsafe { w.bits(bits) });
+    } else {
+        i2s.rxctrl.write(|w| unsafe { w.bits(bits) });
+    }
+}
+
+#[cfg(feature = "samd21")]
+#[inline]
+fn data_ptr<S: SerializerId>(i2s: &i2s::RegisterBlock) -> *mut u32 {
+    i2s.data[S::N].as_ptr()
+}
+
+#[cfg(feature = "min-samd51j")]
+#[inline]
+fn data_ptr<S: SerializerId>(i2s: &i2s::RegisterBlock) -> *mut u32 {
+    if S::N == 0 {
+        i2s.txdata.as_ptr()
+    } else {
+        i2s.rxdata.as_ptr()
+    }
+}
+
+//==============================================================================
+// ClockUnit
+//==============================================================================
+
+/// Enabled clock unit
+///
+/// A reference to the `ClockUnit` is required to configure the serializers
+/// it clocks.
+pub struct ClockUnit<U: ClockUnitId, Sck, Fs, Mck> {
+    pins: ClockPins<U, Sck, Fs, Mck>,
+}
+
+//==============================================================================
+// Tx / Rx
+//==============================================================================
+
+/// Accessors shared by [`Tx`] and [`Rx`]
+trait SerializerRegs<S: SerializerId> {
+    #[inline]
+    fn i2s(&self) -> &i2s::RegisterBlock {
+        // Safety: each serializer only accesses its own data register, and
+        // writes its own (write-one-to-clear) interrupt flags
+        unsafe { &*I2S::ptr() }
+    }
+
+    #[inline]
+    fn flags(&self) -> u16 {
+        self.i2s().intflag.read().bits()
+    }
+
+    #[inline]
+    fn clear_flags(&self, flags: u16) {
+        self.i2s().intflag.write(|w| unsafe { w.bits(flags) });
+    }
+
+    #[inline]
+    fn data_synced(&self) -> bool {
+        self.i2s().syncbusy.read().bits() & (1 << (8 + S::N)) == 0
+    }
+}
+
+/// Serializer configured as a transmitter
+pub struct Tx<I: SdId> {
+    pin: Pin<I, I2sAlternate>,
+}
+
+impl<I: SdId> SerializerRegs<I::Serializer> for Tx<I> {}
+
+impl<I: SdId> Tx<I> {
+    /// Return whether the transmitter is ready to accept a new word
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.flags() & I::Serializer::FLAGS << 8 != 0 && self.data_synced()
+    }
+
+    /// Write the word of the next slot
+    #[inline]
+    pub fn write(&mut self, word: u32) -> nb::Result<(), Infallible> {
+        if !self.is_ready() {
+            return Err(nb::Error::WouldBlock);
+        }
+        // Safety: the pointer is valid, and only used by this serializer
+        unsafe { ptr::write_volatile(data_ptr::<I::Serializer>(self.i2s()), word) };
+        Ok(())
+    }
+
+    /// Return whether an underrun occurred since the last call, which means
+    /// that words were not written fast enough
+    #[inline]
+    pub fn underrun(&mut self) -> bool {
+        let flag = I::Serializer::FLAGS << 12;
+        let underrun = self.flags() & flag != 0;
+        self.clear_flags(flag);
+        underrun
+    }
+
+    /// DMAC trigger source of the transmitter
+    #[cfg(feature = "dma")]
+    #[inline]
+    pub fn dma_trigger(&self) -> crate::dmac::TriggerSource {
+        if I::Serializer::N == 0 {
+            crate::dmac::TriggerSource::I2S_TX_0
+        } else {
+            crate::dmac::TriggerSource::I2S_TX_1
+        }
+    }
+}
+
+/// Serializer configured as a receiver
+pub struct Rx<I: SdId> {
+    pin: Pin<I, I2sAlternate>,
+}
+
+impl<I: SdId> SerializerRegs<I::Serializer> for Rx<I> {}
+
+impl<I: SdId> Rx<I> {
+    /// Return whether a word has been received
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.flags() & I::Serializer::FLAGS != 0 && self.data_synced()
+    }
+
+    /// Read the word of the last received slot
+    #[inline]
+    pub fn read(&mut self) -> nb::Result<u32, Infallible> {
+        if !self.is_ready() {
+            return Err(nb::Error::WouldBlock);
+        }
+        // Safety: the pointer is valid, and only used by this serializer
+        Ok(unsafe { ptr::read_volatile(data_ptr::<I::Serializer>(self.i2s())) })
+    }
+
+    /// Return whether an overrun occurred since the last call, which means
+    /// that words were not read fast enough and were lost
+    #[inline]
+    pub fn overrun(&mut self) -> bool {
+        let flag = I::Serializer::FLAGS << 4;
+        let overrun = self.flags() & flag != 0;
+        self.clear_flags(flag);
+        overrun
+    }
+
+    /// DMAC trigger source of the receiver
+    #[cfg(feature = "dma")]
+    #[inline]
+    pub fn dma_trigger(&self) -> crate::dmac::TriggerSource {
+        #[cfg(feature = "samd21")]
+        let trigger = if I::Serializer::N == 0 {
+            crate::dmac::TriggerSource::I2S_RX_0
+        } else {
+            crate::dmac::TriggerSource::I2S_RX_1
+        };
+        // On SAMD5x/E5x, the receive triggers are numbered by slot parity,
+        // like the flags
+        #[cfg(feature = "min-samd51j")]
+        let trigger = crate::dmac::TriggerSource::I2S_RX_0;
+        trigger
+    }
+}
+
+#[cfg(feature = "dma")]
+unsafe impl<I: SdId> crate::dmac::Buffer for Tx<I> {
+    type Beat = u32;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        data_ptr::<I::Serializer>(self.i2s())
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+#[cfg(feature = "dma")]
+unsafe impl<I: SdId> crate::dmac::Buffer for Rx<I> {
+    type Beat = u32;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        data_ptr::<I::Serializer>(self.i2s())
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
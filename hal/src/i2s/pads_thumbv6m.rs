@@ -0,0 +1,34 @@
+//! I2S pin mapping for SAMD21 chips
+
+#[cfg(feature = "min-samd21g")]
+use super::ClockUnit1;
+use super::{ClockUnit0, FsId, MckId, SckId, SdId, Serializer0, Serializer1};
+use crate::gpio::*;
+
+/// Alternate function of the I2S pins
+pub type I2sAlternate = AlternateG;
+
+macro_rules! i2s_pins {
+    ($Trait:ident, $Assoc:ident = $Id:ident: $( $( #[$cfg:meta] )? $Pin:ident ),+) => {
+        $(
+            $( #[$cfg] )?
+            impl $Trait for $Pin {
+                type $Assoc = $Id;
+            }
+        )+
+    };
+}
+
+i2s_pins!(SckId, ClockUnit = ClockUnit0: PA10, #[cfg(feature = "min-samd21g")] PA20);
+i2s_pins!(FsId, ClockUnit = ClockUnit0: PA11, #[cfg(feature = "min-samd21g")] PA21);
+i2s_pins!(MckId, ClockUnit = ClockUnit0: PA09, #[cfg(feature = "min-samd21j")] PB17);
+
+#[cfg(feature = "min-samd21g")]
+i2s_pins!(SckId, ClockUnit = ClockUnit1: PB11);
+#[cfg(feature = "min-samd21j")]
+i2s_pins!(FsId, ClockUnit = ClockUnit1: PB12);
+#[cfg(feature = "min-samd21g")]
+i2s_pins!(MckId, ClockUnit = ClockUnit1: PB10);
+
+i2s_pins!(SdId, Serializer = Serializer0: PA07, PA19);
+i2s_pins!(SdId, Serializer = Serializer1: PA08, #[cfg(feature = "min-samd21j")] PB16);
@@ -0,0 +1,28 @@
+//! I2S pin mapping for SAMD5x/E5x chips
+
+use super::{ClockUnit0, ClockUnit1, FsId, MckId, Receiver, SckId, SdId, Transmitter};
+use crate::gpio::*;
+
+/// Alternate function of the I2S pins
+pub type I2sAlternate = AlternateJ;
+
+macro_rules! i2s_pins {
+    ($Trait:ident, $Assoc:ident = $Id:ident: $( $Pin:ident ),+) => {
+        $(
+            impl $Trait for $Pin {
+                type $Assoc = $Id;
+            }
+        )+
+    };
+}
+
+i2s_pins!(SckId, ClockUnit = ClockUnit0: PA10, PB16);
+i2s_pins!(FsId, ClockUnit = ClockUnit0: PA09, PA20);
+i2s_pins!(MckId, ClockUnit = ClockUnit0: PA08, PB17);
+
+i2s_pins!(SckId, ClockUnit = ClockUnit1: PB12);
+i2s_pins!(FsId, ClockUnit = ClockUnit1: PA23, PB11);
+i2s_pins!(MckId, ClockUnit = ClockUnit1: PB13);
+
+i2s_pins!(SdId, Serializer = Transmitter: PA11, PA21);
+i2s_pins!(SdId, Serializer = Receiver: PA22, PB10);
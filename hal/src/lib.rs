@@ -96,10 +96,23 @@ pub mod sleeping_delay;
 pub mod time;
 pub mod timer_params;
 pub mod timer_traits;
+mod watchdog_params;
+
+#[cfg(feature = "device")]
+pub mod ac;
+
+#[cfg(feature = "async")]
+mod async_hal;
 
 #[cfg(all(feature = "unproven", feature = "dma"))]
 pub mod dmac;
 
+#[cfg(feature = "device")]
+pub mod evsys;
+
+#[cfg(any(feature = "samd21", feature = "min-samd51j"))]
+pub mod i2s;
+
 #[cfg(all(feature = "usb", feature = "samd11"))]
 compile_error!("'usb' is enabled, but USB isn't supported on SAMD11");
 
@@ -109,6 +122,21 @@ compile_error!("'usb' is enabled, but USB isn't supported on SAMD11");
 ))]
 compile_error!("The 'usb' feature is enabled, but not a chip with USB support");
 
+#[cfg(all(
+    feature = "can",
+    not(any(feature = "same51", feature = "same54", feature = "library"))
+))]
+compile_error!("The 'can' feature is enabled, but not a chip with CAN support");
+
+#[cfg(all(
+    feature = "ethernet",
+    not(any(
+        all(feature = "min-samd51n", any(feature = "same53", feature = "same54")),
+        feature = "library"
+    ))
+))]
+compile_error!("The 'ethernet' feature is enabled, but not a SAME53N or SAME54 chip");
+
 #[cfg(any(feature = "samd11", feature = "samd21"))]
 pub mod thumbv6m;
 #[cfg(any(feature = "samd11", feature = "samd21"))]
@@ -1,6 +1,18 @@
 //! Real-time clock/counter
+//!
+//! The RTC can run as a calendar ([`ClockMode`]), as a 32-bit counter
+//! ([`Count32Mode`]) or as a 16-bit counter with a configurable period
+//! ([`Count16Mode`]). In every mode, it provides periodic events derived from
+//! the RTC clock and a frequency correction.
+//!
+//! In clock mode, [`Alarm`]s trigger when the calendar matches the fields
+//! selected by an [`AlarmMask`].
+//!
+//! On SAMx5x, the RTC also provides tamper detection with timestamp capture,
+//! as well as general purpose and backup registers that are retained in
+//! backup sleep.
 use crate::ehal::timer::{CountDown, Periodic};
-use crate::pac::rtc::{MODE0, MODE2};
+use crate::pac::rtc::{MODE0, MODE1, MODE2};
 use crate::pac::RTC;
 use crate::time::{Hertz, Nanoseconds};
 use crate::timer_traits::InterruptDrivenTimer;
@@ -24,14 +36,14 @@ use rtic_monotonic::Monotonic;
 #[cfg(feature = "min-samd51g")]
 use crate::pac::{
     rtc::mode0::ctrla::PRESCALER_A, rtc::mode0::CTRLA as MODE0_CTRLA,
-    rtc::mode2::CTRLA as MODE2_CTRLA, MCLK as PM,
+    rtc::mode1::CTRLA as MODE1_CTRLA, rtc::mode2::CTRLA as MODE2_CTRLA, MCLK as PM,
 };
 
 // SAMD11/SAMD21 imports
 #[cfg(any(feature = "samd11", feature = "samd21"))]
 use crate::pac::{
     rtc::mode0::ctrl::PRESCALER_A, rtc::mode0::CTRL as MODE0_CTRLA,
-    rtc::mode2::CTRL as MODE2_CTRLA, PM,
+    rtc::mode1::CTRL as MODE1_CTRLA, rtc::mode2::CTRL as MODE2_CTRLA, PM,
 };
 
 /// Datetime represents an RTC clock/calendar value.
@@ -77,6 +89,122 @@ pub enum Count32Mode {}
 impl RtcMode for Count32Mode {}
 impl Sealed for Count32Mode {}
 
+/// Count16Mode represents the 16-bit counter mode. This is a free running
+/// count-up timer, which wraps around to zero after reaching the period set
+/// with [`Rtc::set_period16`].
+pub enum Count16Mode {}
+
+impl RtcMode for Count16Mode {}
+impl Sealed for Count16Mode {}
+
+/// AlarmMask selects the calendar fields compared by an alarm. The fields
+/// that are not compared make the alarm repeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AlarmMask {
+    /// The alarm never triggers
+    Off = 0,
+    /// Match the seconds, triggering once a minute
+    EveryMinute,
+    /// Match the minutes and seconds, triggering once an hour
+    Hourly,
+    /// Match the hours, minutes and seconds, triggering once a day
+    Daily,
+    /// Match the day, hours, minutes and seconds, triggering once a month
+    Monthly,
+    /// Match the month, day, hours, minutes and seconds, triggering once a
+    /// year
+    Yearly,
+    /// Match every field, triggering only once
+    Once,
+}
+
+/// Alarm selects one of the calendar alarms of the RTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Alarm {
+    Alarm0 = 0,
+    #[cfg(feature = "min-samd51g")]
+    Alarm1,
+}
+
+/// GpPair selects a pair of general purpose registers. Each pair shares its
+/// storage with a compare or alarm register of the RTC.
+#[cfg(feature = "min-samd51g")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpPair {
+    /// GP0 and GP1, stored in COMP0 or ALARM0
+    Gp0Gp1,
+    /// GP2 and GP3, stored in COMP1 or ALARM1
+    Gp2Gp3,
+}
+
+impl Alarm {
+    /// Mask of the alarm in the INTFLAG, INTENSET and INTENCLR registers
+    #[inline]
+    fn flag(self) -> u32 {
+        #[cfg(feature = "min-samd51g")]
+        return 1 << (8 + self as u32);
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        return 1 << self as u32;
+    }
+}
+
+/// PeriodicInterval selects one of the periodic signals of the RTC. `PerN`
+/// toggles at the RTC clock frequency divided by 2^(N + 3), independently of
+/// the prescaler, so that [`PeriodicInterval::Per7`] ticks at 1 Hz with a
+/// 1.024 kHz clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PeriodicInterval {
+    Per0 = 0,
+    Per1,
+    Per2,
+    Per3,
+    Per4,
+    Per5,
+    Per6,
+    Per7,
+}
+
+/// TamperInput selects one of the tamper detection inputs. The corresponding
+/// pins must be configured for their RTC function.
+#[cfg(feature = "min-samd51g")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TamperInput {
+    In0 = 0,
+    In1,
+    In2,
+    In3,
+    In4,
+}
+
+/// TamperAction represents what the RTC does when a tamper input is detected
+#[cfg(feature = "min-samd51g")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TamperAction {
+    /// Tamper detection is disabled for the input
+    Off = 0,
+    /// Set the tamper flag, without capturing a timestamp
+    Wake,
+    /// Set the tamper flag and capture a timestamp
+    Capture,
+    /// Compare the input with the active layer output, capturing a timestamp
+    /// when they differ
+    ActiveLayer,
+}
+
+/// TamperLevel represents the edge of the tamper input that is detected
+#[cfg(feature = "min-samd51g")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TamperLevel {
+    Falling = 0,
+    Rising,
+}
+
 #[cfg(feature = "sdmmc")]
 impl From<Datetime> for Timestamp {
     fn from(clock: Datetime) -> Timestamp {
@@ -105,6 +233,11 @@ impl<Mode: RtcMode> Rtc<Mode> {
         self.rtc.mode0()
     }
 
+    #[inline]
+    fn mode1(&self) -> &MODE1 {
+        self.rtc.mode1()
+    }
+
     #[inline]
     fn mode2(&self) -> &MODE2 {
         self.rtc.mode2()
@@ -118,6 +251,14 @@ impl<Mode: RtcMode> Rtc<Mode> {
         return &self.mode0().ctrl;
     }
 
+    #[inline]
+    fn mode1_ctrla(&self) -> &MODE1_CTRLA {
+        #[cfg(feature = "min-samd51g")]
+        return &self.mode1().ctrla;
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        return &self.mode1().ctrl;
+    }
+
     #[inline]
     fn mode2_ctrla(&self) -> &MODE2_CTRLA {
         #[cfg(feature = "min-samd51g")]
@@ -173,6 +314,11 @@ impl<Mode: RtcMode> Rtc<Mode> {
         });
         self.sync();
 
+        // COMP0 is used by the CountDown and Monotonic implementations, so
+        // it can't hold GP0 and GP1 anymore
+        #[cfg(feature = "min-samd51g")]
+        self.mode0().ctrlb.modify(|_, w| w.gp0en().clear_bit());
+
         // enable clock sync on SAMx5x
         #[cfg(feature = "min-samd51g")]
         {
@@ -187,6 +333,35 @@ impl<Mode: RtcMode> Rtc<Mode> {
         self.into_mode()
     }
 
+    /// Reconfigures the peripheral for 16-bit counter mode, with no prescaler
+    /// and the period set to its maximum.
+    pub fn into_count16_mode(mut self) -> Rtc<Count16Mode> {
+        self.enable(false);
+        self.sync();
+        self.mode1_ctrla().modify(|_, w| {
+            w.mode().count16() // enable mode1 (16-bit counter)
+            .prescaler().div1() // No prescaler
+        });
+        self.sync();
+
+        // enable count sync on SAMx5x
+        #[cfg(feature = "min-samd51g")]
+        {
+            self.mode1_ctrla().modify(|_, w| {
+                w.countsync().set_bit() // synchronize the COUNT register
+            });
+
+            self.sync();
+        }
+
+        self.mode1()
+            .per
+            .write(|w| unsafe { w.per().bits(u16::MAX) });
+        self.sync();
+        self.enable(true);
+        self.into_mode()
+    }
+
     /// Reconfigures the peripheral for clock/calendar mode. Requires the source
     /// clock to be running at 1024 Hz.
     pub fn into_clock_mode(mut self) -> Rtc<ClockMode> {
@@ -222,6 +397,248 @@ impl<Mode: RtcMode> Rtc<Mode> {
     pub fn free(self) -> RTC {
         self.rtc
     }
+
+    /// Modifies the EVCTRL register, which can only be written while the RTC
+    /// is disabled.
+    // EVCTRL is 32 bits wide on SAMx5x, and 16 bits wide on SAMD11/21
+    #[allow(clippy::unnecessary_cast)]
+    fn set_event_outputs(&mut self, mask: u32, enable: bool) {
+        self.enable(false);
+        self.mode0().evctrl.modify(|r, w| unsafe {
+            if enable {
+                w.bits((r.bits() as u32 | mask) as _)
+            } else {
+                w.bits((r.bits() as u32 & !mask) as _)
+            }
+        });
+        self.enable(true);
+    }
+
+    /// Enables the event output for the periodic `interval`. The RTC is
+    /// briefly disabled while the event output is configured.
+    pub fn enable_periodic_event(&mut self, interval: PeriodicInterval) {
+        self.set_event_outputs(1 << interval as u32, true);
+    }
+
+    /// Disables the event output for the periodic `interval`. The RTC is
+    /// briefly disabled while the event output is configured.
+    pub fn disable_periodic_event(&mut self, interval: PeriodicInterval) {
+        self.set_event_outputs(1 << interval as u32, false);
+    }
+
+    /// Enables the interrupt for the periodic `interval`.
+    #[cfg(feature = "min-samd51g")]
+    pub fn enable_periodic_interrupt(&mut self, interval: PeriodicInterval) {
+        self.mode0()
+            .intenset
+            .write(|w| unsafe { w.bits((1 << interval as u32) as _) });
+    }
+
+    /// Disables the interrupt for the periodic `interval`.
+    #[cfg(feature = "min-samd51g")]
+    pub fn disable_periodic_interrupt(&mut self, interval: PeriodicInterval) {
+        self.mode0()
+            .intenclr
+            .write(|w| unsafe { w.bits((1 << interval as u32) as _) });
+    }
+
+    /// Returns whether the periodic `interval` interrupt flag is set.
+    #[cfg(feature = "min-samd51g")]
+    pub fn is_periodic_interrupt(&self, interval: PeriodicInterval) -> bool {
+        self.mode0().intflag.read().bits() as u32 & (1 << interval as u32) != 0
+    }
+
+    /// Clears the periodic `interval` interrupt flag.
+    #[cfg(feature = "min-samd51g")]
+    pub fn clear_periodic_interrupt(&mut self, interval: PeriodicInterval) {
+        // Writing a 1 clears the flag
+        self.mode0()
+            .intflag
+            .write(|w| unsafe { w.bits((1 << interval as u32) as _) });
+    }
+
+    /// Sets the frequency correction, in steps of about 0.95 ppm of the RTC
+    /// clock. A positive `correction` slows the RTC down and a negative one
+    /// speeds it up. The correction saturates at 127 steps either way.
+    pub fn set_frequency_correction(&mut self, correction: i8) {
+        let value = correction.unsigned_abs().min(127);
+        self.mode0()
+            .freqcorr
+            .write(|w| unsafe { w.sign().bit(correction < 0).value().bits(value) });
+        self.sync();
+    }
+
+    /// Configures the tamper detection of `input`. The RTC is briefly disabled
+    /// while the input is configured.
+    ///
+    /// When `debounce` is set, the input must be stable for three samples of
+    /// the debounce clock before it is detected.
+    #[cfg(feature = "min-samd51g")]
+    pub fn configure_tamper(
+        &mut self,
+        input: TamperInput,
+        action: TamperAction,
+        level: TamperLevel,
+        debounce: bool,
+    ) {
+        let n = input as u32;
+        // INnACT, TAMLVLn and DEBNCn
+        let mask = (0b11 << (2 * n)) | (1 << (16 + n)) | (1 << (24 + n));
+        let value = ((action as u32) << (2 * n))
+            | ((level as u32) << (16 + n))
+            | ((debounce as u32) << (24 + n));
+
+        self.enable(false);
+        self.mode0()
+            .tampctrl
+            .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | value) });
+        self.enable(true);
+    }
+
+    /// Selects whether a tamper detection erases the backup registers and the
+    /// general purpose registers. The RTC is briefly disabled while this is
+    /// configured.
+    #[cfg(feature = "min-samd51g")]
+    pub fn set_tamper_erase(&mut self, backup: bool, general_purpose: bool) {
+        self.enable(false);
+        self.mode0_ctrla()
+            .modify(|_, w| w.bktrst().bit(backup).gptrst().bit(general_purpose));
+        self.sync();
+        self.enable(true);
+    }
+
+    /// Enables the tamper event output. The RTC is briefly disabled while the
+    /// event output is configured.
+    #[cfg(feature = "min-samd51g")]
+    pub fn enable_tamper_event(&mut self) {
+        // TAMPEREO
+        self.set_event_outputs(1 << 14, true);
+    }
+
+    /// Enables the tamper interrupt.
+    #[cfg(feature = "min-samd51g")]
+    pub fn enable_tamper_interrupt(&mut self) {
+        self.mode0().intenset.write(|w| w.tamper().set_bit());
+    }
+
+    /// Disables the tamper interrupt.
+    #[cfg(feature = "min-samd51g")]
+    pub fn disable_tamper_interrupt(&mut self) {
+        self.mode0().intenclr.write(|w| w.tamper().set_bit());
+    }
+
+    /// Returns whether a tamper was detected.
+    #[cfg(feature = "min-samd51g")]
+    pub fn is_tamper_detected(&self) -> bool {
+        self.mode0().intflag.read().tamper().bit_is_set()
+    }
+
+    /// Returns the mask of the [`TamperInput`]s that were detected, with
+    /// `TamperInput::InN` at bit N.
+    #[cfg(feature = "min-samd51g")]
+    pub fn tamper_inputs(&self) -> u8 {
+        (self.mode0().tampid.read().bits() & 0x1f) as u8
+    }
+
+    /// Clears the tamper flag and the detected tamper inputs.
+    #[cfg(feature = "min-samd51g")]
+    pub fn clear_tamper(&mut self) {
+        // Writing a 1 clears the flags
+        self.mode0()
+            .tampid
+            .write(|w| unsafe { w.bits(self.mode0().tampid.read().bits()) });
+        self.mode0().intflag.write(|w| w.tamper().set_bit());
+    }
+
+    /// Returns the backup register `index`. The backup registers are retained
+    /// in backup sleep.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than 8.
+    #[cfg(feature = "min-samd51g")]
+    pub fn backup_register(&self, index: usize) -> u32 {
+        self.mode0().bkup[index].read().bits()
+    }
+
+    /// Sets the backup register `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than 8.
+    #[cfg(feature = "min-samd51g")]
+    pub fn set_backup_register(&mut self, index: usize, value: u32) {
+        self.mode0().bkup[index].write(|w| unsafe { w.bits(value) });
+    }
+
+    /// Enables a pair of general purpose registers. The RTC is briefly
+    /// disabled while the pair is enabled.
+    ///
+    /// The pair takes over the storage of its compare or alarm register, so
+    /// [`GpPair::Gp0Gp1`] gives up COMP0/ALARM0 and [`GpPair::Gp2Gp3`] gives
+    /// up COMP1/ALARM1 until the RTC is reset. Switching to 32-bit counter
+    /// mode disables GP0 and GP1 again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pair` is [`GpPair::Gp0Gp1`] in 32-bit counter mode, where
+    /// COMP0 is used by the `CountDown` and `Monotonic` implementations.
+    #[cfg(feature = "min-samd51g")]
+    pub fn enable_general_purpose_registers(&mut self, pair: GpPair) {
+        assert!(
+            pair != GpPair::Gp0Gp1 || !self.mode0_ctrla().read().mode().is_count32(),
+            "COMP0 is in use in 32-bit counter mode"
+        );
+        self.enable(false);
+        self.mode0().ctrlb.modify(|_, w| match pair {
+            GpPair::Gp0Gp1 => w.gp0en().set_bit(),
+            GpPair::Gp2Gp3 => w.gp2en().set_bit(),
+        });
+        self.enable(true);
+    }
+
+    /// Returns whether the general purpose register `index` belongs to an
+    /// enabled pair
+    #[cfg(feature = "min-samd51g")]
+    fn general_purpose_enabled(&self, index: usize) -> bool {
+        let ctrlb = self.mode0().ctrlb.read();
+        match index {
+            0 | 1 => ctrlb.gp0en().bit_is_set(),
+            _ => ctrlb.gp2en().bit_is_set(),
+        }
+    }
+
+    /// Returns the general purpose register `index`. The general purpose
+    /// registers are retained in backup sleep.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than 4, or if its pair was not enabled
+    /// with [`enable_general_purpose_registers`](Self::enable_general_purpose_registers).
+    #[cfg(feature = "min-samd51g")]
+    pub fn general_purpose_register(&self, index: usize) -> u32 {
+        assert!(
+            index < 4 && self.general_purpose_enabled(index),
+            "General purpose register not enabled"
+        );
+        self.mode0().gp[index].read().bits()
+    }
+
+    /// Sets the general purpose register `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than 4, or if its pair was not enabled
+    /// with [`enable_general_purpose_registers`](Self::enable_general_purpose_registers).
+    #[cfg(feature = "min-samd51g")]
+    pub fn set_general_purpose_register(&mut self, index: usize, value: u32) {
+        assert!(
+            index < 4 && self.general_purpose_enabled(index),
+            "General purpose register not enabled"
+        );
+        self.mode0().gp[index].write(|w| unsafe { w.bits(value) });
+        self.sync();
+    }
 }
 
 impl Rtc<Count32Mode> {
@@ -299,6 +716,108 @@ impl Rtc<Count32Mode> {
     }
 }
 
+impl Rtc<Count32Mode> {
+    /// Returns the counter value captured by the last tamper detection.
+    #[cfg(feature = "min-samd51g")]
+    pub fn tamper_timestamp(&self) -> u32 {
+        self.mode0().timestamp.read().count().bits()
+    }
+}
+
+impl Rtc<Count16Mode> {
+    /// Configures the RTC in 16-bit counter mode with no prescaler, the
+    /// counter initialized to zero and the period set to its maximum.
+    pub fn count16_mode(rtc: RTC, rtc_clock_freq: Hertz, pm: &mut PM) -> Self {
+        Rtc::count32_mode(rtc, rtc_clock_freq, pm).into_count16_mode()
+    }
+
+    /// Returns the internal counter value.
+    #[inline]
+    pub fn count16(&self) -> u16 {
+        // synchronize this read on SAMD11/21. SAMx5x is automatically synchronized
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        {
+            self.mode1().readreq.modify(|_, w| w.rcont().set_bit());
+            self.sync();
+        }
+        self.mode1().count.read().bits()
+    }
+
+    /// Sets the internal counter value.
+    #[inline]
+    pub fn set_count16(&mut self, count: u16) {
+        self.sync();
+        self.enable(false);
+
+        self.sync();
+        self.mode1()
+            .count
+            .write(|w| unsafe { w.count().bits(count) });
+
+        self.sync();
+        self.enable(true);
+    }
+
+    /// Sets the period, the highest value of the counter before it wraps
+    /// around to zero.
+    #[inline]
+    pub fn set_period16(&mut self, period: u16) {
+        self.mode1().per.write(|w| unsafe { w.per().bits(period) });
+        self.sync();
+    }
+
+    /// Sets the compare value `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than the number of compare registers, 2
+    /// on SAMD11/21 and 4 on SAMx5x.
+    #[inline]
+    pub fn set_compare16(&mut self, index: usize, value: u16) {
+        self.mode1().comp[index].write(|w| unsafe { w.comp().bits(value) });
+        self.sync();
+    }
+
+    /// Mask of the compare `index` in the INTFLAG, INTENSET and INTENCLR
+    /// registers
+    #[inline]
+    fn compare16_flag(&self, index: usize) -> u32 {
+        assert!(index < self.mode1().comp.len(), "Invalid compare index");
+        #[cfg(feature = "min-samd51g")]
+        return 1 << (8 + index);
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        return 1 << index;
+    }
+
+    /// Returns whether the compare `index` interrupt flag is set.
+    pub fn is_compare16(&self, index: usize) -> bool {
+        self.mode1().intflag.read().bits() as u32 & self.compare16_flag(index) != 0
+    }
+
+    /// Clears the compare `index` interrupt flag.
+    pub fn clear_compare16(&mut self, index: usize) {
+        let flag = self.compare16_flag(index);
+        // Writing a 1 clears the flag
+        self.mode1().intflag.write(|w| unsafe { w.bits(flag as _) });
+    }
+
+    /// Enables the compare `index` interrupt.
+    pub fn enable_compare16_interrupt(&mut self, index: usize) {
+        let flag = self.compare16_flag(index);
+        self.mode1()
+            .intenset
+            .write(|w| unsafe { w.bits(flag as _) });
+    }
+
+    /// Disables the compare `index` interrupt.
+    pub fn disable_compare16_interrupt(&mut self, index: usize) {
+        let flag = self.compare16_flag(index);
+        self.mode1()
+            .intenclr
+            .write(|w| unsafe { w.bits(flag as _) });
+    }
+}
+
 impl Rtc<ClockMode> {
     pub fn clock_mode(rtc: RTC, rtc_clock_freq: Hertz, pm: &mut PM) -> Self {
         Rtc::count32_mode(rtc, rtc_clock_freq, pm).into_clock_mode()
@@ -333,6 +852,81 @@ impl Rtc<ClockMode> {
         });
         self.sync();
     }
+
+    /// Sets `alarm` to trigger when the calendar matches the fields of `time`
+    /// selected by `mask`.
+    pub fn set_alarm(&mut self, alarm: Alarm, time: Datetime, mask: AlarmMask) {
+        macro_rules! write_alarm {
+            ($alarm:ident, $mask:ident) => {{
+                self.mode2().$alarm.write(|w| unsafe {
+                    w.second()
+                        .bits(time.seconds)
+                        .minute()
+                        .bits(time.minutes)
+                        .hour()
+                        .bits(time.hours)
+                        .day()
+                        .bits(time.day)
+                        .month()
+                        .bits(time.month)
+                        .year()
+                        .bits(time.year)
+                });
+                self.sync();
+                self.mode2()
+                    .$mask
+                    .write(|w| unsafe { w.sel().bits(mask as u8) });
+                self.sync();
+            }};
+        }
+
+        match alarm {
+            Alarm::Alarm0 => write_alarm!(alarm0, mask0),
+            #[cfg(feature = "min-samd51g")]
+            Alarm::Alarm1 => write_alarm!(alarm1, mask1),
+        }
+    }
+
+    /// Returns whether the `alarm` interrupt flag is set.
+    pub fn is_alarm_triggered(&self, alarm: Alarm) -> bool {
+        self.mode2().intflag.read().bits() as u32 & alarm.flag() != 0
+    }
+
+    /// Clears the `alarm` interrupt flag.
+    pub fn clear_alarm(&mut self, alarm: Alarm) {
+        // Writing a 1 clears the flag
+        self.mode2()
+            .intflag
+            .write(|w| unsafe { w.bits(alarm.flag() as _) });
+    }
+
+    /// Enables the `alarm` interrupt.
+    pub fn enable_alarm_interrupt(&mut self, alarm: Alarm) {
+        self.mode2()
+            .intenset
+            .write(|w| unsafe { w.bits(alarm.flag() as _) });
+    }
+
+    /// Disables the `alarm` interrupt.
+    pub fn disable_alarm_interrupt(&mut self, alarm: Alarm) {
+        self.mode2()
+            .intenclr
+            .write(|w| unsafe { w.bits(alarm.flag() as _) });
+    }
+
+    /// Returns the calendar value captured by the last tamper detection.
+    #[cfg(feature = "min-samd51g")]
+    pub fn tamper_timestamp(&self) -> Datetime {
+        let timestamp = self.mode2().timestamp.read();
+        Datetime {
+            seconds: timestamp.second().bits(),
+            minutes: timestamp.minute().bits(),
+            hours: timestamp.hour().bits(),
+            day: timestamp.day().bits(),
+            month: timestamp.month().bits(),
+            year: timestamp.year().bits(),
+        }
+    }
 }
 
 // --- Timer / Counter Functionality
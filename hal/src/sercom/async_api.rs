@@ -0,0 +1,100 @@
+//! Interrupt handling for the `async` SERCOM drivers
+//!
+//! The [`Uart`](super::uart::Uart), [`Spi`](super::spi::Spi) and
+//! [`I2c`](super::i2c::I2c) types implement the `embedded-io-async` and
+//! `embedded-hal-async` traits when the `async` feature is enabled. Their
+//! futures enable the SERCOM interrupts they are waiting on, and rely on
+//! [`on_interrupt`] being called from the corresponding interrupt handler to
+//! be woken up.
+//!
+//! ```no_run
+//! use atsamd_hal::pac::interrupt;
+//! use atsamd_hal::sercom::{self, Sercom0};
+//!
+//! #[interrupt]
+//! fn SERCOM0() {
+//!     sercom::on_interrupt::<Sercom0>();
+//! }
+//! ```
+//!
+//! On SAMx5x chips, each SERCOM has four interrupt lines (`SERCOMn_0` to
+//! `SERCOMn_OTHER`), and [`on_interrupt`] must be called from all of them.
+//!
+//! With the `dma` feature also enabled, the `*_with_dma_async` methods
+//! transfer whole buffers with the DMAC instead. Those futures are woken by
+//! [`dmac::on_interrupt`](crate::dmac::on_interrupt), not by the SERCOM
+//! interrupt.
+
+use core::future::poll_fn;
+use core::task::Poll;
+
+use super::Sercom;
+use crate::async_hal::WakerCell;
+
+/// Maximum number of SERCOM instances on any supported chip
+const NUM_SERCOM: usize = 8;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: WakerCell = WakerCell::new();
+
+/// Wakers for tasks waiting on receive-side flags
+pub(super) static RX_WAKERS: [WakerCell; NUM_SERCOM] = [NEW_WAKER; NUM_SERCOM];
+
+/// Wakers for tasks waiting on transmit-side flags, or on any flag for
+/// peripherals that can't be split
+pub(super) static TX_WAKERS: [WakerCell; NUM_SERCOM] = [NEW_WAKER; NUM_SERCOM];
+
+/// SERCOM interrupt handler for the `async` drivers
+///
+/// Disables the interrupts that fired, then wakes the tasks waiting on the
+/// SERCOM. This function must be called from the SERCOM interrupt handler(s)
+/// for every SERCOM used through the `async` API.
+#[inline]
+pub fn on_interrupt<S: Sercom>() {
+    // INTFLAG, INTENSET and INTENCLR are at the same offsets in every mode
+    #[cfg(any(feature = "samd11", feature = "samd21"))]
+    let regs = S::reg_block().usart();
+    #[cfg(feature = "min-samd51g")]
+    let regs = S::reg_block().usart_int();
+
+    let pending = regs.intflag.read().bits() & regs.intenset.read().bits();
+    regs.intenclr.write(|w| unsafe { w.bits(pending) });
+
+    RX_WAKERS[S::NUM].wake();
+    TX_WAKERS[S::NUM].wake();
+}
+
+/// Wait until one of the flags in `mask` is set
+///
+/// `read` returns the current interrupt flags of `periph`, and `enable`
+/// enables its interrupts for the given flags. Returns the set flags from
+/// `mask`.
+// The `async` feature requires Rust 1.75 or later
+#[allow(clippy::incompatible_msrv)]
+#[inline]
+pub(super) async fn wait_flags<T>(
+    periph: &mut T,
+    waker: &WakerCell,
+    mask: u8,
+    read: fn(&T) -> u8,
+    enable: fn(&mut T, u8),
+) -> u8 {
+    poll_fn(|cx| {
+        let flags = read(periph) & mask;
+        if flags != 0 {
+            return Poll::Ready(flags);
+        }
+
+        waker.register(cx.waker());
+        enable(periph, mask);
+
+        // The flags could have been set before the interrupts were enabled
+        let flags = read(periph) & mask;
+        if flags != 0 {
+            Poll::Ready(flags)
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
@@ -11,7 +11,7 @@ use crate::{
         Beat, Buffer, Transfer, TriggerAction,
     },
     sercom::{
-        i2c::{self, I2c},
+        i2c::{self, I2c, I2cClient},
         spi::{self, Spi},
         uart::{self, Uart},
         Sercom,
@@ -170,6 +170,124 @@ impl<C: i2c::AnyConfig> I2c<C> {
     }
 }
 
+unsafe impl<C: i2c::AnyClientConfig> Buffer for I2cClient<C> {
+    type Beat = i2c::Word;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.data_ptr()
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+impl<C: i2c::AnyClientConfig> I2cClient<C> {
+    /// Transform an [`I2cClient`] into a DMA [`Transfer`] and receive the
+    /// bytes written by the host into the provided buffer.
+    ///
+    /// This method should be called upon an address match with a
+    /// [`Direction::Write`](i2c::Direction::Write) transaction, instead of
+    /// [`respond_address`](I2cClient::respond_address). It acknowledges the
+    /// address and lets the DMAC read every received byte. Smart mode must be
+    /// enabled. The `waker` is called once the buffer is full; the transfer
+    /// should also be stopped when the host sends a stop condition
+    /// ([`PREC`](i2c::Flags::PREC)).
+    pub fn receive_with_dma<Ch, B, W>(
+        self,
+        buf: B,
+        mut channel: Ch,
+        waker: W,
+    ) -> Transfer<Channel<Ch::Id, Busy>, BufferPair<Self, B>, W>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = i2c::Word> + 'static,
+        W: FnOnce(CallbackStatus) + 'static,
+    {
+        assert!(self.smart_mode());
+
+        channel
+            .as_mut()
+            .enable_interrupts(InterruptFlags::new().with_tcmpl(true));
+
+        #[cfg(feature = "min-samd51g")]
+        let trigger_action = TriggerAction::BURST;
+
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        let trigger_action = TriggerAction::BEAT;
+
+        // SAFETY: This is safe because the of the `'static` bound check
+        // for `B`, and the fact that the buffer length of an `I2cClient` is
+        // always 1.
+        let xfer = unsafe { dmac::Transfer::new_unchecked(channel, self, buf, false) };
+        let mut xfer = xfer
+            .with_waker(waker)
+            .begin(C::Sercom::DMA_RX_TRIGGER, trigger_action);
+
+        // SAFETY: we borrow the source from under a `Busy` transfer. The client
+        // holds the clock until the address is acknowledged, so no trigger
+        // happens before we call `respond_address`.
+        unsafe { xfer.borrow_source().respond_address(true) };
+        xfer
+    }
+
+    /// Transform an [`I2cClient`] into a DMA [`Transfer`] and send the
+    /// provided buffer to the host.
+    ///
+    /// This method should be called upon an address match with a
+    /// [`Direction::Read`](i2c::Direction::Read) transaction, instead of
+    /// [`respond_address`](I2cClient::respond_address). It acknowledges the
+    /// address and lets the DMAC write every transmitted byte. Smart mode must
+    /// be enabled. The `waker` is called once the whole buffer has been
+    /// written; the transfer should also be stopped when the host ends the
+    /// transaction ([`PREC`](i2c::Flags::PREC)).
+    #[inline]
+    pub fn send_with_dma<Ch, B, W>(
+        self,
+        buf: B,
+        mut channel: Ch,
+        waker: W,
+    ) -> Transfer<Channel<Ch::Id, Busy>, BufferPair<B, Self>, W>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = i2c::Word> + 'static,
+        W: FnOnce(CallbackStatus) + 'static,
+    {
+        assert!(self.smart_mode());
+
+        channel
+            .as_mut()
+            .enable_interrupts(InterruptFlags::new().with_tcmpl(true));
+
+        #[cfg(feature = "min-samd51g")]
+        let trigger_action = TriggerAction::BURST;
+
+        #[cfg(any(feature = "samd11", feature = "samd21"))]
+        let trigger_action = TriggerAction::BEAT;
+
+        // SAFETY: This is safe because the of the `'static` bound check
+        // for `B`, and the fact that the buffer length of an `I2cClient` is
+        // always 1.
+        let xfer = unsafe { dmac::Transfer::new_unchecked(channel, buf, self, false) };
+        let mut xfer = xfer
+            .with_waker(waker)
+            .begin(C::Sercom::DMA_TX_TRIGGER, trigger_action);
+
+        // SAFETY: we borrow the destination from under a `Busy` transfer. The
+        // client holds the clock until the address is acknowledged, so no
+        // trigger happens before we call `respond_address`.
+        unsafe { xfer.borrow_destination().respond_address(true) };
+        xfer
+    }
+}
+
 //=============================================================================
 // UART DMA transfers
 //=============================================================================
@@ -378,3 +496,213 @@ where
             .begin(C::Sercom::DMA_RX_TRIGGER, trigger_action)
     }
 }
+
+//=============================================================================
+// Async DMA transfers
+//=============================================================================
+
+/// Channel and buffers returned by the `async` DMA methods, in the same order
+/// as [`Transfer::wait_async`]. They are returned in the `Err` variant if the
+/// DMAC reported a transfer error.
+#[cfg(feature = "async")]
+pub type AsyncDmaResult<Id, S, D> = Result<(Channel<Id, Ready>, S, D), (Channel<Id, Ready>, S, D)>;
+
+/// Trigger action of the SERCOM DMA transfers
+#[cfg(all(feature = "async", feature = "min-samd51g"))]
+const TRIGGER_ACTION: TriggerAction = TriggerAction::BURST;
+
+/// Trigger action of the SERCOM DMA transfers
+#[cfg(all(feature = "async", any(feature = "samd11", feature = "samd21")))]
+const TRIGGER_ACTION: TriggerAction = TriggerAction::BEAT;
+
+#[cfg(feature = "async")]
+impl<C: i2c::AnyConfig> I2c<C> {
+    /// Asynchronously receive into the provided buffer with DMA. The buffer
+    /// length must be 255 bytes or shorter.
+    ///
+    /// This is the `async` counterpart of
+    /// [`receive_with_dma`](Self::receive_with_dma). The returned future is
+    /// driven by the DMAC interrupt, see [`dmac::on_interrupt`]. It is
+    /// recommended that you check for errors after the transfer is complete
+    /// by calling [`read_status`](I2c::read_status).
+    pub async fn receive_with_dma_async<Ch, B>(
+        self,
+        address: u8,
+        _ready_token: I2cBusReady,
+        buf: B,
+        channel: Ch,
+    ) -> AsyncDmaResult<Ch::Id, Self, B>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = i2c::Word> + 'static,
+    {
+        let len = buf.buffer_len();
+        assert!(len > 0 && len <= 255);
+
+        // SAFETY: This is safe because the of the `'static` bound check
+        // for `B`, and the fact that the buffer length of an `I2c` is always 1.
+        let xfer = unsafe { dmac::Transfer::new_unchecked(channel, self, buf, false) };
+        let mut xfer = xfer.begin(C::Sercom::DMA_RX_TRIGGER, TRIGGER_ACTION);
+
+        // SAFETY: no trigger happens before we call `start_dma_read`, see
+        // `receive_with_dma`.
+        unsafe { xfer.borrow_source().start_dma_read(address, len as u8) };
+        xfer.wait_async().await
+    }
+
+    /// Asynchronously send the provided buffer with DMA. The buffer length
+    /// must be 255 bytes or shorter.
+    ///
+    /// This is the `async` counterpart of
+    /// [`send_with_dma`](Self::send_with_dma). The returned future is driven
+    /// by the DMAC interrupt, see [`dmac::on_interrupt`]. It is recommended
+    /// that you check for errors after the transfer is complete by calling
+    /// [`read_status`](I2c::read_status).
+    pub async fn send_with_dma_async<Ch, B>(
+        self,
+        address: u8,
+        _ready_token: I2cBusReady,
+        buf: B,
+        channel: Ch,
+    ) -> AsyncDmaResult<Ch::Id, B, Self>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = i2c::Word> + 'static,
+    {
+        let len = buf.buffer_len();
+        assert!(len > 0 && len <= 255);
+
+        // SAFETY: This is safe because the of the `'static` bound check
+        // for `B`, and the fact that the buffer length of an `I2c` is always 1.
+        let xfer = unsafe { dmac::Transfer::new_unchecked(channel, buf, self, false) };
+        let mut xfer = xfer.begin(C::Sercom::DMA_TX_TRIGGER, TRIGGER_ACTION);
+
+        // SAFETY: no trigger happens before we call `start_dma_write`, see
+        // `send_with_dma`.
+        unsafe {
+            xfer.borrow_destination()
+                .start_dma_write(address, len as u8)
+        };
+        xfer.wait_async().await
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C, D> Uart<C, D>
+where
+    Self: Buffer<Beat = C::Word>,
+    C: uart::ValidConfig,
+    D: uart::Receive,
+{
+    /// Asynchronously receive into the provided buffer with DMA
+    ///
+    /// This is the `async` counterpart of
+    /// [`receive_with_dma`](Self::receive_with_dma). The returned future is
+    /// driven by the DMAC interrupt, see [`dmac::on_interrupt`].
+    pub async fn receive_with_dma_async<Ch, B>(
+        self,
+        buf: B,
+        channel: Ch,
+    ) -> AsyncDmaResult<Ch::Id, Self, B>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = C::Word> + 'static,
+    {
+        // SAFETY: This is safe because the of the `'static` bound check
+        // for `B`, and the fact that the buffer length of an `Uart` is always 1.
+        let xfer = unsafe { dmac::Transfer::new_unchecked(channel, self, buf, false) };
+        xfer.begin(C::Sercom::DMA_RX_TRIGGER, TRIGGER_ACTION)
+            .wait_async()
+            .await
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C, D> Uart<C, D>
+where
+    Self: Buffer<Beat = C::Word>,
+    C: uart::ValidConfig,
+    D: uart::Transmit,
+{
+    /// Asynchronously send the provided buffer with DMA
+    ///
+    /// This is the `async` counterpart of
+    /// [`send_with_dma`](Self::send_with_dma). The returned future is driven
+    /// by the DMAC interrupt, see [`dmac::on_interrupt`].
+    pub async fn send_with_dma_async<Ch, B>(
+        self,
+        buf: B,
+        channel: Ch,
+    ) -> AsyncDmaResult<Ch::Id, B, Self>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = C::Word> + 'static,
+    {
+        // SAFETY: This is safe because the of the `'static` bound check
+        // for `B`, and the fact that the buffer length of an `Uart` is always 1.
+        let xfer = unsafe { dmac::Transfer::new_unchecked(channel, buf, self, false) };
+        xfer.begin(C::Sercom::DMA_TX_TRIGGER, TRIGGER_ACTION)
+            .wait_async()
+            .await
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C, A> Spi<C, A>
+where
+    C: spi::ValidConfig,
+    A: spi::Transmit,
+    Self: Buffer<Beat = C::Word>,
+{
+    /// Asynchronously send the provided buffer with DMA
+    ///
+    /// This is the `async` counterpart of
+    /// [`send_with_dma`](Self::send_with_dma). The returned future is driven
+    /// by the DMAC interrupt, see [`dmac::on_interrupt`].
+    pub async fn send_with_dma_async<Ch, B>(
+        self,
+        buf: B,
+        channel: Ch,
+    ) -> AsyncDmaResult<Ch::Id, B, Self>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = C::Word> + 'static,
+    {
+        // SAFETY: This is safe because the of the `'static` bound check
+        // for `B`, and the fact that the buffer length of an `Spi` is always 1.
+        let xfer = unsafe { Transfer::new_unchecked(channel, buf, self, false) };
+        xfer.begin(C::Sercom::DMA_TX_TRIGGER, TRIGGER_ACTION)
+            .wait_async()
+            .await
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C, A> Spi<C, A>
+where
+    C: spi::ValidConfig,
+    A: spi::Receive,
+    Self: Buffer<Beat = C::Word>,
+{
+    /// Asynchronously receive into the provided buffer with DMA
+    ///
+    /// This is the `async` counterpart of
+    /// [`receive_with_dma`](Self::receive_with_dma). The returned future is
+    /// driven by the DMAC interrupt, see [`dmac::on_interrupt`].
+    pub async fn receive_with_dma_async<Ch, B>(
+        self,
+        buf: B,
+        channel: Ch,
+    ) -> AsyncDmaResult<Ch::Id, Self, B>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = C::Word> + 'static,
+    {
+        // SAFETY: This is safe because the of the `'static` bound check
+        // for `B`, and the fact that the buffer length of an `Spi` is always 1.
+        let xfer = unsafe { Transfer::new_unchecked(channel, self, buf, false) };
+        xfer.begin(C::Sercom::DMA_RX_TRIGGER, TRIGGER_ACTION)
+            .wait_async()
+            .await
+    }
+}
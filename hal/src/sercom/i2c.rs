@@ -177,9 +177,28 @@
 //! let config = i2c.disable();
 //! ```
 //!
+//! # Client mode
+//!
+//! The SERCOM can also act as an I2C client (slave), responding to a host on
+//! the bus. A [`ClientConfig`] is created from the same [`Pads`] as a
+//! [`Config`], along with an [`AddressMatch`] describing the 7-bit or 10-bit
+//! address(es) the client responds to. Enabling it yields an [`I2cClient`].
+//!
+//! ```no_run
+//! use atsamd_hal::sercom::i2c::{self, AddressMatch, Flags};
+//!
+//! let mut client = i2c::ClientConfig::new(&pm, sercom, pads, AddressMatch::single(0x42))
+//!     .enable();
+//! client.enable_interrupts(Flags::AMATCH | Flags::DRDY | Flags::PREC);
+//! ```
+//!
+//! Transactions are driven from the SERCOM interrupt handler by calling
+//! [`I2cClient::handle_interrupt`] with a [`ClientHandler`], which receives
+//! callbacks for address matches, received and transmitted bytes, and stop
+//! conditions.
+//!
 //! # Non-supported features
 //!
-//! * Slave mode is not supported at this time.
 //! * High-speed mode is not supported.
 //! * 4-wire mode is not supported.
 //! * 32-bit extension mode is not supported (SAMx5x). If you need to transfer
@@ -274,8 +293,14 @@ pub use flags::*;
 mod config;
 pub use config::*;
 
+mod client;
+pub use client::*;
+
 mod impl_ehal;
 
+#[cfg(feature = "async")]
+mod async_api;
+
 /// Word size for an I2C message
 pub type Word = u8;
 
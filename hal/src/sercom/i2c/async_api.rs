@@ -0,0 +1,150 @@
+//! `embedded-hal-async` trait implementations for [`I2c`]s
+//!
+//! The futures are driven by the SERCOM interrupt, which must call
+//! [`on_interrupt`](crate::sercom::on_interrupt).
+
+use super::{AnyConfig, Error, Flags, I2c};
+use crate::sercom::async_api::{wait_flags, TX_WAKERS};
+use crate::sercom::Sercom;
+use embedded_hal_async::i2c::{ErrorKind, ErrorType, NoAcknowledgeSource, Operation};
+
+impl embedded_hal_async::i2c::Error for Error {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Error::BusError => ErrorKind::Bus,
+            Error::ArbitrationLost => ErrorKind::ArbitrationLoss,
+            Error::Nack => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+            Error::LengthError | Error::Timeout => ErrorKind::Other,
+        }
+    }
+}
+
+impl<C: AnyConfig> I2c<C> {
+    /// Wait until one of the `flags` is set
+    #[inline]
+    async fn wait_flags(&mut self, flags: Flags) -> Flags {
+        let bits = wait_flags(
+            self,
+            &TX_WAKERS[<C::Sercom as Sercom>::NUM],
+            flags.bits(),
+            |i2c| i2c.read_flags().bits(),
+            |i2c, bits| i2c.enable_interrupts(Flags::from_bits_truncate(bits)),
+        )
+        .await;
+        Flags::from_bits_truncate(bits)
+    }
+
+    /// Send the address for a write transaction
+    async fn start_write_async(&mut self, addr: u8) -> Result<(), Error> {
+        self.config.as_mut().registers.start_write(addr)?;
+        self.wait_flags(Flags::MB | Flags::ERROR).await;
+        self.read_status().check_bus_error()
+    }
+
+    /// Send the address for a read transaction
+    async fn start_read_async(&mut self, addr: u8) -> Result<(), Error> {
+        self.config.as_mut().registers.start_read(addr)?;
+        let flags = self.wait_flags(Flags::MB | Flags::SB | Flags::ERROR).await;
+        self.read_status().check_bus_error()?;
+        // If arbitration was lost, it will be signalled via the MB flag
+        if flags.contains(Flags::MB) {
+            return Err(Error::ArbitrationLost);
+        }
+        Ok(())
+    }
+
+    /// Send `bytes`, waiting for each one to be acknowledged
+    async fn send_bytes_async(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        for &byte in bytes {
+            self.config.as_mut().registers.write_byte(byte);
+            self.wait_flags(Flags::MB | Flags::ERROR).await;
+            self.read_status().check_bus_error()?;
+        }
+        Ok(())
+    }
+
+    /// Fill `buffer` with received bytes
+    ///
+    /// When `continuing` is set, the byte received last by a previous read is
+    /// acknowledged before reading into `buffer`.
+    async fn fill_buffer_async(
+        &mut self,
+        buffer: &mut [u8],
+        continuing: bool,
+    ) -> Result<(), Error> {
+        for (i, dest) in buffer.iter_mut().enumerate() {
+            if i > 0 || continuing {
+                // Ack the last byte so that we can receive another one
+                self.config.as_mut().registers.cmd_read();
+            }
+            self.wait_flags(Flags::SB | Flags::ERROR).await;
+            self.read_status().check_bus_error()?;
+            *dest = self.config.as_mut().registers.read_byte();
+        }
+        Ok(())
+    }
+
+    /// Perform `operations` without the final stop condition
+    ///
+    /// Adjacent operations of the same type are merged, and a repeated start
+    /// is sent whenever the direction changes.
+    async fn do_transaction_async(
+        &mut self,
+        addr: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Error> {
+        // Direction of the previous operation, `true` for reads
+        let mut previous_read = None;
+        for op in operations {
+            match op {
+                Operation::Write(bytes) => {
+                    if previous_read != Some(false) {
+                        if previous_read == Some(true) {
+                            self.config.as_mut().registers.set_nack();
+                        }
+                        self.start_write_async(addr).await?;
+                    }
+                    self.send_bytes_async(bytes).await?;
+                    previous_read = Some(false);
+                }
+                Operation::Read(buffer) => {
+                    if buffer.is_empty() {
+                        continue;
+                    }
+                    let continuing = previous_read == Some(true);
+                    if !continuing {
+                        self.start_read_async(addr).await?;
+                    }
+                    self.fill_buffer_async(buffer, continuing).await?;
+                    previous_read = Some(true);
+                }
+            }
+        }
+
+        if previous_read == Some(true) {
+            self.config.as_mut().registers.set_nack();
+        }
+        Ok(())
+    }
+}
+
+impl<C: AnyConfig> ErrorType for I2c<C> {
+    type Error = Error;
+}
+
+impl<C: AnyConfig> embedded_hal_async::i2c::I2c for I2c<C> {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Error> {
+        let result = self.do_transaction_async(address, operations).await;
+        match result {
+            // The bus isn't ours anymore, don't try to send a stop condition
+            Err(Error::BusError) | Err(Error::ArbitrationLost) => (),
+            _ => self.cmd_stop(),
+        }
+        result
+    }
+}
@@ -0,0 +1,545 @@
+//! I2C client mode
+//!
+//! In client mode, the SERCOM responds to transactions started by a host on
+//! the bus. A [`ClientConfig`] is created and configured in the same way as a
+//! [`Config`](super::Config), using the same [`Pads`](super::Pads) types, and
+//! is enabled to yield an [`I2cClient`].
+//!
+//! Transactions are driven from the SERCOM interrupt. Enable the
+//! [`AMATCH`](Flags::AMATCH), [`DRDY`](Flags::DRDY), [`PREC`](Flags::PREC)
+//! and, optionally, [`ERROR`](Flags::ERROR) interrupts, then call
+//! [`I2cClient::handle_interrupt`] from the interrupt handler with a type
+//! implementing [`ClientHandler`]. The handler is called back for every
+//! address match, received byte, transmitted byte and stop condition.
+
+use super::{Error, Flags, PadSet, Registers, Status};
+use crate::{
+    sercom::*,
+    typelevel::{Is, Sealed},
+};
+
+//=============================================================================
+// AddressMatch
+//=============================================================================
+
+/// Address matching mode of an I2C client
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressMatch {
+    /// Respond to `address`, ignoring the bits set in `mask`
+    ///
+    /// When `ten_bit` is set, `address` and `mask` are 10-bit values and the
+    /// client responds to 10-bit addressing. Otherwise, only the lower 7 bits
+    /// are used.
+    Mask {
+        address: u16,
+        mask: u16,
+        ten_bit: bool,
+    },
+    /// Respond to either of the two 7-bit addresses
+    Dual(u8, u8),
+    /// Respond to any 7-bit address between `low` and `high`, inclusive
+    Range { low: u8, high: u8 },
+}
+
+impl AddressMatch {
+    /// Respond to a single 7-bit address
+    #[inline]
+    pub fn single(address: u8) -> Self {
+        AddressMatch::Mask {
+            address: address as u16,
+            mask: 0,
+            ten_bit: false,
+        }
+    }
+
+    /// Respond to a single 10-bit address
+    #[inline]
+    pub fn single_ten_bit(address: u16) -> Self {
+        AddressMatch::Mask {
+            address,
+            mask: 0,
+            ten_bit: true,
+        }
+    }
+}
+
+/// Direction of a client transaction, as seen from the host
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// The host writes to the client
+    Write,
+    /// The host reads from the client
+    Read,
+}
+
+//=============================================================================
+// ClientConfig
+//=============================================================================
+
+/// A configurable, disabled I2C peripheral in client mode
+///
+/// This `struct` is the client mode counterpart of [`Config`](super::Config).
+/// Upon creation, the [`ClientConfig`] takes ownership of the [`Sercom`] and
+/// resets it, returning it configured as an I2C client. Unlike a host, a
+/// client does not generate the bus clock, so no GCLK frequency is needed.
+///
+/// [`ClientConfig`] uses a builder-pattern API to configure the peripheral,
+/// culminating in a call to [`enable`](ClientConfig::enable), which consumes
+/// the [`ClientConfig`] and returns an enabled [`I2cClient`].
+pub struct ClientConfig<P>
+where
+    P: PadSet,
+{
+    pub(super) registers: Registers<P::Sercom>,
+    pads: P,
+}
+
+impl<P: PadSet> ClientConfig<P> {
+    /// Create a new [`ClientConfig`] in the default configuration.
+    #[inline]
+    fn default(sercom: P::Sercom, pads: P, address: AddressMatch) -> Self {
+        let mut registers = Registers::new(sercom);
+        registers.swrst();
+        registers.set_client_mode();
+        registers.set_address_match(address);
+        Self { registers, pads }
+    }
+
+    /// Create a new [`ClientConfig`] in the default configuration
+    ///
+    /// This function will enable the corresponding APB clock, reset the
+    /// [`Sercom`] peripheral, and return a [`ClientConfig`] responding to the
+    /// given [`AddressMatch`].
+    ///
+    /// Note that [`ClientConfig`] takes ownership of both the
+    /// PAC [`Sercom`] struct as well as the [`Pads`](super::Pads).
+    ///
+    /// Users must configure GCLK manually.
+    #[inline]
+    pub fn new(
+        apb_clk_ctrl: &APB_CLK_CTRL,
+        mut sercom: P::Sercom,
+        pads: P,
+        address: AddressMatch,
+    ) -> Self {
+        sercom.enable_apb_clock(apb_clk_ctrl);
+        Self::default(sercom, pads, address)
+    }
+
+    /// Obtain a reference to the PAC `SERCOM` struct
+    ///
+    /// # Safety
+    ///
+    /// Directly accessing the `SERCOM` could break the invariants of the
+    /// type-level tracking in this module, so it is unsafe.
+    #[inline]
+    pub unsafe fn sercom(&self) -> &P::Sercom {
+        &self.registers.sercom
+    }
+
+    /// Trigger the [`Sercom`]'s SWRST and return a [`ClientConfig`] in the
+    /// default configuration, keeping the current address matching.
+    #[inline]
+    pub fn reset(self) -> ClientConfig<P> {
+        let address = self.get_address();
+        ClientConfig::default(self.registers.sercom, self.pads, address)
+    }
+
+    /// Consume the [`ClientConfig`], reset the peripheral, and return the
+    /// [`Sercom`] and [`Pads`](super::Pads)
+    #[inline]
+    pub fn free(mut self) -> (P::Sercom, P) {
+        self.registers.swrst();
+        (self.registers.free(), self.pads)
+    }
+
+    /// Set the address matching (builder pattern version)
+    #[inline]
+    pub fn address(mut self, address: AddressMatch) -> Self {
+        self.set_address(address);
+        self
+    }
+
+    /// Set the address matching (setter version)
+    #[inline]
+    pub fn set_address(&mut self, address: AddressMatch) {
+        self.registers.set_address_match(address);
+    }
+
+    /// Get the current address matching
+    #[inline]
+    pub fn get_address(&self) -> AddressMatch {
+        self.registers.get_address_match()
+    }
+
+    /// Respond to the general call address (builder pattern version)
+    ///
+    /// When set, the client also matches the general call address (0x00).
+    #[inline]
+    pub fn general_call(mut self, set: bool) -> Self {
+        self.set_general_call(set);
+        self
+    }
+
+    /// Respond to the general call address (setter version)
+    ///
+    /// When set, the client also matches the general call address (0x00).
+    #[inline]
+    pub fn set_general_call(&mut self, set: bool) {
+        self.registers.set_general_call(set);
+    }
+
+    /// Get the current general call setting
+    #[inline]
+    pub fn get_general_call(&self) -> bool {
+        self.registers.get_general_call()
+    }
+
+    /// Run in standby mode (builder pattern version)
+    ///
+    /// When set, the I2C peripheral will run in standby mode, and an address
+    /// match can wake up the device. See the datasheet for more details.
+    #[inline]
+    pub fn run_in_standby(mut self, set: bool) -> Self {
+        self.set_run_in_standby(set);
+        self
+    }
+
+    /// Run in standby mode (setter version)
+    ///
+    /// When set, the I2C peripheral will run in standby mode, and an address
+    /// match can wake up the device. See the datasheet for more details.
+    #[inline]
+    pub fn set_run_in_standby(&mut self, set: bool) {
+        self.registers.set_client_run_in_standby(set);
+    }
+
+    /// Get the current run in standby mode
+    #[inline]
+    pub fn get_run_in_standby(&self) -> bool {
+        self.registers.get_client_run_in_standby()
+    }
+
+    /// Set SCL Low Time-Out (builder pattern version)
+    ///
+    /// If SCL is held low for 25ms-35ms, the client will release its clock
+    /// hold and reset its internal state machine. The STATUS.LOWTOUT status
+    /// bit will be set.
+    #[inline]
+    pub fn low_timeout(mut self, set: bool) -> Self {
+        self.set_low_timeout(set);
+        self
+    }
+
+    /// Set SCL Low Time-Out (setter version)
+    ///
+    /// If SCL is held low for 25ms-35ms, the client will release its clock
+    /// hold and reset its internal state machine. The STATUS.LOWTOUT status
+    /// bit will be set.
+    #[inline]
+    pub fn set_low_timeout(&mut self, set: bool) {
+        self.registers.set_client_low_timeout(set);
+    }
+
+    /// Get SCL Low Time-Out setting
+    #[inline]
+    pub fn get_low_timeout(&self) -> bool {
+        self.registers.get_client_low_timeout()
+    }
+
+    /// Set Smart Mode (builder pattern version)
+    ///
+    /// In smart mode, reading the `DATA` register automatically acknowledges
+    /// the received byte, and writing it automatically transmits the next
+    /// byte. Smart mode is required for DMA transfers.
+    #[inline]
+    pub fn smart_mode(mut self, set: bool) -> Self {
+        self.set_smart_mode(set);
+        self
+    }
+
+    /// Set Smart Mode (setter version)
+    ///
+    /// In smart mode, reading the `DATA` register automatically acknowledges
+    /// the received byte, and writing it automatically transmits the next
+    /// byte. Smart mode is required for DMA transfers.
+    #[inline]
+    pub fn set_smart_mode(&mut self, set: bool) {
+        self.registers.set_client_smart_mode(set);
+    }
+
+    /// Get the current Smart Mode setting
+    #[inline]
+    pub fn get_smart_mode(&self) -> bool {
+        self.registers.get_client_smart_mode()
+    }
+
+    /// Enable the I2C peripheral
+    ///
+    /// The client will not respond to the host until the peripheral is
+    /// enabled.
+    #[inline]
+    pub fn enable(mut self) -> I2cClient<Self>
+    where
+        Self: AnyClientConfig,
+    {
+        self.registers.enable_peripheral(true);
+
+        I2cClient { config: self }
+    }
+}
+
+//=============================================================================
+// AnyClientConfig
+//=============================================================================
+
+/// Type class for all possible [`ClientConfig`] types
+///
+/// This trait uses the [`AnyKind`] trait pattern to create a [type class] for
+/// [`ClientConfig`] types. See the [`AnyKind`] documentation for more details
+/// on the pattern.
+///
+/// [`AnyKind`]: crate::typelevel#anykind-trait-pattern
+/// [type class]: crate::typelevel#type-classes
+pub trait AnyClientConfig: Is<Type = SpecificClientConfig<Self>> {
+    type Sercom: Sercom;
+    type Pads: PadSet<Sercom = Self::Sercom>;
+}
+
+/// Type alias to recover the specific [`ClientConfig`] type from an
+/// implementation of [`AnyClientConfig`]
+pub type SpecificClientConfig<C> = ClientConfig<<C as AnyClientConfig>::Pads>;
+
+/// Type alias to recover the specific [`Sercom`] type from an implementation
+/// of [`AnyClientConfig`]
+pub type ClientConfigSercom<C> = <C as AnyClientConfig>::Sercom;
+
+impl<P: PadSet> Sealed for ClientConfig<P> {}
+
+impl<P: PadSet> AnyClientConfig for ClientConfig<P> {
+    type Sercom = P::Sercom;
+    type Pads = P;
+}
+
+impl<P: PadSet> AsRef<Self> for ClientConfig<P> {
+    #[inline]
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<P: PadSet> AsMut<Self> for ClientConfig<P> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
+//=============================================================================
+// ClientHandler
+//=============================================================================
+
+/// Callbacks for interrupt-driven client transactions
+///
+/// See [`I2cClient::handle_interrupt`].
+pub trait ClientHandler {
+    /// The host addressed the client
+    ///
+    /// Return `true` to acknowledge the address, or `false` to ignore the
+    /// transaction.
+    fn address_match(&mut self, _direction: Direction) -> bool {
+        true
+    }
+
+    /// The host wrote `byte` to the client
+    ///
+    /// Return `true` to acknowledge the byte, or `false` to signal the host
+    /// that no more bytes are accepted. In smart mode, the acknowledge
+    /// decision applies to the next received byte instead.
+    fn write(&mut self, byte: u8) -> bool;
+
+    /// The host reads a byte from the client
+    ///
+    /// Return the byte to transmit.
+    fn read(&mut self) -> u8;
+
+    /// The host ended the transaction with a stop condition
+    fn stop(&mut self) {}
+
+    /// An error occurred on the bus
+    fn error(&mut self, _error: Error) {}
+}
+
+//=============================================================================
+// I2cClient
+//=============================================================================
+
+/// Abstraction over an I2C peripheral in client mode
+pub struct I2cClient<C: AnyClientConfig> {
+    config: C,
+}
+
+impl<C: AnyClientConfig> I2cClient<C> {
+    /// Obtain a pointer to the `DATA` register. Necessary for DMA transfers.
+    #[inline]
+    pub fn data_ptr(&self) -> *mut super::Word {
+        self.config.as_ref().registers.data_ptr()
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        self.config.as_ref().registers.read_flags()
+    }
+
+    /// Clear interrupt status flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.config.as_mut().registers.clear_flags(flags);
+    }
+
+    /// Enable interrupts for the specified flags.
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.config.as_mut().registers.enable_interrupts(flags);
+    }
+
+    /// Disable interrupts for the specified flags.
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.config.as_mut().registers.disable_interrupts(flags);
+    }
+
+    /// Read the status flags
+    ///
+    /// In client mode, [`Status::arblost`] reports a transmit collision
+    /// (`STATUS.COLL`).
+    #[inline]
+    pub fn read_status(&self) -> Status {
+        self.config.as_ref().registers.read_status()
+    }
+
+    /// Clear the status flags
+    #[inline]
+    pub fn clear_status(&mut self, status: Status) {
+        self.config.as_mut().registers.clear_status(status);
+    }
+
+    /// Direction of the current transaction
+    ///
+    /// Only valid after an address match.
+    #[inline]
+    pub fn direction(&self) -> Direction {
+        self.config.as_ref().registers.client_direction()
+    }
+
+    /// Whether the current transaction was started by a repeated start
+    ///
+    /// Only valid after an address match.
+    #[inline]
+    pub fn repeated_start(&self) -> bool {
+        self.config.as_ref().registers.client_repeated_start()
+    }
+
+    /// Acknowledge or refuse a matched address, releasing the clock hold
+    ///
+    /// This also clears the [`AMATCH`](Flags::AMATCH) flag.
+    #[inline]
+    pub fn respond_address(&mut self, ack: bool) {
+        self.config.as_mut().registers.client_respond(ack);
+    }
+
+    #[cfg(feature = "dma")]
+    #[inline]
+    pub(in crate::sercom) fn smart_mode(&self) -> bool {
+        self.config.as_ref().get_smart_mode()
+    }
+
+    /// Service a SERCOM interrupt, calling back into `handler`
+    ///
+    /// This method should be called from the SERCOM interrupt handler. It
+    /// handles the [`ERROR`](Flags::ERROR), [`AMATCH`](Flags::AMATCH),
+    /// [`DRDY`](Flags::DRDY) and [`PREC`](Flags::PREC) flags, in that order,
+    /// and clears them.
+    pub fn handle_interrupt<H: ClientHandler>(&mut self, handler: &mut H) {
+        let flags = self.read_flags();
+        let smart_mode = self.config.as_ref().get_smart_mode();
+        let registers = &mut self.config.as_mut().registers;
+
+        if flags.contains(Flags::ERROR) {
+            if let Err(e) = registers.read_status().check_bus_error() {
+                handler.error(e);
+            }
+            let status = registers.read_status();
+            registers.clear_status(status);
+            registers.clear_flags(Flags::ERROR);
+        }
+
+        if flags.contains(Flags::AMATCH) {
+            let ack = handler.address_match(registers.client_direction());
+            registers.client_respond(ack);
+        }
+
+        if flags.contains(Flags::DRDY) {
+            match registers.client_direction() {
+                Direction::Write => {
+                    // In smart mode, reading DATA acknowledges the byte with
+                    // the previously set action
+                    let byte = registers.client_read_data();
+                    let ack = handler.write(byte);
+                    if smart_mode {
+                        registers.set_client_ack_action(ack);
+                    } else {
+                        registers.client_respond(ack);
+                    }
+                }
+                Direction::Read => {
+                    if registers.read_status().rxnack() {
+                        // The host refused the last byte: end the transaction
+                        registers.client_wait_start();
+                    } else {
+                        registers.client_write_data(handler.read());
+                        if !smart_mode {
+                            registers.client_respond(true);
+                        }
+                    }
+                }
+            }
+        }
+
+        if flags.contains(Flags::PREC) {
+            registers.clear_flags(Flags::PREC);
+            handler.stop();
+        }
+    }
+
+    /// Reconfigure the I2C peripheral.
+    ///
+    /// Calling this method will temporarily disable the SERCOM peripheral, as
+    /// some registers are enable-protected. This may interrupt any ongoing
+    /// transactions.
+    #[inline]
+    pub fn reconfigure<F>(&mut self, update: F)
+    where
+        F: FnOnce(&mut SpecificClientConfig<C>),
+    {
+        self.config.as_mut().registers.enable_peripheral(false);
+        update(self.config.as_mut());
+        self.config.as_mut().registers.enable_peripheral(true);
+    }
+
+    /// Disable the I2C peripheral and return the underlying [`ClientConfig`]
+    #[inline]
+    pub fn disable(self) -> C {
+        let mut config = self.config;
+        config.as_mut().registers.disable();
+        config
+    }
+}
+
+impl<P: PadSet> AsRef<ClientConfig<P>> for I2cClient<ClientConfig<P>> {
+    #[inline]
+    fn as_ref(&self) -> &ClientConfig<P> {
+        self.config.as_ref()
+    }
+}
@@ -9,13 +9,20 @@ use modular_bitfield::*;
 bitflags! {
     /// Interrupt bitflags for I2C transactions
     ///
-    /// The available interrupt flags are `MB`, `SB`, and `ERROR`. The binary format of the underlying bits exactly matches the
-    /// INTFLAG bits.
+    /// The available interrupt flags are `MB`, `SB`, and `ERROR` in host mode,
+    /// and `PREC`, `AMATCH`, `DRDY` and `ERROR` in client mode. The binary
+    /// format of the underlying bits exactly matches the INTFLAG bits.
     pub struct Flags: u8 {
         /// Master on bus interrupt
         const MB = 0x01;
         /// Slave on bus interrupt
         const SB = 0x02;
+        /// Stop received interrupt (client mode)
+        const PREC = 0x01;
+        /// Address match interrupt (client mode)
+        const AMATCH = 0x02;
+        /// Data ready interrupt (client mode)
+        const DRDY = 0x04;
         /// Error interrupt
         const ERROR = 0x80;
     }
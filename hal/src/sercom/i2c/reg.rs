@@ -1,7 +1,7 @@
 //! Register-level access to I2C configuration
 
 use super::flags::{BusState, Error};
-use super::InactiveTimeout;
+use super::{AddressMatch, Direction, InactiveTimeout};
 use super::{Flags, Status};
 use crate::pac;
 use crate::sercom::*;
@@ -209,9 +209,10 @@ impl<S: Sercom> Registers<S> {
         }
     }
 
-    /// Start a blocking write transaction
+    /// Start a write transaction, without waiting for the address to be
+    /// transmitted
     #[inline]
-    pub(super) fn start_write_blocking(&mut self, addr: u8) -> Result<(), Error> {
+    pub(super) fn start_write(&mut self, addr: u8) -> Result<(), Error> {
         if self.get_smart_mode() {
             self.disable();
             self.set_smart_mode(false);
@@ -227,15 +228,23 @@ impl<S: Sercom> Registers<S> {
                 .addr
                 .write(|w| w.addr().bits(encode_write_address(addr)));
         }
+        Ok(())
+    }
+
+    /// Start a blocking write transaction
+    #[inline]
+    pub(super) fn start_write_blocking(&mut self, addr: u8) -> Result<(), Error> {
+        self.start_write(addr)?;
 
         // wait for transmission to complete
         while !self.i2c_master().intflag.read().mb().bit_is_set() {}
         self.read_status().check_bus_error()
     }
 
-    /// Start a blocking read transaction
+    /// Start a read transaction, without waiting for the address to be
+    /// transmitted
     #[inline]
-    pub(super) fn start_read_blocking(&mut self, addr: u8) -> Result<(), Error> {
+    pub(super) fn start_read(&mut self, addr: u8) -> Result<(), Error> {
         if self.get_smart_mode() {
             self.disable();
             self.set_smart_mode(false);
@@ -255,6 +264,13 @@ impl<S: Sercom> Registers<S> {
                 .addr
                 .write(|w| w.addr().bits(encode_read_address(addr)));
         }
+        Ok(())
+    }
+
+    /// Start a blocking read transaction
+    #[inline]
+    pub(super) fn start_read_blocking(&mut self, addr: u8) -> Result<(), Error> {
+        self.start_read(addr)?;
 
         // wait for transmission to complete
         loop {
@@ -347,12 +363,32 @@ impl<S: Sercom> Registers<S> {
         self.sync_sysop();
     }
 
+    /// Write a byte to the `DATA` register, without waiting for it to be
+    /// transmitted
+    #[inline]
+    pub(super) fn write_byte(&mut self, byte: u8) {
+        unsafe {
+            self.i2c_master().data.write(|w| w.bits(byte));
+        }
+    }
+
+    /// Read the last received byte from the `DATA` register
+    #[inline]
+    pub(super) fn read_byte(&mut self) -> u8 {
+        self.i2c_master().data.read().bits()
+    }
+
+    /// Arrange to send a NACK on the next command, to stop the slave from
+    /// transmitting more data
+    #[inline]
+    pub(super) fn set_nack(&mut self) {
+        self.i2c_master().ctrlb.modify(|_, w| w.ackact().set_bit());
+    }
+
     #[inline]
     pub(super) fn send_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
         for b in bytes {
-            unsafe {
-                self.i2c_master().data.write(|w| w.bits(*b));
-            }
+            self.write_byte(*b);
 
             loop {
                 let intflag = self.i2c_master().intflag.read();
@@ -368,7 +404,7 @@ impl<S: Sercom> Registers<S> {
     #[inline]
     pub(super) fn read_one(&mut self) -> u8 {
         while !self.i2c_master().intflag.read().sb().bit_is_set() {}
-        self.i2c_master().data.read().bits()
+        self.read_byte()
     }
 
     #[inline]
@@ -388,9 +424,7 @@ impl<S: Sercom> Registers<S> {
             }
         }
 
-        // arrange to send nack on next command to
-        // stop slave from transmitting more data
-        self.i2c_master().ctrlb.modify(|_, w| w.ackact().set_bit());
+        self.set_nack();
 
         Ok(())
     }
@@ -464,6 +498,181 @@ impl<S: Sercom> Registers<S> {
     }
 }
 
+//=============================================================================
+// Client mode
+//=============================================================================
+
+const CLIENT_CMD_WAIT_START: u8 = 2;
+const CLIENT_CMD_RESPOND: u8 = 3;
+
+const CLIENT_STATUS_DIR: u16 = 1 << 3;
+const CLIENT_STATUS_SR: u16 = 1 << 4;
+
+impl<S: Sercom> Registers<S> {
+    /// Helper function to access the underlying `I2CS` from the given `SERCOM`
+    #[inline]
+    fn i2c_client(&self) -> &pac::sercom0::I2CS {
+        self.sercom.i2cs()
+    }
+
+    /// Configure the SERCOM to use I2C client mode
+    #[inline]
+    pub(super) fn set_client_mode(&mut self) {
+        self.i2c_client().ctrla.modify(|_, w| w.mode().i2c_slave());
+    }
+
+    /// Set the address matching mode and addresses
+    #[inline]
+    pub(super) fn set_address_match(&mut self, address: AddressMatch) {
+        let (amode, addr, mask, ten_bit) = match address {
+            AddressMatch::Mask {
+                address,
+                mask,
+                ten_bit,
+            } => (0, address, mask, ten_bit),
+            AddressMatch::Dual(first, second) => (1, first as u16, second as u16, false),
+            AddressMatch::Range { low, high } => (2, high as u16, low as u16, false),
+        };
+        self.i2c_client()
+            .ctrlb
+            .modify(|_, w| unsafe { w.amode().bits(amode) });
+        self.i2c_client().addr.modify(|_, w| unsafe {
+            w.addr()
+                .bits(addr & 0x3ff)
+                .addrmask()
+                .bits(mask & 0x3ff)
+                .tenbiten()
+                .bit(ten_bit)
+        });
+    }
+
+    /// Get the address matching mode and addresses
+    #[inline]
+    pub(super) fn get_address_match(&self) -> AddressMatch {
+        let addr = self.i2c_client().addr.read();
+        match self.i2c_client().ctrlb.read().amode().bits() {
+            1 => AddressMatch::Dual(addr.addr().bits() as u8, addr.addrmask().bits() as u8),
+            2 => AddressMatch::Range {
+                low: addr.addrmask().bits() as u8,
+                high: addr.addr().bits() as u8,
+            },
+            _ => AddressMatch::Mask {
+                address: addr.addr().bits(),
+                mask: addr.addrmask().bits(),
+                ten_bit: addr.tenbiten().bit(),
+            },
+        }
+    }
+
+    /// Respond to the general call address
+    #[inline]
+    pub(super) fn set_general_call(&mut self, set: bool) {
+        self.i2c_client().addr.modify(|_, w| w.gencen().bit(set));
+    }
+
+    /// Get the general call setting
+    #[inline]
+    pub(super) fn get_general_call(&self) -> bool {
+        self.i2c_client().addr.read().gencen().bit()
+    }
+
+    /// Run in standby mode (client mode)
+    #[inline]
+    pub(super) fn set_client_run_in_standby(&mut self, set: bool) {
+        self.i2c_client().ctrla.modify(|_, w| w.runstdby().bit(set));
+    }
+
+    /// Get the current run in standby mode (client mode)
+    #[inline]
+    pub(super) fn get_client_run_in_standby(&self) -> bool {
+        self.i2c_client().ctrla.read().runstdby().bit()
+    }
+
+    /// Set SCL Low Time-Out (client mode)
+    #[inline]
+    pub(super) fn set_client_low_timeout(&mut self, set: bool) {
+        self.i2c_client()
+            .ctrla
+            .modify(|_, w| w.lowtouten().bit(set));
+    }
+
+    /// Get SCL Low Time-Out setting (client mode)
+    #[inline]
+    pub(super) fn get_client_low_timeout(&self) -> bool {
+        self.i2c_client().ctrla.read().lowtouten().bit()
+    }
+
+    /// Set Smart Mode (client mode)
+    #[inline]
+    pub(super) fn set_client_smart_mode(&mut self, set: bool) {
+        self.i2c_client().ctrlb.modify(|_, w| w.smen().bit(set));
+    }
+
+    /// Get the current Smart Mode setting (client mode)
+    #[inline]
+    pub(super) fn get_client_smart_mode(&self) -> bool {
+        self.i2c_client().ctrlb.read().smen().bit()
+    }
+
+    /// Read the transfer direction of the current transaction
+    #[inline]
+    pub(super) fn client_direction(&self) -> Direction {
+        if self.i2c_client().status.read().bits() & CLIENT_STATUS_DIR != 0 {
+            Direction::Read
+        } else {
+            Direction::Write
+        }
+    }
+
+    /// Check whether the current transaction was started by a repeated start
+    #[inline]
+    pub(super) fn client_repeated_start(&self) -> bool {
+        self.i2c_client().status.read().bits() & CLIENT_STATUS_SR != 0
+    }
+
+    /// Set the acknowledge action and issue the "respond" command, which
+    /// acknowledges an address match or a received byte, or transmits the
+    /// byte in `DATA`
+    #[inline]
+    pub(super) fn client_respond(&mut self, ack: bool) {
+        self.i2c_client()
+            .ctrlb
+            .modify(|_, w| unsafe { w.ackact().bit(!ack).cmd().bits(CLIENT_CMD_RESPOND) });
+    }
+
+    /// Set the acknowledge action that will be sent in smart mode upon the
+    /// next read of `DATA`
+    #[inline]
+    pub(super) fn set_client_ack_action(&mut self, ack: bool) {
+        self.i2c_client().ctrlb.modify(|_, w| w.ackact().bit(!ack));
+    }
+
+    /// Issue the "wait for start" command, ending the current transaction
+    #[inline]
+    pub(super) fn client_wait_start(&mut self) {
+        self.i2c_client()
+            .ctrlb
+            .modify(|_, w| unsafe { w.cmd().bits(CLIENT_CMD_WAIT_START) });
+    }
+
+    /// Read a byte from the `DATA` register
+    // `DATA` is 32-bit wide on SAMx5x chips
+    #[allow(clippy::unnecessary_cast)]
+    #[inline]
+    pub(super) fn client_read_data(&mut self) -> u8 {
+        self.i2c_client().data.read().bits() as u8
+    }
+
+    /// Write a byte to the `DATA` register
+    #[allow(clippy::unnecessary_cast)]
+    #[inline]
+    pub(super) fn client_write_data(&mut self, byte: u8) {
+        self.i2c_client()
+            .data
+            .write(|w| unsafe { w.bits(byte as _) });
+    }
+}
+
 fn encode_write_address(addr_7_bits: u8) -> u16 {
     (addr_7_bits as u16) << 1
 }
@@ -2,6 +2,11 @@
 //!
 //! The SERCOM module is used to configure the SERCOM peripherals as USART, SPI
 //! or I2C interfaces.
+//!
+//! With the `async` feature, the UART, SPI and I2C drivers also implement the
+//! `embedded-io-async` and `embedded-hal-async` traits. See the
+//! [`async_api`] module for the required interrupt handler setup. Note that
+//! the `async` feature requires Rust 1.75 or later.
 #![cfg_attr(
     feature = "min-samd51g",
     doc = "
@@ -67,6 +72,11 @@ pub mod uart;
 #[cfg(feature = "dma")]
 pub mod dma;
 
+#[cfg(feature = "async")]
+pub mod async_api;
+#[cfg(feature = "async")]
+pub use async_api::on_interrupt;
+
 //==============================================================================
 //  Sercom
 //==============================================================================
@@ -83,6 +93,10 @@ pub trait Sercom: Sealed + Deref<Target = sercom0::RegisterBlock> {
     const DMA_TX_TRIGGER: TriggerSource;
     /// Enable the corresponding APB clock
     fn enable_apb_clock(&mut self, ctrl: &APB_CLK_CTRL);
+    /// Get a reference to the SERCOM register block, for use in interrupt
+    /// handlers
+    #[cfg(feature = "async")]
+    fn reg_block() -> &'static sercom0::RegisterBlock;
 }
 
 macro_rules! sercom {
@@ -102,6 +116,11 @@ macro_rules! sercom {
                     fn enable_apb_clock(&mut self, ctrl: &APB_CLK_CTRL) {
                         ctrl.$apbmask.modify(|_, w| w.[<sercom~N _>]().set_bit());
                     }
+                    #[cfg(feature = "async")]
+                    #[inline]
+                    fn reg_block() -> &'static sercom0::RegisterBlock {
+                        unsafe { &*Self::ptr() }
+                    }
                 }
             }
         });
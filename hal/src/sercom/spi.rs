@@ -371,6 +371,9 @@ pub mod impl_ehal;
 #[path = "spi/impl_ehal_thumbv7em.rs"]
 pub mod impl_ehal;
 
+#[cfg(feature = "async")]
+mod async_api;
+
 //=============================================================================
 // BitOrder
 //=============================================================================
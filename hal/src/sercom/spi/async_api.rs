@@ -0,0 +1,112 @@
+//! `embedded-hal-async` trait implementations for [`Spi`]s
+//!
+//! [`SpiBus`] is implemented for [`Duplex`] [`Spi`]s in a [`MasterMode`],
+//! with an [`AtomicSize`] of one byte. The futures are driven by the SERCOM
+//! interrupt, which must call [`on_interrupt`](crate::sercom::on_interrupt).
+
+use super::{
+    AnyConfig, AtomicSize, Capability, DataWidth, Duplex, Error, Flags, MasterMode, Spi,
+    ValidConfig,
+};
+use crate::sercom::async_api::{wait_flags, TX_WAKERS};
+use crate::sercom::Sercom;
+use embedded_hal_async::spi::{ErrorKind, ErrorType, SpiBus};
+
+impl embedded_hal_async::spi::Error for Error {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Overflow => ErrorKind::Overrun,
+            Error::LengthError => ErrorKind::Other,
+        }
+    }
+}
+
+impl<C, A> Spi<C, A>
+where
+    C: ValidConfig,
+    A: Capability,
+{
+    /// Wait until one of the `flags` is set
+    #[inline]
+    async fn wait_flags(&mut self, flags: Flags) -> Flags {
+        let bits = wait_flags(
+            self,
+            &TX_WAKERS[<C::Sercom as Sercom>::NUM],
+            flags.bits(),
+            |spi| spi.read_flags().bits(),
+            |spi, bits| spi.enable_interrupts(Flags::from_bits_truncate(bits)),
+        )
+        .await;
+        Flags::from_bits_truncate(bits)
+    }
+
+    /// Send a word and wait for the word received in exchange
+    #[inline]
+    async fn transfer_word(&mut self, word: u8) -> Result<u8, Error> {
+        self.wait_flags(Flags::DRE).await;
+        unsafe { self.write_data(word as DataWidth) };
+        self.wait_flags(Flags::RXC | Flags::ERROR).await;
+        match self.read_flags_errors() {
+            Ok(_) => Ok(unsafe { self.read_data() } as u8),
+            Err(err) => {
+                self.clear_status(self.read_status());
+                self.clear_flags(Flags::ERROR);
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<C> ErrorType for Spi<C, Duplex>
+where
+    C: ValidConfig,
+{
+    type Error = Error;
+}
+
+impl<C> SpiBus<u8> for Spi<C, Duplex>
+where
+    C: ValidConfig + AnyConfig<Word = u8>,
+    C::OpMode: MasterMode,
+    C::Size: AtomicSize,
+{
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Error> {
+        for word in words.iter_mut() {
+            *word = self.transfer_word(0).await?;
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Error> {
+        for &word in words {
+            self.transfer_word(word).await?;
+        }
+        Ok(())
+    }
+
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Error> {
+        for i in 0..read.len().max(write.len()) {
+            let word = self
+                .transfer_word(write.get(i).copied().unwrap_or(0))
+                .await?;
+            if let Some(dest) = read.get_mut(i) {
+                *dest = word;
+            }
+        }
+        Ok(())
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Error> {
+        for word in words.iter_mut() {
+            *word = self.transfer_word(*word).await?;
+        }
+        Ok(())
+    }
+
+    /// Every word is fully exchanged before the methods above return, so
+    /// there is nothing to flush
+    async fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -322,10 +322,45 @@ type Pads = uart::PadsFromIds<Sercom0, PA08, PA09>;
 //! let config = uart.disable();
 //! ```
 //!
+//! # LIN
+//!
+//! [`Config::lin_mode`] selects the LIN mode of the peripheral. In
+//! [`LinMode::Slave`], the peripheral detects the break field of a LIN header,
+//! sets the `RXBRK` flag, and measures the baud rate on the sync field. An
+//! inconsistent sync field is reported through the `ISF` status.
+#![cfg_attr(
+    feature = "min-samd51g",
+    doc = "
+On SAMx5x chips, [`LinMode::Master`] generates the break and sync fields
+automatically, when a header is sent with [`Uart::send_lin_header`].
+
+# RS-485
+
+On SAMx5x chips, the peripheral drives a transmit-enable `TE` pad, which
+switches the direction of an RS-485 transceiver. It stays high during each
+transmission, and for the [guard time](Config::rs485_guard_time) after the
+last stop bit. The `TE` pad is always `Pad2`, and is set with [`Pads::te`].
+A set of [`Pads`] with a `TE` pad implements [`Rs485Pads`].
+
+```
+use atsamd_hal::sercom::{Sercom0, uart};
+use atsamd_hal::sercom::pad::IoSet1;
+
+let pads = uart::Pads::<Sercom0, IoSet1>::default()
+    .rx(pins.pa07)
+    .tx(pins.pa04)
+    .te(pins.pa06);
+let uart = uart::Config::new(&mclk, sercom, pads, freq)
+    .baud(19200.hz(), BaudMode::Fractional(Oversampling::Bits16))
+    .rs485_guard_time(2)
+    .enable();
+```
+"
+)]
+//!
 //! # Non-supported advanced features
 //!
 //! * Synchronous mode (USART) is not supported
-//! * LIN mode is not supported (SAMx5x)
 //! * 32-bit extension mode is not supported (SAMx5x). If you need to transfer
 //!   slices, consider using the DMA methods instead. The `dma` Cargo feature
 //!   must be enabled.
@@ -411,6 +446,9 @@ pub use config::*;
 
 pub mod impl_ehal;
 
+#[cfg(feature = "async")]
+mod async_api;
+
 use crate::{sercom::*, typelevel::Sealed};
 use core::{convert::TryInto, marker::PhantomData};
 use num_traits::AsPrimitive;
@@ -481,6 +519,48 @@ pub enum BaudMode {
     Fractional(Oversampling),
 }
 
+/// LIN mode of a UART
+#[derive(Debug, Clone, Copy)]
+pub enum LinMode {
+    /// Regular UART frames
+    Disabled,
+    /// LIN slave, with break detection and auto-baud on the sync field
+    Slave,
+    /// LIN master, with break and sync field generation
+    #[cfg(feature = "min-samd51g")]
+    Master(LinBreakLength, LinHeaderDelay),
+}
+
+/// Length of the break field sent by a LIN master
+#[cfg(feature = "min-samd51g")]
+#[repr(u8)]
+#[derive(Debug, Clone, Copy)]
+pub enum LinBreakLength {
+    /// 13 bit times
+    Bits13,
+    /// 17 bit times
+    Bits17,
+    /// 21 bit times
+    Bits21,
+    /// 26 bit times
+    Bits26,
+}
+
+/// Delay between the break and sync fields sent by a LIN master
+#[cfg(feature = "min-samd51g")]
+#[repr(u8)]
+#[derive(Debug, Clone, Copy)]
+pub enum LinHeaderDelay {
+    /// 1 bit time
+    Bits1,
+    /// 4 bit times
+    Bits4,
+    /// 8 bit times
+    Bits8,
+    /// 14 bit times
+    Bits14,
+}
+
 //=============================================================================
 // Capability
 //=============================================================================
@@ -966,4 +1046,20 @@ where
     pub unsafe fn write_data(&mut self, data: DataReg) {
         self.config.as_mut().registers.write_data(data);
     }
+
+    /// Wait for a `DRE` flag, then send a LIN header
+    ///
+    /// The break and sync fields are generated by the peripheral, followed by
+    /// the protected identifier `pid`. The UART must be configured in
+    /// [`LinMode::Master`].
+    #[cfg(feature = "min-samd51g")]
+    #[inline]
+    pub fn send_lin_header(&mut self, pid: u8) -> nb::Result<(), core::convert::Infallible> {
+        if self.read_flags().contains(Flags::DRE) {
+            self.config.as_mut().registers.send_lin_header(pid);
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
 }
@@ -0,0 +1,119 @@
+//! `embedded-io-async` trait implementations for [`Uart`]s
+//!
+//! The futures are driven by the SERCOM interrupt, which must call
+//! [`on_interrupt`](crate::sercom::on_interrupt).
+
+use super::{AnyConfig, Capability, Error, Flags, Receive, Transmit, Uart, ValidConfig};
+use crate::sercom::async_api::{wait_flags, RX_WAKERS, TX_WAKERS};
+use crate::sercom::Sercom;
+use embedded_io_async::{ErrorKind, ErrorType, Read, Write};
+
+impl embedded_io_async::Error for Error {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Error::ParityError | Error::FrameError | Error::InconsistentSyncField => {
+                ErrorKind::InvalidData
+            }
+            Error::Overflow | Error::CollisionDetected => ErrorKind::Other,
+        }
+    }
+}
+
+impl<C, D> Uart<C, D>
+where
+    C: ValidConfig,
+    D: Capability,
+{
+    /// Wait until one of the receive-side `flags` is set
+    #[inline]
+    async fn wait_rx_flags(&mut self, flags: Flags) -> Flags {
+        let bits = wait_flags(
+            self,
+            &RX_WAKERS[<C::Sercom as Sercom>::NUM],
+            flags.bits(),
+            |uart| uart.read_flags().bits(),
+            |uart, bits| uart.enable_interrupts(Flags::from_bits_truncate(bits)),
+        )
+        .await;
+        Flags::from_bits_truncate(bits)
+    }
+
+    /// Wait until one of the transmit-side `flags` is set
+    #[inline]
+    async fn wait_tx_flags(&mut self, flags: Flags) -> Flags {
+        let bits = wait_flags(
+            self,
+            &TX_WAKERS[<C::Sercom as Sercom>::NUM],
+            flags.bits(),
+            |uart| uart.read_flags().bits(),
+            |uart, bits| uart.enable_interrupts(Flags::from_bits_truncate(bits)),
+        )
+        .await;
+        Flags::from_bits_truncate(bits)
+    }
+}
+
+impl<C, D> ErrorType for Uart<C, D>
+where
+    C: ValidConfig,
+    D: Capability,
+{
+    type Error = Error;
+}
+
+impl<C, D> Read for Uart<C, D>
+where
+    C: ValidConfig + AnyConfig<Word = u8>,
+    D: Receive,
+{
+    /// Wait for at least one word, then read all the words already received
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut count = 0;
+        while count < buf.len() {
+            if count == 0 {
+                self.wait_rx_flags(Flags::RXC | Flags::ERROR).await;
+            }
+
+            match self.read_flags_errors() {
+                Ok(flags) if flags.contains(Flags::RXC) => {
+                    buf[count] = unsafe { self.read_data() } as u8;
+                    count += 1;
+                }
+                Ok(_) => break,
+                Err(err) => {
+                    self.clear_status(err.into());
+                    self.clear_flags(Flags::ERROR);
+                    return Err(err);
+                }
+            }
+        }
+        Ok(count)
+    }
+}
+
+impl<C, D> Write for Uart<C, D>
+where
+    C: ValidConfig + AnyConfig<Word = u8>,
+    D: Transmit,
+{
+    /// Write every word in `buf`, waiting on `DRE` before each one
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        for &word in buf {
+            self.wait_tx_flags(Flags::DRE).await;
+            unsafe { self.write_data(word.into()) };
+        }
+        Ok(buf.len())
+    }
+
+    /// Wait for a `TXC` flag
+    async fn flush(&mut self) -> Result<(), Error> {
+        self.wait_tx_flags(Flags::TXC).await;
+        self.clear_flags(Flags::TXC);
+        Ok(())
+    }
+}
@@ -2,8 +2,11 @@
 
 use super::{
     BaudMode, BitOrder, Capability, CharSize, CharSizeEnum, DataReg, DynCharSize, EightBit,
-    FixedCharSize, Parity, Registers, StopBits, Uart, ValidConfig, ValidPads,
+    FixedCharSize, LinMode, Parity, Registers, StopBits, Uart, ValidConfig, ValidPads,
 };
+
+#[cfg(feature = "min-samd51g")]
+use super::Rs485Pads;
 use crate::{
     pac,
     sercom::*,
@@ -374,6 +377,65 @@ where
     pub fn get_irda_encoding(&self) -> Option<u8> {
         self.registers.get_irda_encoding()
     }
+
+    /// Change the LIN mode (builder pattern version)
+    ///
+    /// In [`LinMode::Slave`], the baud rate is measured on the sync field of
+    /// each LIN header, and can be read back with
+    /// [`get_baud`](Self::get_baud).
+    #[inline]
+    pub fn lin_mode(mut self, mode: LinMode) -> Self {
+        self.set_lin_mode(mode);
+        self
+    }
+
+    /// Change the LIN mode (setter version)
+    ///
+    /// In [`LinMode::Slave`], the baud rate is measured on the sync field of
+    /// each LIN header, and can be read back with
+    /// [`get_baud`](Self::get_baud).
+    #[inline]
+    pub fn set_lin_mode(&mut self, mode: LinMode) {
+        self.registers.set_lin_mode(mode);
+    }
+
+    /// Get the current LIN mode
+    #[inline]
+    pub fn get_lin_mode(&self) -> LinMode {
+        self.registers.get_lin_mode()
+    }
+}
+
+#[cfg(feature = "min-samd51g")]
+impl<P, C> Config<P, C>
+where
+    P: Rs485Pads,
+    C: CharSize,
+{
+    /// Change the RS-485 guard time (builder pattern version)
+    ///
+    /// The `TE` pad stays high for `bits` bit times after the last stop bit of
+    /// a transmission. Values above 7 saturate.
+    #[inline]
+    pub fn rs485_guard_time(mut self, bits: u8) -> Self {
+        self.set_rs485_guard_time(bits);
+        self
+    }
+
+    /// Change the RS-485 guard time (setter version)
+    ///
+    /// The `TE` pad stays high for `bits` bit times after the last stop bit of
+    /// a transmission. Values above 7 saturate.
+    #[inline]
+    pub fn set_rs485_guard_time(&mut self, bits: u8) {
+        self.registers.set_guard_time(bits);
+    }
+
+    /// Get the current RS-485 guard time, in bit times
+    #[inline]
+    pub fn get_rs485_guard_time(&self) -> u8 {
+        self.registers.get_guard_time()
+    }
 }
 
 impl<P: ValidPads> Config<P, DynCharSize> {
@@ -61,6 +61,7 @@ macro_rules! impl_rxpotxpo {
 
     // Check whether RTS and CTS form a valid pair.
     // They both must be the correct pad or absent.
+    // RTS without CTS is the TE pad of the RS-485 mode.
     (@check_rts_cts, $RX:ident, $TX:ident, NoneT, NoneT) => { impl_rxpotxpo!(@rxpo, $RX, $TX, NoneT, NoneT); };
     (@check_rts_cts, $RX:ident, $TX:ident, Pad2, NoneT) => { impl_rxpotxpo!(@rxpo, $RX, $TX, Pad2, NoneT); };
     (@check_rts_cts, $RX:ident, $TX:ident, NoneT, Pad3) => { impl_rxpotxpo!(@rxpo, $RX, $TX, NoneT, Pad3); };
@@ -280,6 +281,27 @@ where
     }
 }
 
+impl<S, I, RX, TX> Pads<S, I, RX, TX>
+where
+    S: Sercom,
+    I: IoSet,
+    RX: OptionalPad,
+    TX: OptionalPad,
+{
+    /// Set the RS-485 `TE` [`Pad`], which is always [`Pad2`]
+    ///
+    /// The `TE` pad takes the place of the `RTS` pad, and can't be combined
+    /// with flow control.
+    #[inline]
+    pub fn te<Id>(self, pin: impl AnyPin<Id = Id>) -> Pads<S, I, RX, TX, Pad<S, Id>>
+    where
+        Id: GetPad<S>,
+        Pad<S, Id>: InIoSet<I>,
+    {
+        self.rts(pin)
+    }
+}
+
 /// Define a set of [`Pads`] using [`PinId`]s instead of [`Pin`]s
 ///
 /// In some cases, it is more convenient to specify a set of `Pads` using
@@ -425,6 +447,23 @@ where
     type Capability = Duplex;
 }
 
+/// Marker trait for sets of [`Pads`] in RS-485 mode
+///
+/// This trait labels [`ValidPads`] with `TX` and `TE` pads, set with
+/// [`Pads::te`], and without a `CTS` pad.
+pub trait Rs485Pads: ValidPads {}
+
+impl<S, I, RX, TX, TE> Rs485Pads for Pads<S, I, RX, TX, TE, NoneT>
+where
+    S: Sercom,
+    I: IoSet,
+    RX: OptionalPad,
+    TX: SomePad,
+    TE: SomePad,
+    Self: ValidPads,
+{
+}
+
 //=============================================================================
 // ValidConfig
 //=============================================================================
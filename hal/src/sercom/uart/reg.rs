@@ -1,6 +1,11 @@
 //! Register-level access to UART configuration
 
-use super::{BaudMode, BitOrder, CharSizeEnum, Flags, Oversampling, Parity, Status, StopBits};
+use super::{
+    BaudMode, BitOrder, CharSizeEnum, Flags, LinMode, Oversampling, Parity, Status, StopBits,
+};
+
+#[cfg(feature = "min-samd51g")]
+use super::{LinBreakLength, LinHeaderDelay};
 
 use crate::pac;
 use crate::sercom::*;
@@ -126,8 +131,6 @@ impl<S: Sercom> Registers<S> {
     /// Change the parity setting
     #[inline]
     pub(super) fn set_parity(&mut self, parity: Parity) {
-        // Use only the first two available settings in the FORM field.
-        // Ignore auto-baud options.
         let enabled = match parity {
             Parity::None => false,
             Parity::Odd => {
@@ -140,9 +143,15 @@ impl<S: Sercom> Registers<S> {
             }
         };
 
+        let form = match self.usart().ctrla.read().form().bits() {
+            // LIN master frames have no parity bit
+            0x2 => 0x2,
+            // Keep the auto-baud setting of the LIN slave mode
+            form => (form & 0x4) | enabled as u8,
+        };
         self.usart()
             .ctrla
-            .modify(|_, w| unsafe { w.form().bits(enabled as u8) });
+            .modify(|_, w| unsafe { w.form().bits(form) });
     }
 
     /// Get the current parity setting
@@ -343,6 +352,84 @@ impl<S: Sercom> Registers<S> {
         }
     }
 
+    /// Change the LIN mode
+    ///
+    /// The parity setting is kept in LIN slave mode. LIN master frames have no
+    /// parity bit.
+    #[inline]
+    pub(super) fn set_lin_mode(&mut self, mode: LinMode) {
+        let parity = self.usart().ctrla.read().form().bits() & 0x1;
+        let form = match mode {
+            LinMode::Disabled => parity,
+            LinMode::Slave => 0x4 | parity,
+            #[cfg(feature = "min-samd51g")]
+            LinMode::Master(break_length, header_delay) => {
+                self.usart().ctrlc.modify(|_, w| unsafe {
+                    w.brklen().bits(break_length as u8);
+                    w.hdrdly().bits(header_delay as u8)
+                });
+                0x2
+            }
+        };
+        self.usart()
+            .ctrla
+            .modify(|_, w| unsafe { w.form().bits(form) });
+    }
+
+    /// Get the current LIN mode
+    #[inline]
+    pub(super) fn get_lin_mode(&self) -> LinMode {
+        match self.usart().ctrla.read().form().bits() {
+            0x4 | 0x5 => LinMode::Slave,
+            #[cfg(feature = "min-samd51g")]
+            0x2 => {
+                let ctrlc = self.usart().ctrlc.read();
+                let break_length = match ctrlc.brklen().bits() {
+                    0x0 => LinBreakLength::Bits13,
+                    0x1 => LinBreakLength::Bits17,
+                    0x2 => LinBreakLength::Bits21,
+                    _ => LinBreakLength::Bits26,
+                };
+                let header_delay = match ctrlc.hdrdly().bits() {
+                    0x0 => LinHeaderDelay::Bits1,
+                    0x1 => LinHeaderDelay::Bits4,
+                    0x2 => LinHeaderDelay::Bits8,
+                    _ => LinHeaderDelay::Bits14,
+                };
+                LinMode::Master(break_length, header_delay)
+            }
+            _ => LinMode::Disabled,
+        }
+    }
+
+    /// Send a LIN header, made of the break and sync fields generated by the
+    /// peripheral, followed by the protected identifier `pid`
+    #[cfg(feature = "min-samd51g")]
+    #[inline]
+    pub(super) fn send_lin_header(&mut self, pid: u8) {
+        let usart = self.usart();
+        usart.ctrlb.modify(|_, w| unsafe { w.lincmd().bits(0x2) });
+        while usart.syncbusy.read().ctrlb().bit_is_set() {}
+        unsafe { self.write_data(pid as super::DataReg) };
+    }
+
+    /// Change the RS-485 guard time, during which the `TE` pad stays high after
+    /// the last stop bit. Values above 7 bit times saturate.
+    #[cfg(feature = "min-samd51g")]
+    #[inline]
+    pub(super) fn set_guard_time(&mut self, bits: u8) {
+        self.usart()
+            .ctrlc
+            .modify(|_, w| unsafe { w.gtime().bits(bits.min(7)) });
+    }
+
+    /// Get the current RS-485 guard time, in bit times
+    #[cfg(feature = "min-samd51g")]
+    #[inline]
+    pub(super) fn get_guard_time(&self) -> u8 {
+        self.usart().ctrlc.read().gtime().bits()
+    }
+
     /// Clear specified interrupt flags
     #[inline]
     pub(super) fn clear_flags(&mut self, flags: Flags) {
@@ -3,6 +3,18 @@
 //! before you can set up most of the peripherals on the atsamd21 device.
 //! The other types in this module are used to enforce at compile time
 //! that the peripherals have been correctly configured.
+//!
+//! The default constructors run the CPU from the DFLL48M or the OSC8M. Other
+//! configurations, including the XOSC crystal oscillator, the FDPLL96M and the
+//! DFLL48M USB clock recovery mode, can be described with a
+//! [`tree::ClockTree`] and applied through
+//! [`GenericClockController::from_tree`].
+//!
+//! The `GenericClockController` keeps track of the generator feeding each
+//! peripheral channel. Peripheral clocks can be released with
+//! [`GenericClockController::disable_clock`], and
+//! [`GenericClockController::disable_gclk`] refuses to stop a generator that
+//! is still in use.
 #![allow(clippy::from_over_into)]
 
 use crate::pac::gclk::clkctrl::GEN_A::*;
@@ -10,6 +22,11 @@ use crate::pac::gclk::clkctrl::ID_A::*;
 use crate::pac::gclk::genctrl::SRC_A::*;
 use crate::pac::{self, GCLK, NVMCTRL, PM, SYSCTRL};
 use crate::time::{Hertz, U32Ext};
+use crate::typelevel::Sealed;
+
+pub mod tree;
+
+use tree::{ClockTree, DfllMode, DpllRef, Node, Source, XoscConfig, GCLK_IDS, NUM_GCLK};
 
 pub type ClockId = pac::gclk::clkctrl::ID_A;
 pub type ClockGenId = pac::gclk::clkctrl::GEN_A;
@@ -100,6 +117,20 @@ impl State {
         self.wait_for_sync();
     }
 
+    fn disable_clock_generator(&mut self, clock: ClockId) {
+        self.gclk
+            .clkctrl
+            .write(|w| unsafe { w.id().bits(u8::from(clock)) });
+        self.wait_for_sync();
+    }
+
+    fn disable_gclk(&mut self, gclk: ClockGenId) {
+        self.gclk
+            .genctrl
+            .write(|w| unsafe { w.id().bits(u8::from(gclk)) });
+        self.wait_for_sync();
+    }
+
     fn configure_standby(&mut self, gclk: ClockGenId, enable: bool) {
         // We must first read out the configuration of genctrl to read/modify/write it.
         //   To do so, we must do an 8-bit write to GENCTRL.ID (ref 15.6.4.1 Indirect
@@ -124,8 +155,47 @@ impl State {
 /// gclk0 at 48Mhz.
 pub struct GenericClockController {
     state: State,
-    gclks: [Hertz; 8],
+    gclks: [Hertz; NUM_GCLK],
+    gclk_sources: [Option<ClockSource>; NUM_GCLK],
+    sources: SourceFreqs,
     used_clocks: u64,
+    channels: [Option<(ClockId, ClockGenId)>; 64],
+}
+
+/// Frequencies of the clock sources that can feed a generator
+struct SourceFreqs {
+    xosc: Option<Hertz>,
+    osc8m: Hertz,
+    dfll: Hertz,
+    dpll: Hertz,
+}
+
+impl Default for SourceFreqs {
+    fn default() -> Self {
+        Self {
+            xosc: None,
+            osc8m: OSC8M_FREQ,
+            dfll: OSC48M_FREQ,
+            dpll: 96.mhz().into(),
+        }
+    }
+}
+
+/// Errors returned when disabling a clock generator that is still in use
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GclkError {
+    /// GCLK0 drives the CPU and can't be disabled
+    MainClock,
+    /// The generator feeds the generic clock channel of a peripheral
+    UsedBy(ClockId),
+    /// The generator is the source of another generator
+    SourceOf(ClockGenId),
+}
+
+/// Typed peripheral clock tokens, returned by the `GenericClockController`
+pub trait PeripheralClock: Sealed {
+    /// Generic clock channel of the peripheral(s)
+    const ID: ClockId;
 }
 
 impl GenericClockController {
@@ -182,7 +252,12 @@ impl GenericClockController {
         // Feed 32khz into the DFLL48
         state.enable_clock_generator(DFLL48, GCLK1);
         // Enable the DFLL48
-        configure_and_enable_dfll48m(sysctrl, use_external_crystal);
+        if use_external_crystal {
+            let mul = ((48_000_000u32 + 32768 / 2) / 32768) as u16;
+            configure_and_enable_dfll48m(sysctrl, DfllMode::ClosedLoop(GCLK1), mul);
+        } else {
+            configure_and_enable_dfll48m(sysctrl, DfllMode::UsbRecovery, 0);
+        }
         // Feed DFLL48 into the main clock
         state.set_gclk_divider_and_source(GCLK0, 1, DFLL48M, true);
         // We are now running at 48Mhz
@@ -197,20 +272,17 @@ impl GenericClockController {
         pm.apbbsel.write(|w| w.apbbdiv().div1());
         pm.apbcsel.write(|w| w.apbcdiv().div1());
 
-        Self {
-            state,
-            gclks: [
-                OSC48M_FREQ,
-                OSC32K_FREQ,
-                Hertz(0),
-                Hertz(0),
-                Hertz(0),
-                Hertz(0),
-                Hertz(0),
-                Hertz(0),
-            ],
-            used_clocks: 1u64 << u8::from(ClockId::DFLL48),
-        }
+        let mut clocks = Self::with_state(state);
+        clocks.gclks[0] = OSC48M_FREQ;
+        clocks.gclks[1] = OSC32K_FREQ;
+        clocks.gclk_sources[0] = Some(DFLL48M);
+        clocks.gclk_sources[1] = Some(if use_external_crystal {
+            XOSC32K
+        } else {
+            OSC32K
+        });
+        clocks.mark_used(ClockId::DFLL48, GCLK1);
+        clocks
     }
 
     /// Reset the clock controller, configure the system to run at 8Mhz from
@@ -248,22 +320,146 @@ impl GenericClockController {
         pm.apbbsel.write(|w| w.apbbdiv().div1());
         pm.apbcsel.write(|w| w.apbcdiv().div1());
 
+        let mut clocks = Self::with_state(state);
+        clocks.gclks[0] = OSC8M_FREQ;
+        clocks.gclk_sources[0] = Some(OSC8M);
+        clocks
+    }
+
+    /// Reset the clock controller and configure the clock sources and
+    /// generators described by `tree`.
+    ///
+    /// The tree is validated with [`ClockTree::compute`] before any register
+    /// is written, and its clocks are enabled in dependency order. The flash
+    /// wait states and the CPU divider are set according to the resulting CPU
+    /// frequency.
+    pub fn from_tree(
+        tree: &ClockTree,
+        gclk: GCLK,
+        pm: &mut PM,
+        sysctrl: &mut SYSCTRL,
+        nvmctrl: &mut NVMCTRL,
+    ) -> Result<Self, tree::Error> {
+        let freqs = tree.compute()?;
+        let mut clocks = Self::with_state(State { gclk });
+
+        // Use the maximum number of wait states while the clocks are switched
+        set_flash_wait_states(nvmctrl, tree::flash_wait_states(tree::CPU_MAX_FREQ));
+        #[cfg(feature = "samd21")]
+        set_flash_manual_write(nvmctrl);
+        enable_gclk_apb(pm);
+        clocks.state.reset_gclk();
+
+        // Divide the CPU clock before GCLK0 is switched, so that the CPU never
+        // runs above its limit. Until then, it only runs slower.
+        // The divider was checked to be a power of two by `compute`
+        let cpudiv = tree.cpu_div.trailing_zeros() as u8;
+        pm.cpusel.write(|w| w.cpudiv().bits(cpudiv));
+
+        for node in freqs.order.iter().flatten() {
+            match *node {
+                Node::Xosc => {
+                    if let Some(config) = tree.xosc {
+                        configure_and_enable_xosc(sysctrl, &config);
+                    }
+                }
+                Node::Xosc32k => enable_external_32kosc(sysctrl),
+                Node::Osc32k => enable_internal_32kosc(sysctrl),
+                Node::Osc8m => {
+                    // The prescaler was checked by `compute`
+                    let presc = tree.osc8m_presc.trailing_zeros() as u8;
+                    sysctrl.osc8m.modify(|_, w| {
+                        w.presc().bits(presc);
+                        w.ondemand().clear_bit()
+                    });
+                }
+                Node::Dfll => {
+                    if let Some(mode) = tree.dfll {
+                        if let DfllMode::ClosedLoop(reference) = mode {
+                            clocks.mark_used(ClockId::DFLL48, reference);
+                            clocks
+                                .state
+                                .enable_clock_generator(ClockId::DFLL48, reference);
+                        }
+                        configure_and_enable_dfll48m(sysctrl, mode, freqs.dfll_mul);
+                    }
+                }
+                Node::Dpll => {
+                    if let Some(config) = tree.dpll {
+                        if let DpllRef::Gclk(reference) = config.reference {
+                            clocks.mark_used(ClockId::FDPLL, reference);
+                            clocks
+                                .state
+                                .enable_clock_generator(ClockId::FDPLL, reference);
+                        }
+                        configure_and_enable_dpll(sysctrl, config.reference, freqs.dpll_ratio);
+                    }
+                }
+                Node::Gclk(index) => {
+                    if let Some(config) = tree.gclk[index] {
+                        let src = match config.source? {
+                            Source::Xosc => XOSC,
+                            Source::GclkIn(_) => GCLKIN,
+                            Source::Gclk1 => GCLKGEN1,
+                            Source::OscUlp32k => OSCULP32K,
+                            Source::Osc32k => OSC32K,
+                            Source::Xosc32k => XOSC32K,
+                            Source::Osc8m => OSC8M,
+                            Source::Dfll => DFLL48M,
+                            Source::Dpll => DPLL96M,
+                        };
+                        clocks.state.set_gclk_divider_and_source(
+                            GCLK_IDS[index],
+                            config.div as u16,
+                            src,
+                            config.improve_duty_cycle,
+                        );
+                        clocks.gclk_sources[index] = Some(src);
+                    }
+                }
+            }
+        }
+
+        pm.apbasel.write(|w| w.apbadiv().div1());
+        pm.apbbsel.write(|w| w.apbbdiv().div1());
+        pm.apbcsel.write(|w| w.apbcdiv().div1());
+        set_flash_wait_states(nvmctrl, freqs.wait_states());
+
+        for (freq, id) in clocks.gclks.iter_mut().zip(GCLK_IDS.iter()) {
+            *freq = freqs.gclk(*id).unwrap_or(Hertz(0));
+        }
+        clocks.sources.xosc = freqs.xosc();
+        if let Some(freq) = freqs.osc8m() {
+            clocks.sources.osc8m = freq;
+        }
+        if let Some(freq) = freqs.dfll() {
+            clocks.sources.dfll = freq;
+        }
+        if let Some(freq) = freqs.dpll() {
+            clocks.sources.dpll = freq;
+        }
+
+        Ok(clocks)
+    }
+
+    fn with_state(state: State) -> Self {
         Self {
             state,
-            gclks: [
-                OSC8M_FREQ,
-                Hertz(0),
-                Hertz(0),
-                Hertz(0),
-                Hertz(0),
-                Hertz(0),
-                Hertz(0),
-                Hertz(0),
-            ],
+            gclks: [Hertz(0); NUM_GCLK],
+            gclk_sources: [None; NUM_GCLK],
+            sources: SourceFreqs::default(),
             used_clocks: 0,
+            channels: [None; 64],
         }
     }
 
+    /// Record that `clock` is fed by `generator`
+    fn mark_used(&mut self, clock: ClockId, generator: ClockGenId) {
+        let id = u8::from(clock);
+        self.used_clocks |= 1u64 << id;
+        self.channels[id as usize] = Some((clock, generator));
+    }
+
     /// Returns a `GClock` for gclk0, the system clock generator at 48Mhz
     pub fn gclk0(&mut self) -> GClock {
         GClock {
@@ -303,7 +499,9 @@ impl GenericClockController {
     /// `improve_duty_cycle` is a boolean that, when set to true, enables
     /// a 5o/50 duty cycle for odd divider values.
     /// Returns a `GClock` for the configured clock generator.
-    /// Returns `None` if the clock generator has already been configured.
+    /// Returns `None` if the clock generator has already been configured, or
+    /// if the frequency of `src` is unknown (`XOSC` without a configured
+    /// crystal, or `GCLKIN`).
     pub fn configure_gclk_divider_and_source(
         &mut self,
         gclk: ClockGenId,
@@ -315,20 +513,76 @@ impl GenericClockController {
         if self.gclks[idx].0 != 0 {
             return None;
         }
-        self.state
-            .set_gclk_divider_and_source(gclk, divider, src, improve_duty_cycle);
         let freq: Hertz = match src {
             XOSC32K | OSC32K | OSCULP32K => OSC32K_FREQ,
             GCLKGEN1 => self.gclks[1],
-            OSC8M => OSC8M_FREQ,
-            DFLL48M => OSC48M_FREQ,
-            DPLL96M => 96.mhz().into(),
-            GCLKIN | XOSC => unimplemented!(),
+            OSC8M => self.sources.osc8m,
+            DFLL48M => self.sources.dfll,
+            DPLL96M => self.sources.dpll,
+            XOSC => self.sources.xosc?,
+            GCLKIN => return None,
         };
+        self.state
+            .set_gclk_divider_and_source(gclk, divider, src, improve_duty_cycle);
         self.gclks[idx] = Hertz(freq.0 / divider as u32);
+        self.gclk_sources[idx] = Some(src);
         Some(GClock { gclk, freq })
     }
 
+    /// Disable the generic clock channel of a peripheral, consuming its clock
+    /// token.
+    ///
+    /// The channel can then be fed by another generator.
+    pub fn disable_clock<C: PeripheralClock>(&mut self, clock: C) {
+        let _ = clock;
+        let id = u8::from(C::ID);
+        self.state.disable_clock_generator(C::ID);
+        self.used_clocks &= !(1u64 << id);
+        self.channels[id as usize] = None;
+    }
+
+    /// Disable a clock generator.
+    ///
+    /// Returns an error if the generator still feeds a peripheral channel or
+    /// another generator. GCLK0 drives the CPU and can never be disabled.
+    ///
+    /// [`GClock`] tokens are `Copy`, so tokens of the disabled generator are
+    /// not invalidated. They must not be used anymore: a peripheral clock
+    /// configured from one would be fed by a stopped generator. Get a new
+    /// token from [`get_gclk`](Self::get_gclk) or
+    /// [`configure_gclk_divider_and_source`](Self::configure_gclk_divider_and_source)
+    /// instead.
+    pub fn disable_gclk(&mut self, gclk: ClockGenId) -> Result<(), GclkError> {
+        if gclk == GCLK0 {
+            return Err(GclkError::MainClock);
+        }
+        let user = self.channels.iter().flatten().find(|(_, gen)| *gen == gclk);
+        if let Some((clock, _)) = user {
+            return Err(GclkError::UsedBy(*clock));
+        }
+        if gclk == GCLK1 {
+            let user = self
+                .gclk_sources
+                .iter()
+                .position(|src| *src == Some(GCLKGEN1));
+            if let Some(index) = user {
+                return Err(GclkError::SourceOf(GCLK_IDS[index]));
+            }
+        }
+
+        let idx = u8::from(gclk) as usize;
+        self.state.disable_gclk(gclk);
+        self.gclks[idx] = Hertz(0);
+        self.gclk_sources[idx] = None;
+        Ok(())
+    }
+
+    /// Returns the generator feeding the generic clock channel of a
+    /// peripheral, if enabled
+    pub fn clock_source(&self, clock: ClockId) -> Option<ClockGenId> {
+        self.channels[u8::from(clock) as usize].map(|(_, gen)| gen)
+    }
+
     /// Enables or disables the given GClk from operation in standby.
     pub fn configure_standby(&mut self, gclk: ClockGenId, enable: bool) {
         self.state.configure_standby(gclk, enable)
@@ -362,6 +616,12 @@ impl Into<Hertz> for $Type {
         self.freq
     }
 }
+
+impl Sealed for $Type {}
+
+impl PeripheralClock for $Type {
+    const ID: ClockId = ClockId::$clock;
+}
 )+
 
 impl GenericClockController {
@@ -377,13 +637,13 @@ impl GenericClockController {
     /// clock token be passed in to ensure that the clock has been initialized
     /// appropriately.
     /// Returns `None` is the specified generic clock has already been
-    /// configured.
+    /// configured. The clock can be released with `disable_clock`.
     pub fn $id(&mut self, generator: &GClock) -> Option<$Type> {
         let bits: u64 = 1<<u8::from(ClockId::$clock) as u64;
         if (self.used_clocks & bits) != 0 {
             return None;
         }
-        self.used_clocks |= bits;
+        self.mark_used(ClockId::$clock, generator.gclk);
 
         self.state.enable_clock_generator(ClockId::$clock, generator.gclk);
         let freq = self.gclks[u8::from(generator.gclk) as usize];
@@ -464,6 +724,13 @@ fn set_flash_to_half_auto_wait_state(nvmctrl: &mut NVMCTRL) {
     nvmctrl.ctrlb.modify(|_, w| w.rws().half());
 }
 
+fn set_flash_wait_states(nvmctrl: &mut NVMCTRL, wait_states: u8) {
+    match wait_states {
+        0 => nvmctrl.ctrlb.modify(|_, w| w.rws().single()),
+        _ => nvmctrl.ctrlb.modify(|_, w| w.rws().half()),
+    }
+}
+
 /// Prevent automatic writes to flash by pointers to flash area
 #[cfg(feature = "samd21")]
 fn set_flash_manual_write(nvmctrl: &mut NVMCTRL) {
@@ -517,8 +784,11 @@ fn wait_for_dfllrdy(sysctrl: &mut SYSCTRL) {
     while sysctrl.pclksr.read().dfllrdy().bit_is_clear() {}
 }
 
-/// Configure the dfll48m to operate at 48Mhz
-fn configure_and_enable_dfll48m(sysctrl: &mut SYSCTRL, use_external_crystal: bool) {
+/// Configure the dfll48m to operate at 48Mhz in the given `mode`
+///
+/// In closed-loop mode, the reference generator must already feed the DFLL48
+/// channel, and `mul` is the ratio between the output and the reference.
+fn configure_and_enable_dfll48m(sysctrl: &mut SYSCTRL, mode: DfllMode, mul: u16) {
     // Turn it off while we configure it.
     // Note that we need to turn off on-demand mode and
     // disable it here, rather than just reseting the ctrl
@@ -526,61 +796,74 @@ fn configure_and_enable_dfll48m(sysctrl: &mut SYSCTRL, use_external_crystal: boo
     sysctrl.dfllctrl.write(|w| w.ondemand().clear_bit());
     wait_for_dfllrdy(sysctrl);
 
-    if use_external_crystal {
-        sysctrl.dfllmul.write(|w| unsafe {
-            w.cstep().bits(31);
-            w.fstep().bits(511);
-            // scaling factor between the clocks
-            w.mul().bits(((48_000_000u32 + 32768 / 2) / 32768) as u16)
-        });
-
-        // Turn it on
-        sysctrl.dfllctrl.write(|w| {
-            // always on
-            w.ondemand().clear_bit();
+    match mode {
+        DfllMode::ClosedLoop(_) => {
+            sysctrl.dfllmul.write(|w| unsafe {
+                w.cstep().bits(31);
+                w.fstep().bits(511);
+                // scaling factor between the clocks
+                w.mul().bits(mul)
+            });
 
-            // closed loop mode
-            w.mode().set_bit();
+            // Turn it on
+            sysctrl.dfllctrl.write(|w| {
+                // always on
+                w.ondemand().clear_bit();
 
-            w.waitlock().set_bit();
+                // closed loop mode
+                w.mode().set_bit();
 
-            // Disable quick lock
-            w.qldis().set_bit()
-        });
-    } else {
-        // Apply calibration
-        let coarse = super::calibration::dfll48m_coarse_cal();
-        let fine = 0x1ff;
-
-        sysctrl.dfllval.write(|w| unsafe {
-            w.coarse().bits(coarse);
-            w.fine().bits(fine)
-        });
+                w.waitlock().set_bit();
 
-        sysctrl.dfllmul.write(|w| unsafe {
-            w.cstep().bits(coarse / 4);
-            w.fstep().bits(10);
-            // scaling factor for 1 kHz USB SOF signal
-            w.mul().bits((48_000_000u32 / 1000) as u16)
-        });
-
-        // Turn it on
-        sysctrl.dfllctrl.write(|w| {
-            // always on
-            w.ondemand().clear_bit();
-
-            // closed loop mode
-            w.mode().set_bit();
-
-            // chill cycle disable
-            w.ccdis().set_bit();
-
-            // usb correction
-            w.usbcrm().set_bit();
-
-            // bypass coarse lock (have calibration data)
-            w.bplckc().set_bit()
-        });
+                // Disable quick lock
+                w.qldis().set_bit()
+            });
+        }
+        DfllMode::UsbRecovery => {
+            // Apply calibration
+            let coarse = super::calibration::dfll48m_coarse_cal();
+            let fine = 0x1ff;
+
+            sysctrl.dfllval.write(|w| unsafe {
+                w.coarse().bits(coarse);
+                w.fine().bits(fine)
+            });
+
+            sysctrl.dfllmul.write(|w| unsafe {
+                w.cstep().bits(coarse / 4);
+                w.fstep().bits(10);
+                // scaling factor for 1 kHz USB SOF signal
+                w.mul().bits((48_000_000u32 / 1000) as u16)
+            });
+
+            // Turn it on
+            sysctrl.dfllctrl.write(|w| {
+                // always on
+                w.ondemand().clear_bit();
+
+                // closed loop mode
+                w.mode().set_bit();
+
+                // chill cycle disable
+                w.ccdis().set_bit();
+
+                // usb correction
+                w.usbcrm().set_bit();
+
+                // bypass coarse lock (have calibration data)
+                w.bplckc().set_bit()
+            });
+        }
+        DfllMode::OpenLoop => {
+            // Apply calibration, the DFLL then runs close to 48Mhz
+            let coarse = super::calibration::dfll48m_coarse_cal();
+            let fine = 0x1ff;
+
+            sysctrl.dfllval.write(|w| unsafe {
+                w.coarse().bits(coarse);
+                w.fine().bits(fine)
+            });
+        }
     }
 
     wait_for_dfllrdy(sysctrl);
@@ -589,7 +872,7 @@ fn configure_and_enable_dfll48m(sysctrl: &mut SYSCTRL, use_external_crystal: boo
     sysctrl.dfllctrl.modify(|_, w| w.enable().set_bit());
 
     #[cfg(feature = "samd21")]
-    if use_external_crystal {
+    if let DfllMode::ClosedLoop(_) = mode {
         // wait for lock
         while sysctrl.pclksr.read().dflllckc().bit_is_clear()
             || sysctrl.pclksr.read().dflllckf().bit_is_clear()
@@ -598,3 +881,44 @@ fn configure_and_enable_dfll48m(sysctrl: &mut SYSCTRL, use_external_crystal: boo
 
     wait_for_dfllrdy(sysctrl);
 }
+
+/// Configure and enable the crystal oscillator, then wait for it to be ready
+fn configure_and_enable_xosc(sysctrl: &mut SYSCTRL, config: &XoscConfig) {
+    sysctrl.xosc.write(|w| {
+        unsafe {
+            w.gain().bits(config.gain());
+            // 8 here means: use 256 cycles of OSCULP32k to start up this oscillator
+            w.startup().bits(8);
+        }
+        w.ampgc().bit(config.crystal);
+        w.xtalen().bit(config.crystal);
+        w.ondemand().clear_bit();
+        w.runstdby().set_bit()
+    });
+    sysctrl.xosc.modify(|_, w| w.enable().set_bit());
+    while sysctrl.pclksr.read().xoscrdy().bit_is_clear() {
+        // Wait for the oscillator to stabilize
+    }
+}
+
+/// Configure and enable the FDPLL96M with the given `(LDR, LDRFRAC)` ratio,
+/// then wait for it to lock
+fn configure_and_enable_dpll(sysctrl: &mut SYSCTRL, reference: DpllRef, (ldr, ldrfrac): (u16, u8)) {
+    sysctrl.dpllratio.write(|w| unsafe {
+        w.ldr().bits(ldr);
+        w.ldrfrac().bits(ldrfrac)
+    });
+    sysctrl.dpllctrlb.write(|w| match reference {
+        DpllRef::Xosc32k => w.refclk().ref0(),
+        // The XOSC reference is divided by 2 * (DIV + 1)
+        DpllRef::Xosc { prediv } => unsafe { w.refclk().ref1().div().bits(prediv / 2 - 1) },
+        DpllRef::Gclk(_) => w.refclk().gclk(),
+    });
+    sysctrl.dpllctrla.write(|w| {
+        w.ondemand().clear_bit();
+        w.enable().set_bit()
+    });
+    while sysctrl.dpllstatus.read().lock().bit_is_clear()
+        || sysctrl.dpllstatus.read().clkrdy().bit_is_clear()
+    {}
+}
@@ -0,0 +1,1094 @@
+//! # Clock tree configuration
+//!
+//! This module describes a complete SAMD11/SAMD21 clock tree at runtime,
+//! checks it against the datasheet frequency limits and computes the resulting
+//! frequencies, before anything is written to the hardware. A validated
+//! [`ClockTree`] is then applied with
+//! [`GenericClockController::from_tree`](super::GenericClockController::from_tree).
+//!
+//! Every clock source is represented by a token, which is handed out when the
+//! source is added to a tree: [`Xosc`], [`Xosc32k`], [`Osc32k`],
+//! [`OscUlp32k`], [`Osc8m`], [`Dfll`], [`Dpll`] and [`Gclk`]. Clock generators
+//! and the FDPLL96M can only be fed from tokens, so a source must be
+//! configured before it can be referenced. Tokens are `Copy` and are not tied
+//! to the tree they came from, though: a token used in another tree is only
+//! caught by [`ClockTree::compute`], which returns [`Error::NotConfigured`] if
+//! its source is missing from that tree.
+//!
+//! At reset, GCLK0 is fed by the OSC8M with a prescaler of 8. A tree created
+//! with [`ClockTree::new`] starts from that state.
+//!
+//! ```no_run
+//! use atsamd_hal::clock::tree::{ClockTree, DpllReference, XoscConfig};
+//! use atsamd_hal::clock::{ClockGenId, GenericClockController};
+//! use atsamd_hal::pac::Peripherals;
+//! use atsamd_hal::time::MegaHertz;
+//!
+//! let mut peripherals = Peripherals::take().unwrap();
+//!
+//! let mut tree = ClockTree::new();
+//! // 16 MHz crystal, divided by 8 to provide a 2 MHz DPLL reference
+//! let xosc = tree.xosc(XoscConfig::crystal(MegaHertz(16)));
+//! let dpll = tree.dpll(DpllReference::xosc(xosc, 8), MegaHertz(96));
+//! tree.gclk(ClockGenId::GCLK0, dpll, 2);
+//! let gclk3 = tree.gclk(ClockGenId::GCLK3, xosc, 2);
+//!
+//! let freqs = tree.compute().unwrap();
+//! assert_eq!(freqs.cpu().0, 48_000_000);
+//! assert_eq!(freqs.gclk(gclk3.id()).unwrap().0, 8_000_000);
+//!
+//! let mut clocks = GenericClockController::from_tree(
+//!     &tree,
+//!     peripherals.GCLK,
+//!     &mut peripherals.PM,
+//!     &mut peripherals.SYSCTRL,
+//!     &mut peripherals.NVMCTRL,
+//! )
+//! .unwrap();
+//! ```
+//!
+//! The computations performed by this module do not touch any register, and
+//! can be unit tested on the host.
+
+use super::{ClockGenId, ClockId};
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+//==============================================================================
+// Frequency limits
+//==============================================================================
+
+/// Nominal frequency of the DFLL48M
+pub const DFLL_FREQ: Hertz = Hertz(48_000_000);
+/// Frequency of the 32 kHz oscillators
+pub const OSC32K_FREQ: Hertz = Hertz(32_768);
+/// Frequency of the OSC8M, before its prescaler
+pub const OSC8M_FREQ: Hertz = Hertz(8_000_000);
+
+/// Maximum CPU frequency
+pub const CPU_MAX_FREQ: Hertz = Hertz(48_000_000);
+/// Maximum frequency of a generic clock generator
+pub const GCLK_MAX_FREQ: Hertz = Hertz(96_000_000);
+
+/// Number of generic clock generators
+#[cfg(feature = "samd11")]
+pub const NUM_GCLK: usize = 6;
+/// Number of generic clock generators
+#[cfg(feature = "samd21")]
+pub const NUM_GCLK: usize = 9;
+
+const XOSC_CRYSTAL_MIN: Hertz = Hertz(400_000);
+const XOSC_MAX: Hertz = Hertz(32_000_000);
+const DPLL_REF_MIN: Hertz = Hertz(32_000);
+const DPLL_REF_MAX: Hertz = Hertz(2_000_000);
+const DPLL_OUT_MIN: Hertz = Hertz(48_000_000);
+const DPLL_OUT_MAX: Hertz = Hertz(96_000_000);
+const DFLL_REF_MIN: Hertz = Hertz(732);
+const DFLL_REF_MAX: Hertz = Hertz(33_000);
+const PERIPHERAL_MAX: Hertz = Hertz(48_000_000);
+
+/// Maximum frequency of the generic clock channel of a peripheral
+pub fn peripheral_max_freq(clock: ClockId) -> Hertz {
+    match clock {
+        ClockId::DFLL48 => DFLL_REF_MAX,
+        ClockId::FDPLL => DPLL_REF_MAX,
+        #[cfg(feature = "samd11")]
+        ClockId::TCC0 => GCLK_MAX_FREQ,
+        #[cfg(feature = "samd21")]
+        ClockId::TCC0_TCC1 => GCLK_MAX_FREQ,
+        _ => PERIPHERAL_MAX,
+    }
+}
+
+/// Number of flash wait states required at the given CPU frequency
+///
+/// Assumes a supply voltage above 2.7 V.
+pub fn flash_wait_states(cpu: Hertz) -> u8 {
+    if cpu.0 <= 24_000_000 {
+        0
+    } else {
+        1
+    }
+}
+
+//==============================================================================
+// Errors
+//==============================================================================
+
+/// A clock in the tree
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Clock {
+    /// Crystal oscillator XOSC
+    Xosc,
+    /// External 32 kHz crystal oscillator
+    Xosc32k,
+    /// Internal 32 kHz oscillator
+    Osc32k,
+    /// Internal 8 MHz oscillator
+    Osc8m,
+    /// DFLL48M
+    Dfll,
+    /// FDPLL96M
+    Dpll,
+    /// Generic clock generator
+    Gclk(ClockGenId),
+    /// CPU clock, derived from GCLK0
+    Cpu,
+    /// Generic clock channel of a peripheral
+    Peripheral(ClockId),
+}
+
+/// Errors reported while validating a [`ClockTree`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// A source was used without being configured in this tree
+    NotConfigured(Clock),
+    /// A clock frequency lies outside of its allowed range
+    FrequencyOutOfRange {
+        /// Offending clock
+        clock: Clock,
+        /// Computed frequency
+        freq: Hertz,
+        /// Minimum allowed frequency
+        min: Hertz,
+        /// Maximum allowed frequency
+        max: Hertz,
+    },
+    /// A divider or prescaler is out of range for the given clock
+    InvalidDivider(Clock),
+    /// The DPLL ratio required for the requested frequency can't be
+    /// represented
+    InvalidRatio(Clock),
+    /// Only GCLK1 can be used as the source of other generators
+    InvalidSource(Clock),
+    /// The clock depends on itself
+    Loop(Clock),
+}
+
+/// Check that `freq` lies within `min..=max`
+fn check_range(clock: Clock, freq: Hertz, min: Hertz, max: Hertz) -> Result<Hertz, Error> {
+    if freq.0 < min.0 || freq.0 > max.0 {
+        Err(Error::FrequencyOutOfRange {
+            clock,
+            freq,
+            min,
+            max,
+        })
+    } else {
+        Ok(freq)
+    }
+}
+
+//==============================================================================
+// Tokens
+//==============================================================================
+
+/// Token for the configured crystal oscillator XOSC
+#[derive(Clone, Copy, Debug)]
+pub struct Xosc(());
+
+/// Token for the configured external 32 kHz crystal oscillator
+#[derive(Clone, Copy, Debug)]
+pub struct Xosc32k(());
+
+/// Token for the configured internal 32 kHz oscillator
+#[derive(Clone, Copy, Debug)]
+pub struct Osc32k(());
+
+/// Token for the always-on 32 kHz ultra low power oscillator
+#[derive(Clone, Copy, Debug)]
+pub struct OscUlp32k(());
+
+/// Token for the internal 8 MHz oscillator, after its prescaler
+#[derive(Clone, Copy, Debug)]
+pub struct Osc8m(());
+
+/// Token for the configured DFLL48M
+#[derive(Clone, Copy, Debug)]
+pub struct Dfll(());
+
+/// Token for the configured FDPLL96M
+#[derive(Clone, Copy, Debug)]
+pub struct Dpll(());
+
+/// Token for a configured generic clock generator
+#[derive(Clone, Copy, Debug)]
+pub struct Gclk(ClockGenId);
+
+impl Gclk {
+    /// Return the ID of the clock generator
+    #[inline]
+    pub fn id(&self) -> ClockGenId {
+        self.0
+    }
+}
+
+/// External clock applied to a generator's GCLK_IO pin
+#[derive(Clone, Copy, Debug)]
+pub struct GclkIn(pub Hertz);
+
+/// Clock source selected for a generic clock generator
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Source {
+    Xosc,
+    GclkIn(Hertz),
+    Gclk1,
+    OscUlp32k,
+    Osc32k,
+    Xosc32k,
+    Osc8m,
+    Dfll,
+    Dpll,
+}
+
+/// Tokens that can feed a generic clock generator
+pub trait GclkSource: Sealed + Copy {
+    #[doc(hidden)]
+    fn source(&self) -> Result<Source, Error>;
+}
+
+macro_rules! gclk_source {
+    ($($Token:ident),+) => {
+        $(
+            impl Sealed for $Token {}
+
+            impl GclkSource for $Token {
+                #[inline]
+                fn source(&self) -> Result<Source, Error> {
+                    Ok(Source::$Token)
+                }
+            }
+        )+
+    };
+}
+
+gclk_source!(Xosc, OscUlp32k, Osc32k, Xosc32k, Osc8m, Dfll, Dpll);
+
+impl Sealed for GclkIn {}
+
+impl GclkSource for GclkIn {
+    #[inline]
+    fn source(&self) -> Result<Source, Error> {
+        Ok(Source::GclkIn(self.0))
+    }
+}
+
+impl Sealed for Gclk {}
+
+impl GclkSource for Gclk {
+    #[inline]
+    fn source(&self) -> Result<Source, Error> {
+        match self.0 {
+            ClockGenId::GCLK1 => Ok(Source::Gclk1),
+            id => Err(Error::InvalidSource(Clock::Gclk(id))),
+        }
+    }
+}
+
+//==============================================================================
+// Source configurations
+//==============================================================================
+
+/// Configuration of the crystal oscillator XOSC
+#[derive(Clone, Copy, Debug)]
+pub struct XoscConfig {
+    pub(super) freq: Hertz,
+    pub(super) crystal: bool,
+}
+
+impl XoscConfig {
+    /// A crystal connected between XIN and XOUT, from 0.4 to 32 MHz
+    #[inline]
+    pub fn crystal(freq: impl Into<Hertz>) -> Self {
+        Self {
+            freq: freq.into(),
+            crystal: true,
+        }
+    }
+
+    /// An external clock applied to XIN, up to 32 MHz
+    #[inline]
+    pub fn external(freq: impl Into<Hertz>) -> Self {
+        Self {
+            freq: freq.into(),
+            crystal: false,
+        }
+    }
+
+    /// Oscillator gain recommended for the crystal frequency
+    pub fn gain(&self) -> u8 {
+        match self.freq.0 {
+            0..=2_000_000 => 0,
+            2_000_001..=4_000_000 => 1,
+            4_000_001..=8_000_000 => 2,
+            8_000_001..=16_000_000 => 3,
+            _ => 4,
+        }
+    }
+}
+
+/// Reference clock of the FDPLL96M
+#[derive(Clone, Copy, Debug)]
+pub struct DpllReference(pub(super) DpllRef);
+
+#[derive(Clone, Copy, Debug)]
+pub(super) enum DpllRef {
+    Xosc32k,
+    Xosc { prediv: u16 },
+    Gclk(ClockGenId),
+}
+
+impl DpllReference {
+    /// Use the external 32 kHz oscillator as reference
+    #[inline]
+    pub fn xosc32k(_xosc: Xosc32k) -> Self {
+        Self(DpllRef::Xosc32k)
+    }
+
+    /// Use XOSC as reference, divided by `prediv`
+    ///
+    /// `prediv` must be an even number from 2 to 4096.
+    #[inline]
+    pub fn xosc(_xosc: Xosc, prediv: u16) -> Self {
+        Self(DpllRef::Xosc { prediv })
+    }
+
+    /// Use a generic clock generator as reference
+    #[inline]
+    pub fn gclk(gclk: Gclk) -> Self {
+        Self(DpllRef::Gclk(gclk.0))
+    }
+}
+
+/// Configuration of the FDPLL96M
+#[derive(Clone, Copy, Debug)]
+pub(super) struct DpllConfig {
+    pub(super) reference: DpllRef,
+    pub(super) target: Hertz,
+}
+
+/// Operating mode of the DFLL48M
+#[derive(Clone, Copy, Debug)]
+pub(super) enum DfllMode {
+    OpenLoop,
+    ClosedLoop(ClockGenId),
+    UsbRecovery,
+}
+
+/// Configuration of a generic clock generator
+#[derive(Clone, Copy, Debug)]
+pub(super) struct GclkConfig {
+    pub(super) source: Result<Source, Error>,
+    pub(super) div: u32,
+    pub(super) improve_duty_cycle: bool,
+}
+
+//==============================================================================
+// ClockTree
+//==============================================================================
+
+/// Description of the clock sources and generators of the chip
+///
+/// See the [module-level documentation](self) for more details.
+#[derive(Clone, Debug)]
+pub struct ClockTree {
+    pub(super) xosc: Option<XoscConfig>,
+    pub(super) xosc32k: bool,
+    pub(super) osc32k: bool,
+    pub(super) osc8m_presc: u8,
+    pub(super) dfll: Option<DfllMode>,
+    pub(super) dpll: Option<DpllConfig>,
+    pub(super) gclk: [Option<GclkConfig>; NUM_GCLK],
+    pub(super) cpu_div: u8,
+}
+
+impl Default for ClockTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockTree {
+    /// Create a clock tree in its reset state
+    ///
+    /// The OSC8M runs with a prescaler of 8 and feeds GCLK0 directly.
+    pub fn new() -> Self {
+        let mut gclk = [None; NUM_GCLK];
+        gclk[0] = Some(GclkConfig {
+            source: Ok(Source::Osc8m),
+            div: 1,
+            improve_duty_cycle: false,
+        });
+        Self {
+            xosc: None,
+            xosc32k: false,
+            osc32k: false,
+            osc8m_presc: 8,
+            dfll: None,
+            dpll: None,
+            gclk,
+            cpu_div: 1,
+        }
+    }
+
+    /// Configure XOSC
+    #[inline]
+    pub fn xosc(&mut self, config: XoscConfig) -> Xosc {
+        self.xosc = Some(config);
+        Xosc(())
+    }
+
+    /// Enable the external 32 kHz crystal oscillator
+    #[inline]
+    pub fn xosc32k(&mut self) -> Xosc32k {
+        self.xosc32k = true;
+        Xosc32k(())
+    }
+
+    /// Enable the internal 32 kHz oscillator
+    #[inline]
+    pub fn osc32k(&mut self) -> Osc32k {
+        self.osc32k = true;
+        Osc32k(())
+    }
+
+    /// Return the token for the ultra low power 32 kHz oscillator
+    #[inline]
+    pub fn osculp32k(&self) -> OscUlp32k {
+        OscUlp32k(())
+    }
+
+    /// Set the OSC8M prescaler
+    ///
+    /// `presc` must be 1, 2, 4 or 8.
+    #[inline]
+    pub fn osc8m(&mut self, presc: u8) -> Osc8m {
+        self.osc8m_presc = presc;
+        Osc8m(())
+    }
+
+    /// Run the DFLL48M open-loop, using the factory calibration
+    #[inline]
+    pub fn dfll_open_loop(&mut self) -> Dfll {
+        self.dfll = Some(DfllMode::OpenLoop);
+        Dfll(())
+    }
+
+    /// Run the DFLL48M in closed-loop mode, locked to `reference`
+    ///
+    /// The multiplication factor is chosen so that the output is as close to
+    /// 48 MHz as possible. The reference must run between 732 Hz and 33 kHz.
+    #[inline]
+    pub fn dfll_closed_loop(&mut self, reference: Gclk) -> Dfll {
+        self.dfll = Some(DfllMode::ClosedLoop(reference.0));
+        Dfll(())
+    }
+
+    /// Run the DFLL48M in USB clock recovery mode, locked to the 1 kHz USB
+    /// start-of-frame
+    #[inline]
+    pub fn dfll_usb_recovery(&mut self) -> Dfll {
+        self.dfll = Some(DfllMode::UsbRecovery);
+        Dfll(())
+    }
+
+    /// Configure the FDPLL96M to output `freq` from `reference`
+    ///
+    /// The ratio is rounded to the closest 1/16; the actual frequency is
+    /// reported by [`compute`](Self::compute).
+    #[inline]
+    pub fn dpll(&mut self, reference: DpllReference, freq: impl Into<Hertz>) -> Dpll {
+        self.dpll = Some(DpllConfig {
+            reference: reference.0,
+            target: freq.into(),
+        });
+        Dpll(())
+    }
+
+    /// Configure a generic clock generator to divide `source` by `div`
+    ///
+    /// `div` ranges from 1 to 65535 for GCLK1, from 1 to 31 for GCLK2, and
+    /// from 1 to 255 for the other generators. Only GCLK1 can be used as the
+    /// source of another generator.
+    #[inline]
+    pub fn gclk(&mut self, id: ClockGenId, source: impl GclkSource, div: u32) -> Gclk {
+        self.gclk[u8::from(id) as usize] = Some(GclkConfig {
+            source: source.source(),
+            div,
+            improve_duty_cycle: false,
+        });
+        Gclk(id)
+    }
+
+    /// Enable or disable the 50/50 duty cycle correction of a generator for
+    /// odd dividers
+    #[inline]
+    pub fn improve_duty_cycle(&mut self, gclk: Gclk, enable: bool) {
+        if let Some(config) = self.gclk[u8::from(gclk.0) as usize].as_mut() {
+            config.improve_duty_cycle = enable;
+        }
+    }
+
+    /// Return a token for GCLK0, which is always running
+    #[inline]
+    pub fn gclk0(&self) -> Gclk {
+        Gclk(ClockGenId::GCLK0)
+    }
+
+    /// Set the CPU clock divider, applied to GCLK0
+    ///
+    /// `div` must be a power of two from 1 to 128.
+    #[inline]
+    pub fn cpu_div(&mut self, div: u8) {
+        self.cpu_div = div;
+    }
+
+    /// Validate the tree and compute the frequency of every clock
+    pub fn compute(&self) -> Result<Frequencies, Error> {
+        let mut resolver = Resolver {
+            tree: self,
+            freqs: Frequencies::empty(),
+            visiting: [false; NUM_NODES],
+            done: [false; NUM_NODES],
+        };
+
+        for node in Node::ALL.iter() {
+            if node.configured(self) {
+                resolver.resolve(*node)?;
+            }
+        }
+
+        if !self.cpu_div.is_power_of_two() {
+            return Err(Error::InvalidDivider(Clock::Cpu));
+        }
+        let main = resolver.freqs.gclk[0].ok_or(Error::NotConfigured(Clock::Gclk(GCLK_IDS[0])))?;
+        let cpu = Hertz(main.0 / self.cpu_div as u32);
+        check_range(Clock::Cpu, cpu, Hertz(0), CPU_MAX_FREQ)?;
+
+        let mut freqs = resolver.freqs;
+        freqs.cpu = cpu;
+        freqs.wait_states = flash_wait_states(cpu);
+        Ok(freqs)
+    }
+}
+
+//==============================================================================
+// Frequencies
+//==============================================================================
+
+/// Frequencies of a validated [`ClockTree`]
+#[derive(Clone, Copy, Debug)]
+pub struct Frequencies {
+    xosc: Option<Hertz>,
+    xosc32k: Option<Hertz>,
+    osc32k: Option<Hertz>,
+    osc8m: Option<Hertz>,
+    dfll: Option<Hertz>,
+    dpll: Option<Hertz>,
+    gclk: [Option<Hertz>; NUM_GCLK],
+    cpu: Hertz,
+    pub(super) wait_states: u8,
+    pub(super) dfll_mul: u16,
+    pub(super) dpll_ratio: (u16, u8),
+    pub(super) order: [Option<Node>; NUM_NODES],
+}
+
+impl Frequencies {
+    fn empty() -> Self {
+        Self {
+            xosc: None,
+            xosc32k: None,
+            osc32k: None,
+            osc8m: None,
+            dfll: None,
+            dpll: None,
+            gclk: [None; NUM_GCLK],
+            cpu: Hertz(0),
+            wait_states: 0,
+            dfll_mul: 0,
+            dpll_ratio: (0, 0),
+            order: [None; NUM_NODES],
+        }
+    }
+
+    /// Frequency of XOSC, if configured
+    #[inline]
+    pub fn xosc(&self) -> Option<Hertz> {
+        self.xosc
+    }
+
+    /// Frequency of the external 32 kHz oscillator, if enabled
+    #[inline]
+    pub fn xosc32k(&self) -> Option<Hertz> {
+        self.xosc32k
+    }
+
+    /// Frequency of the internal 32 kHz oscillator, if enabled
+    #[inline]
+    pub fn osc32k(&self) -> Option<Hertz> {
+        self.osc32k
+    }
+
+    /// Frequency of the OSC8M after its prescaler, if used by the tree
+    #[inline]
+    pub fn osc8m(&self) -> Option<Hertz> {
+        self.osc8m
+    }
+
+    /// Frequency of the DFLL48M, if configured
+    #[inline]
+    pub fn dfll(&self) -> Option<Hertz> {
+        self.dfll
+    }
+
+    /// Frequency of the FDPLL96M, if configured
+    #[inline]
+    pub fn dpll(&self) -> Option<Hertz> {
+        self.dpll
+    }
+
+    /// Frequency of a generic clock generator, if configured
+    #[inline]
+    pub fn gclk(&self, id: ClockGenId) -> Option<Hertz> {
+        self.gclk[u8::from(id) as usize]
+    }
+
+    /// Frequency of the CPU
+    #[inline]
+    pub fn cpu(&self) -> Hertz {
+        self.cpu
+    }
+
+    /// Number of flash wait states required by the CPU frequency
+    #[inline]
+    pub fn wait_states(&self) -> u8 {
+        self.wait_states
+    }
+
+    /// Frequency of a peripheral channel fed by generator `id`
+    ///
+    /// Returns an error if the generator is not configured, or runs too fast
+    /// for the peripheral.
+    pub fn peripheral(&self, clock: ClockId, id: ClockGenId) -> Result<Hertz, Error> {
+        let freq = self.gclk(id).ok_or(Error::NotConfigured(Clock::Gclk(id)))?;
+        check_range(
+            Clock::Peripheral(clock),
+            freq,
+            Hertz(0),
+            peripheral_max_freq(clock),
+        )
+    }
+}
+
+//==============================================================================
+// Resolver
+//==============================================================================
+
+const NUM_NODES: usize = 6 + NUM_GCLK;
+
+/// A clock that must be configured in hardware
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Node {
+    Xosc,
+    Xosc32k,
+    Osc32k,
+    Osc8m,
+    Dfll,
+    Dpll,
+    Gclk(usize),
+}
+
+impl Node {
+    fn index(self) -> usize {
+        match self {
+            Node::Xosc => 0,
+            Node::Xosc32k => 1,
+            Node::Osc32k => 2,
+            Node::Osc8m => 3,
+            Node::Dfll => 4,
+            Node::Dpll => 5,
+            Node::Gclk(i) => 6 + i,
+        }
+    }
+
+    const ALL: [Node; NUM_NODES] = {
+        let mut all = [Node::Xosc; NUM_NODES];
+        all[1] = Node::Xosc32k;
+        all[2] = Node::Osc32k;
+        all[3] = Node::Osc8m;
+        all[4] = Node::Dfll;
+        all[5] = Node::Dpll;
+        let mut i = 0;
+        while i < NUM_GCLK {
+            all[6 + i] = Node::Gclk(i);
+            i += 1;
+        }
+        all
+    };
+
+    fn clock(self) -> Clock {
+        match self {
+            Node::Xosc => Clock::Xosc,
+            Node::Xosc32k => Clock::Xosc32k,
+            Node::Osc32k => Clock::Osc32k,
+            Node::Osc8m => Clock::Osc8m,
+            Node::Dfll => Clock::Dfll,
+            Node::Dpll => Clock::Dpll,
+            Node::Gclk(i) => Clock::Gclk(GCLK_IDS[i]),
+        }
+    }
+
+    /// Whether the node is explicitly part of the tree. Other nodes are only
+    /// resolved if something depends on them.
+    fn configured(self, tree: &ClockTree) -> bool {
+        match self {
+            Node::Xosc => tree.xosc.is_some(),
+            Node::Xosc32k => tree.xosc32k,
+            Node::Osc32k => tree.osc32k,
+            Node::Osc8m => false,
+            Node::Dfll => tree.dfll.is_some(),
+            Node::Dpll => tree.dpll.is_some(),
+            Node::Gclk(i) => tree.gclk[i].is_some(),
+        }
+    }
+}
+
+pub(super) const GCLK_IDS: [ClockGenId; NUM_GCLK] = [
+    ClockGenId::GCLK0,
+    ClockGenId::GCLK1,
+    ClockGenId::GCLK2,
+    ClockGenId::GCLK3,
+    ClockGenId::GCLK4,
+    ClockGenId::GCLK5,
+    #[cfg(feature = "samd21")]
+    ClockGenId::GCLK6,
+    #[cfg(feature = "samd21")]
+    ClockGenId::GCLK7,
+    #[cfg(feature = "samd21")]
+    ClockGenId::GCLK8,
+];
+
+/// Depth-first frequency computation, recording the order in which the
+/// clocks must be enabled
+struct Resolver<'a> {
+    tree: &'a ClockTree,
+    freqs: Frequencies,
+    visiting: [bool; NUM_NODES],
+    done: [bool; NUM_NODES],
+}
+
+impl Resolver<'_> {
+    fn resolve(&mut self, node: Node) -> Result<Hertz, Error> {
+        let idx = node.index();
+        if self.done[idx] {
+            return self.freq(node).ok_or(Error::NotConfigured(node.clock()));
+        }
+        if self.visiting[idx] {
+            return Err(Error::Loop(node.clock()));
+        }
+        self.visiting[idx] = true;
+
+        let freq = match node {
+            Node::Xosc => self.xosc()?,
+            Node::Xosc32k => {
+                if !self.tree.xosc32k {
+                    return Err(Error::NotConfigured(Clock::Xosc32k));
+                }
+                self.freqs.xosc32k = Some(OSC32K_FREQ);
+                OSC32K_FREQ
+            }
+            Node::Osc32k => {
+                if !self.tree.osc32k {
+                    return Err(Error::NotConfigured(Clock::Osc32k));
+                }
+                self.freqs.osc32k = Some(OSC32K_FREQ);
+                OSC32K_FREQ
+            }
+            Node::Osc8m => self.osc8m()?,
+            Node::Dfll => self.dfll()?,
+            Node::Dpll => self.dpll()?,
+            Node::Gclk(i) => self.gclk(i)?,
+        };
+
+        self.visiting[idx] = false;
+        self.done[idx] = true;
+        let slot = self.freqs.order.iter_mut().find(|n| n.is_none());
+        if let Some(slot) = slot {
+            *slot = Some(node);
+        }
+        Ok(freq)
+    }
+
+    fn freq(&self, node: Node) -> Option<Hertz> {
+        match node {
+            Node::Xosc => self.freqs.xosc,
+            Node::Xosc32k => self.freqs.xosc32k,
+            Node::Osc32k => self.freqs.osc32k,
+            Node::Osc8m => self.freqs.osc8m,
+            Node::Dfll => self.freqs.dfll,
+            Node::Dpll => self.freqs.dpll,
+            Node::Gclk(i) => self.freqs.gclk[i],
+        }
+    }
+
+    fn xosc(&mut self) -> Result<Hertz, Error> {
+        let config = self.tree.xosc.ok_or(Error::NotConfigured(Clock::Xosc))?;
+        let min = if config.crystal {
+            XOSC_CRYSTAL_MIN
+        } else {
+            Hertz(1)
+        };
+        let freq = check_range(Clock::Xosc, config.freq, min, XOSC_MAX)?;
+        self.freqs.xosc = Some(freq);
+        Ok(freq)
+    }
+
+    fn osc8m(&mut self) -> Result<Hertz, Error> {
+        let presc = self.tree.osc8m_presc;
+        if !matches!(presc, 1 | 2 | 4 | 8) {
+            return Err(Error::InvalidDivider(Clock::Osc8m));
+        }
+        let freq = Hertz(OSC8M_FREQ.0 / presc as u32);
+        self.freqs.osc8m = Some(freq);
+        Ok(freq)
+    }
+
+    fn dfll(&mut self) -> Result<Hertz, Error> {
+        let mode = self.tree.dfll.ok_or(Error::NotConfigured(Clock::Dfll))?;
+        let (mul, freq) = match mode {
+            DfllMode::OpenLoop => (0, DFLL_FREQ),
+            DfllMode::UsbRecovery => ((DFLL_FREQ.0 / 1000) as u16, DFLL_FREQ),
+            DfllMode::ClosedLoop(id) => {
+                let reference = self.resolve(Node::Gclk(u8::from(id) as usize))?;
+                let reference = check_range(
+                    Clock::Peripheral(ClockId::DFLL48),
+                    reference,
+                    DFLL_REF_MIN,
+                    DFLL_REF_MAX,
+                )?;
+                // Round down, so that the CPU never runs above its maximum
+                // frequency
+                let mul = DFLL_FREQ.0 / reference.0;
+                if mul > u16::MAX as u32 {
+                    return Err(Error::InvalidRatio(Clock::Dfll));
+                }
+                (mul as u16, Hertz(reference.0 * mul))
+            }
+        };
+        self.freqs.dfll_mul = mul;
+        self.freqs.dfll = Some(freq);
+        Ok(freq)
+    }
+
+    fn dpll(&mut self) -> Result<Hertz, Error> {
+        let config = self.tree.dpll.ok_or(Error::NotConfigured(Clock::Dpll))?;
+
+        let reference = match config.reference {
+            DpllRef::Xosc32k => self.resolve(Node::Xosc32k)?,
+            DpllRef::Xosc { prediv } => {
+                if !(2..=4096).contains(&prediv) || prediv % 2 != 0 {
+                    return Err(Error::InvalidDivider(Clock::Dpll));
+                }
+                let freq = self.resolve(Node::Xosc)?;
+                Hertz(freq.0 / prediv as u32)
+            }
+            DpllRef::Gclk(id) => self.resolve(Node::Gclk(u8::from(id) as usize))?,
+        };
+        check_range(
+            Clock::Peripheral(ClockId::FDPLL),
+            reference,
+            DPLL_REF_MIN,
+            DPLL_REF_MAX,
+        )?;
+
+        // The ratio is LDR + 1 + LDRFRAC / 16
+        let ratio =
+            ((config.target.0 as u64 * 16 + reference.0 as u64 / 2) / reference.0 as u64) as u32;
+        let ldr = (ratio / 16).checked_sub(1);
+        let ldr = match ldr {
+            Some(ldr) if ldr <= 0xFFF => ldr,
+            _ => return Err(Error::InvalidRatio(Clock::Dpll)),
+        };
+        let freq = Hertz(((reference.0 as u64 * ratio as u64) / 16) as u32);
+        let freq = check_range(Clock::Dpll, freq, DPLL_OUT_MIN, DPLL_OUT_MAX)?;
+
+        self.freqs.dpll_ratio = (ldr as u16, (ratio % 16) as u8);
+        self.freqs.dpll = Some(freq);
+        Ok(freq)
+    }
+
+    fn gclk(&mut self, i: usize) -> Result<Hertz, Error> {
+        let clock = Node::Gclk(i).clock();
+        let config = self.tree.gclk[i].ok_or(Error::NotConfigured(clock))?;
+
+        let max_div = match i {
+            1 => u16::MAX as u32,
+            2 => 31,
+            _ => u8::MAX as u32,
+        };
+        if config.div == 0 || config.div > max_div {
+            return Err(Error::InvalidDivider(clock));
+        }
+
+        let source = match config.source? {
+            Source::Xosc => self.resolve(Node::Xosc)?,
+            Source::GclkIn(freq) => freq,
+            Source::Gclk1 => self.resolve(Node::Gclk(1))?,
+            Source::OscUlp32k => OSC32K_FREQ,
+            Source::Osc32k => self.resolve(Node::Osc32k)?,
+            Source::Xosc32k => self.resolve(Node::Xosc32k)?,
+            Source::Osc8m => self.resolve(Node::Osc8m)?,
+            Source::Dfll => self.resolve(Node::Dfll)?,
+            Source::Dpll => self.resolve(Node::Dpll)?,
+        };
+        let freq = Hertz(source.0 / config.div);
+        let freq = check_range(clock, freq, Hertz(0), GCLK_MAX_FREQ)?;
+        self.freqs.gclk[i] = Some(freq);
+        Ok(freq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::MegaHertz;
+
+    #[test]
+    fn reset_state() {
+        let freqs = ClockTree::new().compute().unwrap();
+        assert_eq!(freqs.cpu(), Hertz(1_000_000));
+        assert_eq!(freqs.osc8m(), Some(Hertz(1_000_000)));
+        assert_eq!(freqs.gclk(ClockGenId::GCLK1), None);
+        assert_eq!(freqs.wait_states(), 0);
+    }
+
+    #[test]
+    fn dfll_from_xosc32k() {
+        // Same tree as `GenericClockController::with_external_32kosc`, which
+        // uses a multiplier of 1465 and slightly overclocks the CPU
+        let mut tree = ClockTree::new();
+        let xosc32k = tree.xosc32k();
+        let gclk1 = tree.gclk(ClockGenId::GCLK1, xosc32k, 1);
+        let dfll = tree.dfll_closed_loop(gclk1);
+        tree.gclk(ClockGenId::GCLK0, dfll, 1);
+
+        let freqs = tree.compute().unwrap();
+        assert_eq!(freqs.dfll_mul, 1464);
+        assert_eq!(freqs.cpu(), Hertz(32_768 * 1464));
+        assert_eq!(freqs.wait_states(), 1);
+
+        // Dependencies come first
+        let pos = |n| freqs.order.iter().position(|o| *o == Some(n)).unwrap();
+        assert!(pos(Node::Xosc32k) < pos(Node::Gclk(1)));
+        assert!(pos(Node::Gclk(1)) < pos(Node::Dfll));
+        assert!(pos(Node::Dfll) < pos(Node::Gclk(0)));
+    }
+
+    #[test]
+    fn dpll_from_xosc() {
+        let mut tree = ClockTree::new();
+        let xosc = tree.xosc(XoscConfig::crystal(MegaHertz(16)));
+        let dpll = tree.dpll(DpllReference::xosc(xosc, 8), MegaHertz(96));
+        tree.gclk(ClockGenId::GCLK0, dpll, 2);
+        tree.gclk(ClockGenId::GCLK3, dpll, 1);
+
+        let freqs = tree.compute().unwrap();
+        assert_eq!(freqs.dpll(), Some(Hertz(96_000_000)));
+        assert_eq!(freqs.dpll_ratio, (47, 0));
+        assert_eq!(freqs.cpu(), Hertz(48_000_000));
+        assert_eq!(
+            freqs.peripheral(ClockId::SERCOM0_CORE, ClockGenId::GCLK0),
+            Ok(Hertz(48_000_000))
+        );
+        assert!(freqs
+            .peripheral(ClockId::SERCOM0_CORE, ClockGenId::GCLK3)
+            .is_err());
+        assert_eq!(XoscConfig::crystal(MegaHertz(16)).gain(), 3);
+    }
+
+    #[test]
+    fn fractional_dpll() {
+        let mut tree = ClockTree::new();
+        let xosc32k = tree.xosc32k();
+        let dpll = tree.dpll(DpllReference::xosc32k(xosc32k), MegaHertz(48));
+        tree.gclk(ClockGenId::GCLK2, dpll, 1);
+
+        let freqs = tree.compute().unwrap();
+        // 48 MHz / 32.768 kHz = 1464.84375 -> 1464 + 13.5/16, rounded to 14/16
+        assert_eq!(freqs.dpll_ratio, (1463, 14));
+        assert_eq!(freqs.dpll(), Some(Hertz(48_001_024)));
+    }
+
+    #[test]
+    fn invalid_configurations() {
+        // Tokens are not tied to the tree they came from
+        let xosc = ClockTree::new().xosc(XoscConfig::crystal(MegaHertz(16)));
+        let mut tree = ClockTree::new();
+        tree.gclk(ClockGenId::GCLK2, xosc, 1);
+        assert_eq!(
+            tree.compute().unwrap_err(),
+            Error::NotConfigured(Clock::Xosc)
+        );
+
+        // The CPU can't run from the FDPLL96M undivided
+        let mut tree = ClockTree::new();
+        let xosc32k = tree.xosc32k();
+        let dpll = tree.dpll(DpllReference::xosc32k(xosc32k), MegaHertz(96));
+        tree.gclk(ClockGenId::GCLK0, dpll, 1);
+        assert!(matches!(
+            tree.compute(),
+            Err(Error::FrequencyOutOfRange {
+                clock: Clock::Cpu,
+                ..
+            })
+        ));
+        tree.cpu_div(2);
+        assert_eq!(tree.compute().unwrap().cpu(), Hertz(48_000_000));
+
+        // GCLK2 has a 5-bit divider
+        let mut tree = ClockTree::new();
+        let osc8m = tree.osc8m(1);
+        tree.gclk(ClockGenId::GCLK2, osc8m, 32);
+        assert_eq!(
+            tree.compute().unwrap_err(),
+            Error::InvalidDivider(Clock::Gclk(ClockGenId::GCLK2))
+        );
+
+        let mut tree = ClockTree::new();
+        tree.osc8m(3);
+        assert_eq!(
+            tree.compute().unwrap_err(),
+            Error::InvalidDivider(Clock::Osc8m)
+        );
+
+        // Only GCLK1 can feed other generators
+        let mut tree = ClockTree::new();
+        let gclk3 = tree.gclk(ClockGenId::GCLK3, osc8m, 1);
+        tree.gclk(ClockGenId::GCLK4, gclk3, 1);
+        assert_eq!(
+            tree.compute().unwrap_err(),
+            Error::InvalidSource(Clock::Gclk(ClockGenId::GCLK3))
+        );
+
+        // DFLL referenced to a generator it feeds
+        let mut tree = ClockTree::new();
+        let osculp32k = tree.osculp32k();
+        let gclk1 = tree.gclk(ClockGenId::GCLK1, osculp32k, 1);
+        let dfll = tree.dfll_closed_loop(gclk1);
+        tree.gclk(ClockGenId::GCLK1, dfll, 1500);
+        assert!(matches!(tree.compute(), Err(Error::Loop(_))));
+
+        // The XOSC crystal range is 0.4 to 32 MHz
+        let mut tree = ClockTree::new();
+        let xosc = tree.xosc(XoscConfig::crystal(MegaHertz(40)));
+        tree.gclk(ClockGenId::GCLK3, xosc, 1);
+        assert!(matches!(
+            tree.compute(),
+            Err(Error::FrequencyOutOfRange {
+                clock: Clock::Xosc,
+                ..
+            })
+        ));
+    }
+}
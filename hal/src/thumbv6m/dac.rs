@@ -0,0 +1,196 @@
+//! Digital-to-Analogue Conversion
+//!
+//! The DAC has a single 10-bit output, available on pin `PA02`. Values are
+//! written either directly to the `DATA` register with [`Dac::write`], or to
+//! the `DATABUF` register with [`Dac::write_buffered`], in which case the
+//! conversion is deferred until a start event is received from the Event
+//! System.
+//!
+//! # Streaming with DMA
+#![cfg_attr(
+    feature = "dma",
+    doc = "
+[`Dac`] implements the DMAC [`Buffer`](crate::dmac::Buffer) trait, targeting
+the `DATABUF` register. To output samples at a fixed rate, connect the overflow
+event of a timer to the `DacStart` event user, enable the start event with
+[`Dac::enable_start_event`], and use `TriggerSource::DAC_EMPTY` as the trigger
+source of the [`Transfer`](crate::dmac::Transfer)."
+)]
+//!
+//! # Example
+//!
+//! ```
+//! let mut dac = Dac::new(
+//!     peripherals.DAC,
+//!     pins.pa02,
+//!     &mut peripherals.PM,
+//!     &dac_clock,
+//!     Reference::AVCC,
+//! );
+//! dac.write(512);
+//! ```
+
+use crate::clock::DacClock;
+use crate::gpio::{AlternateB, AnyPin, Pin, PA02};
+use crate::pac::{DAC, PM};
+
+/// Reference voltage (or its source)
+pub use crate::pac::dac::ctrlb::REFSEL_A as Reference;
+
+/// Maximum value accepted by the DAC
+pub const MAX_VALUE: u16 = 0x3FF;
+
+/// `Dac` encapsulates the device DAC and its output pin
+pub struct Dac {
+    dac: DAC,
+    pin: Pin<PA02, AlternateB>,
+}
+
+impl Dac {
+    /// Create and enable a new `Dac`, outputting to `PA02`.
+    ///
+    /// The DAC is reset, then configured with the given reference voltage and
+    /// right-adjusted data.
+    pub fn new(
+        dac: DAC,
+        pin: impl AnyPin<Id = PA02>,
+        pm: &mut PM,
+        _clock: &DacClock,
+        reference: Reference,
+    ) -> Self {
+        pm.apbcmask.modify(|_, w| w.dac_().set_bit());
+
+        dac.ctrla.write(|w| w.swrst().set_bit());
+        while dac.ctrla.read().swrst().bit_is_set() || Self::syncbusy(&dac) {}
+
+        dac.ctrlb.write(|w| {
+            w.eoen().set_bit();
+            w.refsel().variant(reference)
+        });
+
+        let pin = pin.into().into_alternate();
+        let mut dac = Self { dac, pin };
+        dac.enable();
+        dac
+    }
+
+    #[inline]
+    fn syncbusy(dac: &DAC) -> bool {
+        dac.status.read().syncbusy().bit_is_set()
+    }
+
+    #[inline]
+    fn sync(&self) {
+        while Self::syncbusy(&self.dac) {}
+    }
+
+    /// Enable the DAC
+    pub fn enable(&mut self) {
+        self.dac.ctrla.modify(|_, w| w.enable().set_bit());
+        self.sync();
+    }
+
+    /// Disable the DAC. Its configuration can only be changed while it is
+    /// disabled.
+    pub fn disable(&mut self) {
+        self.dac.ctrla.modify(|_, w| w.enable().clear_bit());
+        self.sync();
+    }
+
+    /// Set the voltage reference
+    ///
+    /// The DAC must be disabled.
+    pub fn reference(&mut self, reference: Reference) {
+        self.dac.ctrlb.modify(|_, w| w.refsel().variant(reference));
+    }
+
+    /// Select whether the 10-bit value is stored in the upper bits of the
+    /// data registers (left-adjusted) or in the lower bits (right-adjusted).
+    ///
+    /// The DAC must be disabled.
+    pub fn left_adjusted(&mut self, enabled: bool) {
+        self.dac.ctrlb.modify(|_, w| w.leftadj().bit(enabled));
+    }
+
+    /// Route the DAC output to the Analog Comparator and ADC, in addition to
+    /// the pin.
+    ///
+    /// The DAC must be disabled.
+    pub fn internal_output(&mut self, enabled: bool) {
+        self.dac.ctrlb.modify(|_, w| w.ioen().bit(enabled));
+    }
+
+    /// Disable the internal voltage pump. This reduces power consumption,
+    /// but should only be done when VDDANA is above 2.5V.
+    ///
+    /// The DAC must be disabled.
+    pub fn voltage_pump_disabled(&mut self, disabled: bool) {
+        self.dac.ctrlb.modify(|_, w| w.vpd().bit(disabled));
+    }
+
+    /// Keep the DAC running in standby sleep mode
+    pub fn run_in_standby(&mut self, enabled: bool) {
+        self.dac.ctrla.modify(|_, w| w.runstdby().bit(enabled));
+        self.sync();
+    }
+
+    /// Immediately convert a new value
+    pub fn write(&mut self, value: u16) {
+        self.dac.data.write(|w| unsafe { w.bits(value) });
+        self.sync();
+    }
+
+    /// Write a value to the data buffer. It will be converted when the next
+    /// start event is received.
+    pub fn write_buffered(&mut self, value: u16) {
+        self.dac.databuf.write(|w| unsafe { w.bits(value) });
+        self.sync();
+    }
+
+    /// Convert the content of the data buffer whenever an event is received
+    /// from the `evsys` module.
+    pub fn enable_start_event(&mut self) {
+        self.dac.evctrl.modify(|_, w| w.startei().set_bit());
+    }
+
+    /// Stop converting on incoming events
+    pub fn disable_start_event(&mut self) {
+        self.dac.evctrl.modify(|_, w| w.startei().clear_bit());
+    }
+
+    /// Enable the data buffer empty event output
+    pub fn enable_empty_event(&mut self) {
+        self.dac.evctrl.modify(|_, w| w.emptyeo().set_bit());
+    }
+
+    /// Disable the data buffer empty event output
+    pub fn disable_empty_event(&mut self) {
+        self.dac.evctrl.modify(|_, w| w.emptyeo().clear_bit());
+    }
+
+    /// Disable the DAC and return the underlying peripheral and pin
+    pub fn free(mut self) -> (DAC, Pin<PA02, AlternateB>) {
+        self.disable();
+        (self.dac, self.pin)
+    }
+}
+
+#[cfg(feature = "dma")]
+unsafe impl crate::dmac::Buffer for Dac {
+    type Beat = u16;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.dac.databuf.as_ptr()
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
@@ -8,6 +8,10 @@ pub use serial_number::*;
 
 pub mod calibration;
 pub mod clock;
+pub mod dac;
+pub mod nvm;
+pub mod power;
+pub mod supc;
 pub mod timer;
 
 #[cfg(feature = "unproven")]
@@ -0,0 +1,547 @@
+//! # Non-volatile Memory Controller
+//!
+//! This module allows users to interact with non-volatile memory controller.
+//!
+//! NVMCTRL is an intermediary between memory buses and physical non-volatile
+//! memory. It provides means of managing a flash memory content, its properties
+//! (wait states, region locks) and power management.
+//!
+//! The flash is organized in rows of four pages. Writes go through a page
+//! buffer and are committed one page at a time, while erasing is done one row
+//! at a time. The flash is also divided in 16 lock regions, which can be
+//! locked and unlocked at runtime. Their state at reset is defined in the user
+//! row.
+//!
+//! Some SAMD21 parts (the "L" and "D" variants) embed an additional flash
+//! array, the Read-While-Write EEPROM (RWWEE). It can be programmed while the
+//! CPU keeps executing from the main flash.
+//!
+//! Module features:
+//! - Erase & write over non-volatile memory in a device.
+//! - Erase & write over the RWWEE section, if present.
+//! - Lock & unlock regions
+//! - Read & write the user row (fuses)
+//! - `embedded-storage` traits over flash partitions (with the `storage`
+//!   feature, see [`storage`])
+#![warn(missing_docs)]
+
+#[cfg(feature = "storage")]
+pub mod storage;
+
+use crate::pac::nvmctrl::ctrla::CMD_A;
+use crate::pac::NVMCTRL;
+use core::num::NonZeroU32;
+use core::ops::Range;
+
+use bitfield::bitfield;
+
+/// Retrieve a total NVM size using HW registers
+#[inline(always)]
+pub fn retrieve_flash_size() -> u32 {
+    static mut FLASHSIZE: Option<NonZeroU32> = None;
+    // Safety: Lazy initialization of a static variable - interactions with
+    // `Option<NonZeroU32>` should be atomic
+    unsafe {
+        match FLASHSIZE {
+            Some(x) => x.into(),
+            None => {
+                let nvm = &*NVMCTRL::ptr();
+                let nvm_params = nvm.param.read();
+                if !nvm_params.psz().is_64() {
+                    unreachable!("NVM page size is always expected to be 64 bytes");
+                }
+                let nvm_pages = nvm_params.nvmp().bits() as u32;
+                let flash_size = nvm_pages * PAGESIZE;
+                // Safety: `flash_size` will never be 0
+                FLASHSIZE = Some(NonZeroU32::new_unchecked(flash_size));
+                flash_size
+            }
+        }
+    }
+}
+
+/// Retrieve the size of the RWWEE section using HW registers
+///
+/// Returns 0 on parts without a RWWEE section.
+#[cfg(feature = "samd21")]
+#[inline]
+pub fn retrieve_rww_eeprom_size() -> u32 {
+    // Safety: PARAM is a read-only register
+    let nvm = unsafe { &*NVMCTRL::ptr() };
+    // RWWEEP (bits 20:31) is not described by the PAC
+    let rwwee_pages = nvm.param.read().bits() >> 20;
+    rwwee_pages * PAGESIZE
+}
+
+/// Size of a page in bytes
+pub const PAGESIZE: u32 = 64;
+
+/// Size of one row
+pub const ROWSIZE: u32 = PAGESIZE * 4;
+
+/// Number of lock regions
+pub const LOCK_REGIONS: u32 = 16;
+
+/// Start address of the RWWEE section
+#[cfg(feature = "samd21")]
+pub const RWW_EEPROM_ADDRESS: u32 = 0x0040_0000;
+
+/// Address of the user row
+pub const USER_ROW_ADDRESS: u32 = 0x0080_4000;
+
+/// Erase Row command for the RWWEE section
+#[cfg(feature = "samd21")]
+const RWWEEER: u8 = 0x1a;
+
+/// Write Page command for the RWWEE section
+#[cfg(feature = "samd21")]
+const RWWEEWP: u8 = 0x1c;
+
+/// Non-volatile memory controller
+pub struct Nvm {
+    /// PAC peripheral
+    nvm: NVMCTRL,
+}
+
+/// Errors generated by the NVM peripheral
+#[derive(Debug)]
+pub enum PeripheralError {
+    /// NVM error
+    NvmError,
+    /// Locked error
+    LockError,
+    /// Programming error
+    ProgrammingError,
+}
+
+/// Driver errors
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum Error {
+    /// Address range outside of flash
+    NonFlash,
+    /// Target sector is protected
+    Protected,
+    /// Memory region is used by the EEPROM emulation area
+    EepromArea,
+    /// Address range outside of the RWWEE section, or no RWWEE section
+    /// present
+    NonRwwEeprom,
+    /// Errors generated by hardware
+    Peripheral(PeripheralError),
+    /// An alignment requirement was not fulfilled
+    Alignment,
+    /// Offset outside of the storage area
+    OutOfBounds,
+}
+
+/// NVM result type
+pub type Result<T> = core::result::Result<T, Error>;
+
+impl Nvm {
+    /// Create a new NVM controller
+    ///
+    /// Writes to the page buffer are only committed to flash through explicit
+    /// write commands, so manual write mode is enabled.
+    #[inline]
+    pub fn new(nvm: NVMCTRL) -> Self {
+        nvm.ctrlb.modify(|_, w| w.manw().set_bit());
+        Self { nvm }
+    }
+
+    /// Release the NVMCTRL peripheral
+    #[inline]
+    pub fn free(self) -> NVMCTRL {
+        self.nvm
+    }
+
+    /// Check if the security bit is set
+    #[inline]
+    pub fn is_secured(&self) -> bool {
+        self.nvm.status.read().sb().bit()
+    }
+
+    /// Set address for reading/writing
+    ///
+    /// The ADDR register holds a 16-bit word address.
+    fn set_address(&mut self, address: u32) {
+        unsafe {
+            self.nvm
+                .addr
+                .write(|w| w.addr().bits((address >> 1) & 0x003f_ffff));
+        }
+    }
+
+    /// Determine if the controller is busy writing or erasing
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.nvm.intflag.read().ready().bit()
+    }
+
+    /// Execute a command, wait until it is done
+    fn command_sync(&mut self, command: u8) {
+        while !self.is_ready() {}
+        self.nvm
+            .ctrla
+            .write(|w| unsafe { w.cmdex().key().cmd().bits(command) });
+        while !self.is_ready() {}
+    }
+
+    /// Read the peripheral state to check error flags and clear the up
+    /// afterwards
+    fn manage_error_states(&mut self) -> Result<()> {
+        let status = self.nvm.status.read();
+        // Check LOCKE first as it is more specific than PROGE
+        let state = if status.locke().bit_is_set() {
+            Err(Error::Peripheral(PeripheralError::LockError))
+        } else if status.proge().bit_is_set() {
+            Err(Error::Peripheral(PeripheralError::ProgrammingError))
+        } else if status.nvme().bit_is_set() {
+            Err(Error::Peripheral(PeripheralError::NvmError))
+        } else {
+            Ok(())
+        };
+
+        // Clear error flags
+        self.nvm.status.write(|w| {
+            w.locke().set_bit();
+            w.proge().set_bit();
+            w.nvme().set_bit()
+        });
+        self.nvm.intflag.write(|w| w.error().set_bit());
+        state
+    }
+
+    /// Read the user row
+    #[inline]
+    pub fn user_row(&self) -> UserRow {
+        let base_addr = USER_ROW_ADDRESS as *const u32;
+        let low = unsafe { core::ptr::read_volatile(base_addr) } as u64;
+        let high = unsafe { core::ptr::read_volatile(base_addr.offset(1)) } as u64;
+        UserRow(low | (high << 32))
+    }
+
+    /// Write the user row
+    ///
+    /// The whole row is read back, erased and written again, so the user data
+    /// stored after the fuses is preserved. The new configuration only takes
+    /// effect after a reset.
+    ///
+    /// # Safety
+    ///
+    /// The user row configures the BOD33, the watchdog, the bootloader
+    /// protection and the region locks at reset. Writing an inconsistent
+    /// configuration can prevent the device from booting. The factory
+    /// calibration bits of the reserved fields must be preserved.
+    pub unsafe fn write_user_row(&mut self, user_row: UserRow) -> Result<()> {
+        if self.is_secured() {
+            return Err(Error::Protected);
+        }
+
+        let base_addr = USER_ROW_ADDRESS as *mut u32;
+        let mut buffer = [0_u32; (ROWSIZE / 4) as usize];
+        for (i, word) in buffer.iter_mut().enumerate() {
+            *word = core::ptr::read_volatile(base_addr.add(i));
+        }
+        buffer[0] = user_row.0 as u32;
+        buffer[1] = (user_row.0 >> 32) as u32;
+
+        self.set_address(USER_ROW_ADDRESS);
+        self.command_sync(CMD_A::EAR.into());
+        self.manage_error_states()?;
+
+        self.write_pages(USER_ROW_ADDRESS, &buffer, CMD_A::WAP.into())
+    }
+
+    /// Check if the region containing `address` is locked
+    #[inline]
+    pub fn is_locked(&self, address: u32) -> Result<bool> {
+        let region = Self::lock_region(address)?;
+        // A cleared bit means the region is locked
+        Ok(self.nvm.lock.read().lock().bits() & (1 << region) == 0)
+    }
+
+    /// Lock the region containing `address`
+    ///
+    /// The region stays locked until it is unlocked or the device is reset.
+    #[inline]
+    pub fn lock(&mut self, address: u32) -> Result<()> {
+        Self::lock_region(address)?;
+        self.set_address(address);
+        self.command_sync(CMD_A::LR.into());
+        self.manage_error_states()
+    }
+
+    /// Unlock the region containing `address`
+    ///
+    /// The region stays unlocked until it is locked or the device is reset.
+    #[inline]
+    pub fn unlock(&mut self, address: u32) -> Result<()> {
+        Self::lock_region(address)?;
+        self.set_address(address);
+        self.command_sync(CMD_A::UR.into());
+        self.manage_error_states()
+    }
+
+    /// Index of the lock region containing `address`
+    fn lock_region(address: u32) -> Result<u32> {
+        if address >= retrieve_flash_size() {
+            Err(Error::NonFlash)
+        } else {
+            Ok(address / (retrieve_flash_size() / LOCK_REGIONS))
+        }
+    }
+
+    /// Write to flash memory from a slice
+    ///
+    /// # Safety
+    ///
+    /// If `destination_address` is not word-aligned, an error is returned.
+    ///
+    /// Writes to flash goes through the NVM controller
+    /// NVM controller sets the `PROGE`/`LOCKE` flag if an error occurs,
+    /// this is checked in `manage_error_states` to propagate
+    /// the relevant error code
+    ///
+    /// The target area must have been erased beforehand.
+    #[inline]
+    pub unsafe fn write(&mut self, destination_address: u32, source_slice: &[u32]) -> Result<()> {
+        let write_addresses = Self::write_range(destination_address, source_slice)?;
+
+        if self.contains_non_flash_memory_area(&write_addresses) {
+            Err(Error::NonFlash)
+        } else if self.contains_bootprotected(&write_addresses) {
+            Err(Error::Protected)
+        } else if self.contains_eeprom(&write_addresses) {
+            Err(Error::EepromArea)
+        } else {
+            self.write_pages(destination_address, source_slice, CMD_A::WP.into())
+        }
+    }
+
+    /// Erase flash memory, one row at a time.
+    ///
+    /// # Safety
+    ///
+    /// Erasing flash goes through the NVM controller
+    /// NVM controller sets the `PROGE`/`LOCKE` flag if an error occurs,
+    /// this is checked in `manage_error_states` to propagate
+    /// the relevant error code.
+    #[inline]
+    pub unsafe fn erase(&mut self, address: u32, rows: u32) -> Result<()> {
+        let range_to_erase = Self::erase_range(address, rows);
+
+        if self.contains_non_flash_memory_area(&range_to_erase) {
+            Err(Error::NonFlash)
+        } else if self.contains_bootprotected(&range_to_erase) {
+            Err(Error::Protected)
+        } else if self.contains_eeprom(&range_to_erase) {
+            Err(Error::EepromArea)
+        } else {
+            self.erase_rows(range_to_erase, CMD_A::ER.into())
+        }
+    }
+
+    /// Write to the RWWEE section from a slice
+    ///
+    /// `destination_address` is an absolute address, starting at
+    /// [`RWW_EEPROM_ADDRESS`]. The CPU can keep executing from the main flash
+    /// while the page is programmed.
+    ///
+    /// # Safety
+    ///
+    /// See [`write`](Nvm::write).
+    #[cfg(feature = "samd21")]
+    #[inline]
+    pub unsafe fn write_rww_eeprom(
+        &mut self,
+        destination_address: u32,
+        source_slice: &[u32],
+    ) -> Result<()> {
+        let write_addresses = Self::write_range(destination_address, source_slice)?;
+
+        if self.contains_non_rww_eeprom_area(&write_addresses) {
+            Err(Error::NonRwwEeprom)
+        } else {
+            self.write_pages(destination_address, source_slice, RWWEEWP)
+        }
+    }
+
+    /// Erase rows of the RWWEE section
+    ///
+    /// `address` is an absolute address, starting at [`RWW_EEPROM_ADDRESS`].
+    ///
+    /// # Safety
+    ///
+    /// See [`erase`](Nvm::erase).
+    #[cfg(feature = "samd21")]
+    #[inline]
+    pub unsafe fn erase_rww_eeprom(&mut self, address: u32, rows: u32) -> Result<()> {
+        let range_to_erase = Self::erase_range(address, rows);
+
+        if self.contains_non_rww_eeprom_area(&range_to_erase) {
+            Err(Error::NonRwwEeprom)
+        } else {
+            self.erase_rows(range_to_erase, RWWEEER)
+        }
+    }
+
+    /// Range of byte addresses written by `source_slice`, checking alignment
+    fn write_range(destination_address: u32, source_slice: &[u32]) -> Result<Range<u32>> {
+        let step_size: u32 = core::mem::size_of::<u32>() as u32;
+        if destination_address % step_size != 0 {
+            return Err(Error::Alignment);
+        }
+        let length = source_slice.len() as u32 * step_size;
+        Ok(destination_address..(destination_address + length))
+    }
+
+    /// Range of byte addresses covered by `rows` rows, starting at the row
+    /// containing `address`
+    fn erase_range(address: u32, rows: u32) -> Range<u32> {
+        // Align to row boundary
+        // While the NVM will accept any address in the row, we need to compute the
+        // aligned address to check for boot protection.
+        let row_address = address - address % ROWSIZE;
+        row_address..(row_address + rows * ROWSIZE)
+    }
+
+    /// Fill the page buffer and commit it with `command` every time a page
+    /// boundary is crossed
+    unsafe fn write_pages(
+        &mut self,
+        destination_address: u32,
+        source_slice: &[u32],
+        command: u8,
+    ) -> Result<()> {
+        let step_size: u32 = core::mem::size_of::<u32>() as u32;
+
+        self.command_sync(CMD_A::PBC.into());
+        // Track whether we have unwritten data in the page buffer
+        let mut dirty = false;
+        let mut page_address = destination_address;
+        for (i, word) in source_slice.iter().enumerate() {
+            let address = destination_address + i as u32 * step_size;
+            // Write to memory, 32 bits, 1 word.
+            // The data is placed in the page buffer.
+            // Memory is not written until the write page command is issued later.
+            core::ptr::write_volatile(address as *mut u32, *word);
+            dirty = true;
+            page_address = address;
+
+            // If we are about to cross a page boundary (and run out of page buffer), write
+            // to flash
+            if address % PAGESIZE >= PAGESIZE - step_size {
+                dirty = false;
+                self.set_address(page_address);
+                self.command_sync(command);
+                self.manage_error_states()?;
+            }
+        }
+
+        if dirty {
+            // Write last page
+            self.set_address(page_address);
+            self.command_sync(command);
+        }
+
+        self.manage_error_states()
+    }
+
+    /// Erase every row of `range` with `command`
+    fn erase_rows(&mut self, range: Range<u32>, command: u8) -> Result<()> {
+        for address in range.step_by(ROWSIZE as usize) {
+            // Set target address to current row offset
+            self.set_address(address);
+
+            // Erase row, wait for completion
+            self.command_sync(command);
+
+            self.manage_error_states()?
+        }
+
+        Ok(())
+    }
+
+    fn contains_bootprotected(&self, input: &Range<u32>) -> bool {
+        let boot = &(0..self.user_row().bootloader_size());
+        range_overlap(input, boot)
+    }
+
+    fn contains_eeprom(&self, input: &Range<u32>) -> bool {
+        let eeprom_end = retrieve_flash_size();
+        let eeprom_start = eeprom_end - self.user_row().eeprom_size();
+        let eeprom = &(eeprom_start..eeprom_end);
+        range_overlap(input, eeprom)
+    }
+
+    fn contains_non_flash_memory_area(&self, input: &Range<u32>) -> bool {
+        input.end > retrieve_flash_size()
+    }
+
+    #[cfg(feature = "samd21")]
+    fn contains_non_rww_eeprom_area(&self, input: &Range<u32>) -> bool {
+        let rww_eeprom_end = RWW_EEPROM_ADDRESS + retrieve_rww_eeprom_size();
+        input.start < RWW_EEPROM_ADDRESS || input.end > rww_eeprom_end
+    }
+}
+
+fn range_overlap(a: &Range<u32>, b: &Range<u32>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+bitfield! {
+    #[derive(Copy, Clone, Default)]
+    /// POD-style struct representing the NVM user row
+    pub struct UserRow(u64);
+    impl Debug;
+    u32;
+    /// Bootloader size, see [`UserRow::bootloader_size`]
+    pub bootprot, set_bootprot: 2, 0;
+    /// EEPROM emulation area size, see [`UserRow::eeprom_size`]
+    pub eeprom, set_eeprom: 6, 4;
+    /// BOD33 threshold level at power on
+    pub bod33_level, set_bod33_level: 13, 8;
+    /// BOD33 enable at power on
+    pub bod33_enable, set_bod33_enable: 14;
+    /// BOD33 action at power on
+    pub bod33_action, set_bod33_action: 16, 15;
+    /// WDT enable at power on
+    pub wdt_enable, set_wdt_enable: 25;
+    /// WDT always-on at power on
+    pub wdt_always_on, set_wdt_always_on: 26;
+    /// WDT period at power on
+    pub wdt_period, set_wdt_period: 30, 27;
+    /// WDT window mode time-out at power on
+    pub wdt_window, set_wdt_window: 34, 31;
+    /// WDT early warning interrupt time offset at power on
+    pub wdt_ewoffset, set_wdt_ewoffset: 38, 35;
+    /// WDT window mode enable at power on
+    pub wdt_wen, set_wdt_wen: 39;
+    /// BOD33 hysteresis configuration at power on
+    pub bod33_hysteresis, set_bod33_hysteresis: 40;
+    /// Region lock bits, a cleared bit locks the region at reset
+    pub region_locks, set_region_locks: 63, 48;
+}
+
+impl UserRow {
+    /// Size in bytes of the bootloader section protected by the `BOOTPROT`
+    /// fuses, at the beginning of the flash
+    #[inline]
+    pub fn bootloader_size(&self) -> u32 {
+        match self.bootprot() {
+            7 => 0,
+            bootprot => 256 << (7 - bootprot),
+        }
+    }
+
+    /// Size in bytes of the EEPROM emulation area defined by the `EEPROM`
+    /// fuses, at the end of the flash
+    #[inline]
+    pub fn eeprom_size(&self) -> u32 {
+        match self.eeprom() {
+            7 => 0,
+            eeprom => 256 << (6 - eeprom),
+        }
+    }
+}
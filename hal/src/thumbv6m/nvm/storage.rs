@@ -0,0 +1,131 @@
+//! # `embedded-storage` support
+//!
+//! [`FlashPartition`] implements the [`ReadNorFlash`] and [`NorFlash`] traits
+//! over a range of rows of the main flash, so that crates built on top of
+//! `embedded-storage` can use the internal flash. It is retrieved with
+//! [`Nvm::partition`].
+//!
+//! This module requires the `storage` feature.
+
+use core::ops::Range;
+
+use embedded_storage::nor_flash::{
+    check_erase, check_read, check_write, ErrorType, NorFlash, NorFlashError, NorFlashErrorKind,
+    ReadNorFlash,
+};
+
+use super::{Error, Nvm, Result, PAGESIZE, ROWSIZE};
+
+/// A range of rows of the main flash, usable through `embedded-storage`
+///
+/// Offsets passed to the trait methods are relative to the start of the
+/// partition.
+pub struct FlashPartition<'a> {
+    nvm: &'a mut Nvm,
+    range: Range<u32>,
+}
+
+impl Nvm {
+    /// Retrieve a [`FlashPartition`] covering the `range` addresses
+    ///
+    /// `range` must be aligned to [`ROWSIZE`], lie within the flash, and must
+    /// not overlap the boot protected area or the EEPROM emulation area.
+    ///
+    /// # Safety
+    ///
+    /// The partition can be erased and written at will, so it must not contain
+    /// the running program or any data still in use.
+    #[inline]
+    pub unsafe fn partition(&mut self, range: Range<u32>) -> Result<FlashPartition<'_>> {
+        if range.start % ROWSIZE != 0 || range.end % ROWSIZE != 0 {
+            Err(Error::Alignment)
+        } else if range.start >= range.end || self.contains_non_flash_memory_area(&range) {
+            Err(Error::NonFlash)
+        } else if self.contains_bootprotected(&range) {
+            Err(Error::Protected)
+        } else if self.contains_eeprom(&range) {
+            Err(Error::EepromArea)
+        } else {
+            Ok(FlashPartition { nvm: self, range })
+        }
+    }
+}
+
+impl FlashPartition<'_> {
+    /// Address range covered by the partition
+    #[inline]
+    pub fn range(&self) -> Range<u32> {
+        self.range.clone()
+    }
+}
+
+impl NorFlashError for Error {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            Error::Alignment => NorFlashErrorKind::NotAligned,
+            Error::NonFlash | Error::OutOfBounds => NorFlashErrorKind::OutOfBounds,
+            _ => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+impl From<NorFlashErrorKind> for Error {
+    fn from(kind: NorFlashErrorKind) -> Self {
+        match kind {
+            NorFlashErrorKind::NotAligned => Error::Alignment,
+            _ => Error::OutOfBounds,
+        }
+    }
+}
+
+impl ErrorType for FlashPartition<'_> {
+    type Error = Error;
+}
+
+impl ReadNorFlash for FlashPartition<'_> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<()> {
+        check_read(self, offset, bytes.len())?;
+        let address = (self.range.start + offset) as *const u8;
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            // Safety: the address was checked to be inside the partition
+            *byte = unsafe { core::ptr::read_volatile(address.add(i)) };
+        }
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.range.len()
+    }
+}
+
+impl NorFlash for FlashPartition<'_> {
+    const WRITE_SIZE: usize = 4;
+
+    const ERASE_SIZE: usize = ROWSIZE as usize;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<()> {
+        check_erase(self, from, to)?;
+        let rows = (to - from) / ROWSIZE;
+        // Safety: the rows were checked to be inside the partition
+        unsafe { self.nvm.erase(self.range.start + from, rows) }
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<()> {
+        check_write(self, offset, bytes.len())?;
+        let mut buffer = [0_u32; (PAGESIZE / 4) as usize];
+        let mut address = self.range.start + offset;
+        // Data is copied to a word-aligned buffer, one page at a time
+        for chunk in bytes.chunks(PAGESIZE as usize) {
+            let words = chunk.len() / 4;
+            for (word, data) in buffer.iter_mut().zip(chunk.chunks_exact(4)) {
+                *word = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            }
+            // Safety: the range was checked to be inside the partition
+            unsafe { self.nvm.write(address, &buffer[..words])? };
+            address += chunk.len() as u32;
+        }
+        Ok(())
+    }
+}
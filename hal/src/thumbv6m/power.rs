@@ -0,0 +1,146 @@
+//! # Power management
+//!
+//! [`Power`] selects the sleep mode entered by [`Power::sleep`]:
+//!
+//! - In the [`SleepMode::Idle0`] to [`SleepMode::Idle2`] modes, the CPU and,
+//!   progressively, the AHB and APB clock domains are stopped, and any
+//!   interrupt wakes the device.
+//! - In [`SleepMode::Standby`], every clock is stopped, except the generic
+//!   clocks and oscillators configured to run in standby. Only peripherals that
+//!   run in standby, or that can operate without a clock like the EIC, can
+//!   wake the device.
+//!
+//! The RAM content is always retained.
+//!
+//! Clock generators are kept running in standby with
+//! [`GenericClockController::configure_standby`], and most peripherals have
+//! a `run_in_standby` setting of their own, e.g. [`uart::Config`].
+//!
+//! After waking from standby, [`Power::sleep`] waits for the oscillators that
+//! were stopped to be ready again, so that the clocks run at their configured
+//! frequencies when it returns.
+//!
+//! [`GenericClockController::configure_standby`]: crate::clock::GenericClockController::configure_standby
+//! [`uart::Config`]: crate::sercom::uart::Config
+
+use cortex_m::asm;
+use cortex_m::peripheral::{NVIC, SCB};
+
+use crate::pac::{PM, SYSCTRL};
+
+/// Number of implemented interrupt set-pending registers
+const NUM_ISPR: usize = 1;
+
+/// Sleep modes of the device
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SleepMode {
+    /// The CPU clock domain is stopped
+    Idle0,
+    /// The CPU and AHB clock domains are stopped
+    Idle1,
+    /// The CPU, AHB and APB clock domains are stopped
+    Idle2,
+    /// Every clock is stopped, except those configured to run in standby
+    Standby,
+}
+
+/// Sleep mode configuration
+pub struct Power {
+    scb: SCB,
+    mode: SleepMode,
+}
+
+impl Power {
+    /// Create a new [`Power`], entering [`SleepMode::Idle0`] on sleep
+    pub fn new(mut scb: SCB, pm: &mut PM) -> Self {
+        scb.clear_sleepdeep();
+        pm.sleep.write(|w| w.idle().cpu());
+        Self {
+            scb,
+            mode: SleepMode::Idle0,
+        }
+    }
+
+    /// Release the `SCB` peripheral
+    pub fn free(self) -> SCB {
+        self.scb
+    }
+
+    /// Sleep mode entered by [`Power::sleep`]
+    #[inline]
+    pub fn sleep_mode(&self) -> SleepMode {
+        self.mode
+    }
+
+    /// Select the sleep mode entered by [`Power::sleep`]
+    pub fn set_sleep_mode(&mut self, pm: &mut PM, mode: SleepMode) {
+        match mode {
+            SleepMode::Idle0 => pm.sleep.write(|w| w.idle().cpu()),
+            SleepMode::Idle1 => pm.sleep.write(|w| w.idle().ahb()),
+            SleepMode::Idle2 => pm.sleep.write(|w| w.idle().apb()),
+            SleepMode::Standby => (),
+        }
+        if mode == SleepMode::Standby {
+            self.scb.set_sleepdeep();
+        } else {
+            self.scb.clear_sleepdeep();
+        }
+        self.mode = mode;
+    }
+
+    /// Enter sleep when returning from an interrupt handler to thread mode
+    #[inline]
+    pub fn set_sleep_on_exit(&mut self, enable: bool) {
+        if enable {
+            self.scb.set_sleeponexit();
+        } else {
+            self.scb.clear_sleeponexit();
+        }
+    }
+
+    /// Enter the selected sleep mode, until an interrupt wakes the device
+    ///
+    /// The device also wakes up on interrupts masked with `PRIMASK`, e.g.
+    /// inside a [`cortex_m::interrupt::free`] critical section. The interrupt
+    /// is then still pending when this returns, see [`pending_interrupt`].
+    pub fn sleep(&mut self) {
+        asm::dsb();
+        asm::wfi();
+        if self.mode == SleepMode::Standby {
+            wait_for_clocks();
+        }
+    }
+}
+
+/// Wait for the enabled oscillators to be ready after waking from standby
+fn wait_for_clocks() {
+    // Safety: only status registers are read
+    let sysctrl = unsafe { &*SYSCTRL::ptr() };
+    if sysctrl.xosc.read().enable().bit_is_set() {
+        while sysctrl.pclksr.read().xoscrdy().bit_is_clear() {}
+    }
+    if sysctrl.xosc32k.read().enable().bit_is_set() {
+        while sysctrl.pclksr.read().xosc32krdy().bit_is_clear() {}
+    }
+    if sysctrl.dfllctrl.read().enable().bit_is_set() {
+        while sysctrl.pclksr.read().dfllrdy().bit_is_clear() {}
+    }
+    if sysctrl.dpllctrla.read().enable().bit_is_set() {
+        while sysctrl.dpllstatus.read().lock().bit_is_clear()
+            || sysctrl.dpllstatus.read().clkrdy().bit_is_clear()
+        {}
+    }
+}
+
+/// Returns the number of the lowest enabled and pending interrupt
+///
+/// After waking from sleep with interrupts masked, this is the interrupt that
+/// woke the device.
+pub fn pending_interrupt() -> Option<u16> {
+    // Safety: the NVIC registers are only read
+    let nvic = unsafe { &*NVIC::PTR };
+    (0..NUM_ISPR).find_map(|i| {
+        let pending = nvic.ispr[i].read() & nvic.iser[i].read();
+        (pending != 0).then(|| (32 * i) as u16 + pending.trailing_zeros() as u16)
+    })
+}
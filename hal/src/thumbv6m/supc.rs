@@ -0,0 +1,212 @@
+//! # Supply controller
+//!
+//! On SAMD11/21, the supply controller functions are part of the SYSCTRL
+//! peripheral:
+//!
+//! - The BOD33 monitors VDD against a threshold, and resets the device or
+//!   requests an interrupt when VDD falls below it. It runs either
+//!   continuously or in sampled mode, to save power.
+//! - The voltage regulator can be kept in normal mode in standby, which
+//!   allows peripherals running in standby to draw more current (SAMD21 only).
+//! - The voltage reference outputs the bandgap and the temperature sensor to
+//!   the ADC.
+//!
+//! The BOD33 interrupt can be used to save state before the power is lost:
+//!
+//! ```no_run
+//! # use atsamd_hal::supc::{Bod33Action, Bod33Config, Supc};
+//! # fn get_sysctrl() -> atsamd_hal::pac::SYSCTRL { unimplemented!() }
+//! let mut sysctrl = get_sysctrl();
+//! let mut supc = Supc::new(&mut sysctrl);
+//! supc.configure_bod33(Bod33Config {
+//!     action: Bod33Action::Interrupt,
+//!     ..Bod33Config::default()
+//! });
+//! supc.enable_bod33_interrupt();
+//! ```
+//!
+//! The BOD33 level and the action at reset are configured by the user row
+//! fuses, see [`UserRow`](crate::nvm::UserRow).
+
+use crate::pac::sysctrl::RegisterBlock;
+use crate::pac::SYSCTRL;
+
+/// Action taken by the BOD33 when VDD falls below the threshold
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bod33Action {
+    /// No action
+    None,
+    /// The device is reset
+    Reset,
+    /// An interrupt is requested
+    Interrupt,
+}
+
+/// Operating mode of the BOD33
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bod33Mode {
+    /// VDD is monitored continuously
+    Continuous,
+    /// VDD is sampled every 2^(`prescaler` + 1) cycles of the 1 kHz output of
+    /// the OSCULP32K, with `prescaler` from 0 to 15
+    Sampled { prescaler: u8 },
+}
+
+/// BOD33 configuration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bod33Config {
+    /// Threshold level, 6 bits. See the electrical characteristics of the
+    /// datasheet for the matching voltages.
+    pub level: u8,
+    /// Enable the hysteresis on the threshold
+    pub hysteresis: bool,
+    /// Action when VDD falls below the threshold
+    pub action: Bod33Action,
+    /// Continuous or sampled operation
+    pub mode: Bod33Mode,
+    /// Keep the BOD33 running in standby
+    pub run_in_standby: bool,
+}
+
+impl Default for Bod33Config {
+    /// Reset at the factory default level, monitoring VDD continuously
+    fn default() -> Self {
+        Self {
+            level: 7,
+            hysteresis: false,
+            action: Bod33Action::Reset,
+            mode: Bod33Mode::Continuous,
+            run_in_standby: false,
+        }
+    }
+}
+
+/// Supply controller
+///
+/// The BOD33, VREG and VREF registers of SYSCTRL are not used by the clock
+/// controller, so [`Supc`] only borrows SYSCTRL while it is created.
+pub struct Supc {
+    _private: (),
+}
+
+impl Supc {
+    /// Create a new [`Supc`]
+    pub fn new(_sysctrl: &mut SYSCTRL) -> Self {
+        Self { _private: () }
+    }
+
+    #[inline]
+    fn sysctrl(&self) -> &RegisterBlock {
+        // Safety: only the BOD33, VREG, VREF and the BOD33 interrupt bits are
+        // accessed, which no other driver uses
+        unsafe { &*SYSCTRL::ptr() }
+    }
+
+    #[inline]
+    fn wait_for_bod33_sync(&self) {
+        while self.sysctrl().pclksr.read().b33srdy().bit_is_clear() {}
+    }
+
+    /// Configure and enable the BOD33
+    ///
+    /// The BOD33 is disabled while it is reconfigured, and this returns once
+    /// it is ready again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level or the prescaler is out of range.
+    pub fn configure_bod33(&mut self, config: Bod33Config) {
+        assert!(config.level < 64, "Invalid BOD33 level");
+        self.disable_bod33();
+
+        let (sampled, prescaler) = match config.mode {
+            Bod33Mode::Continuous => (false, 0),
+            Bod33Mode::Sampled { prescaler } => {
+                assert!(prescaler < 16, "Invalid BOD33 prescaler");
+                (true, prescaler)
+            }
+        };
+        self.sysctrl().bod33.write(|w| {
+            match config.action {
+                Bod33Action::None => w.action().none(),
+                Bod33Action::Reset => w.action().reset(),
+                Bod33Action::Interrupt => w.action().interrupt(),
+            };
+            unsafe { w.level().bits(config.level) };
+            w.psel().bits(prescaler);
+            w.mode().bit(sampled);
+            w.hyst().bit(config.hysteresis);
+            w.runstdby().bit(config.run_in_standby)
+        });
+        self.wait_for_bod33_sync();
+
+        // The sampling clock must be enabled after the prescaler is written
+        if sampled {
+            self.sysctrl().bod33.modify(|_, w| w.cen().set_bit());
+            self.wait_for_bod33_sync();
+        }
+        self.sysctrl().bod33.modify(|_, w| w.enable().set_bit());
+        self.wait_for_bod33_sync();
+        while self.sysctrl().pclksr.read().bod33rdy().bit_is_clear() {}
+    }
+
+    /// Disable the BOD33
+    pub fn disable_bod33(&mut self) {
+        self.sysctrl().bod33.modify(|_, w| w.enable().clear_bit());
+        self.wait_for_bod33_sync();
+    }
+
+    /// Check if VDD is currently below the BOD33 threshold
+    #[inline]
+    pub fn is_bod33_detected(&self) -> bool {
+        self.sysctrl().pclksr.read().bod33det().bit_is_set()
+    }
+
+    /// Enable the BOD33 detection interrupt
+    #[inline]
+    pub fn enable_bod33_interrupt(&mut self) {
+        self.sysctrl().intenset.write(|w| w.bod33det().set_bit());
+    }
+
+    /// Disable the BOD33 detection interrupt
+    #[inline]
+    pub fn disable_bod33_interrupt(&mut self) {
+        self.sysctrl().intenclr.write(|w| w.bod33det().set_bit());
+    }
+
+    /// Check if the BOD33 detection interrupt flag is set
+    #[inline]
+    pub fn is_bod33_interrupt(&self) -> bool {
+        self.sysctrl().intflag.read().bod33det().bit_is_set()
+    }
+
+    /// Clear the BOD33 detection interrupt flag
+    #[inline]
+    pub fn clear_bod33_interrupt(&mut self) {
+        self.sysctrl().intflag.write(|w| w.bod33det().set_bit());
+    }
+
+    /// Keep the voltage regulator in normal mode in standby
+    ///
+    /// By default, the regulator switches to low power mode in standby, which
+    /// limits the current available to the peripherals running in standby.
+    #[cfg(feature = "samd21")]
+    #[inline]
+    pub fn set_regulator_run_in_standby(&mut self, enable: bool) {
+        self.sysctrl()
+            .vreg
+            .modify(|_, w| w.runstdby().bit(enable).forceldo().bit(enable));
+    }
+
+    /// Enable the output of the temperature sensor to the ADC
+    #[inline]
+    pub fn set_temperature_sensor(&mut self, enable: bool) {
+        self.sysctrl().vref.modify(|_, w| w.tsen().bit(enable));
+    }
+
+    /// Enable the output of the bandgap voltage reference to the ADC
+    #[inline]
+    pub fn set_bandgap_output(&mut self, enable: bool) {
+        self.sysctrl().vref.modify(|_, w| w.bgouten().bit(enable));
+    }
+}
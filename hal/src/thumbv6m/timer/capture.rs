@@ -0,0 +1,649 @@
+//! # Input capture
+//!
+//! A [`TimerCounter`] is turned into a [`TcCapture`] with
+//! [`TimerCounter::into_capture`]. The counter then runs freely, and its
+//! value is copied to the CC registers on each event received on the TC event
+//! input. The events usually come from an EIC pin, routed through the
+//! [`evsys`](crate::evsys) module to the `TcNEvu` event user of the timer.
+//!
+//! The [`CaptureMode`] selects how the events are captured:
+//!
+//! - [`CaptureMode::Timestamp`] copies the counter to each enabled channel.
+//! - [`CaptureMode::PeriodPulseWidth`] and [`CaptureMode::PulseWidthPeriod`]
+//!   restart the counter on each rising edge of the event, and capture the
+//!   period and the pulse width of the input signal on the two channels.
+//!   [`TcCapture::period_and_pulse_width`] and [`TcCapture::frequency`] read
+//!   them.
+//!
+//! [`TcCapture`] implements the embedded-hal [`Capture`] trait, with a
+//! resolution selecting the prescaler of the counter.
+//!
+//! The TCC instances capture in the same way with a [`TccCapture`], created
+//! from the peripheral and its clock, e.g. with [`TccCapture::tcc0_`]. The
+//! period and pulse width are measured from the `TccNEv1` event user, and
+//! timestamps are captured on channel x from the `TccNMcx` event user.
+//! Captures are up to 24 bits wide, depending on the instance.
+//!
+//! ```no_run
+//! # use atsamd_hal::timer::TimerCounter;
+//! # use atsamd_hal::timer::capture::{CaptureMode, TcCapture};
+//! # fn get_timer() -> TimerCounter<atsamd_hal::pac::TC3> { unimplemented!() }
+//! use atsamd_hal::prelude::*;
+//!
+//! // Measure the pulses of an RC receiver, with the EIC event of the input
+//! // pin routed to `users::Tc3Evu`
+//! let mut capture = get_timer().into_capture(CaptureMode::PulseWidthPeriod, false);
+//! capture.set_resolution(1.us());
+//! let (period, pulse_width) = nb::block!(capture.period_and_pulse_width()).unwrap();
+//! ```
+#![cfg_attr(
+    feature = "dma",
+    doc = "
+[`TcCapture`] also implements the DMAC [`Buffer`](crate::dmac::Buffer) trait,
+targeting the `CC0` register. Use `TriggerSource::TCn_MC0` as the trigger
+source of the [`Transfer`](crate::dmac::Transfer) to copy each capture to a
+buffer, e.g. the pulse widths of an IR signal in
+[`CaptureMode::PulseWidthPeriod`]. [`TccCapture`] does the same with
+`TriggerSource::TCCn_MC0`."
+)]
+
+use core::ops::Deref;
+
+use crate::clock;
+use crate::ehal::Capture;
+#[cfg(feature = "samd11")]
+use crate::pac::tc1::count16::ctrlc;
+#[cfg(feature = "samd21")]
+use crate::pac::tc3::count16::ctrlc;
+use crate::pac::tcc0;
+use crate::pac::{PM, TCC0};
+#[cfg(feature = "samd21")]
+use crate::pac::{TCC1, TCC2};
+use crate::time::{Hertz, Nanoseconds};
+
+use super::{Count16, TimerCounter};
+
+/// Prescaler divisions supported by the counter
+const DIVIDERS: [u16; 8] = [1, 2, 4, 8, 16, 64, 256, 1024];
+
+/// Tick of a counter clocked at `freq` and divided by `divider`
+fn resolution(freq: Hertz, divider: u16) -> Nanoseconds {
+    Nanoseconds((divider as u64 * 1_000_000_000 / freq.0 as u64) as u32)
+}
+
+/// Largest prescaler division whose tick is not longer than `resolution`
+fn divider_for(freq: Hertz, resolution: Nanoseconds) -> u16 {
+    let resolution = resolution.0 as u64;
+    DIVIDERS
+        .iter()
+        .copied()
+        .rev()
+        .find(|&d| d as u64 * 1_000_000_000 <= resolution * freq.0 as u64)
+        .unwrap_or(1)
+}
+
+/// Capture channels of a TC, or the first two channels of a TCC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureChannel {
+    Ch0,
+    Ch1,
+}
+
+impl CaptureChannel {
+    #[inline]
+    fn index(self) -> usize {
+        match self {
+            CaptureChannel::Ch0 => 0,
+            CaptureChannel::Ch1 => 1,
+        }
+    }
+}
+
+/// Action taken on each event received by the TC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureMode {
+    /// The counter is captured on each enabled channel
+    Timestamp,
+    /// The period is captured on channel 0, and the pulse width on channel 1
+    PeriodPulseWidth,
+    /// The pulse width is captured on channel 0, and the period on channel 1
+    PulseWidthPeriod,
+}
+
+/// Input capture errors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A new value was captured before the previous one was read
+    Overcapture,
+}
+
+/// Timer counter capturing its input events
+pub struct TcCapture<TC> {
+    freq: Hertz,
+    divider: u16,
+    mode: CaptureMode,
+    tc: TC,
+}
+
+impl<TC> TimerCounter<TC>
+where
+    TC: Count16,
+{
+    /// Turn the timer into a [`TcCapture`], capturing on both channels
+    ///
+    /// With `invert`, the input event is inverted, so that the period and
+    /// pulse width are measured from the falling edges.
+    pub fn into_capture(self, mode: CaptureMode, invert: bool) -> TcCapture<TC> {
+        let count = self.tc.count_16();
+
+        // Disable the timer while we reconfigure it
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        count.ctrla.write(|w| w.swrst().set_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+        // the SVD erroneously marks swrst as write-only, so we
+        // need to manually read the bit here
+        while count.ctrla.read().bits() & 1 != 0 {}
+
+        count.evctrl.write(|w| {
+            match mode {
+                CaptureMode::Timestamp => w.evact().off(),
+                CaptureMode::PeriodPulseWidth => w.evact().ppw(),
+                CaptureMode::PulseWidthPeriod => w.evact().pwp(),
+            };
+            w.tcinv().bit(invert);
+            w.tcei().set_bit()
+        });
+
+        let mut capture = TcCapture {
+            freq: self.freq,
+            divider: 1,
+            mode,
+            tc: self.tc,
+        };
+        capture.update(|w| {
+            w.cpten0().set_bit();
+            w.cpten1().set_bit()
+        });
+        capture
+    }
+}
+
+impl<TC> TcCapture<TC>
+where
+    TC: Count16,
+{
+    /// Return to a [`TimerCounter`]
+    ///
+    /// The capture configuration is reset on the next
+    /// [`CountDown::start`](crate::ehal::timer::CountDown::start).
+    pub fn into_timer(self) -> TimerCounter<TC> {
+        let count = self.tc.count_16();
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+        TimerCounter {
+            freq: self.freq,
+            tc: self.tc,
+        }
+    }
+
+    /// Modify the `CTRLC` register, then restart the counter with the current
+    /// prescaler
+    fn update(&mut self, f: impl FnOnce(&mut ctrlc::W) -> &mut ctrlc::W) {
+        let count = self.tc.count_16();
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        count.ctrlc.modify(|_, w| f(w));
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        let divider = self.divider;
+        count.ctrla.modify(|_, w| {
+            match divider {
+                1 => w.prescaler().div1(),
+                2 => w.prescaler().div2(),
+                4 => w.prescaler().div4(),
+                8 => w.prescaler().div8(),
+                16 => w.prescaler().div16(),
+                64 => w.prescaler().div64(),
+                256 => w.prescaler().div256(),
+                1024 => w.prescaler().div1024(),
+                _ => unreachable!(),
+            };
+            w.enable().set_bit()
+        });
+        while count.status.read().syncbusy().bit_is_set() {}
+    }
+
+    /// Capture mode of the timer
+    #[inline]
+    pub fn mode(&self) -> CaptureMode {
+        self.mode
+    }
+
+    /// Frequency of the counter, after the prescaler
+    #[inline]
+    pub fn counter_freq(&self) -> Hertz {
+        Hertz(self.freq.0 / self.divider as u32)
+    }
+
+    /// Read the period and the pulse width of the input signal, in counter
+    /// ticks
+    ///
+    /// This returns once both have been captured for the same period.
+    ///
+    /// # Panics
+    ///
+    /// Panics in [`CaptureMode::Timestamp`].
+    pub fn period_and_pulse_width(&mut self) -> nb::Result<(u16, u16), Error> {
+        assert!(
+            self.mode != CaptureMode::Timestamp,
+            "Period capture in timestamp mode"
+        );
+        let count = self.tc.count_16();
+        let flags = count.intflag.read();
+        if flags.err().bit_is_set() {
+            count.intflag.write(|w| w.err().set_bit());
+            return Err(nb::Error::Other(Error::Overcapture));
+        }
+        if flags.mc0().bit_is_clear() || flags.mc1().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        // Reading the CC registers clears the MCx flags
+        let cc0 = count.cc[0].read().cc().bits();
+        let cc1 = count.cc[1].read().cc().bits();
+        match self.mode {
+            CaptureMode::PeriodPulseWidth => Ok((cc0, cc1)),
+            _ => Ok((cc1, cc0)),
+        }
+    }
+
+    /// Measure the frequency of the input signal
+    ///
+    /// # Panics
+    ///
+    /// Panics in [`CaptureMode::Timestamp`].
+    pub fn frequency(&mut self) -> nb::Result<Hertz, Error> {
+        let (period, _) = self.period_and_pulse_width()?;
+        Ok(Hertz(
+            self.counter_freq()
+                .0
+                .checked_div(period as u32)
+                .unwrap_or(0),
+        ))
+    }
+
+    /// Enable the capture interrupt of `channel`
+    #[inline]
+    pub fn enable_interrupt(&mut self, channel: CaptureChannel) {
+        self.tc.count_16().intenset.write(|w| match channel {
+            CaptureChannel::Ch0 => w.mc0().set_bit(),
+            CaptureChannel::Ch1 => w.mc1().set_bit(),
+        });
+    }
+
+    /// Disable the capture interrupt of `channel`
+    #[inline]
+    pub fn disable_interrupt(&mut self, channel: CaptureChannel) {
+        self.tc.count_16().intenclr.write(|w| match channel {
+            CaptureChannel::Ch0 => w.mc0().set_bit(),
+            CaptureChannel::Ch1 => w.mc1().set_bit(),
+        });
+    }
+}
+
+impl<TC> Capture for TcCapture<TC>
+where
+    TC: Count16,
+{
+    type Error = Error;
+    type Channel = CaptureChannel;
+    type Time = Nanoseconds;
+    type Capture = u16;
+
+    fn capture(&mut self, channel: CaptureChannel) -> nb::Result<u16, Error> {
+        let count = self.tc.count_16();
+        let flags = count.intflag.read();
+        if flags.err().bit_is_set() {
+            count.intflag.write(|w| w.err().set_bit());
+            return Err(nb::Error::Other(Error::Overcapture));
+        }
+        let captured = match channel {
+            CaptureChannel::Ch0 => flags.mc0().bit_is_set(),
+            CaptureChannel::Ch1 => flags.mc1().bit_is_set(),
+        };
+        if captured {
+            // Reading the CC register clears the MCx flag
+            Ok(count.cc[channel.index()].read().cc().bits())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    fn disable(&mut self, channel: CaptureChannel) {
+        self.update(|w| match channel {
+            CaptureChannel::Ch0 => w.cpten0().clear_bit(),
+            CaptureChannel::Ch1 => w.cpten1().clear_bit(),
+        });
+    }
+
+    fn enable(&mut self, channel: CaptureChannel) {
+        self.update(|w| match channel {
+            CaptureChannel::Ch0 => w.cpten0().set_bit(),
+            CaptureChannel::Ch1 => w.cpten1().set_bit(),
+        });
+    }
+
+    fn get_resolution(&self) -> Nanoseconds {
+        resolution(self.freq, self.divider)
+    }
+
+    /// Select the largest prescaler whose tick is not longer than
+    /// `resolution`
+    fn set_resolution<R>(&mut self, resolution: R)
+    where
+        R: Into<Nanoseconds>,
+    {
+        self.divider = divider_for(self.freq, resolution.into());
+        self.update(|w| w);
+    }
+}
+
+#[cfg(feature = "dma")]
+unsafe impl<TC> crate::dmac::Buffer for TcCapture<TC>
+where
+    TC: Count16,
+{
+    type Beat = u16;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.tc.count_16().cc[0].as_ptr()
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+//==============================================================================
+// TCC
+//==============================================================================
+
+/// Timer/counter for control applications capturing its input events
+///
+/// Only the first two capture channels are used, so that the same
+/// [`CaptureMode`]s are available on every TCC instance.
+pub struct TccCapture<TCC> {
+    freq: Hertz,
+    divider: u16,
+    mode: CaptureMode,
+    tcc: TCC,
+}
+
+macro_rules! tcc_capture {
+    ($($TCC:ident: ($clock:ident, $apmask:ident, $apbits:ident),)+) => {
+        $(
+impl TccCapture<$TCC> {
+    /// Configure the TCC to capture its input events on channels 0 and 1
+    ///
+    /// With `invert`, the `EV1` input event is inverted, so that the period
+    /// and pulse width are measured from the falling edges. The `MCx` event
+    /// inputs used in [`CaptureMode::Timestamp`] can't be inverted.
+    pub fn $apbits(
+        clock: &clock::$clock,
+        tcc: $TCC,
+        mode: CaptureMode,
+        invert: bool,
+        pm: &mut PM,
+    ) -> Self {
+        pm.$apmask.modify(|_, w| w.$apbits().set_bit());
+        Self::new(clock.freq(), tcc, mode, invert)
+    }
+}
+        )+
+    };
+}
+
+#[cfg(feature = "samd11")]
+tcc_capture! {
+    TCC0: (Tcc0Clock, apbcmask, tcc0_),
+}
+
+#[cfg(feature = "samd21")]
+tcc_capture! {
+    TCC0: (Tcc0Tcc1Clock, apbcmask, tcc0_),
+    TCC1: (Tcc0Tcc1Clock, apbcmask, tcc1_),
+    TCC2: (Tcc2Tc3Clock, apbcmask, tcc2_),
+}
+
+impl<TCC> TccCapture<TCC>
+where
+    TCC: Deref<Target = tcc0::RegisterBlock>,
+{
+    fn new(freq: Hertz, tcc: TCC, mode: CaptureMode, invert: bool) -> Self {
+        // Disable the TCC while we reconfigure it
+        tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+        while tcc.syncbusy.read().enable().bit_is_set() {}
+
+        tcc.ctrla.write(|w| w.swrst().set_bit());
+        while tcc.syncbusy.read().swrst().bit_is_set() {}
+
+        tcc.evctrl.write(|w| {
+            match mode {
+                CaptureMode::Timestamp => {
+                    w.mcei0().set_bit();
+                    w.mcei1().set_bit()
+                }
+                CaptureMode::PeriodPulseWidth => w.evact1().ppw(),
+                CaptureMode::PulseWidthPeriod => w.evact1().pwp(),
+            };
+            w.tcinv1().bit(invert);
+            w.tcei1().bit(mode != CaptureMode::Timestamp)
+        });
+
+        let mut capture = Self {
+            freq,
+            divider: 1,
+            mode,
+            tcc,
+        };
+        capture.update(|w| {
+            w.cpten0().set_bit();
+            w.cpten1().set_bit()
+        });
+        capture
+    }
+
+    /// Stop the TCC and return it
+    pub fn free(self) -> TCC {
+        self.tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.tcc.syncbusy.read().enable().bit_is_set() {}
+        self.tcc
+    }
+
+    /// Modify the enable-protected `CTRLA` register, then restart the counter
+    /// with the current prescaler
+    fn update(&mut self, f: impl FnOnce(&mut tcc0::ctrla::W) -> &mut tcc0::ctrla::W) {
+        self.tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.tcc.syncbusy.read().enable().bit_is_set() {}
+
+        let divider = self.divider;
+        self.tcc.ctrla.modify(|_, w| {
+            f(w);
+            match divider {
+                1 => w.prescaler().div1(),
+                2 => w.prescaler().div2(),
+                4 => w.prescaler().div4(),
+                8 => w.prescaler().div8(),
+                16 => w.prescaler().div16(),
+                64 => w.prescaler().div64(),
+                256 => w.prescaler().div256(),
+                1024 => w.prescaler().div1024(),
+                _ => unreachable!(),
+            };
+            w.enable().set_bit()
+        });
+        while self.tcc.syncbusy.read().enable().bit_is_set() {}
+    }
+
+    /// Capture mode of the TCC
+    #[inline]
+    pub fn mode(&self) -> CaptureMode {
+        self.mode
+    }
+
+    /// Frequency of the counter, after the prescaler
+    #[inline]
+    pub fn counter_freq(&self) -> Hertz {
+        Hertz(self.freq.0 / self.divider as u32)
+    }
+
+    /// Read the period and the pulse width of the input signal, in counter
+    /// ticks
+    ///
+    /// This returns once both have been captured for the same period.
+    ///
+    /// # Panics
+    ///
+    /// Panics in [`CaptureMode::Timestamp`].
+    pub fn period_and_pulse_width(&mut self) -> nb::Result<(u32, u32), Error> {
+        assert!(
+            self.mode != CaptureMode::Timestamp,
+            "Period capture in timestamp mode"
+        );
+        let flags = self.tcc.intflag.read();
+        if flags.err().bit_is_set() {
+            self.tcc.intflag.write(|w| w.err().set_bit());
+            return Err(nb::Error::Other(Error::Overcapture));
+        }
+        if flags.mc0().bit_is_clear() || flags.mc1().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        // Reading the CC registers clears the MCx flags
+        let cc0 = self.tcc.cc()[0].read().cc().bits();
+        let cc1 = self.tcc.cc()[1].read().cc().bits();
+        match self.mode {
+            CaptureMode::PeriodPulseWidth => Ok((cc0, cc1)),
+            _ => Ok((cc1, cc0)),
+        }
+    }
+
+    /// Measure the frequency of the input signal
+    ///
+    /// # Panics
+    ///
+    /// Panics in [`CaptureMode::Timestamp`].
+    pub fn frequency(&mut self) -> nb::Result<Hertz, Error> {
+        let (period, _) = self.period_and_pulse_width()?;
+        Ok(Hertz(
+            self.counter_freq().0.checked_div(period).unwrap_or(0),
+        ))
+    }
+
+    /// Enable the capture interrupt of `channel`
+    #[inline]
+    pub fn enable_interrupt(&mut self, channel: CaptureChannel) {
+        self.tcc.intenset.write(|w| match channel {
+            CaptureChannel::Ch0 => w.mc0().set_bit(),
+            CaptureChannel::Ch1 => w.mc1().set_bit(),
+        });
+    }
+
+    /// Disable the capture interrupt of `channel`
+    #[inline]
+    pub fn disable_interrupt(&mut self, channel: CaptureChannel) {
+        self.tcc.intenclr.write(|w| match channel {
+            CaptureChannel::Ch0 => w.mc0().set_bit(),
+            CaptureChannel::Ch1 => w.mc1().set_bit(),
+        });
+    }
+}
+
+impl<TCC> Capture for TccCapture<TCC>
+where
+    TCC: Deref<Target = tcc0::RegisterBlock>,
+{
+    type Error = Error;
+    type Channel = CaptureChannel;
+    type Time = Nanoseconds;
+    type Capture = u32;
+
+    fn capture(&mut self, channel: CaptureChannel) -> nb::Result<u32, Error> {
+        let flags = self.tcc.intflag.read();
+        if flags.err().bit_is_set() {
+            self.tcc.intflag.write(|w| w.err().set_bit());
+            return Err(nb::Error::Other(Error::Overcapture));
+        }
+        let captured = match channel {
+            CaptureChannel::Ch0 => flags.mc0().bit_is_set(),
+            CaptureChannel::Ch1 => flags.mc1().bit_is_set(),
+        };
+        if captured {
+            // Reading the CC register clears the MCx flag
+            Ok(self.tcc.cc()[channel.index()].read().cc().bits())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    fn disable(&mut self, channel: CaptureChannel) {
+        self.update(|w| match channel {
+            CaptureChannel::Ch0 => w.cpten0().clear_bit(),
+            CaptureChannel::Ch1 => w.cpten1().clear_bit(),
+        });
+    }
+
+    fn enable(&mut self, channel: CaptureChannel) {
+        self.update(|w| match channel {
+            CaptureChannel::Ch0 => w.cpten0().set_bit(),
+            CaptureChannel::Ch1 => w.cpten1().set_bit(),
+        });
+    }
+
+    fn get_resolution(&self) -> Nanoseconds {
+        resolution(self.freq, self.divider)
+    }
+
+    /// Select the largest prescaler whose tick is not longer than
+    /// `resolution`
+    fn set_resolution<R>(&mut self, resolution: R)
+    where
+        R: Into<Nanoseconds>,
+    {
+        self.divider = divider_for(self.freq, resolution.into());
+        self.update(|w| w);
+    }
+}
+
+#[cfg(feature = "dma")]
+unsafe impl<TCC> crate::dmac::Buffer for TccCapture<TCC>
+where
+    TCC: Deref<Target = tcc0::RegisterBlock>,
+{
+    type Beat = u32;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.tcc.cc()[0].as_ptr()
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
@@ -1,5 +1,28 @@
+//! # Watchdog Timer
+//!
+//! The watchdog resets the device if it is not fed in time. Besides the
+//! embedded-hal traits, which take the timeout as a [`WatchdogTimeout`]
+//! setting, [`Watchdog`] offers a time-based API:
+//!
+//! - [`Watchdog::start_timeout`] starts the watchdog in normal mode
+//! - [`Watchdog::start_window`] starts it in window mode, where feeding it too
+//!   early also resets the device
+//! - [`Watchdog::enable_early_warning`] requests an interrupt some time before
+//!   the reset, so that the application can save its state
+//! - [`Watchdog::lock_always_on`] prevents the watchdog from being disabled or
+//!   reconfigured until the next reset
+//!
+//! The watchdog is clocked by the generic clock `WDT`. The time-based API
+//! needs its frequency, see [`Watchdog::with_clock`]. [`Watchdog::new`]
+//! assumes the usual 1.024 kHz clock.
+
+use crate::clock::WdtClock;
 use crate::ehal::watchdog;
 use crate::pac::WDT;
+use crate::time::{Hertz, Milliseconds};
+use crate::watchdog_params::{timeout_setting, window_settings};
+
+pub use crate::watchdog_params::Error;
 
 /// WatchdogTimeout enumerates usable values for configuring
 /// the timeout of the watchdog peripheral.
@@ -20,13 +43,139 @@ pub enum WatchdogTimeout {
     Cycles16K,
 }
 
+/// Frequency of the watchdog clock
+const WDT_FREQ: Hertz = Hertz(1024);
+
 pub struct Watchdog {
     wdt: WDT,
+    freq: Hertz,
 }
 
 impl Watchdog {
+    /// Create a watchdog clocked at 1.024 kHz
     pub fn new(wdt: WDT) -> Self {
-        Self { wdt }
+        Self {
+            wdt,
+            freq: WDT_FREQ,
+        }
+    }
+
+    /// Create a watchdog clocked by `clock`
+    pub fn with_clock(wdt: WDT, clock: &WdtClock) -> Self {
+        Self {
+            wdt,
+            freq: clock.freq(),
+        }
+    }
+
+    /// Check if the watchdog is locked in always-on mode
+    #[inline]
+    pub fn is_always_on(&self) -> bool {
+        self.wdt.ctrl.read().alwayson().bit_is_set()
+    }
+
+    /// Disable the watchdog and write its configuration
+    fn configure(&mut self, per: u8, window: Option<u8>) -> Result<(), Error> {
+        if self.is_always_on() {
+            return Err(Error::AlwaysOn);
+        }
+        self.wdt.ctrl.modify(|_, w| w.enable().clear_bit());
+        while self.wdt.status.read().syncbusy().bit_is_set() {}
+
+        self.wdt.config.write(|w| unsafe {
+            w.per().bits(per);
+            w.window().bits(window.unwrap_or(0))
+        });
+        self.wdt.ctrl.modify(|_, w| w.wen().bit(window.is_some()));
+        while self.wdt.status.read().syncbusy().bit_is_set() {}
+        Ok(())
+    }
+
+    fn enable(&mut self) {
+        self.wdt.ctrl.modify(|_, w| w.enable().set_bit());
+        while self.wdt.status.read().syncbusy().bit_is_set() {}
+    }
+
+    /// Start the watchdog in normal mode
+    ///
+    /// The device is reset if the watchdog is not fed within `timeout`, which
+    /// is rounded up to the next period supported by the watchdog.
+    pub fn start_timeout(&mut self, timeout: Milliseconds) -> Result<(), Error> {
+        let per = timeout_setting(timeout, self.freq)?;
+        if self.wdt.intenset.read().ew().bit_is_set()
+            && self.wdt.ewctrl.read().ewoffset().bits() >= per
+        {
+            return Err(Error::InvalidTime);
+        }
+        self.configure(per, None)?;
+        self.enable();
+        Ok(())
+    }
+
+    /// Start the watchdog in window mode
+    ///
+    /// Feeding the watchdog during the `closed` window, which starts right
+    /// after the previous feed, resets the device. It must then be fed within
+    /// the `open` window that follows. The closed window is rounded down to
+    /// the previous period supported by the watchdog, so that a feed after
+    /// `closed` is never early, and the open window is rounded up, so that it
+    /// ends no earlier than `closed + open`.
+    pub fn start_window(&mut self, closed: Milliseconds, open: Milliseconds) -> Result<(), Error> {
+        let (window, per) = window_settings(closed, open, self.freq)?;
+        self.configure(per, Some(window))?;
+        self.enable();
+        Ok(())
+    }
+
+    /// Enable the early warning interrupt
+    ///
+    /// In normal mode, the interrupt fires `offset` after the last feed, which
+    /// must be shorter than the timeout. In window mode, it fires at the start
+    /// of the open window and `offset` is ignored. `offset` is rounded up to
+    /// the next period supported by the watchdog.
+    ///
+    /// This must be called before the watchdog is started, which fails with
+    /// [`Error::InvalidTime`] if `offset` is not shorter than the timeout.
+    pub fn enable_early_warning(&mut self, offset: Milliseconds) -> Result<(), Error> {
+        let ewoffset = timeout_setting(offset, self.freq)?;
+        if self.wdt.ctrl.read().enable().bit_is_set() {
+            return Err(Error::Enabled);
+        }
+        self.wdt
+            .ewctrl
+            .write(|w| unsafe { w.ewoffset().bits(ewoffset) });
+        self.clear_early_warning();
+        self.wdt.intenset.write(|w| w.ew().set_bit());
+        Ok(())
+    }
+
+    /// Disable the early warning interrupt
+    #[inline]
+    pub fn disable_early_warning(&mut self) {
+        self.wdt.intenclr.write(|w| w.ew().set_bit());
+    }
+
+    /// Check if the early warning interrupt flag is set
+    #[inline]
+    pub fn is_early_warning(&self) -> bool {
+        self.wdt.intflag.read().ew().bit_is_set()
+    }
+
+    /// Clear the early warning interrupt flag
+    #[inline]
+    pub fn clear_early_warning(&mut self) {
+        self.wdt.intflag.write(|w| w.ew().set_bit());
+    }
+
+    /// Lock the running watchdog in always-on mode
+    ///
+    /// The watchdog can't be disabled or reconfigured until the next reset,
+    /// except for the early warning interrupt. The watchdog must have been
+    /// started beforehand.
+    #[inline]
+    pub fn lock_always_on(&mut self) {
+        self.wdt.ctrl.modify(|_, w| w.alwayson().set_bit());
+        while self.wdt.status.read().syncbusy().bit_is_set() {}
     }
 }
 
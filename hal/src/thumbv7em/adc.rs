@@ -118,6 +118,28 @@ impl Adc<$ADC> {
         while self.adc.syncbusy.read().ctrlb().bit_is_set() {}
     }
 
+    /// Enable the result ready event output, so that the ADC can be used as
+    /// an event generator through the [`evsys`](crate::evsys) module.
+    pub fn enable_result_ready_event(&mut self) {
+        self.adc.evctrl.modify(|_, w| w.resrdyeo().set_bit());
+    }
+
+    /// Disable the result ready event output
+    pub fn disable_result_ready_event(&mut self) {
+        self.adc.evctrl.modify(|_, w| w.resrdyeo().clear_bit());
+    }
+
+    /// Start a conversion whenever an event is received from the
+    /// [`evsys`](crate::evsys) module.
+    pub fn enable_start_event(&mut self) {
+        self.adc.evctrl.modify(|_, w| w.startei().set_bit());
+    }
+
+    /// Stop starting conversions on incoming events
+    pub fn disable_start_event(&mut self) {
+        self.adc.evctrl.modify(|_, w| w.startei().clear_bit());
+    }
+
     fn power_up(&mut self) {
         while self.adc.syncbusy.read().enable().bit_is_set() {}
         self.adc.ctrla.modify(|_, w| w.enable().set_bit());
@@ -0,0 +1,147 @@
+//! Bit timing
+//!
+//! A CAN bit is divided into time quanta of `prescaler` CAN clock cycles. It
+//! starts with a one-quantum synchronization segment, followed by `seg1` and
+//! `seg2` quanta. The bus is sampled between `seg1` and `seg2`.
+
+use crate::time::Hertz;
+
+/// Bit timing of the nominal or data phase
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitTiming {
+    /// Number of CAN clock cycles per time quantum
+    pub prescaler: u16,
+    /// Time quanta before the sample point, excluding the synchronization
+    /// segment
+    pub seg1: u16,
+    /// Time quanta after the sample point
+    pub seg2: u8,
+    /// Synchronization jump width, in time quanta
+    pub sjw: u8,
+}
+
+/// Limits of the bit timing fields
+struct Limits {
+    prescaler: u16,
+    seg1: u16,
+    seg2: u16,
+    sjw: u16,
+}
+
+const NOMINAL: Limits = Limits {
+    prescaler: 512,
+    seg1: 256,
+    seg2: 128,
+    sjw: 128,
+};
+
+const DATA: Limits = Limits {
+    prescaler: 32,
+    seg1: 32,
+    seg2: 16,
+    sjw: 16,
+};
+
+impl BitTiming {
+    /// Compute the nominal bit timing for `bitrate`, with a sample point at
+    /// 87.5% of the bit. Return `None` if the CAN clock frequency is not an
+    /// exact multiple of a suitable number of time quanta.
+    pub fn nominal(clock: impl Into<Hertz>, bitrate: u32) -> Option<Self> {
+        Self::compute(clock.into(), bitrate, &NOMINAL)
+    }
+
+    /// Compute the data bit timing of CAN FD frames for `bitrate`, with a
+    /// sample point at 87.5% of the bit. Return `None` if the CAN clock
+    /// frequency is not an exact multiple of a suitable number of time quanta.
+    pub fn data(clock: impl Into<Hertz>, bitrate: u32) -> Option<Self> {
+        Self::compute(clock.into(), bitrate, &DATA)
+    }
+
+    fn compute(clock: Hertz, bitrate: u32, limits: &Limits) -> Option<Self> {
+        if bitrate == 0 {
+            return None;
+        }
+        let max_quanta = 1 + u32::from(limits.seg1) + u32::from(limits.seg2);
+
+        // Use the smallest prescaler, which gives the finest resolution
+        (1..=u32::from(limits.prescaler)).find_map(|prescaler| {
+            let div = prescaler * bitrate;
+            if clock.0 % div != 0 {
+                return None;
+            }
+            let quanta = clock.0 / div;
+            if !(4..=max_quanta).contains(&quanta) {
+                return None;
+            }
+            let seg2 = (quanta / 8).max(1);
+            let seg1 = quanta - 1 - seg2;
+            let timing = Self {
+                prescaler: prescaler as u16,
+                seg1: seg1 as u16,
+                seg2: seg2 as u8,
+                sjw: seg2 as u8,
+            };
+            timing.is_valid_for(limits).then(|| timing)
+        })
+    }
+
+    fn is_valid_for(&self, limits: &Limits) -> bool {
+        (1..=limits.prescaler).contains(&self.prescaler)
+            && (1..=limits.seg1).contains(&self.seg1)
+            && (1..=limits.seg2).contains(&u16::from(self.seg2))
+            && (1..=limits.sjw).contains(&u16::from(self.sjw))
+            && self.sjw <= self.seg2
+    }
+
+    #[inline]
+    pub(super) fn is_valid_nominal(&self) -> bool {
+        self.is_valid_for(&NOMINAL) && self.seg1 >= 2
+    }
+
+    #[inline]
+    pub(super) fn is_valid_data(&self) -> bool {
+        self.is_valid_for(&DATA)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(prescaler: u16, seg1: u16, seg2: u8) -> Option<BitTiming> {
+        Some(BitTiming {
+            prescaler,
+            seg1,
+            seg2,
+            sjw: seg2,
+        })
+    }
+
+    #[test]
+    fn timing_uses_the_smallest_valid_prescaler() {
+        assert_eq!(
+            BitTiming::nominal(Hertz(48_000_000), 500_000),
+            timing(1, 83, 12)
+        );
+        assert_eq!(
+            BitTiming::data(Hertz(48_000_000), 2_000_000),
+            timing(1, 20, 3)
+        );
+        // 96 and 48 quanta exceed the data phase limits, but 32 quanta fit
+        assert_eq!(
+            BitTiming::data(Hertz(48_000_000), 500_000),
+            timing(3, 27, 4)
+        );
+    }
+
+    #[test]
+    fn timing_without_valid_quanta_is_none() {
+        assert_eq!(BitTiming::nominal(Hertz(48_000_000), 0), None);
+        // 48 MHz is not a multiple of 7 kbit/s
+        assert_eq!(BitTiming::nominal(Hertz(48_000_000), 7_000), None);
+        // Less than 4 quanta per bit
+        assert_eq!(BitTiming::nominal(Hertz(8_000_000), 4_000_000), None);
+        // More quanta per bit than the largest prescaler can divide down
+        assert_eq!(BitTiming::data(Hertz(48_000_000), 10_000), None);
+    }
+}
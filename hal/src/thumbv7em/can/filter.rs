@@ -0,0 +1,132 @@
+//! Acceptance filters
+//!
+//! Received frames are compared against the filters of their identifier type
+//! in order, and the first matching filter decides what happens to the frame.
+//! Frames matching no filter are handled according to
+//! [`Config::non_matching_standard`](super::Config::non_matching_standard)
+//! and [`Config::non_matching_extended`](super::Config::non_matching_extended).
+
+use super::frame::{ExtendedId, StandardId};
+
+/// Action taken on frames matching a filter
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterAction {
+    /// Store the frame in RX FIFO 0
+    Fifo0,
+    /// Store the frame in RX FIFO 1
+    Fifo1,
+    /// Reject the frame
+    Reject,
+    /// Flag the frame as high priority, without storing it
+    Priority,
+    /// Flag the frame as high priority and store it in RX FIFO 0
+    PriorityFifo0,
+    /// Flag the frame as high priority and store it in RX FIFO 1
+    PriorityFifo1,
+}
+
+impl FilterAction {
+    #[inline]
+    fn bits(self) -> u32 {
+        match self {
+            Self::Fifo0 => 1,
+            Self::Fifo1 => 2,
+            Self::Reject => 3,
+            Self::Priority => 4,
+            Self::PriorityFifo0 => 5,
+            Self::PriorityFifo1 => 6,
+        }
+    }
+}
+
+/// Store the frame into a dedicated RX buffer
+const STORE_RX_BUFFER: u32 = 7;
+
+/// Filter for frames with a standard (11-bit) identifier
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StandardFilter(u32);
+
+impl StandardFilter {
+    #[inline]
+    fn new(kind: u32, action: u32, id1: StandardId, id2: u32) -> Self {
+        Self(kind << 30 | action << 27 | u32::from(id1.as_raw()) << 16 | id2)
+    }
+
+    /// Match identifiers in the range `from..=to`
+    pub fn range(from: StandardId, to: StandardId, action: FilterAction) -> Self {
+        Self::new(0, action.bits(), from, to.as_raw().into())
+    }
+
+    /// Match either of two identifiers
+    pub fn dual(id1: StandardId, id2: StandardId, action: FilterAction) -> Self {
+        Self::new(1, action.bits(), id1, id2.as_raw().into())
+    }
+
+    /// Match identifiers equal to `id` on the bits set in `mask`
+    pub fn masked(id: StandardId, mask: u16, action: FilterAction) -> Self {
+        Self::new(2, action.bits(), id, u32::from(mask & 0x7FF))
+    }
+
+    /// Store frames with identifier `id` in the dedicated RX buffer `buffer`
+    pub fn rx_buffer(id: StandardId, buffer: u8) -> Self {
+        Self::new(0, STORE_RX_BUFFER, id, u32::from(buffer & 0x3F))
+    }
+
+    /// Disabled filter
+    pub const fn disabled() -> Self {
+        Self(0)
+    }
+
+    #[inline]
+    pub(super) fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+/// Filter for frames with an extended (29-bit) identifier
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtendedFilter([u32; 2]);
+
+impl ExtendedFilter {
+    #[inline]
+    fn new(kind: u32, action: u32, id1: ExtendedId, id2: u32) -> Self {
+        Self([action << 29 | id1.as_raw(), kind << 30 | id2])
+    }
+
+    /// Match identifiers in the range `from..=to`. The extended ID mask set in
+    /// the [`Config`](super::Config) is applied to the identifier first.
+    pub fn range(from: ExtendedId, to: ExtendedId, action: FilterAction) -> Self {
+        Self::new(0, action.bits(), from, to.as_raw())
+    }
+
+    /// Match identifiers in the range `from..=to`, ignoring the extended ID
+    /// mask
+    pub fn range_unmasked(from: ExtendedId, to: ExtendedId, action: FilterAction) -> Self {
+        Self::new(3, action.bits(), from, to.as_raw())
+    }
+
+    /// Match either of two identifiers
+    pub fn dual(id1: ExtendedId, id2: ExtendedId, action: FilterAction) -> Self {
+        Self::new(1, action.bits(), id1, id2.as_raw())
+    }
+
+    /// Match identifiers equal to `id` on the bits set in `mask`
+    pub fn masked(id: ExtendedId, mask: u32, action: FilterAction) -> Self {
+        Self::new(2, action.bits(), id, mask & 0x1FFF_FFFF)
+    }
+
+    /// Store frames with identifier `id` in the dedicated RX buffer `buffer`
+    pub fn rx_buffer(id: ExtendedId, buffer: u8) -> Self {
+        Self::new(0, STORE_RX_BUFFER, id, u32::from(buffer & 0x3F))
+    }
+
+    /// Disabled filter
+    pub const fn disabled() -> Self {
+        Self([0; 2])
+    }
+
+    #[inline]
+    pub(super) fn bits(&self) -> [u32; 2] {
+        self.0
+    }
+}
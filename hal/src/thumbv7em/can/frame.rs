@@ -0,0 +1,291 @@
+//! CAN and CAN FD frames
+
+use super::message_ram::DATA_LEN;
+
+pub use embedded_can::{ExtendedId, Id, StandardId};
+
+/// Valid CAN FD payload lengths, indexed by DLC
+const FD_LENGTHS: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64];
+
+/// Header bits of RX/TX elements
+const XTD: u32 = 1 << 30;
+const RTR: u32 = 1 << 29;
+const BRS: u32 = 1 << 20;
+const FDF: u32 = 1 << 21;
+
+/// A classic CAN or CAN FD frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Frame {
+    id: Id,
+    remote: bool,
+    fd: bool,
+    bit_rate_switch: bool,
+    dlc: u8,
+    data: [u8; DATA_LEN],
+}
+
+impl Frame {
+    /// Create a classic CAN data frame, with at most 8 bytes of data
+    pub fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+        if data.len() > 8 {
+            return None;
+        }
+        Some(Self::with_data(
+            id.into(),
+            false,
+            false,
+            data.len() as u8,
+            data,
+        ))
+    }
+
+    /// Create a classic CAN remote frame
+    pub fn new_remote(id: impl Into<Id>, dlc: usize) -> Option<Self> {
+        if dlc > 8 {
+            return None;
+        }
+        let mut frame = Self::with_data(id.into(), false, false, dlc as u8, &[]);
+        frame.remote = true;
+        Some(frame)
+    }
+
+    /// Create a CAN FD data frame, with at most 64 bytes of data.
+    ///
+    /// If the length of `data` is not a valid CAN FD payload length, the
+    /// payload is padded with zeros to the next valid length. When
+    /// `bit_rate_switch` is set, the data phase is transmitted with the data
+    /// bit timing.
+    pub fn new_fd(id: impl Into<Id>, data: &[u8], bit_rate_switch: bool) -> Option<Self> {
+        let dlc = FD_LENGTHS
+            .iter()
+            .position(|&len| data.len() <= len as usize)?;
+        Some(Self::with_data(
+            id.into(),
+            true,
+            bit_rate_switch,
+            dlc as u8,
+            data,
+        ))
+    }
+
+    fn with_data(id: Id, fd: bool, bit_rate_switch: bool, dlc: u8, data: &[u8]) -> Self {
+        let mut buf = [0; DATA_LEN];
+        buf[..data.len()].copy_from_slice(data);
+        Self {
+            id,
+            remote: false,
+            fd,
+            bit_rate_switch,
+            dlc,
+            data: buf,
+        }
+    }
+
+    /// Frame identifier
+    #[inline]
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Return whether this is a remote frame
+    #[inline]
+    pub fn is_remote_frame(&self) -> bool {
+        self.remote
+    }
+
+    /// Return whether this is a CAN FD frame
+    #[inline]
+    pub fn is_fd(&self) -> bool {
+        self.fd
+    }
+
+    /// Return whether the data phase of this CAN FD frame uses the data bit
+    /// rate
+    #[inline]
+    pub fn bit_rate_switch(&self) -> bool {
+        self.bit_rate_switch
+    }
+
+    /// Data length code
+    #[inline]
+    pub fn dlc(&self) -> u8 {
+        self.dlc
+    }
+
+    /// Frame payload. This is empty for remote frames.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        if self.remote {
+            &[]
+        } else {
+            &self.data[..self.len()]
+        }
+    }
+
+    /// Payload length in bytes
+    #[inline]
+    fn len(&self) -> usize {
+        if self.fd {
+            FD_LENGTHS[self.dlc as usize] as usize
+        } else {
+            usize::from(self.dlc.min(8))
+        }
+    }
+
+    /// Encode the frame into the two header words of a TX buffer element and
+    /// its data words
+    pub(super) fn encode(&self, marker: u8, event: bool) -> ([u32; 2], [u32; DATA_LEN / 4]) {
+        let mut t0 = match self.id {
+            Id::Standard(id) => u32::from(id.as_raw()) << 18,
+            Id::Extended(id) => id.as_raw() | XTD,
+        };
+        if self.remote {
+            t0 |= RTR;
+        }
+
+        let mut t1 = u32::from(self.dlc) << 16 | u32::from(marker) << 24;
+        if self.fd {
+            t1 |= FDF;
+        }
+        if self.bit_rate_switch {
+            t1 |= BRS;
+        }
+        if event {
+            t1 |= 1 << 23;
+        }
+
+        let mut words = [0; DATA_LEN / 4];
+        for (word, bytes) in words.iter_mut().zip(self.data.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        ([t0, t1], words)
+    }
+
+    /// Decode a frame from the header and data words of an RX element
+    pub(super) fn decode(header: [u32; 2], words: &[u32; DATA_LEN / 4]) -> Self {
+        let [r0, r1] = header;
+        let id = if r0 & XTD != 0 {
+            // Safety: the mask guarantees a valid 29-bit identifier
+            Id::Extended(unsafe { ExtendedId::new_unchecked(r0 & 0x1FFF_FFFF) })
+        } else {
+            // Safety: the mask guarantees a valid 11-bit identifier
+            Id::Standard(unsafe { StandardId::new_unchecked(((r0 >> 18) & 0x7FF) as u16) })
+        };
+
+        let mut data = [0; DATA_LEN];
+        for (bytes, word) in data.chunks_exact_mut(4).zip(words.iter()) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+
+        Self {
+            id,
+            remote: r0 & RTR != 0,
+            fd: r1 & FDF != 0,
+            bit_rate_switch: r1 & BRS != 0,
+            dlc: ((r1 >> 16) & 0xF) as u8,
+            data,
+        }
+    }
+}
+
+impl embedded_can::Frame for Frame {
+    #[inline]
+    fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+        Frame::new(id, data)
+    }
+
+    #[inline]
+    fn new_remote(id: impl Into<Id>, dlc: usize) -> Option<Self> {
+        Frame::new_remote(id, dlc)
+    }
+
+    #[inline]
+    fn is_extended(&self) -> bool {
+        matches!(self.id, Id::Extended(_))
+    }
+
+    #[inline]
+    fn is_remote_frame(&self) -> bool {
+        self.remote
+    }
+
+    #[inline]
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    #[inline]
+    fn dlc(&self) -> usize {
+        self.dlc as usize
+    }
+
+    #[inline]
+    fn data(&self) -> &[u8] {
+        Frame::data(self)
+    }
+}
+
+/// Entry of the TX event FIFO, recording a transmitted frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TxEvent {
+    /// Identifier of the transmitted frame
+    pub id: Id,
+    /// Index of the TX buffer the frame was transmitted from
+    pub buffer: u8,
+    /// Value of the timestamp counter when the frame was transmitted
+    pub timestamp: u16,
+}
+
+impl TxEvent {
+    pub(super) fn decode([e0, e1]: [u32; 2]) -> Self {
+        let frame = Frame::decode([e0, 0], &[0; DATA_LEN / 4]);
+        Self {
+            id: frame.id,
+            buffer: (e1 >> 24) as u8,
+            timestamp: e1 as u16,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_frame_is_encoded_into_the_tx_element() {
+        let id = StandardId::new(0x123).unwrap();
+        let frame = Frame::new(id, &[1, 2, 3, 4, 5]).unwrap();
+        let ([t0, t1], words) = frame.encode(7, true);
+        assert_eq!(t0, 0x123 << 18);
+        assert_eq!(t1, 7 << 24 | 1 << 23 | 5 << 16);
+        assert_eq!(words[..2], [0x0403_0201, 0x05]);
+        assert_eq!(Frame::decode([t0, t1], &words), frame);
+    }
+
+    #[test]
+    fn frames_survive_an_encode_decode_round_trip() {
+        let id = ExtendedId::new(0x1ABC_DEF0).unwrap();
+        let fd = Frame::new_fd(id, &[0xAA; 10], true).unwrap();
+        assert_eq!(fd.dlc(), 9);
+        assert_eq!(
+            fd.data(),
+            &[0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0, 0]
+        );
+        let remote = Frame::new_remote(StandardId::MAX, 8).unwrap();
+        assert_eq!(remote.data(), &[]);
+
+        for frame in [fd, remote] {
+            let (header, words) = frame.encode(0, false);
+            assert_eq!(Frame::decode(header, &words), frame);
+        }
+    }
+
+    #[test]
+    fn tx_event_is_decoded() {
+        let id = StandardId::new(0x42).unwrap();
+        let event = TxEvent::decode([0x42 << 18, 3 << 24 | 0x1234]);
+        assert_eq!(event.id, Id::Standard(id));
+        assert_eq!(event.buffer, 3);
+        assert_eq!(event.timestamp, 0x1234);
+    }
+}
@@ -0,0 +1,166 @@
+//! Message RAM
+//!
+//! The MCAN stores its filters, received frames, frames to transmit and
+//! transmit events in a region of the system RAM, called the message RAM.
+//! The start address of each section is configured with a 16-bit offset, so
+//! the whole message RAM must be located in the first 64 KiB of RAM
+//! (`0x2000_0000..0x2001_0000`).
+//!
+//! The size of each section is selected with the const generic parameters of
+//! [`MessageRam`]. Every RX and TX element has room for a 64-byte CAN FD
+//! payload.
+
+use super::Error;
+
+/// Size of the data field of RX and TX elements, in bytes
+pub(super) const DATA_LEN: usize = 64;
+const DATA_WORDS: usize = DATA_LEN / 4;
+
+/// Start of the RAM region addressable by the MCAN
+const RAM_START: usize = 0x2000_0000;
+/// End of the RAM region addressable by the MCAN
+const RAM_END: usize = 0x2001_0000;
+
+/// RX buffer, RX FIFO or TX buffer element
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(super) struct Element {
+    pub(super) header: [u32; 2],
+    pub(super) data: [u32; DATA_WORDS],
+}
+
+impl Element {
+    const EMPTY: Self = Self {
+        header: [0; 2],
+        data: [0; DATA_WORDS],
+    };
+}
+
+/// Message RAM of a CAN peripheral
+///
+/// * `STD`: number of standard ID filters (at most 128)
+/// * `EXT`: number of extended ID filters (at most 64)
+/// * `RX0`: number of RX FIFO 0 elements (at most 64)
+/// * `RX1`: number of RX FIFO 1 elements (at most 64)
+/// * `RXB`: number of dedicated RX buffers (at most 64)
+/// * `TX`: number of TX buffers, shared between the dedicated TX buffers and
+///   the TX FIFO/queue, and number of TX event FIFO elements (at most 32)
+///
+/// The message RAM must be located in the first 64 KiB of RAM, and must live
+/// as long as the peripheral uses it. It is therefore passed to
+/// [`Can::new`](super::Can::new) as a `&'static mut` reference, which can be
+/// obtained with `cortex_m::singleton!`.
+#[repr(C)]
+pub struct MessageRam<
+    const STD: usize,
+    const EXT: usize,
+    const RX0: usize,
+    const RX1: usize,
+    const RXB: usize,
+    const TX: usize,
+> {
+    standard_filters: [u32; STD],
+    extended_filters: [[u32; 2]; EXT],
+    rx_fifo0: [Element; RX0],
+    rx_fifo1: [Element; RX1],
+    rx_buffers: [Element; RXB],
+    tx_events: [[u32; 2]; TX],
+    tx_buffers: [Element; TX],
+}
+
+impl<
+        const STD: usize,
+        const EXT: usize,
+        const RX0: usize,
+        const RX1: usize,
+        const RXB: usize,
+        const TX: usize,
+    > MessageRam<STD, EXT, RX0, RX1, RXB, TX>
+{
+    /// Create a new, zeroed message RAM
+    pub const fn new() -> Self {
+        Self {
+            standard_filters: [0; STD],
+            extended_filters: [[0; 2]; EXT],
+            rx_fifo0: [Element::EMPTY; RX0],
+            rx_fifo1: [Element::EMPTY; RX1],
+            rx_buffers: [Element::EMPTY; RXB],
+            tx_events: [[0; 2]; TX],
+            tx_buffers: [Element::EMPTY; TX],
+        }
+    }
+
+    /// Check the capacities and location of the message RAM, and return the
+    /// addresses of its sections
+    pub(super) fn layout(&'static mut self) -> Result<Layout, Error> {
+        if STD > 128 || EXT > 64 || RX0 > 64 || RX1 > 64 || RXB > 64 || TX > 32 {
+            return Err(Error::InvalidMessageRam);
+        }
+
+        let start = self as *mut Self as usize;
+        let end = start + core::mem::size_of::<Self>();
+        if start < RAM_START || end > RAM_END {
+            return Err(Error::InvalidMessageRam);
+        }
+
+        Ok(Layout {
+            standard_filters: self.standard_filters.as_mut_ptr(),
+            standard_filters_len: STD as u8,
+            extended_filters: self.extended_filters.as_mut_ptr(),
+            extended_filters_len: EXT as u8,
+            rx_fifo0: self.rx_fifo0.as_mut_ptr(),
+            rx_fifo0_len: RX0 as u8,
+            rx_fifo1: self.rx_fifo1.as_mut_ptr(),
+            rx_fifo1_len: RX1 as u8,
+            rx_buffers: self.rx_buffers.as_mut_ptr(),
+            rx_buffers_len: RXB as u8,
+            tx_events: self.tx_events.as_mut_ptr(),
+            tx_buffers: self.tx_buffers.as_mut_ptr(),
+            tx_buffers_len: TX as u8,
+        })
+    }
+}
+
+impl<
+        const STD: usize,
+        const EXT: usize,
+        const RX0: usize,
+        const RX1: usize,
+        const RXB: usize,
+        const TX: usize,
+    > Default for MessageRam<STD, EXT, RX0, RX1, RXB, TX>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Addresses and capacities of the message RAM sections
+///
+/// The message RAM is shared with the peripheral, so it is only ever accessed
+/// through these raw pointers, with volatile operations.
+pub(super) struct Layout {
+    pub(super) standard_filters: *mut u32,
+    pub(super) standard_filters_len: u8,
+    pub(super) extended_filters: *mut [u32; 2],
+    pub(super) extended_filters_len: u8,
+    pub(super) rx_fifo0: *mut Element,
+    pub(super) rx_fifo0_len: u8,
+    pub(super) rx_fifo1: *mut Element,
+    pub(super) rx_fifo1_len: u8,
+    pub(super) rx_buffers: *mut Element,
+    pub(super) rx_buffers_len: u8,
+    pub(super) tx_events: *mut [u32; 2],
+    pub(super) tx_buffers: *mut Element,
+    pub(super) tx_buffers_len: u8,
+}
+
+/// Return the 16-bit offset used by the MCAN to address a message RAM section
+#[inline]
+pub(super) fn offset<T>(ptr: *mut T) -> u16 {
+    ptr as usize as u16
+}
+
+// Safety: the message RAM is exclusively owned by the driver holding the
+// `Layout`, so it can be moved to another context along with it.
+unsafe impl Send for Layout {}
@@ -0,0 +1,913 @@
+//! # Controller Area Network
+//!
+//! The SAME51 and SAME54 contain two MCAN controllers, `CAN0` and `CAN1`
+//! (`CAN1` is not available on the SAME51G), supporting classic CAN and CAN FD with bit rate switching.
+//!
+//! # Message RAM
+//!
+//! The MCAN keeps its filters, received frames, frames to transmit and
+//! transmit events in a [`MessageRam`], allocated by the user in the first
+//! 64 KiB of RAM. The capacity of each section is selected with the const
+//! generic parameters of [`MessageRam`].
+//!
+//! # Initializing
+//!
+//! The generic clock of the peripheral must be configured through the
+//! [`GenericClockController`](crate::clock::GenericClockController), and its
+//! frequency used to compute the [`BitTiming`]s of the [`Config`].
+//!
+//! ```
+//! let gclk0 = clocks.gclk0();
+//! let can_clock = clocks.can0(&gclk0).unwrap();
+//! let ram = cortex_m::singleton!(: MessageRam<4, 0, 8, 0, 0, 8> = MessageRam::new()).unwrap();
+//!
+//! let mut config = Config::new(BitTiming::nominal(can_clock.freq(), 500_000).unwrap());
+//! config.data = BitTiming::data(can_clock.freq(), 2_000_000);
+//!
+//! let mut can = Can::new(
+//!     peripherals.CAN0,
+//!     pins.pa22,
+//!     pins.pa23,
+//!     &mut peripherals.MCLK,
+//!     &can_clock,
+//!     ram,
+//!     config,
+//! )
+//! .unwrap();
+//!
+//! let filter = StandardFilter::range(
+//!     StandardId::ZERO,
+//!     StandardId::MAX,
+//!     FilterAction::Fifo0,
+//! );
+//! can.set_standard_filter(0, filter).unwrap();
+//!
+//! let frame = Frame::new_fd(StandardId::new(0x123).unwrap(), &[0; 24], true).unwrap();
+//! nb::block!(can.transmit(&frame)).unwrap();
+//! let frame = nb::block!(can.receive()).unwrap();
+//! ```
+//!
+//! # Transmitting
+//!
+//! The first [`Config::dedicated_tx_buffers`] TX buffers of the message RAM
+//! are dedicated buffers, written with [`Can::write_tx_buffer`] and sent with
+//! [`Can::request_tx_buffer`]. The remaining buffers form the TX FIFO, or the
+//! TX queue when [`Config::tx_queue`] is set, used by [`Can::transmit`].
+//!
+//! # Receiving
+//!
+//! Frames accepted by the filters are stored in RX FIFO 0, RX FIFO 1 or a
+//! dedicated RX buffer. [`Can::receive`] reads FIFO 0 and then FIFO 1,
+//! [`Can::receive_fifo`] reads a single FIFO, and [`Can::receive_buffer`]
+//! reads a dedicated RX buffer.
+//!
+//! # `embedded-can`
+//!
+//! [`Can`] implements the non-blocking and blocking `Can` traits of the
+//! `embedded-can` crate, and [`Frame`] implements its `Frame` trait.
+
+use core::ptr;
+
+use bitflags::bitflags;
+
+use crate::clock::Can0Clock;
+#[cfg(feature = "min-samd51j")]
+use crate::clock::Can1Clock;
+use crate::gpio::*;
+#[cfg(feature = "min-samd51j")]
+use crate::pac::CAN1;
+use crate::pac::{can0, CAN0, MCLK};
+use crate::typelevel::Sealed;
+
+mod bit_timing;
+mod filter;
+mod frame;
+mod message_ram;
+
+pub use bit_timing::BitTiming;
+pub use filter::{ExtendedFilter, FilterAction, StandardFilter};
+pub use frame::{ExtendedId, Frame, Id, StandardId, TxEvent};
+pub use message_ram::MessageRam;
+
+use message_ram::{offset, Element, Layout};
+
+/// Action taken on frames matching no filter
+pub use can0::gfc::ANFS_A as NonMatchingAction;
+
+//==============================================================================
+// CanInstance
+//==============================================================================
+
+/// Type-level enum of the CAN peripherals
+pub trait CanInstance: Sealed + core::ops::Deref<Target = can0::RegisterBlock> {
+    /// Clock token of the peripheral
+    type Clock;
+
+    /// Enable the AHB clock of the peripheral
+    fn enable_ahb_clock(mclk: &mut MCLK);
+}
+
+/// Alias for the `CAN0` peripheral
+pub type Can0 = CAN0;
+
+/// Alias for the `CAN1` peripheral
+#[cfg(feature = "min-samd51j")]
+pub type Can1 = CAN1;
+
+impl Sealed for CAN0 {}
+
+impl CanInstance for CAN0 {
+    type Clock = Can0Clock;
+
+    #[inline]
+    fn enable_ahb_clock(mclk: &mut MCLK) {
+        mclk.ahbmask.modify(|_, w| w.can0_().set_bit());
+    }
+}
+
+#[cfg(feature = "min-samd51j")]
+impl Sealed for CAN1 {}
+
+#[cfg(feature = "min-samd51j")]
+impl CanInstance for CAN1 {
+    type Clock = Can1Clock;
+
+    #[inline]
+    fn enable_ahb_clock(mclk: &mut MCLK) {
+        mclk.ahbmask.modify(|_, w| w.can1_().set_bit());
+    }
+}
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+/// Pins which can transmit for the CAN peripheral `I`
+pub trait TxPin<I: CanInstance>: PinId {
+    /// Alternate function of the pin
+    type Function: AlternateConfig;
+}
+
+/// Pins which can receive for the CAN peripheral `I`
+pub trait RxPin<I: CanInstance>: PinId {
+    /// Alternate function of the pin
+    type Function: AlternateConfig;
+}
+
+macro_rules! can_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $Trait:ident<$Instance:ident>: $PinId:ident => $Function:ident,
+        )+
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl $Trait<$Instance> for $PinId {
+                type Function = $Function;
+            }
+        )+
+    };
+}
+
+can_pins!(
+    TxPin<CAN0>: PA22 => I,
+    RxPin<CAN0>: PA23 => I,
+    TxPin<CAN0>: PA24 => I,
+    RxPin<CAN0>: PA25 => I,
+    #[cfg(feature = "min-samd51j")]
+    TxPin<CAN1>: PB12 => H,
+    #[cfg(feature = "min-samd51j")]
+    RxPin<CAN1>: PB13 => H,
+    #[cfg(feature = "min-samd51j")]
+    TxPin<CAN1>: PB14 => H,
+    #[cfg(feature = "min-samd51j")]
+    RxPin<CAN1>: PB15 => H,
+);
+
+//==============================================================================
+// Error
+//==============================================================================
+
+/// CAN errors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The message RAM is too large or not in the first 64 KiB of RAM
+    InvalidMessageRam,
+    /// A bit timing or the number of dedicated TX buffers is out of range
+    InvalidConfig,
+    /// A filter or buffer index is out of range
+    InvalidIndex,
+    /// A received frame was lost because its FIFO was full
+    Overrun,
+    /// The controller is in the bus off state
+    BusOff,
+    /// More than 5 equal bits in a row were detected
+    Stuff,
+    /// A fixed-format part of a frame had the wrong format
+    Form,
+    /// A transmitted frame was not acknowledged
+    Ack,
+    /// A transmitted bit was not read back with the same value
+    Bit,
+    /// The CRC of a received frame was wrong
+    Crc,
+}
+
+impl embedded_can::Error for Error {
+    fn kind(&self) -> embedded_can::ErrorKind {
+        use embedded_can::ErrorKind;
+        match self {
+            Error::Overrun => ErrorKind::Overrun,
+            Error::Stuff => ErrorKind::Stuff,
+            Error::Form => ErrorKind::Form,
+            Error::Ack => ErrorKind::Acknowledge,
+            Error::Bit => ErrorKind::Bit,
+            Error::Crc => ErrorKind::Crc,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+//==============================================================================
+// Config
+//==============================================================================
+
+/// Operating mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Normal operation
+    Normal,
+    /// Receive frames without acknowledging them or transmitting anything
+    BusMonitoring,
+    /// Receive frames and acknowledge them, but do not transmit
+    Restricted,
+    /// Transmitted frames are looped back internally, and nothing is driven
+    /// on the bus. Useful for self-tests.
+    InternalLoopback,
+    /// Transmitted frames are looped back internally and also driven on the
+    /// bus
+    ExternalLoopback,
+}
+
+/// CAN configuration
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// Bit timing of classic frames and of the arbitration phase of CAN FD
+    /// frames
+    pub nominal: BitTiming,
+    /// Bit timing of the data phase of CAN FD frames. CAN FD operation is
+    /// enabled when this is set.
+    pub data: Option<BitTiming>,
+    /// Operating mode
+    pub mode: Mode,
+    /// Retransmit frames which lost arbitration or were disturbed by errors
+    pub auto_retransmit: bool,
+    /// Use the TX queue, which sends the frame with the highest priority
+    /// first, instead of the TX FIFO
+    pub tx_queue: bool,
+    /// Number of dedicated TX buffers, taken from the start of the TX buffers
+    /// of the message RAM
+    pub dedicated_tx_buffers: u8,
+    /// Action taken on standard frames matching no filter
+    pub non_matching_standard: NonMatchingAction,
+    /// Action taken on extended frames matching no filter
+    pub non_matching_extended: NonMatchingAction,
+    /// Reject all standard remote frames
+    pub reject_remote_standard: bool,
+    /// Reject all extended remote frames
+    pub reject_remote_extended: bool,
+    /// Mask applied to extended identifiers before they are compared to range
+    /// filters
+    pub extended_id_mask: u32,
+}
+
+impl Config {
+    /// Create a configuration for classic CAN in normal mode, with automatic
+    /// retransmission and a TX FIFO. Frames matching no filter are rejected.
+    pub const fn new(nominal: BitTiming) -> Self {
+        Self {
+            nominal,
+            data: None,
+            mode: Mode::Normal,
+            auto_retransmit: true,
+            tx_queue: false,
+            dedicated_tx_buffers: 0,
+            non_matching_standard: NonMatchingAction::REJECT,
+            non_matching_extended: NonMatchingAction::REJECT,
+            reject_remote_standard: false,
+            reject_remote_extended: false,
+            extended_id_mask: 0x1FFF_FFFF,
+        }
+    }
+}
+
+//==============================================================================
+// Interrupts & status
+//==============================================================================
+
+bitflags! {
+    /// Interrupt flags
+    ///
+    /// The binary format of the underlying bits exactly matches the `IR`
+    /// register.
+    pub struct Interrupts: u32 {
+        /// New frame in RX FIFO 0
+        const RX_FIFO0_NEW = 1 << 0;
+        /// RX FIFO 0 full
+        const RX_FIFO0_FULL = 1 << 2;
+        /// Frame lost because RX FIFO 0 was full
+        const RX_FIFO0_LOST = 1 << 3;
+        /// New frame in RX FIFO 1
+        const RX_FIFO1_NEW = 1 << 4;
+        /// RX FIFO 1 full
+        const RX_FIFO1_FULL = 1 << 6;
+        /// Frame lost because RX FIFO 1 was full
+        const RX_FIFO1_LOST = 1 << 7;
+        /// High priority frame received
+        const HIGH_PRIORITY = 1 << 8;
+        /// Transmission completed
+        const TX_COMPLETE = 1 << 9;
+        /// Transmission cancellation finished
+        const TX_CANCELLED = 1 << 10;
+        /// TX FIFO empty
+        const TX_FIFO_EMPTY = 1 << 11;
+        /// New entry in the TX event FIFO
+        const TX_EVENT_NEW = 1 << 12;
+        /// Entry lost because the TX event FIFO was full
+        const TX_EVENT_LOST = 1 << 15;
+        /// Message RAM access failure
+        const RAM_ACCESS_FAILURE = 1 << 17;
+        /// Frame stored in a dedicated RX buffer
+        const RX_BUFFER_NEW = 1 << 19;
+        /// Entered or left the error passive state
+        const ERROR_PASSIVE = 1 << 23;
+        /// Error warning status changed
+        const ERROR_WARNING = 1 << 24;
+        /// Entered or left the bus off state
+        const BUS_OFF = 1 << 25;
+        /// Protocol error in the arbitration phase
+        const PROTOCOL_ERROR_ARBITRATION = 1 << 27;
+        /// Protocol error in the data phase
+        const PROTOCOL_ERROR_DATA = 1 << 28;
+    }
+}
+
+/// RX FIFO
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fifo {
+    /// RX FIFO 0
+    Fifo0,
+    /// RX FIFO 1
+    Fifo1,
+}
+
+/// Error counters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrorCounters {
+    /// Transmit error counter
+    pub transmit: u8,
+    /// Receive error counter
+    pub receive: u8,
+    /// The receive error counter reached the error passive level
+    pub receive_passive: bool,
+}
+
+// Bits of the CCCR register
+const CCCR_INIT: u32 = 1 << 0;
+const CCCR_CCE: u32 = 1 << 1;
+const CCCR_ASM: u32 = 1 << 2;
+const CCCR_MON: u32 = 1 << 5;
+const CCCR_DAR: u32 = 1 << 6;
+const CCCR_TEST: u32 = 1 << 7;
+const CCCR_FDOE: u32 = 1 << 8;
+const CCCR_BRSE: u32 = 1 << 9;
+
+//==============================================================================
+// Can
+//==============================================================================
+
+/// `Can` encapsulates a CAN peripheral, its pins and its message RAM
+pub struct Can<I, Tx, Rx>
+where
+    I: CanInstance,
+    Tx: TxPin<I>,
+    Rx: RxPin<I>,
+{
+    can: I,
+    tx: Pin<Tx, Alternate<Tx::Function>>,
+    rx: Pin<Rx, Alternate<Rx::Function>>,
+    ram: Layout,
+    dedicated_tx_buffers: u8,
+}
+
+impl<I, Tx, Rx> Can<I, Tx, Rx>
+where
+    I: CanInstance,
+    Tx: TxPin<I>,
+    Rx: RxPin<I>,
+{
+    /// Configure and start the CAN peripheral.
+    ///
+    /// All filters start disabled, so frames are only received once filters
+    /// are set or [`Config::non_matching_standard`] and
+    /// [`Config::non_matching_extended`] accept them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<
+        const STD: usize,
+        const EXT: usize,
+        const RX0: usize,
+        const RX1: usize,
+        const RXB: usize,
+        const TX: usize,
+    >(
+        can: I,
+        tx: impl AnyPin<Id = Tx>,
+        rx: impl AnyPin<Id = Rx>,
+        mclk: &mut MCLK,
+        _clock: &I::Clock,
+        ram: &'static mut MessageRam<STD, EXT, RX0, RX1, RXB, TX>,
+        config: Config,
+    ) -> Result<Self, Error> {
+        let ram = ram.layout()?;
+        if !config.nominal.is_valid_nominal()
+            || !config.data.map_or(true, |data| data.is_valid_data())
+            || config.dedicated_tx_buffers > ram.tx_buffers_len
+        {
+            return Err(Error::InvalidConfig);
+        }
+
+        I::enable_ahb_clock(mclk);
+        let tx = tx.into().into_alternate();
+        let rx = rx.into().into_alternate();
+
+        can.cccr.write(|w| unsafe { w.bits(CCCR_INIT) });
+        while can.cccr.read().bits() & CCCR_INIT == 0 {}
+        can.cccr.write(|w| unsafe { w.bits(CCCR_INIT | CCCR_CCE) });
+
+        let mut can = Self {
+            can,
+            tx,
+            rx,
+            ram,
+            dedicated_tx_buffers: config.dedicated_tx_buffers,
+        };
+        can.configure(&config);
+
+        for index in 0..usize::from(can.ram.standard_filters_len) {
+            can.set_standard_filter(index, StandardFilter::disabled())?;
+        }
+        for index in 0..usize::from(can.ram.extended_filters_len) {
+            can.set_extended_filter(index, ExtendedFilter::disabled())?;
+        }
+
+        // Clearing INIT also clears CCE
+        can.can
+            .cccr
+            .modify(|r, w| unsafe { w.bits(r.bits() & !CCCR_INIT) });
+        while can.can.cccr.read().bits() & CCCR_INIT != 0 {}
+        Ok(can)
+    }
+
+    /// Program the message RAM layout, bit timings and operating mode
+    fn configure(&mut self, config: &Config) {
+        let can = &self.can;
+        let ram = &self.ram;
+        unsafe {
+            can.sidfc.write(|w| {
+                w.bits(
+                    u32::from(ram.standard_filters_len) << 16
+                        | u32::from(offset(ram.standard_filters)),
+                )
+            });
+            can.xidfc.write(|w| {
+                w.bits(
+                    u32::from(ram.extended_filters_len) << 16
+                        | u32::from(offset(ram.extended_filters)),
+                )
+            });
+            can.xidam
+                .write(|w| w.bits(config.extended_id_mask & 0x1FFF_FFFF));
+            can.rxf0c.write(|w| {
+                w.bits(u32::from(ram.rx_fifo0_len) << 16 | u32::from(offset(ram.rx_fifo0)))
+            });
+            can.rxf1c.write(|w| {
+                w.bits(u32::from(ram.rx_fifo1_len) << 16 | u32::from(offset(ram.rx_fifo1)))
+            });
+            can.rxbc
+                .write(|w| w.bits(u32::from(offset(ram.rx_buffers))));
+            // 64-byte data field for RX FIFO 0, RX FIFO 1 and RX buffers
+            can.rxesc.write(|w| w.bits(0x777));
+            can.txefc.write(|w| {
+                w.bits(u32::from(ram.tx_buffers_len) << 16 | u32::from(offset(ram.tx_events)))
+            });
+            let fifo_len = ram.tx_buffers_len - config.dedicated_tx_buffers;
+            can.txbc.write(|w| {
+                w.bits(
+                    u32::from(config.tx_queue) << 30
+                        | u32::from(fifo_len) << 24
+                        | u32::from(config.dedicated_tx_buffers) << 16
+                        | u32::from(offset(ram.tx_buffers)),
+                )
+            });
+            // 64-byte data field for TX buffers
+            can.txesc.write(|w| w.bits(0x7));
+
+            can.gfc.write(|w| {
+                w.anfs().variant(config.non_matching_standard);
+                w.anfe().bits(config.non_matching_extended as u8);
+                w.rrfs().bit(config.reject_remote_standard);
+                w.rrfe().bit(config.reject_remote_extended)
+            });
+
+            let nominal = config.nominal;
+            can.nbtp.write(|w| {
+                w.bits(
+                    u32::from(nominal.sjw - 1) << 25
+                        | u32::from(nominal.prescaler - 1) << 16
+                        | u32::from(nominal.seg1 - 1) << 8
+                        | u32::from(nominal.seg2 - 1),
+                )
+            });
+            if let Some(data) = config.data {
+                // Enable transceiver delay compensation at high data rates,
+                // with the secondary sample point in the middle of the bit
+                let tdc = data.prescaler <= 2;
+                can.dbtp.write(|w| {
+                    w.bits(
+                        u32::from(tdc) << 23
+                            | u32::from(data.prescaler - 1) << 16
+                            | u32::from(data.seg1 - 1) << 8
+                            | u32::from(data.seg2 - 1) << 4
+                            | u32::from(data.sjw - 1),
+                    )
+                });
+                let tdco = data.prescaler * (1 + data.seg1);
+                can.tdcr.write(|w| w.bits(u32::from(tdco.min(127)) << 8));
+            }
+
+            // Timestamps count nominal bit times
+            can.tscc.write(|w| w.bits(1));
+
+            let mut cccr = CCCR_INIT | CCCR_CCE;
+            if !config.auto_retransmit {
+                cccr |= CCCR_DAR;
+            }
+            if config.data.is_some() {
+                cccr |= CCCR_FDOE | CCCR_BRSE;
+            }
+            cccr |= match config.mode {
+                Mode::Normal => 0,
+                Mode::BusMonitoring => CCCR_MON,
+                Mode::Restricted => CCCR_ASM,
+                Mode::InternalLoopback => CCCR_TEST | CCCR_MON,
+                Mode::ExternalLoopback => CCCR_TEST,
+            };
+            can.cccr.write(|w| w.bits(cccr));
+            let loopback = matches!(config.mode, Mode::InternalLoopback | Mode::ExternalLoopback);
+            can.test.write(|w| w.lbck().bit(loopback));
+
+            // Route all interrupts to line 0
+            can.ils.write(|w| w.bits(0));
+            can.ile.write(|w| w.bits(1));
+        }
+    }
+
+    //--------------------------------------------------------------------------
+    // Filters
+    //--------------------------------------------------------------------------
+
+    /// Set the standard ID filter `index`
+    pub fn set_standard_filter(
+        &mut self,
+        index: usize,
+        filter: StandardFilter,
+    ) -> Result<(), Error> {
+        if index >= usize::from(self.ram.standard_filters_len) {
+            return Err(Error::InvalidIndex);
+        }
+        // Safety: the index is within the standard filter section
+        unsafe {
+            ptr::write_volatile(self.ram.standard_filters.add(index), filter.bits());
+        }
+        Ok(())
+    }
+
+    /// Set the extended ID filter `index`
+    pub fn set_extended_filter(
+        &mut self,
+        index: usize,
+        filter: ExtendedFilter,
+    ) -> Result<(), Error> {
+        if index >= usize::from(self.ram.extended_filters_len) {
+            return Err(Error::InvalidIndex);
+        }
+        // Safety: the index is within the extended filter section
+        unsafe {
+            ptr::write_volatile(self.ram.extended_filters.add(index), filter.bits());
+        }
+        Ok(())
+    }
+
+    //--------------------------------------------------------------------------
+    // Transmit
+    //--------------------------------------------------------------------------
+
+    /// Write `frame` into the TX buffer `index` and request its transmission
+    fn write_element(&mut self, index: u8, frame: &Frame) {
+        let (header, data) = frame.encode(index, self.ram.tx_buffers_len > 0);
+        // Safety: the caller checks the index, and the buffer has no pending
+        // transmission request
+        unsafe {
+            ptr::write_volatile(
+                self.ram.tx_buffers.add(usize::from(index)),
+                Element { header, data },
+            );
+        }
+    }
+
+    /// Queue a frame in the TX FIFO or TX queue.
+    ///
+    /// Return the index of the TX buffer used, which is recorded in the
+    /// matching [`TxEvent`]. Return [`nb::Error::WouldBlock`] if the TX FIFO
+    /// or queue is full.
+    pub fn transmit(&mut self, frame: &Frame) -> nb::Result<u8, Error> {
+        if self.can.psr.read().bo().bit_is_set() {
+            return Err(nb::Error::Other(Error::BusOff));
+        }
+        let status = self.can.txfqs.read();
+        if status.tfqf().bit_is_set() || self.dedicated_tx_buffers == self.ram.tx_buffers_len {
+            return Err(nb::Error::WouldBlock);
+        }
+        let index = status.tfqpi().bits();
+        self.write_element(index, frame);
+        self.can.txbar.write(|w| unsafe { w.bits(1 << index) });
+        Ok(index)
+    }
+
+    /// Write a frame into the dedicated TX buffer `index`, without requesting
+    /// its transmission.
+    ///
+    /// Return [`nb::Error::WouldBlock`] if the buffer still has a pending
+    /// transmission request.
+    pub fn write_tx_buffer(&mut self, index: u8, frame: &Frame) -> nb::Result<(), Error> {
+        if index >= self.dedicated_tx_buffers {
+            return Err(nb::Error::Other(Error::InvalidIndex));
+        }
+        if self.can.txbrp.read().bits() & (1 << index) != 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.write_element(index, frame);
+        Ok(())
+    }
+
+    /// Request the transmission of the dedicated TX buffer `index`
+    pub fn request_tx_buffer(&mut self, index: u8) -> Result<(), Error> {
+        if index >= self.dedicated_tx_buffers {
+            return Err(Error::InvalidIndex);
+        }
+        self.can.txbar.write(|w| unsafe { w.bits(1 << index) });
+        Ok(())
+    }
+
+    /// Request the cancellation of a pending transmission from the TX buffer
+    /// `index`
+    pub fn cancel(&mut self, index: u8) {
+        if index < self.ram.tx_buffers_len {
+            self.can.txbcr.write(|w| unsafe { w.bits(1 << index) });
+        }
+    }
+
+    /// Return whether the last transmission requested from the TX buffer
+    /// `index` has completed
+    pub fn is_transmitted(&self, index: u8) -> bool {
+        index < 32 && self.can.txbto.read().bits() & (1 << index) != 0
+    }
+
+    /// Return whether a transmission is pending in any TX buffer
+    pub fn is_transmitting(&self) -> bool {
+        self.can.txbrp.read().bits() != 0
+    }
+
+    /// Read the oldest entry of the TX event FIFO
+    pub fn read_tx_event(&mut self) -> Option<TxEvent> {
+        let status = self.can.txefs.read();
+        if status.effl().bits() == 0 {
+            return None;
+        }
+        let index = status.efgi().bits();
+        // Safety: the get index is always within the TX event section
+        let event = unsafe { ptr::read_volatile(self.ram.tx_events.add(usize::from(index))) };
+        self.can.txefa.write(|w| unsafe { w.efai().bits(index) });
+        Some(TxEvent::decode(event))
+    }
+
+    //--------------------------------------------------------------------------
+    // Receive
+    //--------------------------------------------------------------------------
+
+    /// Read a frame from RX FIFO 0, or from RX FIFO 1 if FIFO 0 is empty
+    pub fn receive(&mut self) -> nb::Result<Frame, Error> {
+        match self.receive_fifo(Fifo::Fifo0) {
+            Err(nb::Error::WouldBlock) => self.receive_fifo(Fifo::Fifo1),
+            result => result,
+        }
+    }
+
+    /// Read a frame from an RX FIFO.
+    ///
+    /// Return [`Error::Overrun`] once after a frame was lost because the FIFO
+    /// was full.
+    pub fn receive_fifo(&mut self, fifo: Fifo) -> nb::Result<Frame, Error> {
+        let (status, elements, lost) = match fifo {
+            Fifo::Fifo0 => (
+                self.can.rxf0s.read().bits(),
+                self.ram.rx_fifo0,
+                Interrupts::RX_FIFO0_LOST,
+            ),
+            Fifo::Fifo1 => (
+                self.can.rxf1s.read().bits(),
+                self.ram.rx_fifo1,
+                Interrupts::RX_FIFO1_LOST,
+            ),
+        };
+
+        if self.interrupt_flags().contains(lost) {
+            self.clear_interrupt_flags(lost);
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+
+        let fill_level = status & 0x7F;
+        if fill_level == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        let index = (status >> 8) & 0x3F;
+        // Safety: the get index is always within the FIFO section
+        let element = unsafe { ptr::read_volatile(elements.add(index as usize)) };
+        match fifo {
+            Fifo::Fifo0 => self.can.rxf0a.write(|w| unsafe { w.bits(index) }),
+            Fifo::Fifo1 => self.can.rxf1a.write(|w| unsafe { w.bits(index) }),
+        }
+        Ok(Frame::decode(element.header, &element.data))
+    }
+
+    /// Read a frame from the dedicated RX buffer `index`, if a new frame was
+    /// stored in it
+    pub fn receive_buffer(&mut self, index: u8) -> nb::Result<Frame, Error> {
+        if index >= self.ram.rx_buffers_len {
+            return Err(nb::Error::Other(Error::InvalidIndex));
+        }
+        let new_data = if index < 32 {
+            self.can.ndat1.read().bits() & 1 << index
+        } else {
+            self.can.ndat2.read().bits() & 1 << (index - 32)
+        };
+        if new_data == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        // Safety: the index is within the RX buffer section
+        let element = unsafe { ptr::read_volatile(self.ram.rx_buffers.add(usize::from(index))) };
+        // Clear the new data flag
+        if index < 32 {
+            self.can.ndat1.write(|w| unsafe { w.bits(new_data) });
+        } else {
+            self.can.ndat2.write(|w| unsafe { w.bits(new_data) });
+        }
+        Ok(Frame::decode(element.header, &element.data))
+    }
+
+    //--------------------------------------------------------------------------
+    // Interrupts & status
+    //--------------------------------------------------------------------------
+
+    /// Enable the given interrupts
+    pub fn enable_interrupts(&mut self, interrupts: Interrupts) {
+        self.can
+            .ie
+            .modify(|r, w| unsafe { w.bits(r.bits() | interrupts.bits()) });
+    }
+
+    /// Disable the given interrupts
+    pub fn disable_interrupts(&mut self, interrupts: Interrupts) {
+        self.can
+            .ie
+            .modify(|r, w| unsafe { w.bits(r.bits() & !interrupts.bits()) });
+    }
+
+    /// Read the interrupt flags
+    pub fn interrupt_flags(&self) -> Interrupts {
+        Interrupts::from_bits_truncate(self.can.ir.read().bits())
+    }
+
+    /// Clear the given interrupt flags
+    pub fn clear_interrupt_flags(&mut self, interrupts: Interrupts) {
+        self.can.ir.write(|w| unsafe { w.bits(interrupts.bits()) });
+    }
+
+    /// Read the error counters
+    pub fn error_counters(&self) -> ErrorCounters {
+        let ecr = self.can.ecr.read();
+        ErrorCounters {
+            transmit: ecr.tec().bits(),
+            receive: ecr.rec().bits(),
+            receive_passive: ecr.rp().bit_is_set(),
+        }
+    }
+
+    /// Return the last protocol error in the arbitration phase, if any, and
+    /// clear it
+    pub fn last_error(&self) -> Option<Error> {
+        use can0::psr::LEC_A;
+        let psr = self.can.psr.read();
+        if psr.bo().bit_is_set() {
+            return Some(Error::BusOff);
+        }
+        match psr.lec().variant() {
+            LEC_A::STUFF => Some(Error::Stuff),
+            LEC_A::FORM => Some(Error::Form),
+            LEC_A::ACK => Some(Error::Ack),
+            LEC_A::BIT1 | LEC_A::BIT0 => Some(Error::Bit),
+            LEC_A::CRC => Some(Error::Crc),
+            LEC_A::NONE | LEC_A::NC => None,
+        }
+    }
+
+    /// Return whether the controller is in the error passive state
+    pub fn is_error_passive(&self) -> bool {
+        self.can.psr.read().ep().bit_is_set()
+    }
+
+    /// Return whether the controller is in the bus off state
+    pub fn is_bus_off(&self) -> bool {
+        self.can.psr.read().bo().bit_is_set()
+    }
+
+    /// Start the recovery from the bus off state. The controller resumes
+    /// operation after monitoring 129 occurrences of 11 recessive bits.
+    pub fn recover_from_bus_off(&mut self) {
+        self.can
+            .cccr
+            .modify(|r, w| unsafe { w.bits(r.bits() & !CCCR_INIT) });
+    }
+
+    /// Stop the CAN peripheral and return it along with its pins. The message
+    /// RAM is no longer used by the peripheral.
+    #[allow(clippy::type_complexity)]
+    pub fn free(
+        self,
+    ) -> (
+        I,
+        Pin<Tx, Alternate<Tx::Function>>,
+        Pin<Rx, Alternate<Rx::Function>>,
+    ) {
+        self.can.cccr.write(|w| unsafe { w.bits(CCCR_INIT) });
+        while self.can.cccr.read().bits() & CCCR_INIT == 0 {}
+        (self.can, self.tx, self.rx)
+    }
+}
+
+//==============================================================================
+// embedded-can
+//==============================================================================
+
+impl<I, Tx, Rx> embedded_can::nb::Can for Can<I, Tx, Rx>
+where
+    I: CanInstance,
+    Tx: TxPin<I>,
+    Rx: RxPin<I>,
+{
+    type Frame = Frame;
+    type Error = Error;
+
+    fn transmit(&mut self, frame: &Frame) -> nb::Result<Option<Frame>, Error> {
+        Can::transmit(self, frame).map(|_| None)
+    }
+
+    fn receive(&mut self) -> nb::Result<Frame, Error> {
+        Can::receive(self)
+    }
+}
+
+impl<I, Tx, Rx> embedded_can::blocking::Can for Can<I, Tx, Rx>
+where
+    I: CanInstance,
+    Tx: TxPin<I>,
+    Rx: RxPin<I>,
+{
+    type Frame = Frame;
+    type Error = Error;
+
+    fn transmit(&mut self, frame: &Frame) -> Result<(), Error> {
+        let index = nb::block!(Can::transmit(self, frame))?;
+        while !self.is_transmitted(index) {
+            if self.is_bus_off() {
+                return Err(Error::BusOff);
+            }
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Frame, Error> {
+        nb::block!(Can::receive(self))
+    }
+}
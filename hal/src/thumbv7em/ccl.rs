@@ -0,0 +1,346 @@
+//! # Configurable Custom Logic
+//!
+//! The Configurable Custom Logic (CCL) contains four programmable look-up
+//! tables (LUTs). Each LUT computes an arbitrary boolean function of its three
+//! inputs, defined by an 8-bit truth table. Bit `n` of the truth table is the
+//! output of the LUT when the inputs, read as a binary number with input 0 as
+//! the least significant bit, are equal to `n`.
+//!
+//! The LUTs are grouped in pairs (`Lut0` with `Lut1`, `Lut2` with `Lut3`).
+//! The outputs of each pair can be combined by a sequential element (a D
+//! flip-flop, a JK flip-flop, a gated D latch or an RS latch), configured with
+//! [`Ccl::set_sequential`].
+//!
+//! # Initializing
+//!
+//! The CCL configuration is enable-protected. A [`Ccl`] is therefore created
+//! in the [`Disabled`] state, in which the LUTs are configured with
+//! [`Ccl::configure`], and then [enabled](Ccl::enable).
+//!
+//! # Pins
+//!
+//! LUT inputs and outputs are connected to pins in alternate function N.
+//! Input pins are checked at compile time by [`LutConfig::io_input`], which
+//! only accepts pins whose [`PinId`] implements [`CclInput`] for the LUT being
+//! configured. Pins implementing [`CclOutput`] output the value of the LUT
+//! once they are in alternate function N.
+//!
+//! # Example
+//!
+//! ```
+//! let mut ccl = Ccl::new(peripherals.CCL, &mut peripherals.MCLK);
+//!
+//! // Output PA04 XOR PA05 on PA07
+//! let in0: Pin<_, AlternateN> = pins.pa04.into_mode();
+//! let in1: Pin<_, AlternateN> = pins.pa05.into_mode();
+//! let _out: Pin<_, AlternateN> = pins.pa07.into_mode();
+//! let config = LutConfig::<Lut0>::new(0b0110)
+//!     .io_input(&in0)
+//!     .io_input(&in1);
+//! ccl.configure(config);
+//! let ccl = ccl.enable();
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::gpio::*;
+use crate::pac::{ccl, CCL, MCLK};
+use crate::typelevel::Sealed;
+
+/// Filter applied to the LUT output
+pub use ccl::lutctrl::FILTSEL_A as Filter;
+/// Source of a LUT input
+pub use ccl::lutctrl::INSEL0_A as InputSource;
+/// Sequential element combining the outputs of a pair of LUTs
+pub use ccl::seqctrl::SEQSEL_A as Sequential;
+
+//==============================================================================
+// LutId, SeqId & InputIndex
+//==============================================================================
+
+/// Type-level enum of the LUTs
+pub trait LutId: Sealed {
+    /// LUT number
+    const N: usize;
+}
+
+/// Type-level enum of the sequential elements
+pub trait SeqId: Sealed {
+    /// Sequential element number
+    const N: usize;
+}
+
+/// Type-level enum of the LUT inputs
+pub trait InputIndex: Sealed {
+    /// Input number
+    const N: usize;
+}
+
+macro_rules! ids {
+    ( $Trait:ident: $( $Type:ident = $n:literal, $doc:literal ),+ ) => {
+        $(
+            #[doc = $doc]
+            pub enum $Type {}
+
+            impl Sealed for $Type {}
+
+            impl $Trait for $Type {
+                const N: usize = $n;
+            }
+        )+
+    };
+}
+
+ids!(
+    LutId:
+    Lut0 = 0, "LUT 0",
+    Lut1 = 1, "LUT 1",
+    Lut2 = 2, "LUT 2",
+    Lut3 = 3, "LUT 3"
+);
+
+ids!(
+    SeqId:
+    Seq0 = 0, "Sequential element of LUT 0 and LUT 1",
+    Seq1 = 1, "Sequential element of LUT 2 and LUT 3"
+);
+
+ids!(
+    InputIndex:
+    In0 = 0, "LUT input 0",
+    In1 = 1, "LUT input 1",
+    In2 = 2, "LUT input 2"
+);
+
+//==============================================================================
+// Pin routing
+//==============================================================================
+
+/// Pins which can be used as an input of the LUT `L`, in alternate function N
+pub trait CclInput<L: LutId>: PinId {
+    /// Input of the LUT connected to the pin
+    type Index: InputIndex;
+}
+
+/// Pins which output the value of the LUT `L`, in alternate function N
+pub trait CclOutput<L: LutId>: PinId {}
+
+macro_rules! ccl_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident: $Lut:ident $( [ $Index:ident ] )?,
+        )+
+    ) => {
+        $(
+            ccl_pins!(@impl $( #[$cfg] )? $PinId, $Lut $(, $Index )?);
+        )+
+    };
+    (@impl $( #[$cfg:meta] )? $PinId:ident, $Lut:ident, $Index:ident) => {
+        $( #[$cfg] )?
+        impl CclInput<$Lut> for $PinId {
+            type Index = $Index;
+        }
+    };
+    (@impl $( #[$cfg:meta] )? $PinId:ident, $Lut:ident) => {
+        $( #[$cfg] )?
+        impl CclOutput<$Lut> for $PinId {}
+    };
+}
+
+ccl_pins!(
+    PA04: Lut0[In0],
+    PA05: Lut0[In1],
+    PA06: Lut0[In2],
+    PA07: Lut0,
+    PA08: Lut1[In0],
+    PA09: Lut1[In1],
+    PA10: Lut1[In2],
+    PA11: Lut1,
+    PA16: Lut0[In0],
+    PA17: Lut0[In1],
+    PA18: Lut0[In2],
+    PA19: Lut0,
+    PA20: Lut3[In0],
+    PA21: Lut3[In1],
+    PA22: Lut3[In2],
+    PA23: Lut3,
+    PA24: Lut2,
+    PA30: Lut1[In0],
+    PA31: Lut1,
+    #[cfg(feature = "min-samd51j")]
+    PB00: Lut0[In1],
+    #[cfg(feature = "min-samd51j")]
+    PB01: Lut0[In2],
+    PB02: Lut0,
+    #[cfg(feature = "min-samd51j")]
+    PB06: Lut2[In0],
+    #[cfg(feature = "min-samd51j")]
+    PB07: Lut2[In1],
+    PB08: Lut2[In2],
+    PB09: Lut2,
+    PB10: Lut1[In1],
+    PB11: Lut1[In2],
+    #[cfg(feature = "min-samd51j")]
+    PB14: Lut3[In0],
+    #[cfg(feature = "min-samd51j")]
+    PB15: Lut3[In1],
+    #[cfg(feature = "min-samd51j")]
+    PB16: Lut3[In2],
+    #[cfg(feature = "min-samd51j")]
+    PB17: Lut3,
+    PB22: Lut0[In0],
+    PB23: Lut0,
+);
+
+//==============================================================================
+// LutConfig
+//==============================================================================
+
+/// Configuration of the LUT `L`
+#[derive(Clone, Copy)]
+pub struct LutConfig<L: LutId> {
+    /// Truth table
+    pub truth: u8,
+    /// Sources of the three inputs
+    pub inputs: [InputSource; 3],
+    /// Output filter
+    pub filter: Filter,
+    /// Enable the edge detector on the output
+    pub edge_detector: bool,
+    /// Use the incoming event as an input (selected with
+    /// `InputSource::EVENT`)
+    pub event_input: bool,
+    /// Invert the incoming event
+    pub invert_event: bool,
+    /// Enable the LUT output event
+    pub event_output: bool,
+    lut: PhantomData<L>,
+}
+
+impl<L: LutId> LutConfig<L> {
+    /// Create a configuration with the given truth table. All inputs are
+    /// masked, and the filter, edge detector and events are disabled.
+    pub const fn new(truth: u8) -> Self {
+        Self {
+            truth,
+            inputs: [InputSource::MASK; 3],
+            filter: Filter::DISABLE,
+            edge_detector: false,
+            event_input: false,
+            invert_event: false,
+            event_output: false,
+            lut: PhantomData,
+        }
+    }
+
+    /// Select the source of an input
+    pub fn input<I: InputIndex>(mut self, source: InputSource) -> Self {
+        self.inputs[I::N] = source;
+        self
+    }
+
+    /// Connect a pin to the matching input of the LUT
+    pub fn io_input<Id: CclInput<L>>(self, _pin: &Pin<Id, AlternateN>) -> Self {
+        self.input::<Id::Index>(InputSource::IO)
+    }
+}
+
+//==============================================================================
+// Ccl
+//==============================================================================
+
+/// Type-level enum of the CCL states
+pub trait State: Sealed {}
+
+/// CCL is disabled and can be configured
+pub enum Disabled {}
+
+impl Sealed for Disabled {}
+impl State for Disabled {}
+
+/// CCL is enabled
+pub enum Enabled {}
+
+impl Sealed for Enabled {}
+impl State for Enabled {}
+
+/// `Ccl` encapsulates the device CCL
+pub struct Ccl<S: State> {
+    ccl: CCL,
+    state: PhantomData<S>,
+}
+
+impl Ccl<Disabled> {
+    /// Enable the CCL bus clock and reset the peripheral.
+    ///
+    /// The generic clock of the CCL is only required by the filters, edge
+    /// detectors and sequential elements. When they are used, it must be
+    /// configured through the
+    /// [`GenericClockController`](crate::clock::GenericClockController).
+    pub fn new(ccl: CCL, mclk: &mut MCLK) -> Self {
+        mclk.apbcmask.modify(|_, w| w.ccl_().set_bit());
+        ccl.ctrl.write(|w| w.swrst().set_bit());
+        while ccl.ctrl.read().swrst().bit_is_set() {}
+        Self {
+            ccl,
+            state: PhantomData,
+        }
+    }
+
+    /// Configure and enable the LUT `L`
+    pub fn configure<L: LutId>(&mut self, config: LutConfig<L>) {
+        self.ccl.lutctrl[L::N].write(|w| unsafe {
+            w.truth().bits(config.truth);
+            w.insel0().bits(config.inputs[0] as u8);
+            w.insel1().bits(config.inputs[1] as u8);
+            w.insel2().bits(config.inputs[2] as u8);
+            w.filtsel().variant(config.filter);
+            w.edgesel().bit(config.edge_detector);
+            w.lutei().bit(config.event_input);
+            w.invei().bit(config.invert_event);
+            w.luteo().bit(config.event_output);
+            w.enable().set_bit()
+        });
+    }
+
+    /// Disable the LUT `L`
+    pub fn disable_lut<L: LutId>(&mut self) {
+        self.ccl.lutctrl[L::N].reset();
+    }
+
+    /// Select the sequential element `S`
+    pub fn set_sequential<S: SeqId>(&mut self, sequential: Sequential) {
+        self.ccl.seqctrl[S::N].write(|w| w.seqsel().variant(sequential));
+    }
+
+    /// Keep the CCL running in standby sleep mode
+    pub fn run_in_standby(&mut self, enabled: bool) {
+        self.ccl.ctrl.modify(|_, w| w.runstdby().bit(enabled));
+    }
+
+    /// Enable the CCL
+    pub fn enable(self) -> Ccl<Enabled> {
+        self.ccl.ctrl.modify(|_, w| w.enable().set_bit());
+        Ccl {
+            ccl: self.ccl,
+            state: PhantomData,
+        }
+    }
+
+    /// Return the underlying peripheral
+    pub fn free(self) -> CCL {
+        self.ccl
+    }
+}
+
+impl Ccl<Enabled> {
+    /// Disable the CCL, allowing it to be reconfigured
+    pub fn disable(self) -> Ccl<Disabled> {
+        self.ccl.ctrl.modify(|_, w| w.enable().clear_bit());
+        Ccl {
+            ccl: self.ccl,
+            state: PhantomData,
+        }
+    }
+}
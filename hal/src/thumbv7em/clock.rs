@@ -3,6 +3,11 @@
 //! before you can set up most of the peripherals on the atsamd51 device.
 //! The other types in this module are used to enforce at compile time
 //! that the peripherals have been correctly configured.
+//!
+//! The default constructors run the CPU at 120MHz from DPLL0. Other
+//! configurations, including the crystal oscillators, both DPLLs and the
+//! DFLL48M in closed-loop mode, can be described with a [`tree::ClockTree`]
+//! and applied through [`GenericClockController::from_tree`].
 #![allow(clippy::from_over_into)]
 
 use crate::pac::gclk::genctrl::SRC_A::*;
@@ -10,10 +15,15 @@ use crate::pac::gclk::pchctrl::GEN_A::*;
 use crate::pac::{self, GCLK, MCLK, NVMCTRL, OSC32KCTRL, OSCCTRL};
 use crate::time::{Hertz, MegaHertz};
 
+pub mod tree;
+
+use tree::{ClockTree, DfllMode, DpllRef, Node, Source, XoscConfig, GCLK_IDS};
+
 pub type ClockGenId = pac::gclk::pchctrl::GEN_A;
 pub type ClockSource = pac::gclk::genctrl::SRC_A;
 
 #[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ClockId {
     DFLL48 = 0,
     FDPLL0,
@@ -227,7 +237,7 @@ impl GenericClockController {
         while state.gclk.syncbusy.read().genctrl().is_gclk5() {}
 
         configure_and_enable_dpll0(oscctrl, &mut state.gclk);
-        wait_for_dpllrdy(oscctrl);
+        wait_for_dpllrdy(oscctrl, 0);
 
         unsafe {
             // GCLK0 set to DPLL0 (120MHz)
@@ -263,6 +273,113 @@ impl GenericClockController {
         }
     }
 
+    /// Reset the clock controller and configure the clock sources and
+    /// generators described by `tree`.
+    ///
+    /// The tree is validated with [`ClockTree::compute`] before any register
+    /// is written, and its clocks are enabled in dependency order. The flash
+    /// wait states and the CPU divider are set according to the resulting CPU
+    /// frequency.
+    pub fn from_tree(
+        tree: &ClockTree,
+        gclk: GCLK,
+        mclk: &mut MCLK,
+        osc32kctrl: &mut OSC32KCTRL,
+        oscctrl: &mut OSCCTRL,
+        nvmctrl: &mut NVMCTRL,
+    ) -> Result<Self, tree::Error> {
+        let freqs = tree.compute()?;
+        let mut state = State { gclk };
+
+        // Use the maximum number of wait states while the clocks are switched
+        set_flash_wait_states(nvmctrl, tree::flash_wait_states(tree::CPU_MAX_FREQ));
+        enable_gclk_apb(mclk);
+
+        if tree.xosc32k {
+            enable_external_32kosc(osc32kctrl);
+        } else {
+            enable_internal_32kosc(osc32kctrl);
+        }
+        state.reset_gclk();
+
+        // Divide the CPU clock before GCLK0 is switched, so that the CPU never
+        // runs above its limit. Until then, it only runs slower.
+        // SAFETY: The divider was checked to be a power of two by `compute`
+        mclk.cpudiv.write(|w| unsafe { w.div().bits(tree.cpu_div) });
+
+        let mut used_clocks = 0;
+        for node in freqs.order.iter().flatten() {
+            match *node {
+                Node::Xosc(index) => {
+                    if let Some(config) = tree.xosc[index] {
+                        configure_and_enable_xosc(oscctrl, index, &config);
+                    }
+                }
+                Node::Xosc32k => (),
+                Node::Dfll => {
+                    if let DfllMode::ClosedLoop(reference) = tree.dfll {
+                        used_clocks |= 1u64 << u8::from(ClockId::DFLL48);
+                        state.enable_clock_generator(ClockId::DFLL48, reference);
+                    }
+                    configure_dfll(oscctrl, tree.dfll, freqs.dfll_mul);
+                }
+                Node::Dpll(index) => {
+                    if let Some(config) = tree.dpll[index] {
+                        if let DpllRef::Gclk(reference) = config.reference {
+                            let clock = match index {
+                                0 => ClockId::FDPLL0,
+                                _ => ClockId::FDPLL1,
+                            };
+                            used_clocks |= 1u64 << u8::from(clock);
+                            state.enable_clock_generator(clock, reference);
+                        }
+                        configure_and_enable_dpll(
+                            oscctrl,
+                            index,
+                            config.reference,
+                            freqs.dpll_ratio[index],
+                        );
+                        wait_for_dpllrdy(oscctrl, index);
+                    }
+                }
+                Node::Gclk(index) => {
+                    if let Some(config) = tree.gclk[index] {
+                        let src = match config.source? {
+                            Source::Xosc0 => XOSC0,
+                            Source::Xosc1 => XOSC1,
+                            Source::GclkIn(_) => GCLKIN,
+                            Source::Gclk1 => GCLKGEN1,
+                            Source::OscUlp32k => OSCULP32K,
+                            Source::Xosc32k => XOSC32K,
+                            Source::Dfll => DFLL,
+                            Source::Dpll0 => DPLL0,
+                            Source::Dpll1 => DPLL1,
+                        };
+                        state.set_gclk_divider_and_source(
+                            GCLK_IDS[index],
+                            config.div as u16,
+                            src,
+                            config.improve_duty_cycle,
+                        );
+                    }
+                }
+            }
+        }
+
+        set_flash_wait_states(nvmctrl, freqs.wait_states);
+
+        let mut gclks = [Hertz(0); 12];
+        for (freq, id) in gclks.iter_mut().zip(GCLK_IDS.iter()) {
+            *freq = freqs.gclk(*id).unwrap_or(Hertz(0));
+        }
+
+        Ok(Self {
+            state,
+            gclks,
+            used_clocks,
+        })
+    }
+
     /// Returns a `GClock` for gclk0, the 120MHz oscillator.
     pub fn gclk0(&mut self) -> GClock {
         GClock {
@@ -464,6 +581,12 @@ fn set_flash_to_half_auto_wait_state(nvmctrl: &mut NVMCTRL) {
     nvmctrl.ctrla.modify(|_, w| unsafe { w.rws().bits(0b0111) });
 }
 
+fn set_flash_wait_states(nvmctrl: &mut NVMCTRL, wait_states: u8) {
+    nvmctrl
+        .ctrla
+        .modify(|_, w| unsafe { w.rws().bits(wait_states) });
+}
+
 fn enable_gclk_apb(mclk: &mut MCLK) {
     mclk.apbamask.modify(|_, w| w.gclk_().set_bit());
 }
@@ -497,9 +620,13 @@ fn enable_external_32kosc(osc32kctrl: &mut OSC32KCTRL) {
     while osc32kctrl.status.read().xosc32krdy().bit_is_clear() {}
 }
 
-fn wait_for_dpllrdy(oscctrl: &mut OSCCTRL) {
-    while oscctrl.dpll[0].dpllstatus.read().lock().bit_is_clear()
-        || oscctrl.dpll[0].dpllstatus.read().clkrdy().bit_is_clear()
+fn wait_for_dpllrdy(oscctrl: &mut OSCCTRL, index: usize) {
+    while oscctrl.dpll[index].dpllstatus.read().lock().bit_is_clear()
+        || oscctrl.dpll[index]
+            .dpllstatus
+            .read()
+            .clkrdy()
+            .bit_is_clear()
     {}
 }
 
@@ -522,7 +649,6 @@ fn configure_and_enable_dpll0(oscctrl: &mut OSCCTRL, gclk: &mut GCLK) {
     });
 }
 
-#[cfg(feature = "usb")]
 /// Configure the dfll48m to calibrate against the 1Khz USB SOF reference.
 fn configure_usb_correction(oscctrl: &mut OSCCTRL) {
     oscctrl.dfllmul.write(|w| unsafe {
@@ -543,3 +669,74 @@ fn configure_usb_correction(oscctrl: &mut OSCCTRL) {
     });
     while oscctrl.dfllsync.read().dfllctrlb().bit_is_set() {}
 }
+
+/// Configure and enable a crystal oscillator, then wait for it to be ready
+fn configure_and_enable_xosc(oscctrl: &mut OSCCTRL, index: usize, config: &XoscConfig) {
+    let (imult, iptat) = config.currents();
+    oscctrl.xoscctrl[index].write(|w| unsafe {
+        w.imult().bits(imult);
+        w.iptat().bits(iptat);
+        w.xtalen().bit(config.crystal);
+        w.enalc().bit(config.crystal);
+        w.startup().cycle32();
+        w.ondemand().clear_bit();
+        w.enable().set_bit()
+    });
+    let status = || oscctrl.status.read();
+    match index {
+        0 => while status().xoscrdy0().bit_is_clear() {},
+        _ => while status().xoscrdy1().bit_is_clear() {},
+    }
+}
+
+/// Configure the DFLL48M operating mode
+fn configure_dfll(oscctrl: &mut OSCCTRL, mode: DfllMode, mul: u16) {
+    match mode {
+        DfllMode::OpenLoop => {
+            oscctrl.dfllctrlb.write(|w| w);
+            while oscctrl.dfllsync.read().dfllctrlb().bit_is_set() {}
+        }
+        DfllMode::UsbRecovery => configure_usb_correction(oscctrl),
+        DfllMode::ClosedLoop(_) => {
+            oscctrl.dfllmul.write(|w| unsafe {
+                w.cstep().bits(0x1);
+                w.fstep().bits(0x1);
+                w.mul().bits(mul)
+            });
+            while oscctrl.dfllsync.read().dfllmul().bit_is_set() {}
+
+            // closed loop mode
+            oscctrl.dfllctrlb.write(|w| w.mode().set_bit());
+            while oscctrl.dfllsync.read().dfllctrlb().bit_is_set() {}
+            while oscctrl.status.read().dfllrdy().bit_is_clear() {}
+        }
+    }
+}
+
+/// Configure and enable a DPLL with the given `(LDR, LDRFRAC)` ratio
+fn configure_and_enable_dpll(
+    oscctrl: &mut OSCCTRL,
+    index: usize,
+    reference: DpllRef,
+    (ldr, ldrfrac): (u16, u8),
+) {
+    let dpll = &oscctrl.dpll[index];
+    dpll.dpllratio.write(|w| unsafe {
+        w.ldr().bits(ldr);
+        w.ldrfrac().bits(ldrfrac)
+    });
+    while dpll.dpllsyncbusy.read().dpllratio().bit_is_set() {}
+
+    dpll.dpllctrlb.write(|w| match reference {
+        DpllRef::Xosc32k => w.refclk().xosc32(),
+        // The XOSC reference is divided by 2 * (DIV + 1)
+        DpllRef::Xosc0 { prediv } => unsafe { w.refclk().xosc0().div().bits(prediv / 2 - 1) },
+        DpllRef::Xosc1 { prediv } => unsafe { w.refclk().xosc1().div().bits(prediv / 2 - 1) },
+        DpllRef::Gclk(_) => w.refclk().gclk(),
+    });
+    dpll.dpllctrla.write(|w| {
+        w.enable().set_bit();
+        w.ondemand().clear_bit()
+    });
+    while dpll.dpllsyncbusy.read().enable().bit_is_set() {}
+}
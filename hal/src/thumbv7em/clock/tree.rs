@@ -0,0 +1,1124 @@
+//! # Clock tree configuration
+//!
+//! This module describes a complete SAMD5x/E5x clock tree at runtime, checks
+//! it against the datasheet frequency limits and computes the resulting
+//! frequencies, before anything is written to the hardware. A validated
+//! [`ClockTree`] is then applied with
+//! [`GenericClockController::from_tree`](super::GenericClockController::from_tree).
+//!
+//! Every clock source is represented by a token, which is handed out when the
+//! source is added to a tree: [`Xosc0`], [`Xosc1`], [`Xosc32k`],
+//! [`OscUlp32k`], [`Dfll`], [`Dpll0`], [`Dpll1`] and [`Gclk`] (for GCLK0 to
+//! GCLK11). Clock generators and PLLs can only be fed from tokens, so a source
+//! must be configured before it can be referenced. Tokens are `Copy` and are
+//! not tied to the tree they came from, though: a token used in another tree
+//! is only caught by [`ClockTree::compute`], which returns
+//! [`Error::NotConfigured`] if its source is missing from that tree.
+//!
+//! The DFLL48M runs open-loop and feeds GCLK0 at reset. A tree created with
+//! [`ClockTree::new`] starts from that state.
+//!
+//! ```no_run
+//! use atsamd_hal::clock::tree::{ClockTree, DpllReference, XoscConfig};
+//! use atsamd_hal::clock::{ClockGenId, GenericClockController};
+//! use atsamd_hal::pac::Peripherals;
+//! use atsamd_hal::time::MegaHertz;
+//!
+//! let mut peripherals = Peripherals::take().unwrap();
+//!
+//! let mut tree = ClockTree::new();
+//! // 12 MHz crystal, divided by 4 to provide a 3 MHz DPLL reference
+//! let xosc0 = tree.xosc0(XoscConfig::crystal(MegaHertz(12)));
+//! let dpll0 = tree.dpll0(DpllReference::xosc0(xosc0, 4), MegaHertz(120));
+//! tree.gclk(ClockGenId::GCLK0, dpll0, 1);
+//! let gclk2 = tree.gclk(ClockGenId::GCLK2, dpll0, 2);
+//!
+//! let freqs = tree.compute().unwrap();
+//! assert_eq!(freqs.cpu().0, 120_000_000);
+//! assert_eq!(freqs.gclk(gclk2.id()).unwrap().0, 60_000_000);
+//!
+//! let mut clocks = GenericClockController::from_tree(
+//!     &tree,
+//!     peripherals.GCLK,
+//!     &mut peripherals.MCLK,
+//!     &mut peripherals.OSC32KCTRL,
+//!     &mut peripherals.OSCCTRL,
+//!     &mut peripherals.NVMCTRL,
+//! )
+//! .unwrap();
+//! ```
+//!
+//! The computations performed by this module do not touch any register, and
+//! can be unit tested on the host.
+
+use super::{ClockGenId, ClockId};
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+//==============================================================================
+// Frequency limits
+//==============================================================================
+
+/// Frequency of the DFLL48M in open-loop mode
+pub const DFLL_FREQ: Hertz = Hertz(48_000_000);
+/// Frequency of the 32 kHz oscillators
+pub const OSC32K_FREQ: Hertz = Hertz(32_768);
+
+/// Maximum CPU frequency
+pub const CPU_MAX_FREQ: Hertz = Hertz(120_000_000);
+/// Maximum frequency of a generic clock generator
+pub const GCLK_MAX_FREQ: Hertz = Hertz(200_000_000);
+
+const XOSC_CRYSTAL_MIN: Hertz = Hertz(8_000_000);
+const XOSC_MAX: Hertz = Hertz(48_000_000);
+const DPLL_REF_MIN: Hertz = Hertz(32_000);
+const DPLL_REF_MAX: Hertz = Hertz(3_200_000);
+const DPLL_OUT_MIN: Hertz = Hertz(96_000_000);
+const DPLL_OUT_MAX: Hertz = Hertz(200_000_000);
+const DFLL_REF_MIN: Hertz = Hertz(732);
+const DFLL_REF_MAX: Hertz = Hertz(33_000);
+const PERIPHERAL_MAX: Hertz = Hertz(100_000_000);
+
+/// Maximum frequency of the generic clock channel of a peripheral
+pub fn peripheral_max_freq(clock: ClockId) -> Hertz {
+    match clock {
+        ClockId::DFLL48 => DFLL_REF_MAX,
+        ClockId::FDPLL0 | ClockId::FDPLL1 => DPLL_REF_MAX,
+        ClockId::TCC0_TCC1 | ClockId::TCC2_TCC3 | ClockId::TCC4 => GCLK_MAX_FREQ,
+        _ => PERIPHERAL_MAX,
+    }
+}
+
+/// Number of flash wait states required at the given CPU frequency
+///
+/// Assumes a supply voltage above 2.7 V.
+pub fn flash_wait_states(cpu: Hertz) -> u8 {
+    match cpu.0 {
+        0..=24_000_000 => 0,
+        24_000_001..=51_000_000 => 1,
+        51_000_001..=77_000_000 => 2,
+        77_000_001..=101_000_000 => 3,
+        101_000_001..=119_000_000 => 4,
+        _ => 5,
+    }
+}
+
+//==============================================================================
+// Errors
+//==============================================================================
+
+/// A clock in the tree
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Clock {
+    /// Crystal oscillator XOSC0
+    Xosc0,
+    /// Crystal oscillator XOSC1
+    Xosc1,
+    /// External 32 kHz crystal oscillator
+    Xosc32k,
+    /// DFLL48M
+    Dfll,
+    /// Fractional digital PLL 0
+    Dpll0,
+    /// Fractional digital PLL 1
+    Dpll1,
+    /// Generic clock generator
+    Gclk(ClockGenId),
+    /// CPU clock, derived from GCLK0
+    Cpu,
+    /// Generic clock channel of a peripheral
+    Peripheral(ClockId),
+}
+
+/// Errors reported while validating a [`ClockTree`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// A source was used without being configured in this tree
+    NotConfigured(Clock),
+    /// A clock frequency lies outside of its allowed range
+    FrequencyOutOfRange {
+        /// Offending clock
+        clock: Clock,
+        /// Computed frequency
+        freq: Hertz,
+        /// Minimum allowed frequency
+        min: Hertz,
+        /// Maximum allowed frequency
+        max: Hertz,
+    },
+    /// A divider is out of range for the given clock
+    InvalidDivider(Clock),
+    /// The DPLL ratio required for the requested frequency can't be
+    /// represented
+    InvalidRatio(Clock),
+    /// Only GCLK1 can be used as the source of other generators
+    InvalidSource(Clock),
+    /// The clock depends on itself
+    Loop(Clock),
+}
+
+/// Check that `freq` lies within `min..=max`
+fn check_range(clock: Clock, freq: Hertz, min: Hertz, max: Hertz) -> Result<Hertz, Error> {
+    if freq.0 < min.0 || freq.0 > max.0 {
+        Err(Error::FrequencyOutOfRange {
+            clock,
+            freq,
+            min,
+            max,
+        })
+    } else {
+        Ok(freq)
+    }
+}
+
+//==============================================================================
+// Tokens
+//==============================================================================
+
+/// Token for the configured crystal oscillator XOSC0
+#[derive(Clone, Copy, Debug)]
+pub struct Xosc0(());
+
+/// Token for the configured crystal oscillator XOSC1
+#[derive(Clone, Copy, Debug)]
+pub struct Xosc1(());
+
+/// Token for the configured external 32 kHz crystal oscillator
+#[derive(Clone, Copy, Debug)]
+pub struct Xosc32k(());
+
+/// Token for the always-on 32 kHz ultra low power oscillator
+#[derive(Clone, Copy, Debug)]
+pub struct OscUlp32k(());
+
+/// Token for the DFLL48M
+#[derive(Clone, Copy, Debug)]
+pub struct Dfll(());
+
+/// Token for the configured fractional digital PLL 0
+#[derive(Clone, Copy, Debug)]
+pub struct Dpll0(());
+
+/// Token for the configured fractional digital PLL 1
+#[derive(Clone, Copy, Debug)]
+pub struct Dpll1(());
+
+/// Token for a configured generic clock generator
+#[derive(Clone, Copy, Debug)]
+pub struct Gclk(ClockGenId);
+
+impl Gclk {
+    /// Return the ID of the clock generator
+    #[inline]
+    pub fn id(&self) -> ClockGenId {
+        self.0
+    }
+}
+
+/// External clock applied to a generator's GCLK_IO pin
+#[derive(Clone, Copy, Debug)]
+pub struct GclkIn(pub Hertz);
+
+/// Clock source selected for a generic clock generator
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Source {
+    Xosc0,
+    Xosc1,
+    GclkIn(Hertz),
+    Gclk1,
+    OscUlp32k,
+    Xosc32k,
+    Dfll,
+    Dpll0,
+    Dpll1,
+}
+
+/// Tokens that can feed a generic clock generator
+pub trait GclkSource: Sealed + Copy {
+    #[doc(hidden)]
+    fn source(&self) -> Result<Source, Error>;
+}
+
+macro_rules! gclk_source {
+    ($($Token:ident),+) => {
+        $(
+            impl Sealed for $Token {}
+
+            impl GclkSource for $Token {
+                #[inline]
+                fn source(&self) -> Result<Source, Error> {
+                    Ok(Source::$Token)
+                }
+            }
+        )+
+    };
+}
+
+gclk_source!(Xosc0, Xosc1, Xosc32k, OscUlp32k, Dfll, Dpll0, Dpll1);
+
+impl Sealed for GclkIn {}
+
+impl GclkSource for GclkIn {
+    #[inline]
+    fn source(&self) -> Result<Source, Error> {
+        Ok(Source::GclkIn(self.0))
+    }
+}
+
+impl Sealed for Gclk {}
+
+impl GclkSource for Gclk {
+    #[inline]
+    fn source(&self) -> Result<Source, Error> {
+        match self.0 {
+            ClockGenId::GCLK1 => Ok(Source::Gclk1),
+            id => Err(Error::InvalidSource(Clock::Gclk(id))),
+        }
+    }
+}
+
+//==============================================================================
+// Source configurations
+//==============================================================================
+
+/// Configuration of a crystal oscillator, XOSC0 or XOSC1
+#[derive(Clone, Copy, Debug)]
+pub struct XoscConfig {
+    pub(super) freq: Hertz,
+    pub(super) crystal: bool,
+}
+
+impl XoscConfig {
+    /// A crystal connected between XIN and XOUT, from 8 to 48 MHz
+    #[inline]
+    pub fn crystal(freq: impl Into<Hertz>) -> Self {
+        Self {
+            freq: freq.into(),
+            crystal: true,
+        }
+    }
+
+    /// An external clock applied to XIN, up to 48 MHz
+    #[inline]
+    pub fn external(freq: impl Into<Hertz>) -> Self {
+        Self {
+            freq: freq.into(),
+            crystal: false,
+        }
+    }
+
+    /// Current multiplier and reference current (`IMULT`, `IPTAT`)
+    /// recommended for the crystal frequency
+    pub fn currents(&self) -> (u8, u8) {
+        match self.freq.0 {
+            0..=8_000_000 => (3, 3),
+            8_000_001..=16_000_000 => (4, 3),
+            16_000_001..=24_000_000 => (5, 3),
+            _ => (6, 3),
+        }
+    }
+}
+
+/// Reference clock of a DPLL
+#[derive(Clone, Copy, Debug)]
+pub struct DpllReference(pub(super) DpllRef);
+
+#[derive(Clone, Copy, Debug)]
+pub(super) enum DpllRef {
+    Xosc32k,
+    Xosc0 { prediv: u16 },
+    Xosc1 { prediv: u16 },
+    Gclk(ClockGenId),
+}
+
+impl DpllReference {
+    /// Use the external 32 kHz oscillator as reference
+    #[inline]
+    pub fn xosc32k(_xosc: Xosc32k) -> Self {
+        Self(DpllRef::Xosc32k)
+    }
+
+    /// Use XOSC0 as reference, divided by `prediv`
+    ///
+    /// `prediv` must be an even number from 2 to 4096.
+    #[inline]
+    pub fn xosc0(_xosc: Xosc0, prediv: u16) -> Self {
+        Self(DpllRef::Xosc0 { prediv })
+    }
+
+    /// Use XOSC1 as reference, divided by `prediv`
+    ///
+    /// `prediv` must be an even number from 2 to 4096.
+    #[inline]
+    pub fn xosc1(_xosc: Xosc1, prediv: u16) -> Self {
+        Self(DpllRef::Xosc1 { prediv })
+    }
+
+    /// Use a generic clock generator as reference
+    #[inline]
+    pub fn gclk(gclk: Gclk) -> Self {
+        Self(DpllRef::Gclk(gclk.0))
+    }
+}
+
+/// Configuration of a DPLL
+#[derive(Clone, Copy, Debug)]
+pub(super) struct DpllConfig {
+    pub(super) reference: DpllRef,
+    pub(super) target: Hertz,
+}
+
+/// Operating mode of the DFLL48M
+#[derive(Clone, Copy, Debug)]
+pub(super) enum DfllMode {
+    OpenLoop,
+    ClosedLoop(ClockGenId),
+    UsbRecovery,
+}
+
+/// Configuration of a generic clock generator
+#[derive(Clone, Copy, Debug)]
+pub(super) struct GclkConfig {
+    pub(super) source: Result<Source, Error>,
+    pub(super) div: u32,
+    pub(super) improve_duty_cycle: bool,
+}
+
+//==============================================================================
+// ClockTree
+//==============================================================================
+
+/// Description of the clock sources and generators of the chip
+///
+/// See the [module-level documentation](self) for more details.
+#[derive(Clone, Debug)]
+pub struct ClockTree {
+    pub(super) xosc: [Option<XoscConfig>; 2],
+    pub(super) xosc32k: bool,
+    pub(super) dfll: DfllMode,
+    pub(super) dpll: [Option<DpllConfig>; 2],
+    pub(super) gclk: [Option<GclkConfig>; 12],
+    pub(super) cpu_div: u8,
+}
+
+impl Default for ClockTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockTree {
+    /// Create a clock tree in its reset state
+    ///
+    /// The DFLL48M runs open-loop and feeds GCLK0 directly.
+    pub fn new() -> Self {
+        let mut gclk = [None; 12];
+        gclk[0] = Some(GclkConfig {
+            source: Ok(Source::Dfll),
+            div: 1,
+            improve_duty_cycle: false,
+        });
+        Self {
+            xosc: [None; 2],
+            xosc32k: false,
+            dfll: DfllMode::OpenLoop,
+            dpll: [None; 2],
+            gclk,
+            cpu_div: 1,
+        }
+    }
+
+    /// Configure XOSC0
+    #[inline]
+    pub fn xosc0(&mut self, config: XoscConfig) -> Xosc0 {
+        self.xosc[0] = Some(config);
+        Xosc0(())
+    }
+
+    /// Configure XOSC1
+    #[inline]
+    pub fn xosc1(&mut self, config: XoscConfig) -> Xosc1 {
+        self.xosc[1] = Some(config);
+        Xosc1(())
+    }
+
+    /// Enable the external 32 kHz crystal oscillator
+    #[inline]
+    pub fn xosc32k(&mut self) -> Xosc32k {
+        self.xosc32k = true;
+        Xosc32k(())
+    }
+
+    /// Return the token for the ultra low power 32 kHz oscillator
+    #[inline]
+    pub fn osculp32k(&self) -> OscUlp32k {
+        OscUlp32k(())
+    }
+
+    /// Return the token for the DFLL48M, in its current mode
+    #[inline]
+    pub fn dfll(&self) -> Dfll {
+        Dfll(())
+    }
+
+    /// Run the DFLL48M open-loop
+    #[inline]
+    pub fn dfll_open_loop(&mut self) -> Dfll {
+        self.dfll = DfllMode::OpenLoop;
+        Dfll(())
+    }
+
+    /// Run the DFLL48M in closed-loop mode, locked to `reference`
+    ///
+    /// The multiplication factor is chosen so that the output is as close to
+    /// 48 MHz as possible. The reference must run between 732 Hz and 33 kHz.
+    #[inline]
+    pub fn dfll_closed_loop(&mut self, reference: Gclk) -> Dfll {
+        self.dfll = DfllMode::ClosedLoop(reference.0);
+        Dfll(())
+    }
+
+    /// Run the DFLL48M in USB clock recovery mode, locked to the 1 kHz USB
+    /// start-of-frame
+    #[inline]
+    pub fn dfll_usb_recovery(&mut self) -> Dfll {
+        self.dfll = DfllMode::UsbRecovery;
+        Dfll(())
+    }
+
+    /// Configure DPLL0 to output `freq` from `reference`
+    ///
+    /// The ratio is rounded to the closest 1/32; the actual frequency is
+    /// reported by [`compute`](Self::compute).
+    #[inline]
+    pub fn dpll0(&mut self, reference: DpllReference, freq: impl Into<Hertz>) -> Dpll0 {
+        self.dpll[0] = Some(DpllConfig {
+            reference: reference.0,
+            target: freq.into(),
+        });
+        Dpll0(())
+    }
+
+    /// Configure DPLL1 to output `freq` from `reference`
+    ///
+    /// The ratio is rounded to the closest 1/32; the actual frequency is
+    /// reported by [`compute`](Self::compute).
+    #[inline]
+    pub fn dpll1(&mut self, reference: DpllReference, freq: impl Into<Hertz>) -> Dpll1 {
+        self.dpll[1] = Some(DpllConfig {
+            reference: reference.0,
+            target: freq.into(),
+        });
+        Dpll1(())
+    }
+
+    /// Configure a generic clock generator to divide `source` by `div`
+    ///
+    /// `div` ranges from 1 to 65535 for GCLK1, and from 1 to 255 for the
+    /// other generators. Only GCLK1 can be used as the source of another
+    /// generator.
+    #[inline]
+    pub fn gclk(&mut self, id: ClockGenId, source: impl GclkSource, div: u32) -> Gclk {
+        self.gclk[u8::from(id) as usize] = Some(GclkConfig {
+            source: source.source(),
+            div,
+            improve_duty_cycle: false,
+        });
+        Gclk(id)
+    }
+
+    /// Enable or disable the 50/50 duty cycle correction of a generator for
+    /// odd dividers
+    #[inline]
+    pub fn improve_duty_cycle(&mut self, gclk: Gclk, enable: bool) {
+        if let Some(config) = self.gclk[u8::from(gclk.0) as usize].as_mut() {
+            config.improve_duty_cycle = enable;
+        }
+    }
+
+    /// Return a token for GCLK0, which is always running
+    #[inline]
+    pub fn gclk0(&self) -> Gclk {
+        Gclk(ClockGenId::GCLK0)
+    }
+
+    /// Set the CPU clock divider, applied to GCLK0
+    ///
+    /// `div` must be a power of two from 1 to 128.
+    #[inline]
+    pub fn cpu_div(&mut self, div: u8) {
+        self.cpu_div = div;
+    }
+
+    /// Validate the tree and compute the frequency of every clock
+    pub fn compute(&self) -> Result<Frequencies, Error> {
+        let mut resolver = Resolver {
+            tree: self,
+            freqs: Frequencies::empty(),
+            visiting: [false; NUM_NODES],
+            done: [false; NUM_NODES],
+        };
+
+        for node in Node::ALL.iter() {
+            if node.configured(self) {
+                resolver.resolve(*node)?;
+            }
+        }
+
+        if !self.cpu_div.is_power_of_two() {
+            return Err(Error::InvalidDivider(Clock::Cpu));
+        }
+        let main = resolver.freqs.gclk[0].unwrap_or(DFLL_FREQ);
+        let cpu = Hertz(main.0 / self.cpu_div as u32);
+        check_range(Clock::Cpu, cpu, Hertz(0), CPU_MAX_FREQ)?;
+
+        let mut freqs = resolver.freqs;
+        freqs.cpu = cpu;
+        freqs.wait_states = flash_wait_states(cpu);
+        Ok(freqs)
+    }
+}
+
+//==============================================================================
+// Frequencies
+//==============================================================================
+
+/// Frequencies of a validated [`ClockTree`]
+#[derive(Clone, Copy, Debug)]
+pub struct Frequencies {
+    xosc: [Option<Hertz>; 2],
+    xosc32k: Option<Hertz>,
+    dfll: Option<Hertz>,
+    dpll: [Option<Hertz>; 2],
+    gclk: [Option<Hertz>; 12],
+    cpu: Hertz,
+    pub(super) wait_states: u8,
+    pub(super) dfll_mul: u16,
+    pub(super) dpll_ratio: [(u16, u8); 2],
+    pub(super) order: [Option<Node>; NUM_NODES],
+}
+
+impl Frequencies {
+    fn empty() -> Self {
+        Self {
+            xosc: [None; 2],
+            xosc32k: None,
+            dfll: None,
+            dpll: [None; 2],
+            gclk: [None; 12],
+            cpu: Hertz(0),
+            wait_states: 0,
+            dfll_mul: 0,
+            dpll_ratio: [(0, 0); 2],
+            order: [None; NUM_NODES],
+        }
+    }
+
+    /// Frequency of XOSC0, if configured
+    #[inline]
+    pub fn xosc0(&self) -> Option<Hertz> {
+        self.xosc[0]
+    }
+
+    /// Frequency of XOSC1, if configured
+    #[inline]
+    pub fn xosc1(&self) -> Option<Hertz> {
+        self.xosc[1]
+    }
+
+    /// Frequency of the external 32 kHz oscillator, if enabled
+    #[inline]
+    pub fn xosc32k(&self) -> Option<Hertz> {
+        self.xosc32k
+    }
+
+    /// Frequency of the DFLL48M, if used by the tree
+    #[inline]
+    pub fn dfll(&self) -> Option<Hertz> {
+        self.dfll
+    }
+
+    /// Frequency of DPLL0, if configured
+    #[inline]
+    pub fn dpll0(&self) -> Option<Hertz> {
+        self.dpll[0]
+    }
+
+    /// Frequency of DPLL1, if configured
+    #[inline]
+    pub fn dpll1(&self) -> Option<Hertz> {
+        self.dpll[1]
+    }
+
+    /// Frequency of a generic clock generator, if configured
+    #[inline]
+    pub fn gclk(&self, id: ClockGenId) -> Option<Hertz> {
+        self.gclk[u8::from(id) as usize]
+    }
+
+    /// Frequency of the CPU
+    #[inline]
+    pub fn cpu(&self) -> Hertz {
+        self.cpu
+    }
+
+    /// Number of flash wait states required by the CPU frequency
+    #[inline]
+    pub fn wait_states(&self) -> u8 {
+        self.wait_states
+    }
+
+    /// Frequency of a peripheral channel fed by generator `id`
+    ///
+    /// Returns an error if the generator is not configured, or runs too fast
+    /// for the peripheral.
+    pub fn peripheral(&self, clock: ClockId, id: ClockGenId) -> Result<Hertz, Error> {
+        let freq = self.gclk(id).ok_or(Error::NotConfigured(Clock::Gclk(id)))?;
+        check_range(
+            Clock::Peripheral(clock),
+            freq,
+            Hertz(0),
+            peripheral_max_freq(clock),
+        )
+    }
+}
+
+//==============================================================================
+// Resolver
+//==============================================================================
+
+const NUM_NODES: usize = 18;
+
+/// A clock that must be configured in hardware
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Node {
+    Xosc(usize),
+    Xosc32k,
+    Dfll,
+    Dpll(usize),
+    Gclk(usize),
+}
+
+impl Node {
+    const ALL: [Node; NUM_NODES] = [
+        Node::Xosc(0),
+        Node::Xosc(1),
+        Node::Xosc32k,
+        Node::Dfll,
+        Node::Dpll(0),
+        Node::Dpll(1),
+        Node::Gclk(0),
+        Node::Gclk(1),
+        Node::Gclk(2),
+        Node::Gclk(3),
+        Node::Gclk(4),
+        Node::Gclk(5),
+        Node::Gclk(6),
+        Node::Gclk(7),
+        Node::Gclk(8),
+        Node::Gclk(9),
+        Node::Gclk(10),
+        Node::Gclk(11),
+    ];
+
+    fn index(self) -> usize {
+        match self {
+            Node::Xosc(i) => i,
+            Node::Xosc32k => 2,
+            Node::Dfll => 3,
+            Node::Dpll(i) => 4 + i,
+            Node::Gclk(i) => 6 + i,
+        }
+    }
+
+    fn clock(self) -> Clock {
+        match self {
+            Node::Xosc(0) => Clock::Xosc0,
+            Node::Xosc(_) => Clock::Xosc1,
+            Node::Xosc32k => Clock::Xosc32k,
+            Node::Dfll => Clock::Dfll,
+            Node::Dpll(0) => Clock::Dpll0,
+            Node::Dpll(_) => Clock::Dpll1,
+            Node::Gclk(i) => Clock::Gclk(GCLK_IDS[i]),
+        }
+    }
+
+    /// Whether the node is explicitly part of the tree. Other nodes are only
+    /// resolved if something depends on them.
+    fn configured(self, tree: &ClockTree) -> bool {
+        match self {
+            Node::Xosc(i) => tree.xosc[i].is_some(),
+            Node::Xosc32k => tree.xosc32k,
+            Node::Dfll => !matches!(tree.dfll, DfllMode::OpenLoop),
+            Node::Dpll(i) => tree.dpll[i].is_some(),
+            Node::Gclk(i) => tree.gclk[i].is_some(),
+        }
+    }
+}
+
+pub(super) const GCLK_IDS: [ClockGenId; 12] = [
+    ClockGenId::GCLK0,
+    ClockGenId::GCLK1,
+    ClockGenId::GCLK2,
+    ClockGenId::GCLK3,
+    ClockGenId::GCLK4,
+    ClockGenId::GCLK5,
+    ClockGenId::GCLK6,
+    ClockGenId::GCLK7,
+    ClockGenId::GCLK8,
+    ClockGenId::GCLK9,
+    ClockGenId::GCLK10,
+    ClockGenId::GCLK11,
+];
+
+/// Depth-first frequency computation, recording the order in which the
+/// clocks must be enabled
+struct Resolver<'a> {
+    tree: &'a ClockTree,
+    freqs: Frequencies,
+    visiting: [bool; NUM_NODES],
+    done: [bool; NUM_NODES],
+}
+
+impl Resolver<'_> {
+    fn resolve(&mut self, node: Node) -> Result<Hertz, Error> {
+        let idx = node.index();
+        if self.done[idx] {
+            return self.freq(node).ok_or(Error::NotConfigured(node.clock()));
+        }
+        if self.visiting[idx] {
+            return Err(Error::Loop(node.clock()));
+        }
+        self.visiting[idx] = true;
+
+        let freq = match node {
+            Node::Xosc(i) => self.xosc(i)?,
+            Node::Xosc32k => {
+                if !self.tree.xosc32k {
+                    return Err(Error::NotConfigured(Clock::Xosc32k));
+                }
+                self.freqs.xosc32k = Some(OSC32K_FREQ);
+                OSC32K_FREQ
+            }
+            Node::Dfll => self.dfll()?,
+            Node::Dpll(i) => self.dpll(i)?,
+            Node::Gclk(i) => self.gclk(i)?,
+        };
+
+        self.visiting[idx] = false;
+        self.done[idx] = true;
+        let slot = self.freqs.order.iter_mut().find(|n| n.is_none());
+        if let Some(slot) = slot {
+            *slot = Some(node);
+        }
+        Ok(freq)
+    }
+
+    fn freq(&self, node: Node) -> Option<Hertz> {
+        match node {
+            Node::Xosc(i) => self.freqs.xosc[i],
+            Node::Xosc32k => self.freqs.xosc32k,
+            Node::Dfll => self.freqs.dfll,
+            Node::Dpll(i) => self.freqs.dpll[i],
+            Node::Gclk(i) => self.freqs.gclk[i],
+        }
+    }
+
+    fn xosc(&mut self, i: usize) -> Result<Hertz, Error> {
+        let clock = Node::Xosc(i).clock();
+        let config = self.tree.xosc[i].ok_or(Error::NotConfigured(clock))?;
+        let min = if config.crystal {
+            XOSC_CRYSTAL_MIN
+        } else {
+            Hertz(1)
+        };
+        let freq = check_range(clock, config.freq, min, XOSC_MAX)?;
+        self.freqs.xosc[i] = Some(freq);
+        Ok(freq)
+    }
+
+    fn dfll(&mut self) -> Result<Hertz, Error> {
+        let mul = match self.tree.dfll {
+            DfllMode::OpenLoop => 0,
+            DfllMode::UsbRecovery => (DFLL_FREQ.0 / 1000) as u16,
+            DfllMode::ClosedLoop(id) => {
+                let reference = self.resolve(Node::Gclk(u8::from(id) as usize))?;
+                let reference = check_range(
+                    Clock::Peripheral(ClockId::DFLL48),
+                    reference,
+                    DFLL_REF_MIN,
+                    DFLL_REF_MAX,
+                )?;
+                let mul = (DFLL_FREQ.0 + reference.0 / 2) / reference.0;
+                if mul > u16::MAX as u32 {
+                    return Err(Error::InvalidRatio(Clock::Dfll));
+                }
+                self.freqs.dfll_mul = mul as u16;
+                self.freqs.dfll = Some(Hertz(reference.0 * mul));
+                return Ok(Hertz(reference.0 * mul));
+            }
+        };
+        self.freqs.dfll_mul = mul;
+        self.freqs.dfll = Some(DFLL_FREQ);
+        Ok(DFLL_FREQ)
+    }
+
+    fn dpll(&mut self, i: usize) -> Result<Hertz, Error> {
+        let clock = Node::Dpll(i).clock();
+        let config = self.tree.dpll[i].ok_or(Error::NotConfigured(clock))?;
+
+        let reference = match config.reference {
+            DpllRef::Xosc32k => self.resolve(Node::Xosc32k)?,
+            DpllRef::Xosc0 { prediv } => self.xosc_ref(clock, 0, prediv)?,
+            DpllRef::Xosc1 { prediv } => self.xosc_ref(clock, 1, prediv)?,
+            DpllRef::Gclk(id) => self.resolve(Node::Gclk(u8::from(id) as usize))?,
+        };
+        let ref_clock = match i {
+            0 => ClockId::FDPLL0,
+            _ => ClockId::FDPLL1,
+        };
+        check_range(
+            Clock::Peripheral(ref_clock),
+            reference,
+            DPLL_REF_MIN,
+            DPLL_REF_MAX,
+        )?;
+
+        // The ratio is LDR + 1 + LDRFRAC / 32
+        let ratio =
+            ((config.target.0 as u64 * 32 + reference.0 as u64 / 2) / reference.0 as u64) as u32;
+        let ldr = (ratio / 32).checked_sub(1);
+        let ldr = match ldr {
+            Some(ldr) if ldr <= 0x1FFF => ldr,
+            _ => return Err(Error::InvalidRatio(clock)),
+        };
+        let freq = Hertz(((reference.0 as u64 * ratio as u64) / 32) as u32);
+        let freq = check_range(clock, freq, DPLL_OUT_MIN, DPLL_OUT_MAX)?;
+
+        self.freqs.dpll_ratio[i] = (ldr as u16, (ratio % 32) as u8);
+        self.freqs.dpll[i] = Some(freq);
+        Ok(freq)
+    }
+
+    fn xosc_ref(&mut self, clock: Clock, i: usize, prediv: u16) -> Result<Hertz, Error> {
+        if !(2..=4096).contains(&prediv) || prediv % 2 != 0 {
+            return Err(Error::InvalidDivider(clock));
+        }
+        let freq = self.resolve(Node::Xosc(i))?;
+        Ok(Hertz(freq.0 / prediv as u32))
+    }
+
+    fn gclk(&mut self, i: usize) -> Result<Hertz, Error> {
+        let clock = Node::Gclk(i).clock();
+        let config = self.tree.gclk[i].ok_or(Error::NotConfigured(clock))?;
+
+        let max_div = if i == 1 {
+            u16::MAX as u32
+        } else {
+            u8::MAX as u32
+        };
+        if config.div == 0 || config.div > max_div {
+            return Err(Error::InvalidDivider(clock));
+        }
+
+        let source = match config.source? {
+            Source::Xosc0 => self.resolve(Node::Xosc(0))?,
+            Source::Xosc1 => self.resolve(Node::Xosc(1))?,
+            Source::GclkIn(freq) => freq,
+            Source::Gclk1 => self.resolve(Node::Gclk(1))?,
+            Source::OscUlp32k => OSC32K_FREQ,
+            Source::Xosc32k => self.resolve(Node::Xosc32k)?,
+            Source::Dfll => self.resolve(Node::Dfll)?,
+            Source::Dpll0 => self.resolve(Node::Dpll(0))?,
+            Source::Dpll1 => self.resolve(Node::Dpll(1))?,
+        };
+        let freq = Hertz(source.0 / config.div);
+        let freq = check_range(clock, freq, Hertz(0), GCLK_MAX_FREQ)?;
+        self.freqs.gclk[i] = Some(freq);
+        Ok(freq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{KiloHertz, MegaHertz};
+
+    #[test]
+    fn reset_state() {
+        let freqs = ClockTree::new().compute().unwrap();
+        assert_eq!(freqs.cpu(), DFLL_FREQ);
+        assert_eq!(freqs.gclk(ClockGenId::GCLK0), Some(DFLL_FREQ));
+        assert_eq!(freqs.gclk(ClockGenId::GCLK1), None);
+        assert_eq!(freqs.wait_states(), 1);
+    }
+
+    #[test]
+    fn dfll_to_dpll0_120mhz() {
+        // Same tree as `GenericClockController::with_internal_32kosc`
+        let mut tree = ClockTree::new();
+        let dfll = tree.dfll();
+        let gclk5 = tree.gclk(ClockGenId::GCLK5, dfll, 24);
+        let dpll0 = tree.dpll0(DpllReference::gclk(gclk5), MegaHertz(120));
+        tree.gclk(ClockGenId::GCLK0, dpll0, 1);
+
+        let freqs = tree.compute().unwrap();
+        assert_eq!(freqs.gclk(ClockGenId::GCLK5), Some(Hertz(2_000_000)));
+        assert_eq!(freqs.dpll0(), Some(Hertz(120_000_000)));
+        assert_eq!(freqs.dpll_ratio[0], (59, 0));
+        assert_eq!(freqs.cpu(), Hertz(120_000_000));
+        assert_eq!(freqs.wait_states(), 5);
+
+        // Dependencies come first
+        let pos = |n| freqs.order.iter().position(|o| *o == Some(n)).unwrap();
+        assert!(pos(Node::Gclk(5)) < pos(Node::Dpll(0)));
+        assert!(pos(Node::Dpll(0)) < pos(Node::Gclk(0)));
+    }
+
+    #[test]
+    fn fractional_dpll() {
+        let mut tree = ClockTree::new();
+        let xosc32k = tree.xosc32k();
+        let dpll1 = tree.dpll1(DpllReference::xosc32k(xosc32k), MegaHertz(100));
+        tree.gclk(ClockGenId::GCLK2, dpll1, 1);
+
+        let freqs = tree.compute().unwrap();
+        // 100 MHz / 32.768 kHz = 3051.7578125 -> 3051 + 24/32
+        assert_eq!(freqs.dpll_ratio[1], (3050, 24));
+        assert_eq!(freqs.dpll1(), Some(Hertz(99_999_744)));
+    }
+
+    #[test]
+    fn xosc_reference() {
+        let mut tree = ClockTree::new();
+        let xosc0 = tree.xosc0(XoscConfig::crystal(MegaHertz(12)));
+        let dpll0 = tree.dpll0(DpllReference::xosc0(xosc0, 6), MegaHertz(200));
+        let gclk1 = tree.gclk(ClockGenId::GCLK1, dpll0, 2);
+        tree.gclk(ClockGenId::GCLK3, gclk1, 4);
+
+        let freqs = tree.compute().unwrap();
+        assert_eq!(freqs.xosc0(), Some(Hertz(12_000_000)));
+        assert_eq!(freqs.gclk(ClockGenId::GCLK3), Some(Hertz(25_000_000)));
+        assert_eq!(
+            freqs.peripheral(ClockId::SERCOM0_CORE, ClockGenId::GCLK1),
+            Ok(Hertz(100_000_000))
+        );
+        assert!(freqs
+            .peripheral(ClockId::SERCOM0_CORE, ClockGenId::GCLK4)
+            .is_err());
+
+        assert!(matches!(
+            XoscConfig::crystal(MegaHertz(12)).currents(),
+            (4, 3)
+        ));
+    }
+
+    #[test]
+    fn dfll_closed_loop() {
+        let mut tree = ClockTree::new();
+        let xosc32k = tree.xosc32k();
+        let gclk1 = tree.gclk(ClockGenId::GCLK1, xosc32k, 1);
+        tree.dfll_closed_loop(gclk1);
+
+        let freqs = tree.compute().unwrap();
+        assert_eq!(freqs.dfll_mul, 1465);
+        assert_eq!(freqs.dfll(), Some(Hertz(32_768 * 1465)));
+        assert_eq!(freqs.cpu(), Hertz(32_768 * 1465));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut tree = ClockTree::new();
+        let xosc1 = tree.xosc1(XoscConfig::crystal(MegaHertz(4)));
+        tree.gclk(ClockGenId::GCLK2, xosc1, 1);
+        assert!(matches!(
+            tree.compute(),
+            Err(Error::FrequencyOutOfRange {
+                clock: Clock::Xosc1,
+                ..
+            })
+        ));
+
+        let mut tree = ClockTree::new();
+        let dfll = tree.dfll();
+        let gclk5 = tree.gclk(ClockGenId::GCLK5, dfll, 24);
+        let dpll0 = tree.dpll0(DpllReference::gclk(gclk5), MegaHertz(240));
+        tree.gclk(ClockGenId::GCLK2, dpll0, 2);
+        assert!(matches!(
+            tree.compute(),
+            Err(Error::FrequencyOutOfRange {
+                clock: Clock::Dpll0,
+                ..
+            })
+        ));
+
+        // The CPU can't run at 200 MHz, unless divided
+        let mut tree = ClockTree::new();
+        let dfll = tree.dfll();
+        let gclk5 = tree.gclk(ClockGenId::GCLK5, dfll, 24);
+        let dpll0 = tree.dpll0(DpllReference::gclk(gclk5), MegaHertz(200));
+        tree.gclk(ClockGenId::GCLK0, dpll0, 1);
+        assert!(matches!(
+            tree.compute(),
+            Err(Error::FrequencyOutOfRange {
+                clock: Clock::Cpu,
+                ..
+            })
+        ));
+        tree.cpu_div(2);
+        assert_eq!(tree.compute().unwrap().cpu(), Hertz(100_000_000));
+    }
+
+    #[test]
+    fn invalid_configurations() {
+        // Tokens are not tied to the tree they came from
+        let xosc0 = ClockTree::new().xosc0(XoscConfig::crystal(MegaHertz(12)));
+        let mut tree = ClockTree::new();
+        tree.gclk(ClockGenId::GCLK2, xosc0, 1);
+        assert_eq!(
+            tree.compute().unwrap_err(),
+            Error::NotConfigured(Clock::Xosc0)
+        );
+
+        let mut tree = ClockTree::new();
+        let dfll = tree.dfll();
+        tree.gclk(ClockGenId::GCLK2, dfll, 256);
+        assert_eq!(
+            tree.compute().unwrap_err(),
+            Error::InvalidDivider(Clock::Gclk(ClockGenId::GCLK2))
+        );
+
+        // Only GCLK1 can feed other generators
+        let mut tree = ClockTree::new();
+        let gclk2 = tree.gclk(ClockGenId::GCLK2, dfll, 1);
+        tree.gclk(ClockGenId::GCLK3, gclk2, 1);
+        assert_eq!(
+            tree.compute().unwrap_err(),
+            Error::InvalidSource(Clock::Gclk(ClockGenId::GCLK2))
+        );
+
+        // DPLL0 referenced to a generator it feeds
+        let mut tree = ClockTree::new();
+        let gclk2 = tree.gclk(ClockGenId::GCLK2, dfll, 24);
+        let dpll0 = tree.dpll0(DpllReference::gclk(gclk2), MegaHertz(120));
+        tree.gclk(ClockGenId::GCLK2, dpll0, 100);
+        assert!(matches!(tree.compute(), Err(Error::Loop(_))));
+
+        let mut tree = ClockTree::new();
+        tree.gclk(ClockGenId::GCLK1, GclkIn(KiloHertz(100).into()), 1);
+        tree.cpu_div(3);
+        assert_eq!(
+            tree.compute().unwrap_err(),
+            Error::InvalidDivider(Clock::Cpu)
+        );
+    }
+
+    #[test]
+    fn wait_states() {
+        assert_eq!(flash_wait_states(Hertz(24_000_000)), 0);
+        assert_eq!(flash_wait_states(Hertz(48_000_000)), 1);
+        assert_eq!(flash_wait_states(Hertz(100_000_000)), 3);
+        assert_eq!(flash_wait_states(Hertz(120_000_000)), 5);
+    }
+}
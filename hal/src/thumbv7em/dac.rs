@@ -0,0 +1,318 @@
+//! Digital-to-Analogue Conversion
+//!
+//! The DAC has two independent 12-bit outputs, `DAC0` on pin `PA02` and
+//! `DAC1` on pin `PA05`. They share a voltage reference, but each channel has
+//! its own refresh, dithering and oversampling settings.
+//!
+//! # Initializing
+//!
+//! The DAC configuration is enable-protected. A [`Dac`] is therefore created
+//! in the [`Disabled`] state, in which channels are configured with
+//! [`Dac::configure`]. The returned [`DacChannel`]s can be used once the DAC
+//! has been [enabled](Dac::enable).
+//!
+//! ```
+//! let mut dac = Dac::new(
+//!     peripherals.DAC,
+//!     &mut peripherals.MCLK,
+//!     &dac_clock,
+//!     Reference::VDDANA,
+//! );
+//! let mut dac0 = dac.configure::<Dac0>(pins.pa02, ChannelConfig::default());
+//! let dac = dac.enable();
+//! while !dac0.is_ready() {}
+//! dac0.write(2048);
+//! ```
+//!
+//! # Streaming with DMA
+#![cfg_attr(
+    feature = "dma",
+    doc = "
+[`DacChannel`] implements the DMAC [`Buffer`](crate::dmac::Buffer) trait,
+targeting the `DATABUF` register of the channel. To output samples at a fixed
+rate, connect the overflow event of a timer to the `DacStart0` or `DacStart1`
+event user, enable the start event with [`ChannelConfig::start_event`],
+and use `TriggerSource::DAC_EMPTY_0` or `TriggerSource::DAC_EMPTY_1` as the
+trigger source of the [`Transfer`](crate::dmac::Transfer)."
+)]
+
+use core::marker::PhantomData;
+
+use crate::clock::DacClock;
+use crate::gpio::{AlternateB, AnyPin, Pin, PinId, PA02, PA05};
+use crate::pac::{dac, DAC, MCLK};
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+/// Reference voltage (or its source)
+pub use dac::ctrlb::REFSEL_A as Reference;
+/// Current control, depending on the GCLK_DAC frequency
+pub use dac::dacctrl::CCTRL_A as CurrentControl;
+/// Interpolation oversampling ratio
+pub use dac::dacctrl::OSR_A as Oversampling;
+/// Output refresh period, used to maintain the output voltage when the
+/// channel is not regularly updated
+pub use dac::dacctrl::REFRESH_A as Refresh;
+
+/// Maximum value accepted by a DAC channel
+pub const MAX_VALUE: u16 = 0xFFF;
+
+//==============================================================================
+// DacId
+//==============================================================================
+
+/// Type-level enum of the DAC channels
+pub trait DacId: Sealed {
+    /// Channel number
+    const N: usize;
+    /// Output pin of the channel
+    type PinId: PinId;
+}
+
+/// DAC channel 0, outputting to `PA02`
+pub enum Dac0 {}
+
+impl Sealed for Dac0 {}
+
+impl DacId for Dac0 {
+    const N: usize = 0;
+    type PinId = PA02;
+}
+
+/// DAC channel 1, outputting to `PA05`
+pub enum Dac1 {}
+
+impl Sealed for Dac1 {}
+
+impl DacId for Dac1 {
+    const N: usize = 1;
+    type PinId = PA05;
+}
+
+//==============================================================================
+// ChannelConfig
+//==============================================================================
+
+/// Configuration of a single DAC channel
+#[derive(Clone, Copy)]
+pub struct ChannelConfig {
+    /// Output refresh period. `Refresh::REFRESH_0` disables the refresh.
+    pub refresh: Refresh,
+    /// Enable dithering, which improves the effective resolution when
+    /// combined with oversampling
+    pub dither: bool,
+    /// Interpolation oversampling ratio
+    pub oversampling: Oversampling,
+    /// Store the 12-bit value in the upper bits of the data registers
+    pub left_adjusted: bool,
+    /// Keep the channel running in standby sleep mode
+    pub run_in_standby: bool,
+    /// Convert the content of the data buffer whenever an event is received
+    /// from the [`evsys`](crate::evsys) module
+    pub start_event: bool,
+    /// Enable the data buffer empty event output
+    pub empty_event: bool,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            refresh: Refresh::REFRESH_0,
+            dither: false,
+            oversampling: Oversampling::OSR_1,
+            left_adjusted: false,
+            run_in_standby: false,
+            start_event: false,
+            empty_event: false,
+        }
+    }
+}
+
+//==============================================================================
+// Dac
+//==============================================================================
+
+/// Type-level enum of the DAC states
+pub trait State: Sealed {}
+
+/// DAC is disabled and can be configured
+pub enum Disabled {}
+
+impl Sealed for Disabled {}
+impl State for Disabled {}
+
+/// DAC is enabled and converting
+pub enum Enabled {}
+
+impl Sealed for Enabled {}
+impl State for Enabled {}
+
+/// `Dac` encapsulates the device DAC
+pub struct Dac<S: State> {
+    dac: DAC,
+    current: CurrentControl,
+    state: PhantomData<S>,
+}
+
+impl Dac<Disabled> {
+    /// Create a new, disabled `Dac` using the given reference voltage.
+    ///
+    /// The current control of the channels is derived from the frequency of
+    /// the DAC clock, which must not exceed 12 MHz.
+    pub fn new(dac: DAC, mclk: &mut MCLK, clock: &DacClock, reference: Reference) -> Self {
+        mclk.apbdmask.modify(|_, w| w.dac_().set_bit());
+
+        dac.ctrla.write(|w| w.swrst().set_bit());
+        while dac.syncbusy.read().swrst().bit_is_set() {}
+
+        dac.ctrlb.write(|w| w.refsel().variant(reference));
+
+        Self {
+            dac,
+            current: current_control(clock.freq()),
+            state: PhantomData,
+        }
+    }
+
+    /// Set the voltage reference
+    pub fn reference(&mut self, reference: Reference) {
+        self.dac.ctrlb.modify(|_, w| w.refsel().variant(reference));
+    }
+
+    /// Configure and enable a channel, returning a [`DacChannel`] which can
+    /// be written to once the DAC is enabled.
+    pub fn configure<I: DacId>(
+        &mut self,
+        pin: impl AnyPin<Id = I::PinId>,
+        config: ChannelConfig,
+    ) -> DacChannel<I> {
+        self.dac.dacctrl[I::N].write(|w| {
+            w.enable().set_bit();
+            w.cctrl().variant(self.current);
+            w.refresh().variant(config.refresh);
+            w.dither().bit(config.dither);
+            w.osr().variant(config.oversampling);
+            w.leftadj().bit(config.left_adjusted);
+            w.runstdby().bit(config.run_in_standby)
+        });
+
+        let events = (config.start_event as u8) | (config.empty_event as u8) << 2;
+        let mask = 0b101 << I::N;
+        self.dac
+            .evctrl
+            .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | events << I::N) });
+
+        DacChannel {
+            pin: pin.into().into_alternate(),
+        }
+    }
+
+    /// Disable a channel and return its pin
+    pub fn unconfigure<I: DacId>(&mut self, channel: DacChannel<I>) -> Pin<I::PinId, AlternateB> {
+        self.dac.dacctrl[I::N].reset();
+        let mask = 0b101 << I::N;
+        self.dac
+            .evctrl
+            .modify(|r, w| unsafe { w.bits(r.bits() & !mask) });
+        channel.pin
+    }
+
+    /// Enable the DAC
+    pub fn enable(self) -> Dac<Enabled> {
+        self.dac.ctrla.modify(|_, w| w.enable().set_bit());
+        while self.dac.syncbusy.read().enable().bit_is_set() {}
+        self.transition()
+    }
+
+    /// Return the underlying peripheral
+    pub fn free(self) -> DAC {
+        self.dac
+    }
+}
+
+impl Dac<Enabled> {
+    /// Disable the DAC, allowing it to be reconfigured
+    pub fn disable(self) -> Dac<Disabled> {
+        self.dac.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.dac.syncbusy.read().enable().bit_is_set() {}
+        self.transition()
+    }
+}
+
+impl<S: State> Dac<S> {
+    #[inline]
+    fn transition<N: State>(self) -> Dac<N> {
+        Dac {
+            dac: self.dac,
+            current: self.current,
+            state: PhantomData,
+        }
+    }
+}
+
+/// Select the current control matching the GCLK_DAC frequency
+fn current_control(freq: Hertz) -> CurrentControl {
+    match freq.0 {
+        f if f < 1_200_000 => CurrentControl::CC100K,
+        f if f < 6_000_000 => CurrentControl::CC1M,
+        _ => CurrentControl::CC12M,
+    }
+}
+
+//==============================================================================
+// DacChannel
+//==============================================================================
+
+/// A single, configured DAC channel and its output pin
+pub struct DacChannel<I: DacId> {
+    pin: Pin<I::PinId, AlternateB>,
+}
+
+impl<I: DacId> DacChannel<I> {
+    #[inline]
+    fn dac(&self) -> &dac::RegisterBlock {
+        // Safety: each `DacChannel` only accesses the registers, or reads the
+        // bits of the shared registers, belonging to its own channel
+        unsafe { &*DAC::ptr() }
+    }
+
+    /// Return whether the channel has finished its startup and is ready to
+    /// convert. This requires the DAC to be enabled.
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.dac().status.read().bits() & (1 << I::N) != 0
+    }
+
+    /// Immediately convert a new value
+    pub fn write(&mut self, value: u16) {
+        self.dac().data[I::N].write(|w| unsafe { w.bits(value) });
+        while self.dac().syncbusy.read().bits() & (1 << (2 + I::N)) != 0 {}
+    }
+
+    /// Write a value to the data buffer. It will be converted when the next
+    /// start event is received.
+    pub fn write_buffered(&mut self, value: u16) {
+        self.dac().databuf[I::N].write(|w| unsafe { w.bits(value) });
+        while self.dac().syncbusy.read().bits() & (1 << (4 + I::N)) != 0 {}
+    }
+}
+
+#[cfg(feature = "dma")]
+unsafe impl<I: DacId> crate::dmac::Buffer for DacChannel<I> {
+    type Beat = u16;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.dac().databuf[I::N].as_ptr()
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
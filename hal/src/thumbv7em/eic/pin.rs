@@ -72,8 +72,8 @@ crate::paste::item! {
         }
 
         pub fn enable_event(&mut self, eic: &mut super::ConfigurableEIC) {
-            eic.eic.evctrl.modify(|_, w| unsafe {
-                w.bits(1 << $num)
+            eic.eic.evctrl.modify(|r, w| unsafe {
+                w.bits(r.bits() | 1 << $num)
             });
         }
 
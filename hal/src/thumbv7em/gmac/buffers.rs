@@ -0,0 +1,261 @@
+//! Descriptor rings and frame buffers
+//!
+//! The GMAC moves frames between its FIFOs and RAM with its own DMA engine,
+//! which follows two rings of buffer descriptors, one for reception and one
+//! for transmission. Each descriptor points to a buffer large enough for a
+//! whole Ethernet frame, so every frame uses exactly one descriptor.
+
+use core::ptr;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+/// Size of each frame buffer, in bytes. This must be a multiple of 64.
+pub const BUFFER_LEN: usize = 1536;
+
+/// Largest frame handled by the driver, excluding the FCS
+pub const MAX_FRAME_LEN: usize = 1514;
+
+// RX descriptor bits
+const RX_OWNERSHIP: u32 = 1 << 0;
+const RX_WRAP: u32 = 1 << 1;
+const RX_LENGTH: u32 = 0x1FFF;
+const RX_SOF: u32 = 1 << 14;
+const RX_EOF: u32 = 1 << 15;
+
+// TX descriptor bits
+const TX_LENGTH: u32 = 0x3FFF;
+const TX_LAST: u32 = 1 << 15;
+const TX_WRAP: u32 = 1 << 30;
+const TX_USED: u32 = 1 << 31;
+
+/// Buffer descriptor, shared by the RX and TX rings
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Descriptor {
+    address: u32,
+    status: u32,
+}
+
+impl Descriptor {
+    const EMPTY: Self = Self {
+        address: 0,
+        status: 0,
+    };
+}
+
+/// Word-aligned frame buffer
+#[repr(C, align(4))]
+#[derive(Clone, Copy)]
+struct Buffer([u8; BUFFER_LEN]);
+
+/// Descriptors and frame buffers of the GMAC
+///
+/// * `RX`: number of receive buffers
+/// * `TX`: number of transmit buffers
+///
+/// The buffers must live as long as the GMAC uses them. They are therefore
+/// passed to [`Gmac::new`](super::Gmac::new) as a `&'static mut` reference,
+/// which can be obtained with `cortex_m::singleton!`.
+#[repr(C, align(8))]
+pub struct Buffers<const RX: usize, const TX: usize> {
+    rx_descriptors: [Descriptor; RX],
+    tx_descriptors: [Descriptor; TX],
+    rx_buffers: [Buffer; RX],
+    tx_buffers: [Buffer; TX],
+}
+
+impl<const RX: usize, const TX: usize> Buffers<RX, TX> {
+    /// Create new, zeroed buffers
+    pub const fn new() -> Self {
+        Self {
+            rx_descriptors: [Descriptor::EMPTY; RX],
+            tx_descriptors: [Descriptor::EMPTY; TX],
+            rx_buffers: [Buffer([0; BUFFER_LEN]); RX],
+            tx_buffers: [Buffer([0; BUFFER_LEN]); TX],
+        }
+    }
+
+    /// Initialize the descriptors and return the RX and TX rings. Return
+    /// `None` if either ring is empty.
+    pub(super) fn rings(&'static mut self) -> Option<(RxRing, TxRing)> {
+        if RX == 0 || TX == 0 {
+            return None;
+        }
+
+        for (index, (descriptor, buffer)) in self
+            .rx_descriptors
+            .iter_mut()
+            .zip(self.rx_buffers.iter_mut())
+            .enumerate()
+        {
+            let mut address = buffer.0.as_mut_ptr() as u32;
+            if index == RX - 1 {
+                address |= RX_WRAP;
+            }
+            *descriptor = Descriptor { address, status: 0 };
+        }
+
+        for (index, (descriptor, buffer)) in self
+            .tx_descriptors
+            .iter_mut()
+            .zip(self.tx_buffers.iter_mut())
+            .enumerate()
+        {
+            let mut status = TX_USED;
+            if index == TX - 1 {
+                status |= TX_WRAP;
+            }
+            *descriptor = Descriptor {
+                address: buffer.0.as_mut_ptr() as u32,
+                status,
+            };
+        }
+
+        let rx = RxRing {
+            descriptors: self.rx_descriptors.as_mut_ptr(),
+            buffers: self.rx_buffers.as_mut_ptr(),
+            len: RX,
+            next: 0,
+        };
+        let tx = TxRing {
+            descriptors: self.tx_descriptors.as_mut_ptr(),
+            buffers: self.tx_buffers.as_mut_ptr(),
+            len: TX,
+            next: 0,
+        };
+        Some((rx, tx))
+    }
+}
+
+impl<const RX: usize, const TX: usize> Default for Buffers<RX, TX> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Receive descriptor ring
+///
+/// The descriptors are shared with the GMAC, so they are only accessed
+/// through raw pointers, with volatile operations.
+pub(super) struct RxRing {
+    descriptors: *mut Descriptor,
+    buffers: *mut Buffer,
+    len: usize,
+    next: usize,
+}
+
+impl RxRing {
+    /// Address of the first descriptor
+    #[inline]
+    pub(super) fn base(&self) -> u32 {
+        self.descriptors as u32
+    }
+
+    #[inline]
+    fn descriptor(&self) -> *mut Descriptor {
+        // Safety: `next` is always smaller than `len`
+        unsafe { self.descriptors.add(self.next) }
+    }
+
+    /// Return the length of the next received frame, if any. Buffers holding
+    /// incomplete frames are dropped.
+    pub(super) fn available(&mut self) -> Option<usize> {
+        loop {
+            // Safety: the descriptor is within the ring
+            let descriptor = unsafe { ptr::read_volatile(self.descriptor()) };
+            if descriptor.address & RX_OWNERSHIP == 0 {
+                return None;
+            }
+            if descriptor.status & (RX_SOF | RX_EOF) == RX_SOF | RX_EOF {
+                return Some((descriptor.status & RX_LENGTH) as usize);
+            }
+            self.release();
+        }
+    }
+
+    /// Buffer of the next received frame. Only valid after
+    /// [`available`](Self::available) returned a frame.
+    #[inline]
+    pub(super) fn buffer(&mut self, len: usize) -> &mut [u8] {
+        compiler_fence(Ordering::Acquire);
+        // Safety: the software owns the buffer until it is released
+        unsafe { &mut (&mut (*self.buffers.add(self.next)).0)[..len.min(BUFFER_LEN)] }
+    }
+
+    /// Give the next buffer back to the GMAC, and move to the following one
+    pub(super) fn release(&mut self) {
+        // Make sure the buffer is no longer accessed once the GMAC owns it
+        cortex_m::asm::dmb();
+        let descriptor = self.descriptor();
+        // Safety: the descriptor is within the ring
+        unsafe {
+            let address = ptr::read_volatile(ptr::addr_of!((*descriptor).address));
+            ptr::write_volatile(
+                ptr::addr_of_mut!((*descriptor).address),
+                address & !RX_OWNERSHIP,
+            );
+        }
+        self.next = (self.next + 1) % self.len;
+    }
+}
+
+/// Transmit descriptor ring
+///
+/// The descriptors are shared with the GMAC, so they are only accessed
+/// through raw pointers, with volatile operations.
+pub(super) struct TxRing {
+    descriptors: *mut Descriptor,
+    buffers: *mut Buffer,
+    len: usize,
+    next: usize,
+}
+
+impl TxRing {
+    /// Address of the first descriptor
+    #[inline]
+    pub(super) fn base(&self) -> u32 {
+        self.descriptors as u32
+    }
+
+    #[inline]
+    fn descriptor(&self) -> *mut Descriptor {
+        // Safety: `next` is always smaller than `len`
+        unsafe { self.descriptors.add(self.next) }
+    }
+
+    /// Return whether the next buffer is free
+    pub(super) fn available(&self) -> bool {
+        // Safety: the descriptor is within the ring
+        let status = unsafe { ptr::read_volatile(ptr::addr_of!((*self.descriptor()).status)) };
+        status & TX_USED != 0
+    }
+
+    /// Next free buffer. Only valid after [`available`](Self::available)
+    /// returned `true`.
+    #[inline]
+    pub(super) fn buffer(&mut self, len: usize) -> &mut [u8] {
+        // Safety: the software owns the buffer until it is committed
+        unsafe { &mut (&mut (*self.buffers.add(self.next)).0)[..len.min(MAX_FRAME_LEN)] }
+    }
+
+    /// Hand the next buffer, holding a frame of `len` bytes, to the GMAC, and
+    /// move to the following one
+    pub(super) fn commit(&mut self, len: usize) {
+        compiler_fence(Ordering::Release);
+        let mut status = (len.min(MAX_FRAME_LEN) as u32 & TX_LENGTH) | TX_LAST;
+        if self.next == self.len - 1 {
+            status |= TX_WRAP;
+        }
+        // Safety: the descriptor is within the ring
+        unsafe {
+            ptr::write_volatile(ptr::addr_of_mut!((*self.descriptor()).status), status);
+        }
+        // Make sure the descriptor is written before the transmission starts
+        cortex_m::asm::dsb();
+        self.next = (self.next + 1) % self.len;
+    }
+}
+
+// Safety: the rings exclusively own their descriptors and buffers, so they can
+// be moved to another context along with them.
+unsafe impl Send for RxRing {}
+unsafe impl Send for TxRing {}
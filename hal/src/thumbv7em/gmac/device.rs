@@ -0,0 +1,87 @@
+//! `smoltcp` integration
+
+use smoltcp::phy::{self, Device, DeviceCapabilities, Medium};
+use smoltcp::time::Instant;
+
+use super::buffers::{RxRing, TxRing, MAX_FRAME_LEN};
+use super::{start_transmission, Gmac, Interface, MdcPin, MdioPin};
+use crate::pac::GMAC;
+
+/// Token giving access to a received frame, in its receive buffer
+pub struct RxToken<'a> {
+    rx: &'a mut RxRing,
+    len: usize,
+}
+
+impl phy::RxToken for RxToken<'_> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let result = f(self.rx.buffer(self.len));
+        self.rx.release();
+        result
+    }
+}
+
+/// Token giving access to a free transmit buffer
+pub struct TxToken<'a> {
+    tx: &'a mut TxRing,
+    gmac: &'a GMAC,
+}
+
+impl phy::TxToken for TxToken<'_> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let result = f(self.tx.buffer(len));
+        self.tx.commit(len);
+        start_transmission(self.gmac);
+        result
+    }
+}
+
+impl<P: Interface, Mdc: MdcPin, Mdio: MdioPin> Device for Gmac<P, Mdc, Mdio> {
+    type RxToken<'a>
+        = RxToken<'a>
+    where
+        Self: 'a;
+    type TxToken<'a>
+        = TxToken<'a>
+    where
+        Self: 'a;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(RxToken<'_>, TxToken<'_>)> {
+        if !self.tx.available() {
+            return None;
+        }
+        let len = self.rx.available()?;
+        let rx = RxToken {
+            rx: &mut self.rx,
+            len,
+        };
+        let tx = TxToken {
+            tx: &mut self.tx,
+            gmac: &self.gmac,
+        };
+        Some((rx, tx))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<TxToken<'_>> {
+        if !self.tx.available() {
+            return None;
+        }
+        Some(TxToken {
+            tx: &mut self.tx,
+            gmac: &self.gmac,
+        })
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut capabilities = DeviceCapabilities::default();
+        capabilities.medium = Medium::Ethernet;
+        capabilities.max_transmission_unit = MAX_FRAME_LEN;
+        capabilities
+    }
+}
@@ -0,0 +1,401 @@
+//! # Ethernet MAC
+//!
+//! The SAME53 and SAME54 contain a 10/100 Mbit/s Ethernet MAC (GMAC),
+//! connected to an external PHY through an RMII or MII interface, and
+//! managing the PHY through an MDIO interface.
+//!
+//! This module is only available on the 100- and 128-pin packages. The
+//! 64-pin SAME53J packages route the GMAC signals to different pins, which
+//! are not mapped yet.
+//!
+//! # Buffers
+//!
+//! Frames are exchanged with the GMAC through rings of DMA descriptors and
+//! frame buffers, held in a [`Buffers`] allocated by the user. The number of
+//! receive and transmit buffers is selected with its const generic
+//! parameters.
+//!
+//! # Initializing
+//!
+//! ```
+//! let buffers = cortex_m::singleton!(: Buffers<4, 2> = Buffers::new()).unwrap();
+//! let rmii = RmiiPins::new(
+//!     pins.pa14, pins.pa17, pins.pa18, pins.pa19, pins.pc20, pins.pa13, pins.pa12,
+//!     pins.pa15,
+//! );
+//! let mdio = MdioPins::new(pins.pc11, pins.pc12);
+//! let config = Config::new([0x02, 0x00, 0x00, 0x00, 0x00, 0x01], clocks.gclk0().freq());
+//!
+//! let mut gmac = Gmac::new(peripherals.GMAC, rmii, mdio, &mut peripherals.MCLK, buffers, config)
+//!     .unwrap();
+//! gmac.reset_phy(0);
+//! // Wait for the link to come up
+//! let link = loop {
+//!     if let Some(link) = gmac.link(0) {
+//!         break link;
+//!     }
+//! };
+//! gmac.set_link(link);
+//! ```
+//!
+//! # Transmitting and receiving
+//!
+//! [`Gmac::transmit`] copies a frame into the next free transmit buffer and
+//! starts its transmission, and [`Gmac::receive`] copies the next received
+//! frame out of its receive buffer. Frames exclude the FCS, which is
+//! generated and checked by the GMAC.
+//!
+//! # `smoltcp`
+//!
+//! [`Gmac`] implements `smoltcp::phy::Device`, which hands out the frame
+//! buffers directly, without copies.
+
+use bitflags::bitflags;
+
+use crate::pac::{GMAC, MCLK};
+use crate::time::Hertz;
+
+mod buffers;
+mod device;
+pub mod phy;
+mod pins;
+
+pub use buffers::{Buffers, BUFFER_LEN, MAX_FRAME_LEN};
+pub use device::{RxToken, TxToken};
+pub use pins::*;
+
+use buffers::{RxRing, TxRing};
+use phy::{Duplex, Link, Speed};
+
+//==============================================================================
+// Error
+//==============================================================================
+
+/// GMAC errors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The [`Buffers`] have no receive or no transmit buffer
+    InvalidBuffers,
+    /// The frame is longer than [`MAX_FRAME_LEN`]
+    FrameTooLong,
+    /// The buffer passed to [`Gmac::receive`] is too short for the received
+    /// frame. The frame is dropped.
+    BufferTooShort,
+}
+
+//==============================================================================
+// Config
+//==============================================================================
+
+/// GMAC configuration
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// MAC address
+    pub mac_address: [u8; 6],
+    /// Frequency of the main clock, used to derive the MDIO clock
+    pub main_clock: Hertz,
+    /// Initial link. Once the link is established, the link negotiated by the
+    /// PHY is set with [`Gmac::set_link`].
+    pub link: Link,
+    /// Receive all frames, whatever their destination address
+    pub promiscuous: bool,
+    /// Reject broadcast frames
+    pub no_broadcast: bool,
+}
+
+impl Config {
+    /// Create a configuration for a 100 Mbit/s full duplex link, receiving
+    /// frames sent to `mac_address` and broadcast frames.
+    pub fn new(mac_address: [u8; 6], main_clock: impl Into<Hertz>) -> Self {
+        Self {
+            mac_address,
+            main_clock: main_clock.into(),
+            link: Link {
+                speed: Speed::Mbps100,
+                duplex: Duplex::Full,
+            },
+            promiscuous: false,
+            no_broadcast: false,
+        }
+    }
+}
+
+//==============================================================================
+// Interrupts
+//==============================================================================
+
+bitflags! {
+    /// Interrupt flags
+    ///
+    /// The binary format of the underlying bits exactly matches the `ISR`
+    /// register.
+    pub struct Interrupts: u32 {
+        /// MDIO management frame sent
+        const MANAGEMENT_DONE = 1 << 0;
+        /// Frame received
+        const RX_COMPLETE = 1 << 1;
+        /// No free receive buffer
+        const RX_USED_BIT_READ = 1 << 2;
+        /// Transmit ring exhausted
+        const TX_USED_BIT_READ = 1 << 3;
+        /// Transmit underrun
+        const TX_UNDERRUN = 1 << 4;
+        /// Retry limit exceeded
+        const RETRY_LIMIT_EXCEEDED = 1 << 5;
+        /// Transmit frame corrupted by an AHB error
+        const TX_FRAME_CORRUPTED = 1 << 6;
+        /// Frame transmitted
+        const TX_COMPLETE = 1 << 7;
+        /// Receive overrun
+        const RX_OVERRUN = 1 << 10;
+        /// AHB error response
+        const HRESP_NOT_OK = 1 << 11;
+    }
+}
+
+//==============================================================================
+// Gmac
+//==============================================================================
+
+/// Divider of the MDIO clock, which must not exceed 2.5 MHz
+fn mdc_divider(main_clock: Hertz) -> u8 {
+    match main_clock.0 {
+        0..=20_000_000 => 0,
+        20_000_001..=40_000_000 => 1,
+        40_000_001..=80_000_000 => 2,
+        80_000_001..=120_000_000 => 3,
+        120_000_001..=160_000_000 => 4,
+        _ => 5,
+    }
+}
+
+/// `Gmac` encapsulates the GMAC, its pins and its buffers
+pub struct Gmac<P: Interface, Mdc: MdcPin, Mdio: MdioPin> {
+    gmac: GMAC,
+    pins: P,
+    mdio: MdioPins<Mdc, Mdio>,
+    rx: RxRing,
+    tx: TxRing,
+}
+
+impl<P: Interface, Mdc: MdcPin, Mdio: MdioPin> Gmac<P, Mdc, Mdio> {
+    /// Configure the GMAC and enable reception and transmission
+    pub fn new<const RX: usize, const TX: usize>(
+        gmac: GMAC,
+        pins: P,
+        mdio: MdioPins<Mdc, Mdio>,
+        mclk: &mut MCLK,
+        buffers: &'static mut Buffers<RX, TX>,
+        config: Config,
+    ) -> Result<Self, Error> {
+        let (rx, tx) = buffers.rings().ok_or(Error::InvalidBuffers)?;
+
+        mclk.ahbmask.modify(|_, w| w.gmac_().set_bit());
+        mclk.apbcmask.modify(|_, w| w.gmac_().set_bit());
+
+        // Stop the GMAC, and clear its statistics and status
+        gmac.ncr.write(|w| w.clrstat().set_bit());
+        gmac.idr.write(|w| unsafe { w.bits(0xFFFF_FFFF) });
+        gmac.isr.read();
+        gmac.rsr.write(|w| unsafe { w.bits(0xF) });
+        gmac.tsr.write(|w| unsafe { w.bits(0xFF) });
+
+        gmac.ur.write(|w| w.mii().bit(P::MII));
+        gmac.ncfgr.write(|w| unsafe {
+            w.spd().bit(config.link.speed == Speed::Mbps100);
+            w.fd().bit(config.link.duplex == Duplex::Full);
+            w.caf().bit(config.promiscuous);
+            w.nbc().bit(config.no_broadcast);
+            w.maxfs().set_bit();
+            w.rfcs().set_bit();
+            w.clk().bits(mdc_divider(config.main_clock));
+            w.dbw().bits(1)
+        });
+        gmac.dcfgr.write(|w| unsafe {
+            // INCR4 bursts
+            w.fbldo().bits(4);
+            w.rxbms().bits(3);
+            w.txpbms().set_bit();
+            w.drbs().bits((BUFFER_LEN / 64) as u8)
+        });
+
+        gmac.rbqb.write(|w| unsafe { w.bits(rx.base()) });
+        gmac.tbqb.write(|w| unsafe { w.bits(tx.base()) });
+
+        let mut gmac = Self {
+            gmac,
+            pins,
+            mdio,
+            rx,
+            tx,
+        };
+        gmac.set_mac_address(config.mac_address);
+
+        gmac.gmac.ncr.write(|w| {
+            w.mpe().set_bit();
+            w.rxen().set_bit();
+            w.txen().set_bit()
+        });
+        Ok(gmac)
+    }
+
+    /// Set the MAC address of the frames to receive
+    pub fn set_mac_address(&mut self, address: [u8; 6]) {
+        let [a0, a1, a2, a3, a4, a5] = address;
+        let sa = &self.gmac.sa[0];
+        // The address is enabled when the top register is written
+        sa.sab
+            .write(|w| unsafe { w.bits(u32::from_le_bytes([a0, a1, a2, a3])) });
+        sa.sat
+            .write(|w| unsafe { w.bits(u32::from_le_bytes([a4, a5, 0, 0])) });
+    }
+
+    /// Set the speed and duplex mode of the link, as negotiated by the PHY
+    pub fn set_link(&mut self, link: Link) {
+        self.gmac.ncfgr.modify(|_, w| {
+            w.spd().bit(link.speed == Speed::Mbps100);
+            w.fd().bit(link.duplex == Duplex::Full)
+        });
+    }
+
+    //--------------------------------------------------------------------------
+    // Transmit & receive
+    //--------------------------------------------------------------------------
+
+    /// Return whether a receive buffer holds a frame
+    #[inline]
+    pub fn is_rx_available(&mut self) -> bool {
+        self.rx.available().is_some()
+    }
+
+    /// Return whether a transmit buffer is free
+    #[inline]
+    pub fn is_tx_available(&self) -> bool {
+        self.tx.available()
+    }
+
+    /// Copy the next received frame into `buffer`, and return its length
+    pub fn receive(&mut self, buffer: &mut [u8]) -> nb::Result<usize, Error> {
+        let len = self.rx.available().ok_or(nb::Error::WouldBlock)?;
+        let result = match buffer.get_mut(..len) {
+            Some(buffer) => {
+                buffer.copy_from_slice(self.rx.buffer(len));
+                Ok(len)
+            }
+            None => Err(nb::Error::Other(Error::BufferTooShort)),
+        };
+        self.rx.release();
+        result
+    }
+
+    /// Copy `frame` into the next free transmit buffer, and start its
+    /// transmission. The FCS is appended by the GMAC.
+    pub fn transmit(&mut self, frame: &[u8]) -> nb::Result<(), Error> {
+        if frame.len() > MAX_FRAME_LEN {
+            return Err(nb::Error::Other(Error::FrameTooLong));
+        }
+        self.send_with(frame.len(), |buffer| buffer.copy_from_slice(frame))
+            .ok_or(nb::Error::WouldBlock)
+    }
+
+    /// Let `f` fill the next free transmit buffer with a frame of `len`
+    /// bytes, and start its transmission
+    fn send_with<R>(&mut self, len: usize, f: impl FnOnce(&mut [u8]) -> R) -> Option<R> {
+        if !self.tx.available() {
+            return None;
+        }
+        let result = f(self.tx.buffer(len));
+        self.tx.commit(len);
+        start_transmission(&self.gmac);
+        Some(result)
+    }
+
+    //--------------------------------------------------------------------------
+    // PHY management
+    //--------------------------------------------------------------------------
+
+    /// Read the register `reg` of the PHY at address `phy`
+    pub fn read_phy(&mut self, phy: u8, reg: u8) -> u16 {
+        self.management_frame(0b10, phy, reg, 0)
+    }
+
+    /// Write `value` into the register `reg` of the PHY at address `phy`
+    pub fn write_phy(&mut self, phy: u8, reg: u8, value: u16) {
+        self.management_frame(0b01, phy, reg, value);
+    }
+
+    fn management_frame(&mut self, op: u8, phy: u8, reg: u8, data: u16) -> u16 {
+        while self.gmac.nsr.read().idle().bit_is_clear() {}
+        self.gmac.man.write(|w| unsafe {
+            w.cltto().set_bit();
+            w.op().bits(op);
+            w.phya().bits(phy & 0x1F);
+            w.rega().bits(reg & 0x1F);
+            w.wtn().bits(0b10);
+            w.data().bits(data)
+        });
+        while self.gmac.nsr.read().idle().bit_is_clear() {}
+        self.gmac.man.read().data().bits()
+    }
+
+    /// Reset the PHY at address `phy`, and restart auto-negotiation
+    pub fn reset_phy(&mut self, phy: u8) {
+        self.write_phy(phy, phy::BMCR, phy::BMCR_RESET);
+        while self.read_phy(phy, phy::BMCR) & phy::BMCR_RESET != 0 {}
+        self.write_phy(
+            phy,
+            phy::BMCR,
+            phy::BMCR_AUTONEG_ENABLE | phy::BMCR_AUTONEG_RESTART,
+        );
+    }
+
+    /// Return the link negotiated by the PHY at address `phy`, if the link is
+    /// up and auto-negotiation has completed
+    pub fn link(&mut self, phy: u8) -> Option<Link> {
+        let status = self.read_phy(phy, phy::BMSR);
+        if status & (phy::BMSR_LINK_STATUS | phy::BMSR_AUTONEG_COMPLETE)
+            != phy::BMSR_LINK_STATUS | phy::BMSR_AUTONEG_COMPLETE
+        {
+            return None;
+        }
+        let advertised = self.read_phy(phy, phy::ANAR);
+        let partner = self.read_phy(phy, phy::ANLPAR);
+        Link::negotiated(advertised, partner)
+    }
+
+    //--------------------------------------------------------------------------
+    // Interrupts
+    //--------------------------------------------------------------------------
+
+    /// Enable the given interrupts
+    pub fn enable_interrupts(&mut self, interrupts: Interrupts) {
+        self.gmac
+            .ier
+            .write(|w| unsafe { w.bits(interrupts.bits()) });
+    }
+
+    /// Disable the given interrupts
+    pub fn disable_interrupts(&mut self, interrupts: Interrupts) {
+        self.gmac
+            .idr
+            .write(|w| unsafe { w.bits(interrupts.bits()) });
+    }
+
+    /// Read and clear the interrupt flags
+    pub fn read_and_clear_interrupt_flags(&mut self) -> Interrupts {
+        Interrupts::from_bits_truncate(self.gmac.isr.read().bits())
+    }
+
+    /// Stop the GMAC and return the peripheral and its pins. The buffers are
+    /// no longer used by the GMAC.
+    pub fn free(self) -> (GMAC, P, MdioPins<Mdc, Mdio>) {
+        self.gmac.ncr.reset();
+        (self.gmac, self.pins, self.mdio)
+    }
+}
+
+/// Start the transmission of the frames in the transmit ring
+#[inline]
+fn start_transmission(gmac: &GMAC) {
+    gmac.ncr.modify(|_, w| w.tstart().set_bit());
+}
@@ -0,0 +1,76 @@
+//! PHY management
+//!
+//! The GMAC accesses the registers of the PHY through the MDIO interface,
+//! using IEEE 802.3 clause 22 frames. The helpers of this module only rely on
+//! the basic registers defined by the standard, which every PHY implements.
+
+/// Basic control register
+pub const BMCR: u8 = 0;
+/// Basic status register
+pub const BMSR: u8 = 1;
+/// PHY identifier 1
+pub const PHYIDR1: u8 = 2;
+/// PHY identifier 2
+pub const PHYIDR2: u8 = 3;
+/// Auto-negotiation advertisement register
+pub const ANAR: u8 = 4;
+/// Auto-negotiation link partner ability register
+pub const ANLPAR: u8 = 5;
+
+pub(super) const BMCR_RESET: u16 = 1 << 15;
+pub(super) const BMCR_AUTONEG_ENABLE: u16 = 1 << 12;
+pub(super) const BMCR_AUTONEG_RESTART: u16 = 1 << 9;
+pub(super) const BMSR_LINK_STATUS: u16 = 1 << 2;
+pub(super) const BMSR_AUTONEG_COMPLETE: u16 = 1 << 5;
+
+const ABILITY_10_HALF: u16 = 1 << 5;
+const ABILITY_10_FULL: u16 = 1 << 6;
+const ABILITY_100_HALF: u16 = 1 << 7;
+const ABILITY_100_FULL: u16 = 1 << 8;
+
+/// Link speed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Speed {
+    /// 10 Mbit/s
+    Mbps10,
+    /// 100 Mbit/s
+    Mbps100,
+}
+
+/// Link duplex mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Duplex {
+    /// Half duplex
+    Half,
+    /// Full duplex
+    Full,
+}
+
+/// Speed and duplex mode of an established link
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Link {
+    /// Link speed
+    pub speed: Speed,
+    /// Duplex mode
+    pub duplex: Duplex,
+}
+
+impl Link {
+    /// Return the best link supported by both our advertisement and the
+    /// abilities of the link partner
+    pub(super) fn negotiated(advertised: u16, partner: u16) -> Option<Self> {
+        let common = advertised & partner;
+        let (speed, duplex) = if common & ABILITY_100_FULL != 0 {
+            (Speed::Mbps100, Duplex::Full)
+        } else if common & ABILITY_100_HALF != 0 {
+            (Speed::Mbps100, Duplex::Half)
+        } else if common & ABILITY_10_FULL != 0 {
+            (Speed::Mbps10, Duplex::Full)
+        } else if common & ABILITY_10_HALF != 0 {
+            (Speed::Mbps10, Duplex::Half)
+        } else {
+            return None;
+        };
+        Some(Self { speed, duplex })
+    }
+}
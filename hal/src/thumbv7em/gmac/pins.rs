@@ -0,0 +1,264 @@
+//! GMAC pins
+//!
+//! All GMAC signals use alternate function L. Signals available on more than
+//! one pin are typed with a trait implemented on the matching [`PinId`]s.
+//!
+//! Pin mappings are provided for the 100- and 128-pin packages. The
+//! interface to the PHY is selected by the pin set given to
+//! [`Gmac::new`](super::Gmac::new): [`RmiiPins`] or [`MiiPins`].
+
+use crate::gpio::*;
+use crate::typelevel::Sealed;
+
+/// Pins which can be used as the MDIO clock (GMDC)
+pub trait MdcPin: PinId {}
+
+/// Pins which can be used as the MDIO data line (GMDIO)
+pub trait MdioPin: PinId {}
+
+/// Pins which can be used as the RMII carrier sense / data valid (GRXDV)
+pub trait CrsDvPin: PinId {}
+
+impl MdcPin for PC11 {}
+impl MdioPin for PC12 {}
+impl CrsDvPin for PC20 {}
+
+/// Pins of the MDIO management interface
+pub struct MdioPins<Mdc: MdcPin, Mdio: MdioPin> {
+    mdc: Pin<Mdc, AlternateL>,
+    mdio: Pin<Mdio, AlternateL>,
+}
+
+impl<Mdc: MdcPin, Mdio: MdioPin> MdioPins<Mdc, Mdio> {
+    /// Configure the pins of the MDIO interface
+    pub fn new(mdc: impl AnyPin<Id = Mdc>, mdio: impl AnyPin<Id = Mdio>) -> Self {
+        Self {
+            mdc: mdc.into().into_alternate(),
+            mdio: mdio.into().into_alternate(),
+        }
+    }
+
+    /// Return the pins
+    pub fn free(self) -> (Pin<Mdc, AlternateL>, Pin<Mdio, AlternateL>) {
+        (self.mdc, self.mdio)
+    }
+}
+
+/// Interface between the GMAC and the PHY
+pub trait Interface: Sealed {
+    /// Whether the interface is MII rather than RMII
+    const MII: bool;
+}
+
+/// Pins of the Reduced Media Independent Interface (RMII)
+///
+/// The 50 MHz reference clock is provided to the GMAC on `GREFCK`.
+pub struct RmiiPins<CrsDv: CrsDvPin> {
+    ref_clk: Pin<PA14, AlternateL>,
+    tx_en: Pin<PA17, AlternateL>,
+    txd0: Pin<PA18, AlternateL>,
+    txd1: Pin<PA19, AlternateL>,
+    crs_dv: Pin<CrsDv, AlternateL>,
+    rxd0: Pin<PA13, AlternateL>,
+    rxd1: Pin<PA12, AlternateL>,
+    rx_er: Pin<PA15, AlternateL>,
+}
+
+/// Pins of an [`RmiiPins`], as returned by [`RmiiPins::free`]
+pub type RmiiPinsFree<CrsDv> = (
+    Pin<PA14, AlternateL>,
+    Pin<PA17, AlternateL>,
+    Pin<PA18, AlternateL>,
+    Pin<PA19, AlternateL>,
+    Pin<CrsDv, AlternateL>,
+    Pin<PA13, AlternateL>,
+    Pin<PA12, AlternateL>,
+    Pin<PA15, AlternateL>,
+);
+
+impl<CrsDv: CrsDvPin> RmiiPins<CrsDv> {
+    /// Configure the pins of the RMII interface
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        ref_clk: impl AnyPin<Id = PA14>,
+        tx_en: impl AnyPin<Id = PA17>,
+        txd0: impl AnyPin<Id = PA18>,
+        txd1: impl AnyPin<Id = PA19>,
+        crs_dv: impl AnyPin<Id = CrsDv>,
+        rxd0: impl AnyPin<Id = PA13>,
+        rxd1: impl AnyPin<Id = PA12>,
+        rx_er: impl AnyPin<Id = PA15>,
+    ) -> Self {
+        Self {
+            ref_clk: ref_clk.into().into_alternate(),
+            tx_en: tx_en.into().into_alternate(),
+            txd0: txd0.into().into_alternate(),
+            txd1: txd1.into().into_alternate(),
+            crs_dv: crs_dv.into().into_alternate(),
+            rxd0: rxd0.into().into_alternate(),
+            rxd1: rxd1.into().into_alternate(),
+            rx_er: rx_er.into().into_alternate(),
+        }
+    }
+
+    /// Return the pins
+    pub fn free(self) -> RmiiPinsFree<CrsDv> {
+        (
+            self.ref_clk,
+            self.tx_en,
+            self.txd0,
+            self.txd1,
+            self.crs_dv,
+            self.rxd0,
+            self.rxd1,
+            self.rx_er,
+        )
+    }
+}
+
+impl<CrsDv: CrsDvPin> Sealed for RmiiPins<CrsDv> {}
+
+impl<CrsDv: CrsDvPin> Interface for RmiiPins<CrsDv> {
+    const MII: bool = false;
+}
+
+/// Pins of the Media Independent Interface (MII)
+///
+/// The 25 MHz transmit and receive clocks are provided by the PHY on `GTXCK`
+/// and `GRXCK`.
+pub struct MiiPins {
+    tx: MiiTxPins,
+    rx: MiiRxPins,
+    crs: Pin<PA16, AlternateL>,
+    col: Pin<PC21, AlternateL>,
+}
+
+/// Transmit data pins (GTX0 to GTX3) of an [`MiiTxPins`]
+pub type MiiTxData = (
+    Pin<PA18, AlternateL>,
+    Pin<PA19, AlternateL>,
+    Pin<PC16, AlternateL>,
+    Pin<PC17, AlternateL>,
+);
+
+/// Transmit pins of an [`MiiPins`]
+pub struct MiiTxPins {
+    /// Transmit clock (GTXCK)
+    pub clk: Pin<PA14, AlternateL>,
+    /// Transmit enable (GTXEN)
+    pub en: Pin<PA17, AlternateL>,
+    /// Transmit error (GTXER)
+    pub er: Pin<PC19, AlternateL>,
+    /// Transmit data (GTX0 to GTX3)
+    pub data: MiiTxData,
+}
+
+impl MiiTxPins {
+    /// Configure the transmit pins of the MII interface
+    pub fn new(
+        clk: impl AnyPin<Id = PA14>,
+        en: impl AnyPin<Id = PA17>,
+        er: impl AnyPin<Id = PC19>,
+        data: (
+            impl AnyPin<Id = PA18>,
+            impl AnyPin<Id = PA19>,
+            impl AnyPin<Id = PC16>,
+            impl AnyPin<Id = PC17>,
+        ),
+    ) -> Self {
+        Self {
+            clk: clk.into().into_alternate(),
+            en: en.into().into_alternate(),
+            er: er.into().into_alternate(),
+            data: (
+                data.0.into().into_alternate(),
+                data.1.into().into_alternate(),
+                data.2.into().into_alternate(),
+                data.3.into().into_alternate(),
+            ),
+        }
+    }
+}
+
+/// Receive data pins (GRX0 to GRX3) of an [`MiiRxPins`]
+pub type MiiRxData = (
+    Pin<PA13, AlternateL>,
+    Pin<PA12, AlternateL>,
+    Pin<PC15, AlternateL>,
+    Pin<PC14, AlternateL>,
+);
+
+/// Receive pins of an [`MiiPins`]
+pub struct MiiRxPins {
+    /// Receive clock (GRXCK)
+    pub clk: Pin<PC18, AlternateL>,
+    /// Receive data valid (GRXDV)
+    pub dv: Pin<PC20, AlternateL>,
+    /// Receive error (GRXER)
+    pub er: Pin<PA15, AlternateL>,
+    /// Receive data (GRX0 to GRX3)
+    pub data: MiiRxData,
+}
+
+impl MiiRxPins {
+    /// Configure the receive pins of the MII interface
+    pub fn new(
+        clk: impl AnyPin<Id = PC18>,
+        dv: impl AnyPin<Id = PC20>,
+        er: impl AnyPin<Id = PA15>,
+        data: (
+            impl AnyPin<Id = PA13>,
+            impl AnyPin<Id = PA12>,
+            impl AnyPin<Id = PC15>,
+            impl AnyPin<Id = PC14>,
+        ),
+    ) -> Self {
+        Self {
+            clk: clk.into().into_alternate(),
+            dv: dv.into().into_alternate(),
+            er: er.into().into_alternate(),
+            data: (
+                data.0.into().into_alternate(),
+                data.1.into().into_alternate(),
+                data.2.into().into_alternate(),
+                data.3.into().into_alternate(),
+            ),
+        }
+    }
+}
+
+/// Pins of an [`MiiPins`], as returned by [`MiiPins::free`]
+pub type MiiPinsFree = (
+    MiiTxPins,
+    MiiRxPins,
+    Pin<PA16, AlternateL>,
+    Pin<PC21, AlternateL>,
+);
+
+impl MiiPins {
+    /// Configure the pins of the MII interface
+    pub fn new(
+        tx: MiiTxPins,
+        rx: MiiRxPins,
+        crs: impl AnyPin<Id = PA16>,
+        col: impl AnyPin<Id = PC21>,
+    ) -> Self {
+        Self {
+            tx,
+            rx,
+            crs: crs.into().into_alternate(),
+            col: col.into().into_alternate(),
+        }
+    }
+
+    /// Return the pins
+    pub fn free(self) -> MiiPinsFree {
+        (self.tx, self.rx, self.crs, self.col)
+    }
+}
+
+impl Sealed for MiiPins {}
+
+impl Interface for MiiPins {
+    const MII: bool = true;
+}
@@ -1,9 +1,20 @@
 pub mod aes;
 pub mod calibration;
+#[cfg(all(feature = "can", any(feature = "same51", feature = "same54")))]
+pub mod can;
+pub mod ccl;
 pub mod clock;
+pub mod dac;
 pub mod eic;
+#[cfg(all(
+    feature = "ethernet",
+    feature = "min-samd51n",
+    any(feature = "same53", feature = "same54")
+))]
+pub mod gmac;
 pub mod pukcc;
 pub mod qspi;
+pub mod sdhc;
 pub mod timer;
 pub mod trng;
 
@@ -30,3 +41,5 @@ pub mod icm;
 
 pub mod dsu;
 pub mod nvm;
+pub mod power;
+pub mod supc;
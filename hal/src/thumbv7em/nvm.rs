@@ -18,9 +18,17 @@
 //! Module features:
 //! - Erase & write over non-volatile memory in a device.
 //! - Swap banks
+//! - Read & write the user page configuration (see [`user_page`])
+//! - Dual-bank firmware updates with automatic rollback (see [`update`])
+//! - `embedded-storage` traits over flash partitions and SmartEEPROM (with the
+//!   `storage` feature, see [`storage`])
 #![warn(missing_docs)]
 
 pub mod smart_eeprom;
+#[cfg(feature = "storage")]
+pub mod storage;
+pub mod update;
+pub mod user_page;
 
 pub use crate::pac::nvmctrl::ctrla::PRM_A;
 use crate::pac::nvmctrl::ctrlb::CMD_AW;
@@ -108,6 +116,10 @@ pub enum Error {
     Dsu(super::dsu::Error),
     /// An alignment requirement was not fulfilled
     Alignment,
+    /// Offset outside of the storage area
+    OutOfBounds,
+    /// A user page field is out of range
+    InvalidUserPage,
 }
 
 /// Physical flash banks
@@ -21,10 +21,13 @@
 //! populated from proper bits in NVM controller user page on power-on-reset. By
 //! default, `SBLK` property is set to `0`, effectively disabling SmartEEPROM.
 //!
-//! One of possible safe ways to change user page content is to use `OpenOCD`
-//! custom commands. `atsame5x`'s `OpenOCD` driver supports `atsame5 userpage`
-//! command. To access it from GDB, it has to be preceded with a `monitor`
-//! clause.
+//! The user page content can be changed at runtime through
+//! [`Nvm::write_user_page`], by setting the
+//! [`see_sblk`](super::user_page::UserPage::see_sblk) and
+//! [`see_psz`](super::user_page::UserPage::see_psz) fields. The new settings
+//! take effect after a reset. Alternatively, `atsame5x`'s `OpenOCD` driver
+//! supports the `atsame5 userpage` command. To access it from GDB, it has to
+//! be preceded with a `monitor` clause.
 //!
 //! To access [`SmartEeprom`] struct, call [`Nvm::smart_eeprom`] method to
 //! retrieve its instance.
@@ -315,6 +318,38 @@ impl<'a, TP: SmartEepromPointableSize> DoubleEndedIterator for SmartEepromIterMu
     }
 }
 
+#[cfg(feature = "storage")]
+impl<'a, T: SmartEepromState> embedded_storage::ReadStorage for SmartEeprom<'a, T> {
+    type Error = super::Error;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> core::result::Result<(), Self::Error> {
+        let offset = offset as usize;
+        match offset.checked_add(bytes.len()) {
+            Some(end) if end <= self.virtual_size => {}
+            _ => return Err(super::Error::OutOfBounds),
+        }
+        self.get(offset, bytes);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.virtual_size
+    }
+}
+
+#[cfg(feature = "storage")]
+impl<'a> embedded_storage::Storage for SmartEeprom<'a, Unlocked> {
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> core::result::Result<(), Self::Error> {
+        let offset = offset as usize;
+        match offset.checked_add(bytes.len()) {
+            Some(end) if end <= self.virtual_size => {}
+            _ => return Err(super::Error::OutOfBounds),
+        }
+        self.set(offset, bytes);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,164 @@
+//! # `embedded-storage` support
+//!
+//! [`FlashPartition`] implements the [`ReadNorFlash`] and [`NorFlash`] traits
+//! over a range of blocks of the main flash, so that crates built on top of
+//! `embedded-storage` can use the internal flash. It is retrieved with
+//! [`Nvm::partition`].
+//!
+//! The SmartEEPROM implements [`ReadStorage`](embedded_storage::ReadStorage)
+//! and, when unlocked, [`Storage`](embedded_storage::Storage).
+//!
+//! This module requires the `storage` feature.
+
+use core::ops::Range;
+
+use embedded_storage::nor_flash::{
+    check_erase, check_read, check_write, ErrorType, NorFlash, NorFlashError, NorFlashErrorKind,
+    ReadNorFlash,
+};
+
+use crate::pac::nvmctrl::ctrlb::CMD_AW;
+
+use super::{EraseGranularity, Error, Nvm, Result, BLOCKSIZE};
+
+/// Size of a quad word, the write granularity of the flash
+const QUAD_WORD: u32 = 16;
+
+/// A range of blocks of the main flash, usable through `embedded-storage`
+///
+/// Offsets passed to the trait methods are relative to the start of the
+/// partition.
+pub struct FlashPartition<'a> {
+    nvm: &'a mut Nvm,
+    range: Range<u32>,
+}
+
+impl Nvm {
+    /// Retrieve a [`FlashPartition`] covering the `range` addresses
+    ///
+    /// `range` must be aligned to [`BLOCKSIZE`], lie within the flash, and
+    /// must not overlap the boot protected area or the SmartEEPROM.
+    ///
+    /// # Safety
+    ///
+    /// The partition can be erased and written at will, so it must not contain
+    /// the running program or any data still in use.
+    #[inline]
+    pub unsafe fn partition(&mut self, range: Range<u32>) -> Result<FlashPartition<'_>> {
+        if range.start % BLOCKSIZE != 0 || range.end % BLOCKSIZE != 0 {
+            Err(Error::Alignment)
+        } else if range.start >= range.end || self.contains_non_flash_memory_area(&range) {
+            Err(Error::NonFlash)
+        } else if self.contains_bootprotected(&range) {
+            Err(Error::Protected)
+        } else if self.contains_smart_eeprom(&range) {
+            Err(Error::SmartEepromArea)
+        } else {
+            Ok(FlashPartition { nvm: self, range })
+        }
+    }
+}
+
+impl Nvm {
+    /// Write a single quad word at `address`, leaving the other quad words of
+    /// its page untouched
+    ///
+    /// # Safety
+    ///
+    /// `address` must be quad word aligned, and inside the flash.
+    unsafe fn write_quad_word(&mut self, address: u32, words: [u32; 4]) -> Result<()> {
+        while !self.is_ready() {}
+        self.command_sync(CMD_AW::PBC);
+        // The data is placed in the page buffer, and only written by WQW
+        for (i, word) in words.iter().enumerate() {
+            core::ptr::write_volatile((address as *mut u32).add(i), *word);
+        }
+        self.command_sync(CMD_AW::WQW);
+        self.manage_error_states()
+    }
+}
+
+impl FlashPartition<'_> {
+    /// Address range covered by the partition
+    #[inline]
+    pub fn range(&self) -> Range<u32> {
+        self.range.clone()
+    }
+}
+
+impl NorFlashError for Error {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            Error::Alignment => NorFlashErrorKind::NotAligned,
+            Error::NonFlash | Error::OutOfBounds => NorFlashErrorKind::OutOfBounds,
+            _ => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+impl From<NorFlashErrorKind> for Error {
+    fn from(kind: NorFlashErrorKind) -> Self {
+        match kind {
+            NorFlashErrorKind::NotAligned => Error::Alignment,
+            _ => Error::OutOfBounds,
+        }
+    }
+}
+
+impl ErrorType for FlashPartition<'_> {
+    type Error = Error;
+}
+
+impl ReadNorFlash for FlashPartition<'_> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<()> {
+        check_read(self, offset, bytes.len())?;
+        let address = (self.range.start + offset) as *const u8;
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            // Safety: the address was checked to be inside the partition
+            *byte = unsafe { core::ptr::read_volatile(address.add(i)) };
+        }
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.range.len()
+    }
+}
+
+impl NorFlash for FlashPartition<'_> {
+    /// The flash is ECC protected per quad word, which can only be written
+    /// once between erases.
+    const WRITE_SIZE: usize = QUAD_WORD as usize;
+
+    const ERASE_SIZE: usize = BLOCKSIZE as usize;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<()> {
+        check_erase(self, from, to)?;
+        let blocks = (to - from) / BLOCKSIZE;
+        // Safety: the blocks were checked to be inside the partition
+        unsafe {
+            self.nvm
+                .erase(self.range.start + from, blocks, EraseGranularity::Block)
+        }
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<()> {
+        check_write(self, offset, bytes.len())?;
+        let mut address = self.range.start + offset;
+        // Each quad word is written with its own WQW command, as a page write
+        // would also program the quad words around it a second time
+        for chunk in bytes.chunks_exact(QUAD_WORD as usize) {
+            let mut words = [0_u32; 4];
+            for (word, data) in words.iter_mut().zip(chunk.chunks_exact(4)) {
+                *word = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            }
+            // Safety: the range was checked to be inside the partition, and
+            // aligned to WRITE_SIZE
+            unsafe { self.nvm.write_quad_word(address, words)? };
+            address += QUAD_WORD;
+        }
+        Ok(())
+    }
+}
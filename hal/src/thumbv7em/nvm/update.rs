@@ -0,0 +1,650 @@
+//! # Dual-bank firmware updates
+//!
+//! [`UpdateManager`] implements an A/B update scheme on top of
+//! [`Nvm::bank_swap`]. A new image is streamed into the inactive bank, then
+//! verified, and finally the banks are swapped. The new image starts in a
+//! trial state and has to [`confirm`](UpdateManager::confirm) itself. If it
+//! fails to do so, for example because the watchdog reset the device, it is
+//! rolled back to the previous image after a number of boot attempts.
+//!
+//! The update state is recorded in a small record stored in the SmartEEPROM,
+//! which must be enabled in the user page (see [`smart_eeprom`](super::smart_eeprom)).
+//! The record occupies [`RECORD_WORDS`] words at a user-chosen offset.
+//!
+//! ## Usage
+//!
+//! On every boot, before doing anything else, call
+//! [`UpdateManager::boot`]. It counts the boot attempts of an image in trial,
+//! rolls back when there are too many, and starts the watchdog so that a
+//! stalled image is reset and eventually rolled back. Once the application
+//! considers itself healthy, it calls [`UpdateManager::confirm`].
+//!
+//! To install an update:
+//! - Call [`UpdateManager::begin`] to erase the inactive bank
+//! - Stream the image with [`UpdateManager::write`]
+//! - Verify it with [`UpdateManager::finish`], using a [`Crc32`] or
+//!   [`Sha256`] verifier
+//! - Call [`UpdateManager::swap`] to reset into the new image
+//!
+//! ```no_run
+//! # use atsamd_hal::nvm::{Nvm, update::{BootStatus, Crc32, UpdateManager}};
+//! # use atsamd_hal::dsu::Dsu;
+//! # use atsamd_hal::watchdog::{Watchdog, WatchdogTimeout};
+//! # fn get_nvm() -> Nvm { unimplemented!() }
+//! # fn get_dsu() -> Dsu { unimplemented!() }
+//! # fn get_watchdog() -> Watchdog { unimplemented!() }
+//! # fn chunks() -> &'static [&'static [u8]] { unimplemented!() }
+//! let mut nvm = get_nvm();
+//! let mut dsu = get_dsu();
+//! let mut wdt = get_watchdog();
+//!
+//! let mut updates = UpdateManager::new(&mut nvm, 0);
+//! match updates.boot(3, &mut wdt, WatchdogTimeout::Cycles16K as u8).unwrap() {
+//!     BootStatus::Trial { .. } => {
+//!         // Run self tests, then keep the new image
+//!         updates.confirm().unwrap();
+//!     }
+//!     _ => {}
+//! }
+//!
+//! // Later on, receive an update
+//! let (length, crc, version) = (0x1_0000, 0x1234_5678, 2);
+//! updates.begin(length).unwrap();
+//! for chunk in chunks() {
+//!     updates.write(chunk).unwrap();
+//! }
+//! updates.finish(&mut Crc32::new(&mut dsu, crc)).unwrap();
+//! unsafe { updates.swap(version) };
+//! ```
+
+use crate::ehal::watchdog::WatchdogEnable;
+
+use super::smart_eeprom::{SmartEepromMode, SmartEepromRetrievalFailure};
+use super::{Bank, EraseGranularity, Nvm, BLOCKSIZE, PAGESIZE};
+use crate::dsu::{self, Dsu};
+
+/// Number of SmartEEPROM words occupied by the update record
+pub const RECORD_WORDS: usize = 5;
+
+/// Marker identifying a valid update record
+const MAGIC: u32 = 0x5550_4454;
+
+/// Size of a quad word, the write granularity of the flash
+const QUAD_WORD: usize = 16;
+
+/// Errors of the update manager
+#[derive(Debug)]
+pub enum Error {
+    /// The NVM controller reported an error
+    Nvm(super::Error),
+    /// The SmartEEPROM is unavailable
+    SmartEeprom(SmartEepromRetrievalFailure),
+    /// The update record does not fit in the SmartEEPROM
+    RecordOutOfBounds,
+    /// The DSU failed to compute the CRC32 of the image
+    Dsu(dsu::Error),
+    /// The ICM reported a bus error while hashing the image
+    IcmBusError,
+    /// The image does not fit in the inactive bank
+    ImageTooLarge,
+    /// More data was written than announced in [`UpdateManager::begin`]
+    Overflow,
+    /// Less data was written than announced in [`UpdateManager::begin`]
+    Incomplete,
+    /// No update was started with [`UpdateManager::begin`]
+    NotStarted,
+    /// The current image has not been confirmed yet. The inactive bank holds
+    /// the rollback image and can't be overwritten
+    TrialInProgress,
+    /// The image did not match the expected checksum or digest
+    VerificationFailed,
+    /// The image was not successfully verified with
+    /// [`UpdateManager::finish`]
+    NotVerified,
+}
+
+impl From<super::Error> for Error {
+    fn from(error: super::Error) -> Self {
+        Error::Nvm(error)
+    }
+}
+
+/// Update manager result type
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// State of the running image
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageState {
+    /// The image was confirmed, or no update was ever installed
+    Confirmed,
+    /// The image was just installed and has not been confirmed yet
+    Trial,
+    /// The last update failed to confirm itself and the previous image was
+    /// restored
+    RolledBack,
+}
+
+/// Update record stored in the SmartEEPROM
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metadata {
+    /// State of the running image
+    pub state: ImageState,
+    /// Version of the last confirmed image
+    pub version: u32,
+    /// Version of the last installed image
+    pub pending_version: u32,
+    /// Number of boots of the image in trial
+    pub boot_attempts: u32,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        Self {
+            state: ImageState::Confirmed,
+            version: 0,
+            pending_version: 0,
+            boot_attempts: 0,
+        }
+    }
+}
+
+/// What to do with the running image at boot
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BootAction {
+    Run,
+    Trial,
+    Rollback,
+}
+
+impl Metadata {
+    fn to_words(self) -> [u32; RECORD_WORDS] {
+        let state = match self.state {
+            ImageState::Confirmed => 0,
+            ImageState::Trial => 1,
+            ImageState::RolledBack => 2,
+        };
+        [
+            MAGIC,
+            state,
+            self.version,
+            self.pending_version,
+            self.boot_attempts,
+        ]
+    }
+
+    /// Decode a record, returning `None` if it was never written
+    fn from_words(words: [u32; RECORD_WORDS]) -> Option<Self> {
+        let state = match words[1] {
+            0 => ImageState::Confirmed,
+            1 => ImageState::Trial,
+            2 => ImageState::RolledBack,
+            _ => return None,
+        };
+        if words[0] != MAGIC {
+            return None;
+        }
+        Some(Self {
+            state,
+            version: words[2],
+            pending_version: words[3],
+            boot_attempts: words[4],
+        })
+    }
+
+    /// Account for a new boot of the running image
+    fn boot(self, max_attempts: u32) -> (Self, BootAction) {
+        match self.state {
+            ImageState::Trial if self.boot_attempts >= max_attempts => (
+                Self {
+                    state: ImageState::RolledBack,
+                    ..self
+                },
+                BootAction::Rollback,
+            ),
+            ImageState::Trial => (
+                Self {
+                    boot_attempts: self.boot_attempts + 1,
+                    ..self
+                },
+                BootAction::Trial,
+            ),
+            _ => (self, BootAction::Run),
+        }
+    }
+}
+
+/// Status of the running image, returned by [`UpdateManager::boot`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BootStatus {
+    /// The running image is confirmed
+    Confirmed {
+        /// Version of the running image
+        version: u32,
+    },
+    /// The running image was just installed and must be confirmed with
+    /// [`UpdateManager::confirm`]
+    Trial {
+        /// Version of the running image
+        version: u32,
+        /// Number of boots of this image, starting at 1
+        attempt: u32,
+    },
+    /// The last update failed and the previous image was restored
+    RolledBack {
+        /// Version of the running image
+        version: u32,
+        /// Version of the image that failed
+        failed_version: u32,
+    },
+}
+
+/// Image verification method used by [`UpdateManager::finish`]
+pub trait ImageVerifier {
+    /// Check the `length` bytes of flash starting at `address`
+    fn verify(&mut self, address: u32, length: u32) -> Result<bool>;
+}
+
+/// Verify an image against a CRC32 computed by the DSU
+///
+/// The expected value is the one returned by [`Dsu::crc32`] over the image.
+/// The image length must be a multiple of 4 bytes.
+pub struct Crc32<'a> {
+    dsu: &'a mut Dsu,
+    expected: u32,
+}
+
+impl<'a> Crc32<'a> {
+    /// Create a new CRC32 verifier
+    #[inline]
+    pub fn new(dsu: &'a mut Dsu, expected: u32) -> Self {
+        Self { dsu, expected }
+    }
+}
+
+impl ImageVerifier for Crc32<'_> {
+    fn verify(&mut self, address: u32, length: u32) -> Result<bool> {
+        let crc = self.dsu.crc32(address, length).map_err(Error::Dsu)?;
+        Ok(crc == self.expected)
+    }
+}
+
+/// Verify an image against a SHA-256 digest computed by the ICM
+///
+/// The ICM does not pad the message. The image must already end with the
+/// SHA-256 padding, so that its length is a multiple of 64 bytes. The
+/// expected digest is given in the format written by the ICM to the
+/// [`HashArea`](crate::icm::HashArea).
+///
+/// The ICM is reset and reconfigured for every verification.
+#[cfg(feature = "unproven")]
+pub struct Sha256<'a> {
+    icm: &'a mut crate::icm::Icm,
+    expected: [u32; 8],
+}
+
+#[cfg(feature = "unproven")]
+impl<'a> Sha256<'a> {
+    /// Create a new SHA-256 verifier
+    #[inline]
+    pub fn new(icm: &'a mut crate::icm::Icm, expected: [u32; 8]) -> Self {
+        Self { icm, expected }
+    }
+}
+
+#[cfg(feature = "unproven")]
+impl ImageVerifier for Sha256<'_> {
+    fn verify(&mut self, address: u32, length: u32) -> Result<bool> {
+        use crate::icm::{icm_algorithm, HashArea, RegionDesc, Regions};
+
+        const SHA_BLOCK: u32 = 64;
+        if length == 0 || length % SHA_BLOCK != 0 {
+            return Err(Error::VerificationFailed);
+        }
+
+        // The descriptor and hash area only need to live until the ICM is
+        // disabled at the end of this function
+        let mut regions = Regions::default();
+        let hash = HashArea::default();
+
+        self.icm.swrst();
+        self.icm.set_eomdis(false);
+        self.icm.set_wbdis(false);
+        self.icm.set_slbdis(false);
+        self.icm.set_ascd(false);
+
+        let mut region = self.icm.enable_region0();
+        region.enable_monitoring();
+
+        let desc = &mut regions.region0;
+        desc.set_region_address(address as *const u32);
+        desc.reset_region_configuration_to_default();
+        desc.rcfg.set_cdwbn(false);
+        desc.rcfg.set_wrap(false);
+        desc.rcfg.set_eom(true);
+        desc.rcfg.set_rhien(false);
+        desc.rcfg.set_algo(icm_algorithm::SHA256);
+        desc.rctrl.trsize = (length / SHA_BLOCK - 1) as u16;
+
+        self.icm.set_hash_addr(&hash);
+        self.icm.set_dscr_addr(&regions.region0);
+        self.icm.enable();
+
+        // Reading the interrupt status clears it, so read it once per loop
+        let result = loop {
+            let status = region.get_interrupt_status();
+            if status.get_rbe_int() {
+                break Err(Error::IcmBusError);
+            }
+            if status.get_rhc_int() {
+                break Ok(());
+            }
+        };
+        self.icm.disable();
+        result?;
+
+        // Safety: the ICM is disabled and no longer writes to the hash area
+        let digest = unsafe { core::ptr::read_volatile(&hash.region0) };
+        Ok(digest == self.expected)
+    }
+}
+
+/// Dual-bank firmware update manager
+///
+/// See the [module-level documentation](self) for details.
+pub struct UpdateManager<'a> {
+    nvm: &'a mut Nvm,
+    /// Offset of the update record in the SmartEEPROM, in words
+    record_offset: usize,
+    /// Length of the image being written
+    image_length: Option<u32>,
+    /// Number of bytes committed to flash
+    flushed: u32,
+    /// Page being filled
+    page: [u8; PAGESIZE as usize],
+    /// Number of bytes in `page`
+    buffered: usize,
+    verified: bool,
+}
+
+impl<'a> UpdateManager<'a> {
+    /// Create a new update manager
+    ///
+    /// The update record is stored in the SmartEEPROM, at `record_offset`
+    /// words.
+    #[inline]
+    pub fn new(nvm: &'a mut Nvm, record_offset: usize) -> Self {
+        Self {
+            nvm,
+            record_offset,
+            image_length: None,
+            flushed: 0,
+            page: [0xff; PAGESIZE as usize],
+            buffered: 0,
+            verified: false,
+        }
+    }
+
+    /// Read the update record
+    ///
+    /// Returns the default record if it was never written.
+    pub fn metadata(&mut self) -> Result<Metadata> {
+        let mut words = [0; RECORD_WORDS];
+        let offset = self.record_offset;
+        let fits = |len: usize| offset + RECORD_WORDS <= len;
+        match self.nvm.smart_eeprom().map_err(Error::SmartEeprom)? {
+            SmartEepromMode::Locked(see) => {
+                // Safety: only the length of the slice is used
+                if !fits(unsafe { see.get_slice::<u32>() }.len()) {
+                    return Err(Error::RecordOutOfBounds);
+                }
+                see.get(offset, &mut words);
+            }
+            SmartEepromMode::Unlocked(see) => {
+                // Safety: only the length of the slice is used
+                if !fits(unsafe { see.get_slice::<u32>() }.len()) {
+                    return Err(Error::RecordOutOfBounds);
+                }
+                see.get(offset, &mut words);
+            }
+        }
+        Ok(Metadata::from_words(words).unwrap_or_default())
+    }
+
+    /// Write the update record
+    ///
+    /// The SmartEEPROM is unlocked if needed.
+    fn set_metadata(&mut self, metadata: Metadata) -> Result<()> {
+        let offset = self.record_offset;
+        let mut see = match self.nvm.smart_eeprom().map_err(Error::SmartEeprom)? {
+            SmartEepromMode::Locked(see) => see.unlock(),
+            SmartEepromMode::Unlocked(see) => see,
+        };
+        // Safety: only the length of the slice is used
+        if offset + RECORD_WORDS > unsafe { see.get_mut_slice::<u32>() }.len() {
+            return Err(Error::RecordOutOfBounds);
+        }
+        see.set(offset, &metadata.to_words());
+        Ok(())
+    }
+
+    /// Account for a boot of the running image
+    ///
+    /// Must be called early on every boot. If the running image is in trial,
+    /// its boot attempts are counted and `watchdog` is started with `period`;
+    /// the image then has to call [`confirm`](Self::confirm) before the
+    /// watchdog fires. Once an image in trial has booted `max_attempts` times
+    /// without being confirmed, the banks are swapped back to the previous
+    /// image and the device is reset.
+    pub fn boot<W: WatchdogEnable>(
+        &mut self,
+        max_attempts: u32,
+        watchdog: &mut W,
+        period: W::Time,
+    ) -> Result<BootStatus> {
+        let (metadata, action) = self.metadata()?.boot(max_attempts);
+        match action {
+            BootAction::Run => {}
+            BootAction::Trial => {
+                self.set_metadata(metadata)?;
+                watchdog.start(period);
+            }
+            BootAction::Rollback => {
+                self.set_metadata(metadata)?;
+                // Safety: the inactive bank holds the last confirmed image, as
+                // `begin` refuses to overwrite it during a trial
+                unsafe { self.nvm.bank_swap() }
+            }
+        }
+
+        Ok(match metadata.state {
+            ImageState::Confirmed => BootStatus::Confirmed {
+                version: metadata.version,
+            },
+            ImageState::Trial => BootStatus::Trial {
+                version: metadata.pending_version,
+                attempt: metadata.boot_attempts,
+            },
+            ImageState::RolledBack => BootStatus::RolledBack {
+                version: metadata.version,
+                failed_version: metadata.pending_version,
+            },
+        })
+    }
+
+    /// Confirm the running image
+    ///
+    /// The image won't be rolled back anymore, and the inactive bank can be
+    /// used for the next update. The watchdog started by
+    /// [`boot`](Self::boot) keeps running.
+    pub fn confirm(&mut self) -> Result<()> {
+        let metadata = self.metadata()?;
+        if metadata.state != ImageState::Trial {
+            return Ok(());
+        }
+        self.set_metadata(Metadata {
+            state: ImageState::Confirmed,
+            version: metadata.pending_version,
+            boot_attempts: 0,
+            ..metadata
+        })
+    }
+
+    /// Start an update of `length` bytes
+    ///
+    /// The blocks of the inactive bank needed to hold the image are erased.
+    pub fn begin(&mut self, length: u32) -> Result<()> {
+        if self.metadata()?.state == ImageState::Trial {
+            return Err(Error::TrialInProgress);
+        }
+        if length > Bank::Inactive.length() {
+            return Err(Error::ImageTooLarge);
+        }
+
+        self.image_length = None;
+        self.verified = false;
+        let blocks = (length + BLOCKSIZE - 1) / BLOCKSIZE;
+        // Safety: the inactive bank is not executing, and does not hold the
+        // rollback image
+        unsafe {
+            self.nvm
+                .erase(Bank::Inactive.address(), blocks, EraseGranularity::Block)
+        }
+        .map_err(|e| match e {
+            super::Error::SmartEepromArea => Error::ImageTooLarge,
+            e => Error::Nvm(e),
+        })?;
+
+        self.image_length = Some(length);
+        self.flushed = 0;
+        self.buffered = 0;
+        Ok(())
+    }
+
+    /// Append a chunk of the image
+    ///
+    /// Chunks can have any size. Data is buffered and written to flash one
+    /// page at a time.
+    pub fn write(&mut self, mut chunk: &[u8]) -> Result<()> {
+        let length = self.image_length.ok_or(Error::NotStarted)?;
+        if self.flushed + (self.buffered + chunk.len()) as u32 > length {
+            return Err(Error::Overflow);
+        }
+
+        while !chunk.is_empty() {
+            let count = chunk.len().min(self.page.len() - self.buffered);
+            self.page[self.buffered..self.buffered + count].copy_from_slice(&chunk[..count]);
+            self.buffered += count;
+            chunk = &chunk[count..];
+            if self.buffered == self.page.len() {
+                self.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the buffered data to flash, padding it to a quad word
+    fn flush(&mut self) -> Result<()> {
+        if self.buffered == 0 {
+            return Ok(());
+        }
+        let padded = (self.buffered + QUAD_WORD - 1) / QUAD_WORD * QUAD_WORD;
+        self.page[self.buffered..padded].fill(0xff);
+
+        let mut words = [0_u32; (PAGESIZE / 4) as usize];
+        for (word, bytes) in words.iter_mut().zip(self.page[..padded].chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        let address = Bank::Inactive.address() + self.flushed;
+        // Safety: the inactive bank is not executing
+        unsafe { self.nvm.write_from_slice(address, &words[..padded / 4])? };
+
+        self.flushed += self.buffered as u32;
+        self.buffered = 0;
+        Ok(())
+    }
+
+    /// Complete the update and verify the image with `verifier`
+    ///
+    /// All the bytes announced in [`begin`](Self::begin) must have been
+    /// written.
+    pub fn finish<V: ImageVerifier>(&mut self, verifier: &mut V) -> Result<()> {
+        let length = self.image_length.ok_or(Error::NotStarted)?;
+        self.flush()?;
+        if self.flushed != length {
+            return Err(Error::Incomplete);
+        }
+
+        self.verified = verifier.verify(Bank::Inactive.address(), length)?;
+        if self.verified {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+
+    /// Record the new image as `version` in trial, then swap the banks and
+    /// reset into it
+    ///
+    /// Only returns if the image was not verified, or the update record could
+    /// not be written.
+    ///
+    /// # Safety
+    ///
+    /// The verified image must be a working, memory safe program, linked to
+    /// run from the start of the flash.
+    pub unsafe fn swap(&mut self, version: u32) -> Result<()> {
+        if !self.verified {
+            return Err(Error::NotVerified);
+        }
+        let metadata = self.metadata()?;
+        self.set_metadata(Metadata {
+            state: ImageState::Trial,
+            pending_version: version,
+            boot_attempts: 0,
+            ..metadata
+        })?;
+        self.nvm.bank_swap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_round_trip() {
+        let metadata = Metadata {
+            state: ImageState::Trial,
+            version: 3,
+            pending_version: 4,
+            boot_attempts: 1,
+        };
+        assert_eq!(Metadata::from_words(metadata.to_words()), Some(metadata));
+        // Erased SmartEEPROM
+        assert_eq!(Metadata::from_words([0xffff_ffff; RECORD_WORDS]), None);
+    }
+
+    #[test]
+    fn trial_is_rolled_back_after_max_attempts() {
+        let mut metadata = Metadata {
+            state: ImageState::Trial,
+            version: 1,
+            pending_version: 2,
+            boot_attempts: 0,
+        };
+        for attempt in 1..=3 {
+            let (next, action) = metadata.boot(3);
+            assert_eq!(action, BootAction::Trial);
+            assert_eq!(next.boot_attempts, attempt);
+            metadata = next;
+        }
+        let (next, action) = metadata.boot(3);
+        assert_eq!(action, BootAction::Rollback);
+        assert_eq!(next.state, ImageState::RolledBack);
+        assert_eq!(next.version, 1);
+
+        // The restored image keeps running
+        let (_, action) = next.boot(3);
+        assert_eq!(action, BootAction::Run);
+    }
+}
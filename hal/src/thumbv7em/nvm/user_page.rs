@@ -0,0 +1,367 @@
+//! # User page editor
+//!
+//! The NVM user page holds the fuses that configure the device at reset: the
+//! BOD33, the bootloader protection size, the SmartEEPROM size, the watchdog
+//! defaults and the NVM region locks. [`Userpage`](super::Userpage) gives a
+//! raw read-only view of it, while [`UserPage`] is a typed, editable copy of
+//! the configuration fields.
+//!
+//! [`Nvm::write_user_page`] applies a [`UserPage`] with a read-erase-rewrite
+//! cycle of the whole page. Only the fields described by [`UserPage`] are
+//! changed; the factory calibration bits, the reserved bits and the user data
+//! stored in the rest of the page are written back unchanged.
+//!
+//! The new configuration takes effect after the next reset.
+//!
+//! ```no_run
+//! # use atsamd_hal::nvm::Nvm;
+//! # fn get_nvm() -> Nvm { unimplemented!() }
+//! let mut nvm = get_nvm();
+//! let mut user_page = nvm.read_user_page();
+//! // Allocate 2 blocks to the SmartEEPROM, with 4 KiB virtual size
+//! user_page.see_sblk = 2;
+//! user_page.see_psz = 3;
+//! nvm.write_user_page(&user_page).unwrap();
+//! ```
+
+use super::{Error, Nvm, Result, PAGESIZE};
+use crate::pac::nvmctrl::ctrlb::CMD_AW;
+
+/// Address of the user page
+pub const USER_PAGE_ADDRESS: u32 = 0x0080_4000;
+
+/// Number of words covered by [`UserPage`]
+pub const USER_PAGE_WORDS: usize = 4;
+
+/// Action taken by the BOD33 when the supply voltage crosses the threshold
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bod33Action {
+    /// No action
+    None = 0,
+    /// The device is reset
+    Reset = 1,
+    /// An interrupt is requested
+    Interrupt = 2,
+    /// The device enters backup sleep mode
+    Backup = 3,
+}
+
+/// BOD33 configuration at reset
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bod33 {
+    /// Disable the BOD33
+    pub disable: bool,
+    /// Threshold level
+    pub level: u8,
+    /// Action when the threshold is crossed
+    pub action: Bod33Action,
+    /// Hysteresis, 4 bits
+    pub hysteresis: u8,
+}
+
+/// Watchdog configuration at reset
+///
+/// The period, window and early warning offset are encoded like the
+/// corresponding WDT register fields, from 0 (8 cycles) to 11 (16K cycles).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WatchdogDefaults {
+    /// Enable the watchdog
+    pub enable: bool,
+    /// Enable the watchdog in always-on mode
+    pub always_on: bool,
+    /// Time-out period
+    pub period: u8,
+    /// Window mode time-out period
+    pub window: u8,
+    /// Early warning interrupt time offset
+    pub ewoffset: u8,
+    /// Enable window mode
+    pub window_mode: bool,
+}
+
+/// Typed configuration fields of the user page
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserPage {
+    /// BOD33 configuration
+    pub bod33: Bod33,
+    /// Bootloader protection, 4 bits. See
+    /// [`bootloader_size`](Self::bootloader_size)
+    pub bootprot: u8,
+    /// Number of blocks allocated to the SmartEEPROM, from 0 (disabled) to 10
+    pub see_sblk: u8,
+    /// SmartEEPROM virtual page size, 3 bits
+    pub see_psz: u8,
+    /// Disable the RAM ECC
+    pub ram_ecc_disable: bool,
+    /// Watchdog configuration
+    pub watchdog: WatchdogDefaults,
+    /// NVM region locks, a cleared bit locks the region at reset
+    pub region_locks: u32,
+}
+
+/// Extract `width` bits of `words` starting at bit `offset`
+fn get_bits(words: &[u32; USER_PAGE_WORDS], offset: usize, width: usize) -> u32 {
+    let word = words[offset / 32] >> (offset % 32);
+    if width == 32 {
+        word
+    } else {
+        word & ((1 << width) - 1)
+    }
+}
+
+/// Write `width` bits of `words` starting at bit `offset`, checking that
+/// `value` fits
+fn set_bits(
+    words: &mut [u32; USER_PAGE_WORDS],
+    offset: usize,
+    width: usize,
+    value: u32,
+) -> Result<()> {
+    let mask = if width == 32 {
+        u32::MAX
+    } else {
+        (1 << width) - 1
+    };
+    if value & !mask != 0 {
+        return Err(Error::InvalidUserPage);
+    }
+    let word = &mut words[offset / 32];
+    *word = (*word & !(mask << (offset % 32))) | (value << (offset % 32));
+    Ok(())
+}
+
+impl UserPage {
+    /// Decode the configuration fields of the first [`USER_PAGE_WORDS`] words
+    /// of the user page
+    pub fn decode(words: &[u32; USER_PAGE_WORDS]) -> Self {
+        let bit = |offset| get_bits(words, offset, 1) != 0;
+        let bits = |offset, width| get_bits(words, offset, width) as u8;
+        Self {
+            bod33: Bod33 {
+                disable: bit(0),
+                level: bits(1, 8),
+                action: match bits(9, 2) {
+                    0 => Bod33Action::None,
+                    1 => Bod33Action::Reset,
+                    2 => Bod33Action::Interrupt,
+                    _ => Bod33Action::Backup,
+                },
+                hysteresis: bits(11, 4),
+            },
+            bootprot: bits(26, 4),
+            see_sblk: bits(32, 4),
+            see_psz: bits(36, 3),
+            ram_ecc_disable: bit(39),
+            watchdog: WatchdogDefaults {
+                enable: bit(48),
+                always_on: bit(49),
+                period: bits(50, 4),
+                window: bits(54, 4),
+                ewoffset: bits(58, 4),
+                window_mode: bit(62),
+            },
+            region_locks: get_bits(words, 64, 32),
+        }
+    }
+
+    /// Encode the configuration fields into `words`
+    ///
+    /// Bits that are not described by [`UserPage`] are left untouched. Returns
+    /// [`Error::InvalidUserPage`] without modifying `words` if a field is out
+    /// of range.
+    pub fn encode(&self, words: &mut [u32; USER_PAGE_WORDS]) -> Result<()> {
+        if self.see_sblk > 10
+            || self.watchdog.period > 11
+            || self.watchdog.window > 11
+            || self.watchdog.ewoffset > 11
+        {
+            return Err(Error::InvalidUserPage);
+        }
+
+        let mut new = *words;
+        let wdt = &self.watchdog;
+        set_bits(&mut new, 0, 1, self.bod33.disable as u32)?;
+        set_bits(&mut new, 1, 8, self.bod33.level as u32)?;
+        set_bits(&mut new, 9, 2, self.bod33.action as u32)?;
+        set_bits(&mut new, 11, 4, self.bod33.hysteresis as u32)?;
+        set_bits(&mut new, 26, 4, self.bootprot as u32)?;
+        set_bits(&mut new, 32, 4, self.see_sblk as u32)?;
+        set_bits(&mut new, 36, 3, self.see_psz as u32)?;
+        set_bits(&mut new, 39, 1, self.ram_ecc_disable as u32)?;
+        set_bits(&mut new, 48, 1, wdt.enable as u32)?;
+        set_bits(&mut new, 49, 1, wdt.always_on as u32)?;
+        set_bits(&mut new, 50, 4, wdt.period as u32)?;
+        set_bits(&mut new, 54, 4, wdt.window as u32)?;
+        set_bits(&mut new, 58, 4, wdt.ewoffset as u32)?;
+        set_bits(&mut new, 62, 1, wdt.window_mode as u32)?;
+        set_bits(&mut new, 64, 32, self.region_locks)?;
+        *words = new;
+        Ok(())
+    }
+
+    /// Size in bytes of the boot protected area at the beginning of the flash
+    #[inline]
+    pub fn bootloader_size(&self) -> u32 {
+        8 * 1024 * (15 - (self.bootprot as u32 & 0xf))
+    }
+
+    /// Set the size of the boot protected area
+    ///
+    /// `size` must be a multiple of 8 KiB, up to 120 KiB.
+    #[inline]
+    pub fn set_bootloader_size(&mut self, size: u32) -> Result<()> {
+        const STEP: u32 = 8 * 1024;
+        if size % STEP != 0 || size > 15 * STEP {
+            return Err(Error::InvalidUserPage);
+        }
+        self.bootprot = (15 - size / STEP) as u8;
+        Ok(())
+    }
+}
+
+impl Nvm {
+    /// Read the configuration fields of the user page
+    #[inline]
+    pub fn read_user_page(&self) -> UserPage {
+        UserPage::decode(&read_words())
+    }
+
+    /// Write the configuration fields of the user page
+    ///
+    /// The whole page is read, erased and written back with the new
+    /// configuration, preserving the calibration and reserved bits as well as
+    /// the user data. Nothing is written if the configuration is unchanged.
+    ///
+    /// A reset or power loss during this operation leaves the user page
+    /// erased, in which case the device boots with every fuse set to `1`.
+    pub fn write_user_page(&mut self, user_page: &UserPage) -> Result<()> {
+        let mut buffer = [0_u32; (PAGESIZE / 4) as usize];
+        let base_addr = USER_PAGE_ADDRESS as *const u32;
+        for (i, word) in buffer.iter_mut().enumerate() {
+            // Safety: the user page is always readable
+            *word = unsafe { core::ptr::read_volatile(base_addr.add(i)) };
+        }
+
+        let mut words = [0_u32; USER_PAGE_WORDS];
+        words.copy_from_slice(&buffer[..USER_PAGE_WORDS]);
+        user_page.encode(&mut words)?;
+        if words[..] == buffer[..USER_PAGE_WORDS] {
+            return Ok(());
+        }
+        buffer[..USER_PAGE_WORDS].copy_from_slice(&words);
+
+        while !self.is_ready() {}
+        self.set_address(USER_PAGE_ADDRESS);
+        self.command_sync(CMD_AW::EP);
+        self.manage_error_states()?;
+
+        // The user page is written one quad word at a time
+        let base_addr = USER_PAGE_ADDRESS as *mut u32;
+        self.command_sync(CMD_AW::PBC);
+        for (i, quad_word) in buffer.chunks_exact(4).enumerate() {
+            for (j, word) in quad_word.iter().enumerate() {
+                // Safety: the data is written to the page buffer
+                unsafe { core::ptr::write_volatile(base_addr.add(4 * i + j), *word) };
+            }
+            while !self.is_ready() {}
+            self.set_address(USER_PAGE_ADDRESS + 16 * i as u32);
+            self.command_sync(CMD_AW::WQW);
+            self.manage_error_states()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Read the words covered by [`UserPage`]
+fn read_words() -> [u32; USER_PAGE_WORDS] {
+    let mut words = [0_u32; USER_PAGE_WORDS];
+    let base_addr = USER_PAGE_ADDRESS as *const u32;
+    for (i, word) in words.iter_mut().enumerate() {
+        // Safety: the user page is always readable
+        *word = unsafe { core::ptr::read_volatile(base_addr.add(i)) };
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // User page of a device fresh from the factory
+    const FACTORY: [u32; USER_PAGE_WORDS] = [0xfe9a_9239, 0xaeec_ff80, 0xffff_ffff, 0xffff_ffff];
+
+    #[test]
+    fn decode_factory_page() {
+        let page = UserPage::decode(&FACTORY);
+        assert!(page.bod33.disable);
+        assert_eq!(page.bod33.level, 0x1c);
+        assert_eq!(page.bod33.action, Bod33Action::Reset);
+        assert_eq!(page.bod33.hysteresis, 2);
+        assert_eq!(page.bootprot, 15);
+        assert_eq!(page.bootloader_size(), 0);
+        assert_eq!(page.see_sblk, 0);
+        assert_eq!(page.see_psz, 0);
+        assert!(!page.watchdog.enable);
+        assert_eq!(page.watchdog.period, 0xb);
+        assert_eq!(page.region_locks, 0xffff_ffff);
+    }
+
+    #[test]
+    fn encode_preserves_other_bits() {
+        let mut words = FACTORY;
+        let mut page = UserPage::decode(&words);
+        page.encode(&mut words).unwrap();
+        assert_eq!(words, FACTORY);
+
+        page.see_sblk = 2;
+        page.see_psz = 3;
+        page.set_bootloader_size(16 * 1024).unwrap();
+        page.encode(&mut words).unwrap();
+        assert_eq!(UserPage::decode(&words), page);
+        // Only the SEE and BOOTPROT fields changed
+        assert_eq!(words[0] ^ FACTORY[0], (15 ^ 13) << 26);
+        assert_eq!(words[1] ^ FACTORY[1], 0x32);
+        assert_eq!(words[2..], FACTORY[2..]);
+    }
+
+    #[test]
+    fn encode_rejects_invalid_fields() {
+        let mut words = FACTORY;
+        let page = UserPage::decode(&words);
+
+        let invalid = [
+            UserPage {
+                see_sblk: 11,
+                ..page
+            },
+            UserPage { see_psz: 8, ..page },
+            UserPage {
+                bootprot: 16,
+                ..page
+            },
+            UserPage {
+                bod33: Bod33 {
+                    hysteresis: 16,
+                    ..page.bod33
+                },
+                ..page
+            },
+            UserPage {
+                watchdog: WatchdogDefaults {
+                    period: 12,
+                    ..page.watchdog
+                },
+                ..page
+            },
+        ];
+        for page in invalid.iter() {
+            assert!(page.encode(&mut words).is_err());
+            assert_eq!(words, FACTORY);
+        }
+
+        let mut page = page;
+        assert!(page.set_bootloader_size(4 * 1024).is_err());
+        assert!(page.set_bootloader_size(128 * 1024).is_err());
+    }
+}
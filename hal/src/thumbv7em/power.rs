@@ -0,0 +1,260 @@
+//! # Power management
+//!
+//! [`Power`] selects the sleep mode entered by [`Power::sleep`]:
+//!
+//! - In [`SleepMode::Idle`], the CPU is stopped and any interrupt wakes the
+//!   device.
+//! - In [`SleepMode::Standby`], every clock is stopped, except the generic
+//!   clocks and oscillators configured to run in standby. Only peripherals that
+//!   run in standby, or that can operate without a clock like the EIC, can
+//!   wake the device. The RAM retained in standby is selected with
+//!   [`Power::set_standby_ram`].
+//! - In [`SleepMode::Hibernate`] and [`SleepMode::Backup`], the core domain is
+//!   powered off, and the device is reset when it wakes up. The reset cause is
+//!   then [`ResetCause::Backup`](super::ResetCause::Backup), and
+//!   [`backup_exit`] reports what woke the device.
+//! - In [`SleepMode::Off`], the whole device is powered off until the next
+//!   external reset.
+//!
+//! Clock generators are kept running in standby with
+//! [`GenericClockController::configure_standby`], and most peripherals have
+//! a `run_in_standby` setting of their own, e.g. [`uart::Config`].
+//!
+//! After waking from standby, [`Power::sleep`] waits for the oscillators that
+//! were stopped to be ready again, so that the clocks run at their configured
+//! frequencies when it returns.
+//!
+//! [`GenericClockController::configure_standby`]: crate::clock::GenericClockController::configure_standby
+//! [`uart::Config`]: crate::sercom::uart::Config
+
+use cortex_m::asm;
+use cortex_m::peripheral::NVIC;
+
+use crate::pac::pm::sleepcfg::SLEEPMODE_A;
+use crate::pac::{OSC32KCTRL, OSCCTRL, PM, RSTC};
+
+/// Number of implemented interrupt set-pending registers
+const NUM_ISPR: usize = 5;
+
+/// Sleep modes of the device
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SleepMode {
+    /// The CPU is stopped
+    Idle,
+    /// Every clock is stopped, except those configured to run in standby
+    Standby,
+    /// The core domain is powered off, the backup domain is kept
+    Hibernate,
+    /// Only the backup domain is kept
+    Backup,
+    /// The whole device is powered off
+    Off,
+}
+
+/// RAM retention in the low power sleep modes
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RamRetention {
+    /// The whole RAM is retained
+    Full,
+    /// Only the RAM configured by the `PRAMx` registers of the PAC is retained
+    Partial,
+    /// The RAM is not retained
+    Off,
+}
+
+/// Voltage regulator and NVM settings for a faster wake up from standby
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FastWakeup {
+    /// Regular wake up
+    Disabled,
+    /// The NVM is kept powered in standby
+    Nvm,
+    /// The main voltage regulator is kept on in standby
+    MainVreg,
+    /// Both the NVM and the main voltage regulator are kept powered
+    Both,
+}
+
+/// Sources that woke the device from hibernate or backup sleep
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BackupExit {
+    /// An RTC event, like an alarm, a periodic event or a tamper detection
+    Rtc,
+    /// The battery backup power switch restored the main supply
+    BatteryBackupPowerSwitch,
+    /// The device woke up from hibernate sleep
+    Hibernate,
+}
+
+/// Sleep mode configuration
+pub struct Power {
+    pm: PM,
+}
+
+impl Power {
+    /// Create a new [`Power`], entering [`SleepMode::Idle`] on sleep
+    pub fn new(pm: PM) -> Self {
+        let mut power = Self { pm };
+        power.set_sleep_mode(SleepMode::Idle);
+        power
+    }
+
+    /// Release the `PM` peripheral
+    pub fn free(self) -> PM {
+        self.pm
+    }
+
+    /// Sleep mode entered by [`Power::sleep`]
+    pub fn sleep_mode(&self) -> SleepMode {
+        match self.pm.sleepcfg.read().sleepmode().variant() {
+            Some(SLEEPMODE_A::STANDBY) => SleepMode::Standby,
+            Some(SLEEPMODE_A::HIBERNATE) => SleepMode::Hibernate,
+            Some(SLEEPMODE_A::BACKUP) => SleepMode::Backup,
+            Some(SLEEPMODE_A::OFF) => SleepMode::Off,
+            _ => SleepMode::Idle,
+        }
+    }
+
+    /// Select the sleep mode entered by [`Power::sleep`]
+    pub fn set_sleep_mode(&mut self, mode: SleepMode) {
+        let variant = match mode {
+            SleepMode::Idle => SLEEPMODE_A::IDLE,
+            SleepMode::Standby => SLEEPMODE_A::STANDBY,
+            SleepMode::Hibernate => SLEEPMODE_A::HIBERNATE,
+            SleepMode::Backup => SLEEPMODE_A::BACKUP,
+            SleepMode::Off => SLEEPMODE_A::OFF,
+        };
+        self.pm.sleepcfg.write(|w| w.sleepmode().variant(variant));
+        // The new mode must be read back before entering sleep
+        while self.pm.sleepcfg.read().sleepmode().variant() != Some(variant) {}
+    }
+
+    /// Select the RAM retained in standby
+    #[inline]
+    pub fn set_standby_ram(&mut self, ram: RamRetention) {
+        self.pm.stdbycfg.modify(|_, w| match ram {
+            RamRetention::Full => w.ramcfg().ret(),
+            RamRetention::Partial => w.ramcfg().partial(),
+            RamRetention::Off => w.ramcfg().off(),
+        });
+    }
+
+    /// Select what is kept powered in standby, for a faster wake up
+    #[inline]
+    pub fn set_fast_wakeup(&mut self, fast_wakeup: FastWakeup) {
+        self.pm.stdbycfg.modify(|_, w| match fast_wakeup {
+            FastWakeup::Disabled => w.fastwkup().no(),
+            FastWakeup::Nvm => w.fastwkup().nvm(),
+            FastWakeup::MainVreg => w.fastwkup().mainvreg(),
+            FastWakeup::Both => w.fastwkup().both(),
+        });
+    }
+
+    /// Select the RAM and backup RAM retained in hibernate
+    #[inline]
+    pub fn set_hibernate_ram(&mut self, ram: RamRetention, backup_ram: RamRetention) {
+        self.pm.hibcfg.write(|w| {
+            match ram {
+                RamRetention::Full => w.ramcfg().ret(),
+                RamRetention::Partial => w.ramcfg().partial(),
+                RamRetention::Off => w.ramcfg().off(),
+            };
+            match backup_ram {
+                RamRetention::Full => w.bramcfg().ret(),
+                RamRetention::Partial => w.bramcfg().partial(),
+                RamRetention::Off => w.bramcfg().off(),
+            }
+        });
+    }
+
+    /// Select the backup RAM retained in backup sleep
+    #[inline]
+    pub fn set_backup_ram(&mut self, backup_ram: RamRetention) {
+        self.pm.bkupcfg.write(|w| match backup_ram {
+            RamRetention::Full => w.bramcfg().ret(),
+            RamRetention::Partial => w.bramcfg().partial(),
+            RamRetention::Off => w.bramcfg().off(),
+        });
+    }
+
+    /// Keep the I/O lines in their state when waking from hibernate or backup
+    ///
+    /// The retention is released by calling this with `false` once the pins
+    /// have been configured again.
+    #[inline]
+    pub fn set_io_retention(&mut self, enable: bool) {
+        self.pm.ctrla.write(|w| w.ioret().bit(enable));
+    }
+
+    /// Enter the selected sleep mode, until an interrupt wakes the device
+    ///
+    /// The device also wakes up on interrupts masked with `PRIMASK`, e.g.
+    /// inside a [`cortex_m::interrupt::free`] critical section. The interrupt
+    /// is then still pending when this returns, see [`pending_interrupt`].
+    ///
+    /// In hibernate, backup and off modes, the device is reset instead of
+    /// returning.
+    pub fn sleep(&mut self) {
+        asm::dsb();
+        asm::wfi();
+        if self.sleep_mode() == SleepMode::Standby {
+            wait_for_clocks();
+        }
+    }
+}
+
+/// Wait for the enabled oscillators to be ready after waking from standby
+fn wait_for_clocks() {
+    // Safety: only status registers are read
+    let oscctrl = unsafe { &*OSCCTRL::ptr() };
+    let osc32kctrl = unsafe { &*OSC32KCTRL::ptr() };
+    if osc32kctrl.xosc32k.read().enable().bit_is_set() {
+        while osc32kctrl.status.read().xosc32krdy().bit_is_clear() {}
+    }
+    if oscctrl.xoscctrl[0].read().enable().bit_is_set() {
+        while oscctrl.status.read().xoscrdy0().bit_is_clear() {}
+    }
+    if oscctrl.xoscctrl[1].read().enable().bit_is_set() {
+        while oscctrl.status.read().xoscrdy1().bit_is_clear() {}
+    }
+    if oscctrl.dfllctrla.read().enable().bit_is_set() {
+        while oscctrl.status.read().dfllrdy().bit_is_clear() {}
+    }
+    for dpll in oscctrl.dpll.iter() {
+        if dpll.dpllctrla.read().enable().bit_is_set() {
+            while dpll.dpllstatus.read().lock().bit_is_clear()
+                || dpll.dpllstatus.read().clkrdy().bit_is_clear()
+            {}
+        }
+    }
+}
+
+/// Returns what woke the device from hibernate or backup sleep
+///
+/// This is only meaningful when the reset cause is
+/// [`ResetCause::Backup`](super::ResetCause::Backup).
+pub fn backup_exit(rstc: &RSTC) -> Option<BackupExit> {
+    let exit = rstc.bkupexit.read();
+    if exit.rtc().bit_is_set() {
+        Some(BackupExit::Rtc)
+    } else if exit.bbps().bit_is_set() {
+        Some(BackupExit::BatteryBackupPowerSwitch)
+    } else if exit.hib().bit_is_set() {
+        Some(BackupExit::Hibernate)
+    } else {
+        None
+    }
+}
+
+/// Returns the number of the lowest enabled and pending interrupt
+///
+/// After waking from sleep with interrupts masked, this is the interrupt that
+/// woke the device.
+pub fn pending_interrupt() -> Option<u16> {
+    // Safety: the NVIC registers are only read
+    let nvic = unsafe { &*NVIC::PTR };
+    (0..NUM_ISPR).find_map(|i| {
+        let pending = nvic.ispr[i].read() & nvic.iser[i].read();
+        (pending != 0).then(|| (32 * i) as u16 + pending.trailing_zeros() as u16)
+    })
+}
@@ -0,0 +1,50 @@
+//! ADMA2 descriptor table
+//!
+//! In ADMA2 mode, the SDHC transfers data between the card and RAM by
+//! following a table of descriptors, each describing a contiguous buffer of up
+//! to 64 KiB. The table only needs to live for the duration of a transfer, so
+//! it is kept on the stack.
+
+/// Number of descriptors in a table
+pub(super) const DESCRIPTORS: usize = 8;
+
+/// Largest buffer described by a single descriptor
+pub(super) const MAX_DESCRIPTOR_LEN: usize = 65536;
+
+/// Largest transfer described by a table
+pub(super) const MAX_TRANSFER_LEN: usize = DESCRIPTORS * MAX_DESCRIPTOR_LEN;
+
+const VALID: u32 = 1 << 0;
+const END: u32 = 1 << 1;
+const ACT_TRAN: u32 = 0b10 << 4;
+
+/// ADMA2 descriptor table
+#[repr(C, align(4))]
+pub(super) struct DescriptorTable([[u32; 2]; DESCRIPTORS]);
+
+impl DescriptorTable {
+    /// Build a table describing `len` bytes starting at `address`. `len` must
+    /// not exceed [`MAX_TRANSFER_LEN`].
+    pub(super) fn new(address: u32, len: usize) -> Self {
+        let mut table = [[0; 2]; DESCRIPTORS];
+        let chunks = (len + MAX_DESCRIPTOR_LEN - 1) / MAX_DESCRIPTOR_LEN;
+        for (index, descriptor) in table.iter_mut().enumerate().take(chunks) {
+            let offset = index * MAX_DESCRIPTOR_LEN;
+            let chunk = (len - offset).min(MAX_DESCRIPTOR_LEN);
+            let mut attributes = VALID | ACT_TRAN;
+            if index == chunks - 1 {
+                attributes |= END;
+            }
+            // A length of 0 stands for 65536 bytes
+            let length = (chunk % MAX_DESCRIPTOR_LEN) as u32;
+            *descriptor = [attributes | length << 16, address + offset as u32];
+        }
+        Self(table)
+    }
+
+    /// Address of the table
+    #[inline]
+    pub(super) fn address(&self) -> u32 {
+        self.0.as_ptr() as u32
+    }
+}
@@ -0,0 +1,100 @@
+//! `embedded-sdmmc` integration
+
+use core::cell::RefCell;
+use core::mem::size_of;
+use core::slice;
+
+use embedded_sdmmc::{Block, BlockCount, BlockDevice, BlockIdx};
+
+use super::{Error, Sdhc, SdhcInstance, SdhcPins, BLOCK_LEN};
+
+/// Return whether `blocks` can be transferred in a single multi-block
+/// transfer, directly from or to their memory
+fn is_contiguous(blocks: &[Block]) -> bool {
+    size_of::<Block>() == BLOCK_LEN && blocks.as_ptr() as usize % 4 == 0
+}
+
+/// Word-aligned bounce buffer, used when the blocks passed by
+/// `embedded-sdmmc` are not word-aligned
+#[repr(C, align(4))]
+struct Aligned([u8; BLOCK_LEN]);
+
+/// [`BlockDevice`] backed by an [`Sdhc`] with an initialized card
+///
+/// `BlockDevice` methods take `&self`, so the [`Sdhc`] is kept in a
+/// [`RefCell`].
+pub struct SdhcBlockDevice<I: SdhcInstance, P: SdhcPins<I>> {
+    sdhc: RefCell<Sdhc<I, P>>,
+}
+
+impl<I: SdhcInstance, P: SdhcPins<I>> SdhcBlockDevice<I, P> {
+    /// Wrap an [`Sdhc`]. Return [`Error::NoCard`] if no card was initialized
+    /// with [`Sdhc::init_card`].
+    pub fn new(sdhc: Sdhc<I, P>) -> Result<Self, Error> {
+        sdhc.card().ok_or(Error::NoCard)?;
+        Ok(Self {
+            sdhc: RefCell::new(sdhc),
+        })
+    }
+
+    /// Return the underlying [`Sdhc`]
+    pub fn free(self) -> Sdhc<I, P> {
+        self.sdhc.into_inner()
+    }
+}
+
+impl<I: SdhcInstance, P: SdhcPins<I>> BlockDevice for SdhcBlockDevice<I, P> {
+    type Error = Error;
+
+    fn read(
+        &self,
+        blocks: &mut [Block],
+        start_block_idx: BlockIdx,
+        _reason: &str,
+    ) -> Result<(), Error> {
+        let mut sdhc = self.sdhc.borrow_mut();
+        if is_contiguous(blocks) {
+            // Safety: the blocks are contiguous arrays of `BLOCK_LEN` bytes
+            let buffer = unsafe {
+                slice::from_raw_parts_mut(blocks.as_mut_ptr() as *mut u8, blocks.len() * BLOCK_LEN)
+            };
+            return sdhc.read_blocks(start_block_idx.0, buffer);
+        }
+        let mut bounce = Aligned([0; BLOCK_LEN]);
+        for (index, block) in (start_block_idx.0..).zip(blocks.iter_mut()) {
+            if block.contents.as_ptr() as usize % 4 == 0 {
+                sdhc.read_blocks(index, &mut block.contents)?;
+            } else {
+                sdhc.read_blocks(index, &mut bounce.0)?;
+                block.contents.copy_from_slice(&bounce.0);
+            }
+        }
+        Ok(())
+    }
+
+    fn write(&self, blocks: &[Block], start_block_idx: BlockIdx) -> Result<(), Error> {
+        let mut sdhc = self.sdhc.borrow_mut();
+        if is_contiguous(blocks) {
+            // Safety: the blocks are contiguous arrays of `BLOCK_LEN` bytes
+            let buffer = unsafe {
+                slice::from_raw_parts(blocks.as_ptr() as *const u8, blocks.len() * BLOCK_LEN)
+            };
+            return sdhc.write_blocks(start_block_idx.0, buffer);
+        }
+        let mut bounce = Aligned([0; BLOCK_LEN]);
+        for (index, block) in (start_block_idx.0..).zip(blocks.iter()) {
+            if block.contents.as_ptr() as usize % 4 == 0 {
+                sdhc.write_blocks(index, &block.contents)?;
+            } else {
+                bounce.0.copy_from_slice(&block.contents);
+                sdhc.write_blocks(index, &bounce.0)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn num_blocks(&self) -> Result<BlockCount, Error> {
+        let card = self.sdhc.borrow().card().ok_or(Error::NoCard)?;
+        Ok(BlockCount(card.blocks))
+    }
+}
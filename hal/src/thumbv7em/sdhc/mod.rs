@@ -0,0 +1,844 @@
+//! # SD/MMC Host Controller
+//!
+//! The SDHC peripherals drive SD cards natively, over a 1-bit or 4-bit data
+//! bus, at up to 50 MHz in high speed mode. Data is moved between the card and
+//! RAM by the ADMA2 engine of the SDHC, without CPU involvement.
+//!
+//! # Initializing
+//!
+//! The generic clock of the SDHC must be configured through the
+//! [`GenericClockController`](crate::clock::GenericClockController). It is
+//! divided down to 400 kHz during card identification, and to 25 MHz or
+//! 50 MHz afterwards.
+//!
+//! ```
+//! let gclk0 = clocks.gclk0();
+//! let sdhc_clock = clocks.sdhc0(&gclk0).unwrap();
+//! let pins = FourBitPins::new(pins.pb11, pins.pa08, pins.pa09, pins.pa10, pins.pa11, pins.pb10);
+//! let mut sdhc = Sdhc::new(
+//!     peripherals.SDHC0,
+//!     pins,
+//!     &mut peripherals.MCLK,
+//!     &sdhc_clock,
+//!     gclk0,
+//! );
+//! let card = sdhc.init_card().unwrap();
+//!
+//! #[repr(align(4))]
+//! struct Aligned([u8; BLOCK_LEN]);
+//!
+//! let mut block = Aligned([0; BLOCK_LEN]);
+//! sdhc.read_blocks(0, &mut block.0).unwrap();
+//! ```
+//!
+//! Buffers passed to [`Sdhc::read_blocks`] and [`Sdhc::write_blocks`] must be
+//! word-aligned, as required by the ADMA2 engine.
+//!
+//! # `embedded-sdmmc`
+//!
+//! With the `sdmmc` feature, [`SdhcBlockDevice`] wraps an initialized [`Sdhc`]
+//! and implements `embedded_sdmmc::BlockDevice`, so it can be used with the
+//! FAT implementation of the `embedded-sdmmc` crate.
+
+use core::ops::Deref;
+
+use crate::clock::Sdhc0Clock;
+#[cfg(any(
+    feature = "samd51n",
+    feature = "samd51p",
+    feature = "same53n",
+    feature = "same54n",
+    feature = "same54p"
+))]
+use crate::clock::Sdhc1Clock;
+use crate::gpio::*;
+#[cfg(any(
+    feature = "samd51n",
+    feature = "samd51p",
+    feature = "same53n",
+    feature = "same54n",
+    feature = "same54p"
+))]
+use crate::pac::SDHC1;
+use crate::pac::{sdhc0, MCLK, SDHC0};
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+mod adma;
+#[cfg(feature = "sdmmc")]
+mod block_device;
+
+#[cfg(feature = "sdmmc")]
+pub use block_device::SdhcBlockDevice;
+
+use adma::DescriptorTable;
+
+/// Size of a block, in bytes
+pub const BLOCK_LEN: usize = 512;
+
+//==============================================================================
+// SdhcInstance
+//==============================================================================
+
+/// Type-level enum of the SDHC peripherals
+pub trait SdhcInstance: Sealed + Deref<Target = sdhc0::RegisterBlock> {
+    /// Clock token of the peripheral
+    type Clock;
+    /// Clock pin
+    type Clk: PinId;
+    /// Command pin
+    type Cmd: PinId;
+    /// Data line 0 pin
+    type Dat0: PinId;
+    /// Data line 1 pin
+    type Dat1: PinId;
+    /// Data line 2 pin
+    type Dat2: PinId;
+    /// Data line 3 pin
+    type Dat3: PinId;
+
+    /// Frequency of the clock token
+    fn clock_freq(clock: &Self::Clock) -> Hertz;
+
+    /// Enable the AHB clock of the peripheral
+    fn enable_ahb_clock(mclk: &mut MCLK);
+}
+
+macro_rules! sdhc_instance {
+    (
+        $( #[$cfg:meta] )?
+        $Instance:ident, $Clock:ident, $mask:ident,
+        $Clk:ident, $Cmd:ident, $Dat0:ident, $Dat1:ident, $Dat2:ident, $Dat3:ident
+    ) => {
+        $( #[$cfg] )?
+        impl Sealed for $Instance {}
+
+        $( #[$cfg] )?
+        impl SdhcInstance for $Instance {
+            type Clock = $Clock;
+            type Clk = $Clk;
+            type Cmd = $Cmd;
+            type Dat0 = $Dat0;
+            type Dat1 = $Dat1;
+            type Dat2 = $Dat2;
+            type Dat3 = $Dat3;
+
+            #[inline]
+            fn clock_freq(clock: &$Clock) -> Hertz {
+                clock.freq()
+            }
+
+            #[inline]
+            fn enable_ahb_clock(mclk: &mut MCLK) {
+                mclk.ahbmask.modify(|_, w| w.$mask().set_bit());
+            }
+        }
+    };
+}
+
+sdhc_instance!(SDHC0, Sdhc0Clock, sdhc0_, PB11, PA08, PA09, PA10, PA11, PB10);
+
+sdhc_instance!(
+    #[cfg(any(
+        feature = "samd51n",
+        feature = "samd51p",
+        feature = "same53n",
+        feature = "same54n",
+        feature = "same54p"
+    ))]
+    SDHC1,
+    Sdhc1Clock,
+    sdhc1_,
+    PA21,
+    PA20,
+    PB18,
+    PB19,
+    PB20,
+    PB21
+);
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+/// Width of the data bus
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BusWidth {
+    /// Data line 0 only
+    OneBit,
+    /// Data lines 0 to 3
+    FourBit,
+}
+
+/// Set of pins connecting an SDHC to a card
+pub trait SdhcPins<I: SdhcInstance>: Sealed {
+    /// Width of the data bus
+    const WIDTH: BusWidth;
+}
+
+/// Pins of a 1-bit bus
+pub struct OneBitPins<I: SdhcInstance> {
+    clk: Pin<I::Clk, AlternateI>,
+    cmd: Pin<I::Cmd, AlternateI>,
+    dat0: Pin<I::Dat0, AlternateI>,
+}
+
+impl<I: SdhcInstance> OneBitPins<I> {
+    /// Configure the pins of a 1-bit bus
+    pub fn new(
+        clk: impl AnyPin<Id = I::Clk>,
+        cmd: impl AnyPin<Id = I::Cmd>,
+        dat0: impl AnyPin<Id = I::Dat0>,
+    ) -> Self {
+        Self {
+            clk: clk.into().into_alternate(),
+            cmd: cmd.into().into_alternate(),
+            dat0: dat0.into().into_alternate(),
+        }
+    }
+
+    /// Return the pins
+    #[allow(clippy::type_complexity)]
+    pub fn free(
+        self,
+    ) -> (
+        Pin<I::Clk, AlternateI>,
+        Pin<I::Cmd, AlternateI>,
+        Pin<I::Dat0, AlternateI>,
+    ) {
+        (self.clk, self.cmd, self.dat0)
+    }
+}
+
+impl<I: SdhcInstance> Sealed for OneBitPins<I> {}
+
+impl<I: SdhcInstance> SdhcPins<I> for OneBitPins<I> {
+    const WIDTH: BusWidth = BusWidth::OneBit;
+}
+
+/// Pins of a 4-bit bus
+pub struct FourBitPins<I: SdhcInstance> {
+    clk: Pin<I::Clk, AlternateI>,
+    cmd: Pin<I::Cmd, AlternateI>,
+    dat0: Pin<I::Dat0, AlternateI>,
+    dat1: Pin<I::Dat1, AlternateI>,
+    dat2: Pin<I::Dat2, AlternateI>,
+    dat3: Pin<I::Dat3, AlternateI>,
+}
+
+impl<I: SdhcInstance> FourBitPins<I> {
+    /// Configure the pins of a 4-bit bus
+    pub fn new(
+        clk: impl AnyPin<Id = I::Clk>,
+        cmd: impl AnyPin<Id = I::Cmd>,
+        dat0: impl AnyPin<Id = I::Dat0>,
+        dat1: impl AnyPin<Id = I::Dat1>,
+        dat2: impl AnyPin<Id = I::Dat2>,
+        dat3: impl AnyPin<Id = I::Dat3>,
+    ) -> Self {
+        Self {
+            clk: clk.into().into_alternate(),
+            cmd: cmd.into().into_alternate(),
+            dat0: dat0.into().into_alternate(),
+            dat1: dat1.into().into_alternate(),
+            dat2: dat2.into().into_alternate(),
+            dat3: dat3.into().into_alternate(),
+        }
+    }
+
+    /// Return the pins
+    #[allow(clippy::type_complexity)]
+    pub fn free(
+        self,
+    ) -> (
+        Pin<I::Clk, AlternateI>,
+        Pin<I::Cmd, AlternateI>,
+        Pin<I::Dat0, AlternateI>,
+        Pin<I::Dat1, AlternateI>,
+        Pin<I::Dat2, AlternateI>,
+        Pin<I::Dat3, AlternateI>,
+    ) {
+        (
+            self.clk, self.cmd, self.dat0, self.dat1, self.dat2, self.dat3,
+        )
+    }
+}
+
+impl<I: SdhcInstance> Sealed for FourBitPins<I> {}
+
+impl<I: SdhcInstance> SdhcPins<I> for FourBitPins<I> {
+    const WIDTH: BusWidth = BusWidth::FourBit;
+}
+
+//==============================================================================
+// Error
+//==============================================================================
+
+/// SDHC errors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The card did not respond to a command
+    CommandTimeout,
+    /// The response to a command had a wrong CRC
+    CommandCrc,
+    /// The response to a command had a wrong end bit
+    CommandEndBit,
+    /// The response to a command had a wrong index
+    CommandIndex,
+    /// The card did not send or accept data in time
+    DataTimeout,
+    /// A data block had a wrong CRC
+    DataCrc,
+    /// A data block had a wrong end bit
+    DataEndBit,
+    /// The current drawn by the card exceeded the limit
+    CurrentLimit,
+    /// The automatic CMD12 ending a multi-block transfer failed
+    AutoCommand,
+    /// The ADMA2 engine failed to access a descriptor or buffer
+    Adma,
+    /// The card is not a supported SD card
+    UnsupportedCard,
+    /// The card did not become ready during initialization
+    InitTimeout,
+    /// No card was initialized with [`Sdhc::init_card`]
+    NoCard,
+    /// The buffer is not word-aligned, or its length is not a multiple of
+    /// [`BLOCK_LEN`]
+    InvalidBuffer,
+    /// The blocks are beyond the end of the card
+    OutOfRange,
+}
+
+impl Error {
+    /// Decode the error interrupt status
+    fn from_status(status: u16) -> Self {
+        const ERRORS: [Error; 10] = [
+            Error::CommandTimeout,
+            Error::CommandCrc,
+            Error::CommandEndBit,
+            Error::CommandIndex,
+            Error::DataTimeout,
+            Error::DataCrc,
+            Error::DataEndBit,
+            Error::CurrentLimit,
+            Error::AutoCommand,
+            Error::Adma,
+        ];
+        ERRORS
+            .iter()
+            .enumerate()
+            .find(|(bit, _)| status & (1 << bit) != 0)
+            .map_or(Error::DataTimeout, |(_, &error)| error)
+    }
+}
+
+//==============================================================================
+// Commands
+//==============================================================================
+
+/// Type of the response to a command
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Response {
+    /// No response
+    None,
+    /// 136-bit response (R2)
+    Long,
+    /// 48-bit response, with CRC and index (R1, R6, R7)
+    Short,
+    /// 48-bit response, without CRC and index (R3)
+    ShortNoCrc,
+    /// 48-bit response followed by a busy signal (R1b)
+    ShortBusy,
+}
+
+impl Response {
+    /// Bits of the CR register
+    fn bits(self) -> u16 {
+        const CMDCCEN: u16 = 1 << 3;
+        const CMDICEN: u16 = 1 << 4;
+        match self {
+            Response::None => 0,
+            Response::Long => 1 | CMDCCEN,
+            Response::Short => 2 | CMDCCEN | CMDICEN,
+            Response::ShortNoCrc => 2,
+            Response::ShortBusy => 3 | CMDCCEN | CMDICEN,
+        }
+    }
+}
+
+/// Direction of a data transfer
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Read,
+    Write,
+}
+
+/// Data phase of a command
+struct Data {
+    address: u32,
+    block_len: u16,
+    blocks: u16,
+    direction: Direction,
+}
+
+// Commands
+const GO_IDLE_STATE: u8 = 0;
+const ALL_SEND_CID: u8 = 2;
+const SEND_RELATIVE_ADDR: u8 = 3;
+const SWITCH_FUNC: u8 = 6;
+const SELECT_CARD: u8 = 7;
+const SEND_IF_COND: u8 = 8;
+const SEND_CSD: u8 = 9;
+const SET_BLOCKLEN: u8 = 16;
+const READ_SINGLE_BLOCK: u8 = 17;
+const READ_MULTIPLE_BLOCK: u8 = 18;
+const WRITE_BLOCK: u8 = 24;
+const WRITE_MULTIPLE_BLOCK: u8 = 25;
+const APP_CMD: u8 = 55;
+// Application commands
+const SET_BUS_WIDTH: u8 = 6;
+const SD_SEND_OP_COND: u8 = 41;
+
+// Bits of the OCR register
+const OCR_BUSY: u32 = 1 << 31;
+const OCR_CCS: u32 = 1 << 30;
+const OCR_VOLTAGE_WINDOW: u32 = 0x00FF_8000;
+
+// Interrupt status bits
+const NISTR_CMDC: u16 = 1 << 0;
+const NISTR_TRFC: u16 = 1 << 1;
+const NISTR_ERRINT: u16 = 1 << 15;
+
+// Transfer mode bits
+const TMR_DMAEN: u16 = 1 << 0;
+const TMR_BCEN: u16 = 1 << 1;
+const TMR_ACMD12: u16 = 1 << 2;
+const TMR_DTDSEL: u16 = 1 << 4;
+const TMR_MSBSEL: u16 = 1 << 5;
+
+// Host control 1 bits
+const HC1R_DW: u8 = 1 << 1;
+const HC1R_HSEN: u8 = 1 << 2;
+const HC1R_ADMA2: u8 = 0b10 << 3;
+const HC1R_CARDDTL: u8 = 1 << 6;
+const HC1R_CARDDSEL: u8 = 1 << 7;
+
+// Clock control bits
+const CCR_INTCLKEN: u16 = 1 << 0;
+const CCR_INTCLKS: u16 = 1 << 1;
+const CCR_SDCLKEN: u16 = 1 << 2;
+
+/// Clock frequency during card identification
+const IDENTIFICATION_FREQ: u32 = 400_000;
+/// Clock frequency in default speed mode
+const DEFAULT_SPEED_FREQ: u32 = 25_000_000;
+/// Clock frequency in high speed mode
+const HIGH_SPEED_FREQ: u32 = 50_000_000;
+/// Number of `SD_SEND_OP_COND` attempts, about 1 ms apart, before giving up on a
+/// card. Cards must become ready within one second.
+const OP_COND_RETRIES: u32 = 1000;
+
+//==============================================================================
+// Card
+//==============================================================================
+
+/// Information about an initialized card
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Card {
+    /// Relative card address
+    pub rca: u16,
+    /// The card is an SDHC or SDXC card, addressed by block rather than by
+    /// byte
+    pub high_capacity: bool,
+    /// The card runs in high speed mode
+    pub high_speed: bool,
+    /// Number of 512-byte blocks
+    pub blocks: u32,
+}
+
+impl Card {
+    /// Compute the number of blocks from the CSD register, as read from the
+    /// response registers (CSD bits 127 to 8)
+    fn blocks_from_csd(rr: [u32; 4]) -> Result<u32, Error> {
+        match (rr[3] >> 22) & 0x3 {
+            // CSD version 1.0
+            0 => {
+                let c_size = (rr[1] >> 22) | (rr[2] & 0x3) << 10;
+                let c_size_mult = (rr[1] >> 7) & 0x7;
+                let read_bl_len = (rr[2] >> 8) & 0xF;
+                let bytes = u64::from(c_size + 1) << (c_size_mult + 2 + read_bl_len);
+                Ok((bytes / BLOCK_LEN as u64) as u32)
+            }
+            // CSD version 2.0
+            1 => {
+                let c_size = (rr[1] >> 8) & 0x3F_FFFF;
+                Ok((c_size + 1) * 1024)
+            }
+            _ => Err(Error::UnsupportedCard),
+        }
+    }
+}
+
+//==============================================================================
+// Sdhc
+//==============================================================================
+
+/// Word-aligned buffer for the SWITCH_FUNC status
+#[repr(C, align(4))]
+struct SwitchStatus([u8; 64]);
+
+/// `Sdhc` encapsulates an SDHC peripheral and its pins
+pub struct Sdhc<I: SdhcInstance, P: SdhcPins<I>> {
+    sdhc: I,
+    pins: P,
+    base_freq: Hertz,
+    cpu_freq: Hertz,
+    card: Option<Card>,
+}
+
+impl<I: SdhcInstance, P: SdhcPins<I>> Sdhc<I, P> {
+    /// Reset the SDHC and power the card at 3.3 V
+    ///
+    /// `cpu_freq` is the frequency of the CPU, which times the delays of the
+    /// card initialization.
+    pub fn new(
+        sdhc: I,
+        pins: P,
+        mclk: &mut MCLK,
+        clock: &I::Clock,
+        cpu_freq: impl Into<Hertz>,
+    ) -> Self {
+        I::enable_ahb_clock(mclk);
+
+        sdhc.srr.write(|w| w.swrstall().set_bit());
+        while sdhc.srr.read().swrstall().bit_is_set() {}
+
+        // Maximum data timeout
+        sdhc.tcr.write(|w| unsafe { w.bits(0xE) });
+        // 3.3 V bus power
+        sdhc.pcr.write(|w| unsafe { w.bits(0b111 << 1 | 1) });
+        // Report all status flags, without signaling interrupts
+        sdhc.nister().write(|w| unsafe { w.bits(0xFFFF) });
+        sdhc.eister().write(|w| unsafe { w.bits(0xFFFF) });
+        // The card detect pin is not used, so report the card as inserted
+        sdhc.hc1r()
+            .write(|w| unsafe { w.bits(HC1R_CARDDSEL | HC1R_CARDDTL | HC1R_ADMA2) });
+
+        let mut sdhc = Self {
+            sdhc,
+            pins,
+            base_freq: I::clock_freq(clock),
+            cpu_freq: cpu_freq.into(),
+            card: None,
+        };
+        sdhc.set_clock(IDENTIFICATION_FREQ);
+        sdhc
+    }
+
+    /// Set the SD clock to the highest frequency not exceeding `freq`
+    fn set_clock(&mut self, freq: u32) {
+        let sdhc = &self.sdhc;
+        sdhc.ccr
+            .modify(|r, w| unsafe { w.bits(r.bits() & !CCR_SDCLKEN) });
+
+        // SDCLK = base / (2 * divider), or base when the divider is 0
+        let base = self.base_freq.0;
+        let divider = if base <= freq {
+            0
+        } else {
+            ((base + 2 * freq - 1) / (2 * freq)).min(0x3FF)
+        } as u16;
+        let bits = (divider & 0xFF) << 8 | (divider >> 8) << 6 | CCR_INTCLKEN;
+        sdhc.ccr.write(|w| unsafe { w.bits(bits) });
+        while sdhc.ccr.read().bits() & CCR_INTCLKS == 0 {}
+        sdhc.ccr.write(|w| unsafe { w.bits(bits | CCR_SDCLKEN) });
+    }
+
+    /// Send a command, and wait for its response and data transfer
+    fn command(
+        &mut self,
+        index: u8,
+        argument: u32,
+        response: Response,
+        data: Option<Data>,
+    ) -> Result<(), Error> {
+        let sdhc = &self.sdhc;
+        let uses_dat = data.is_some() || response == Response::ShortBusy;
+        loop {
+            let psr = sdhc.psr.read();
+            if psr.cmdinhc().bit_is_clear() && !(uses_dat && psr.cmdinhd().bit_is_set()) {
+                break;
+            }
+        }
+
+        sdhc.nistr().write(|w| unsafe { w.bits(0xFFFF) });
+        sdhc.eistr().write(|w| unsafe { w.bits(0xFFFF) });
+
+        let mut cr = response.bits() | u16::from(index) << 8;
+        // The table must stay in place until the transfer completes, as the
+        // ADMA2 engine fetches the descriptors while it runs
+        let mut table = None;
+        if let Some(data) = &data {
+            let table = table.insert(DescriptorTable::new(
+                data.address,
+                usize::from(data.block_len) * usize::from(data.blocks),
+            ));
+            sdhc.asar[0].write(|w| unsafe { w.bits(table.address()) });
+            sdhc.bsr.write(|w| unsafe { w.bits(data.block_len) });
+            sdhc.bcr.write(|w| unsafe { w.bits(data.blocks) });
+            let mut tmr = TMR_DMAEN;
+            if data.blocks > 1 {
+                tmr |= TMR_BCEN | TMR_MSBSEL | TMR_ACMD12;
+            }
+            if data.direction == Direction::Read {
+                tmr |= TMR_DTDSEL;
+            }
+            sdhc.tmr.write(|w| unsafe { w.bits(tmr) });
+            cr |= 1 << 5;
+        } else {
+            sdhc.tmr.write(|w| unsafe { w.bits(0) });
+        }
+
+        sdhc.arg1r.write(|w| unsafe { w.bits(argument) });
+        // Make sure buffers are written before the transfer starts
+        cortex_m::asm::dsb();
+        sdhc.cr.write(|w| unsafe { w.bits(cr) });
+
+        self.wait_for(NISTR_CMDC)?;
+        if table.is_some() || response == Response::ShortBusy {
+            self.wait_for(NISTR_TRFC)?;
+        }
+        // Make sure buffers are read after the transfer ends
+        cortex_m::asm::dmb();
+        Ok(())
+    }
+
+    /// Wait for a normal interrupt status flag, and clear it
+    fn wait_for(&mut self, flag: u16) -> Result<(), Error> {
+        let sdhc = &self.sdhc;
+        loop {
+            let status = sdhc.nistr().read().bits();
+            if status & NISTR_ERRINT != 0 {
+                let errors = sdhc.eistr().read().bits();
+                sdhc.eistr().write(|w| unsafe { w.bits(errors) });
+                // Reset the command and data lines after an error
+                sdhc.srr.write(|w| unsafe { w.bits(0b110) });
+                while sdhc.srr.read().bits() & 0b110 != 0 {}
+                return Err(Error::from_status(errors));
+            }
+            if status & flag != 0 {
+                sdhc.nistr().write(|w| unsafe { w.bits(flag) });
+                return Ok(());
+            }
+        }
+    }
+
+    /// Busy-wait for about 1 ms
+    #[inline]
+    fn delay_ms(&self) {
+        cortex_m::asm::delay(self.cpu_freq.0 / 1000);
+    }
+
+    /// First word of the response
+    #[inline]
+    fn response(&self) -> u32 {
+        self.sdhc.rr[0].read().bits()
+    }
+
+    /// Send an application-specific command
+    fn app_command(&mut self, index: u8, argument: u32, response: Response) -> Result<(), Error> {
+        let rca = self.card.map_or(0, |card| card.rca);
+        self.command(APP_CMD, u32::from(rca) << 16, Response::Short, None)?;
+        self.command(index, argument, response, None)
+    }
+
+    /// Identify and initialize the card, switch it to the widest bus
+    /// supported by the pins, and to high speed mode if it supports it.
+    pub fn init_card(&mut self) -> Result<Card, Error> {
+        self.card = None;
+        self.set_clock(IDENTIFICATION_FREQ);
+        self.sdhc
+            .hc1r()
+            .modify(|r, w| unsafe { w.bits(r.bits() & !(HC1R_DW | HC1R_HSEN)) });
+        // Let the card power up for at least 74 clock cycles
+        self.delay_ms();
+
+        self.command(GO_IDLE_STATE, 0, Response::None, None)?;
+
+        // Cards compliant with version 2.00 echo the check pattern
+        let v2 = match self.command(SEND_IF_COND, 0x1AA, Response::Short, None) {
+            Ok(()) if self.response() & 0xFFF == 0x1AA => true,
+            Ok(()) => return Err(Error::UnsupportedCard),
+            Err(Error::CommandTimeout) => false,
+            Err(error) => return Err(error),
+        };
+
+        let mut argument = OCR_VOLTAGE_WINDOW;
+        if v2 {
+            argument |= OCR_CCS;
+        }
+        let mut retries = OP_COND_RETRIES;
+        let ocr = loop {
+            self.app_command(SD_SEND_OP_COND, argument, Response::ShortNoCrc)?;
+            let ocr = self.response();
+            if ocr & OCR_BUSY != 0 {
+                break ocr;
+            }
+            retries -= 1;
+            if retries == 0 {
+                return Err(Error::InitTimeout);
+            }
+            self.delay_ms();
+        };
+
+        self.command(ALL_SEND_CID, 0, Response::Long, None)?;
+        self.command(SEND_RELATIVE_ADDR, 0, Response::Short, None)?;
+        let rca = (self.response() >> 16) as u16;
+
+        self.command(SEND_CSD, u32::from(rca) << 16, Response::Long, None)?;
+        let rr = [0, 1, 2, 3].map(|index| self.sdhc.rr[index].read().bits());
+        let blocks = Card::blocks_from_csd(rr)?;
+
+        self.command(SELECT_CARD, u32::from(rca) << 16, Response::ShortBusy, None)?;
+
+        let mut card = Card {
+            rca,
+            high_capacity: ocr & OCR_CCS != 0,
+            high_speed: false,
+            blocks,
+        };
+        self.card = Some(card);
+
+        if !card.high_capacity {
+            self.command(SET_BLOCKLEN, BLOCK_LEN as u32, Response::Short, None)?;
+        }
+
+        if P::WIDTH == BusWidth::FourBit {
+            self.app_command(SET_BUS_WIDTH, 0b10, Response::Short)?;
+            self.sdhc
+                .hc1r()
+                .modify(|r, w| unsafe { w.bits(r.bits() | HC1R_DW) });
+        }
+        self.set_clock(DEFAULT_SPEED_FREQ);
+
+        card.high_speed = self.switch_to_high_speed();
+        self.card = Some(card);
+        Ok(card)
+    }
+
+    /// Switch the card to high speed mode, if it supports it
+    ///
+    /// High speed mode is optional: cards which reject CMD6, such as SD 1.0
+    /// cards, stay at the default speed.
+    fn switch_to_high_speed(&mut self) -> bool {
+        let mut status = SwitchStatus([0; 64]);
+        let data = Data {
+            address: status.0.as_mut_ptr() as u32,
+            block_len: 64,
+            blocks: 1,
+            direction: Direction::Read,
+        };
+        // Set function group 1 to high speed, leave the others unchanged
+        if self
+            .command(SWITCH_FUNC, 0x80FF_FFF1, Response::Short, Some(data))
+            .is_err()
+            || status.0[16] & 0xF != 1
+        {
+            return false;
+        }
+        self.sdhc
+            .hc1r()
+            .modify(|r, w| unsafe { w.bits(r.bits() | HC1R_HSEN) });
+        self.set_clock(HIGH_SPEED_FREQ);
+        true
+    }
+
+    /// Return the initialized card, if any
+    #[inline]
+    pub fn card(&self) -> Option<Card> {
+        self.card
+    }
+
+    /// Check a transfer of `len` bytes at `address`, starting at block
+    /// `start`, and return the number of blocks
+    fn check_transfer(&self, start: u32, address: usize, len: usize) -> Result<u32, Error> {
+        let card = self.card.ok_or(Error::NoCard)?;
+        if address % 4 != 0 || len % BLOCK_LEN != 0 {
+            return Err(Error::InvalidBuffer);
+        }
+        let blocks = (len / BLOCK_LEN) as u32;
+        if start
+            .checked_add(blocks)
+            .map_or(true, |end| end > card.blocks)
+        {
+            return Err(Error::OutOfRange);
+        }
+        Ok(blocks)
+    }
+
+    /// Argument addressing block `block` of `card`
+    #[inline]
+    fn block_address(card: &Card, block: u32) -> u32 {
+        if card.high_capacity {
+            block
+        } else {
+            block * BLOCK_LEN as u32
+        }
+    }
+
+    /// Transfer `blocks` blocks between the card and the buffer at `address`
+    fn transfer(
+        &mut self,
+        start: u32,
+        address: u32,
+        blocks: u32,
+        direction: Direction,
+    ) -> Result<(), Error> {
+        let card = self.card.ok_or(Error::NoCard)?;
+        let max_blocks = (adma::MAX_TRANSFER_LEN / BLOCK_LEN).min(u16::MAX as usize) as u32;
+        let mut done = 0;
+        while done < blocks {
+            let count = (blocks - done).min(max_blocks);
+            let index = match (direction, count) {
+                (Direction::Read, 1) => READ_SINGLE_BLOCK,
+                (Direction::Read, _) => READ_MULTIPLE_BLOCK,
+                (Direction::Write, 1) => WRITE_BLOCK,
+                (Direction::Write, _) => WRITE_MULTIPLE_BLOCK,
+            };
+            let data = Data {
+                address: address + done * BLOCK_LEN as u32,
+                block_len: BLOCK_LEN as u16,
+                blocks: count as u16,
+                direction,
+            };
+            let argument = Self::block_address(&card, start + done);
+            self.command(index, argument, Response::Short, Some(data))?;
+            done += count;
+        }
+        Ok(())
+    }
+
+    /// Read blocks starting at block `start` into `buffer`. The buffer must
+    /// be word-aligned, and its length a multiple of [`BLOCK_LEN`].
+    pub fn read_blocks(&mut self, start: u32, buffer: &mut [u8]) -> Result<(), Error> {
+        let address = buffer.as_mut_ptr() as usize;
+        let blocks = self.check_transfer(start, address, buffer.len())?;
+        self.transfer(start, address as u32, blocks, Direction::Read)
+    }
+
+    /// Write `buffer` into blocks starting at block `start`. The buffer must
+    /// be word-aligned, and its length a multiple of [`BLOCK_LEN`].
+    pub fn write_blocks(&mut self, start: u32, buffer: &[u8]) -> Result<(), Error> {
+        let address = buffer.as_ptr() as usize;
+        let blocks = self.check_transfer(start, address, buffer.len())?;
+        self.transfer(start, address as u32, blocks, Direction::Write)
+    }
+
+    /// Power the card off, and return the SDHC and its pins
+    pub fn free(self) -> (I, P) {
+        self.sdhc.srr.write(|w| w.swrstall().set_bit());
+        while self.sdhc.srr.read().swrstall().bit_is_set() {}
+        (self.sdhc, self.pins)
+    }
+}
@@ -0,0 +1,331 @@
+//! # Supply controller
+//!
+//! [`Supc`] drives the SUPC peripheral:
+//!
+//! - The BOD33 monitors VDD against a threshold, and resets the device,
+//!   requests an interrupt or switches to the battery backup when VDD falls
+//!   below it. In standby, it runs either continuously or in sampled mode, to
+//!   save power.
+//! - The main voltage regulator is either a linear regulator (LDO) or a buck
+//!   converter, which is more efficient but needs an external inductor.
+//! - The voltage reference supplies the ADC, DAC and AC, and can be output on
+//!   a pin. It also enables the temperature sensors.
+//! - The battery backup power switch selects when the backup domain is powered
+//!   from VBAT.
+//! - The backup outputs `OUT0` and `OUT1` are driven from the backup domain,
+//!   and keep their level in backup sleep.
+//!
+//! The BOD33 interrupt can be used to save state before the power is lost:
+//!
+//! ```no_run
+//! # use atsamd_hal::supc::{Bod33Action, Bod33Config, Supc};
+//! # fn get_supc() -> atsamd_hal::pac::SUPC { unimplemented!() }
+//! let mut supc = Supc::new(get_supc());
+//! supc.configure_bod33(Bod33Config {
+//!     action: Bod33Action::Interrupt,
+//!     ..Bod33Config::default()
+//! });
+//! supc.enable_bod33_interrupt();
+//! ```
+//!
+//! The BOD33 configuration at reset is stored in the user page, see
+//! [`UserPage`](crate::nvm::user_page::UserPage).
+
+use crate::pac::supc::vref::SEL_A;
+use crate::pac::SUPC;
+
+pub use crate::nvm::user_page::Bod33Action;
+
+/// Operating mode of the BOD33 in standby
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bod33Mode {
+    /// VDD is monitored continuously
+    Continuous,
+    /// VDD is sampled from the 1 kHz output of the OSCULP32K, every cycle
+    /// when `prescaler` is 0, and every 2^(`prescaler` + 1) cycles otherwise,
+    /// up to 256 cycles when `prescaler` is 7
+    Sampled { prescaler: u8 },
+}
+
+/// BOD33 configuration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bod33Config {
+    /// Threshold level for VDD. See the electrical characteristics of the
+    /// datasheet for the matching voltages.
+    pub level: u8,
+    /// Threshold level for VBAT in battery backup sleep
+    pub vbat_level: u8,
+    /// Hysteresis, 4 bits
+    pub hysteresis: u8,
+    /// Action when VDD falls below the threshold
+    pub action: Bod33Action,
+    /// Operation in standby
+    pub standby_mode: Bod33Mode,
+    /// Keep the BOD33 running in standby
+    pub run_in_standby: bool,
+    /// Keep the BOD33 running in hibernate
+    pub run_in_hibernate: bool,
+    /// Keep the BOD33 running in backup sleep
+    pub run_in_backup: bool,
+}
+
+impl Default for Bod33Config {
+    /// Reset at the factory default level, monitoring VDD continuously
+    fn default() -> Self {
+        Self {
+            level: 0x1c,
+            vbat_level: 0x1c,
+            hysteresis: 2,
+            action: Bod33Action::Reset,
+            standby_mode: Bod33Mode::Continuous,
+            run_in_standby: false,
+            run_in_hibernate: false,
+            run_in_backup: false,
+        }
+    }
+}
+
+/// Main voltage regulator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Regulator {
+    /// Linear regulator
+    Ldo,
+    /// Buck converter
+    Buck,
+}
+
+/// Voltage reference level
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VrefLevel {
+    V1_0,
+    V1_1,
+    V1_2,
+    V1_25,
+    V2_0,
+    V2_2,
+    V2_4,
+    V2_5,
+}
+
+/// Condition for powering the backup domain from VBAT
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatterySwitch {
+    /// Switch to VBAT when the BOD33 detects that VDD is too low
+    Bod33,
+    /// Always power the backup domain from VBAT
+    Forced,
+}
+
+/// Backup output pins
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupOutput {
+    Out0,
+    Out1,
+}
+
+/// Supply controller
+pub struct Supc {
+    supc: SUPC,
+}
+
+impl Supc {
+    /// Create a new [`Supc`]
+    pub fn new(supc: SUPC) -> Self {
+        Self { supc }
+    }
+
+    /// Release the `SUPC` peripheral
+    pub fn free(self) -> SUPC {
+        self.supc
+    }
+
+    /// Configure and enable the BOD33
+    ///
+    /// The BOD33 is disabled while it is reconfigured, and this returns once
+    /// it is ready again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the hysteresis or the prescaler is out of range.
+    pub fn configure_bod33(&mut self, config: Bod33Config) {
+        assert!(config.hysteresis < 16, "Invalid BOD33 hysteresis");
+        self.disable_bod33();
+
+        let (sampled, prescaler) = match config.standby_mode {
+            Bod33Mode::Continuous => (false, 0),
+            Bod33Mode::Sampled { prescaler } => {
+                assert!(prescaler < 8, "Invalid BOD33 prescaler");
+                (true, prescaler)
+            }
+        };
+        self.supc.bod33.write(|w| {
+            match config.action {
+                Bod33Action::None => w.action().none(),
+                Bod33Action::Reset => w.action().reset(),
+                Bod33Action::Interrupt => w.action().int(),
+                Bod33Action::Backup => w.action().bkup(),
+            };
+            unsafe {
+                w.level().bits(config.level);
+                w.vbatlevel().bits(config.vbat_level);
+                w.hyst().bits(config.hysteresis);
+            }
+            w.psel().bits(prescaler);
+            w.stdbycfg().bit(sampled);
+            w.runstdby().bit(config.run_in_standby);
+            w.runhib().bit(config.run_in_hibernate);
+            w.runbkup().bit(config.run_in_backup)
+        });
+        self.supc.bod33.modify(|_, w| w.enable().set_bit());
+        while self.supc.status.read().b33srdy().bit_is_clear() {}
+        while self.supc.status.read().bod33rdy().bit_is_clear() {}
+    }
+
+    /// Disable the BOD33
+    pub fn disable_bod33(&mut self) {
+        self.supc.bod33.modify(|_, w| w.enable().clear_bit());
+        while self.supc.status.read().b33srdy().bit_is_clear() {}
+    }
+
+    /// Check if VDD is currently below the BOD33 threshold
+    #[inline]
+    pub fn is_bod33_detected(&self) -> bool {
+        self.supc.status.read().bod33det().bit_is_set()
+    }
+
+    /// Enable the BOD33 detection interrupt
+    #[inline]
+    pub fn enable_bod33_interrupt(&mut self) {
+        self.supc.intenset.write(|w| w.bod33det().set_bit());
+    }
+
+    /// Disable the BOD33 detection interrupt
+    #[inline]
+    pub fn disable_bod33_interrupt(&mut self) {
+        self.supc.intenclr.write(|w| w.bod33det().set_bit());
+    }
+
+    /// Check if the BOD33 detection interrupt flag is set
+    #[inline]
+    pub fn is_bod33_interrupt(&self) -> bool {
+        self.supc.intflag.read().bod33det().bit_is_set()
+    }
+
+    /// Clear the BOD33 detection interrupt flag
+    #[inline]
+    pub fn clear_bod33_interrupt(&mut self) {
+        self.supc.intflag.write(|w| w.bod33det().set_bit());
+    }
+
+    /// Select the main voltage regulator
+    ///
+    /// The buck converter requires an inductor on the VSW pin.
+    pub fn set_regulator(&mut self, regulator: Regulator) {
+        self.supc.vreg.modify(|_, w| match regulator {
+            Regulator::Ldo => w.sel().ldo(),
+            Regulator::Buck => w.sel().buck(),
+        });
+        while self.supc.status.read().vregrdy().bit_is_clear() {}
+    }
+
+    /// Main voltage regulator in use
+    #[inline]
+    pub fn regulator(&self) -> Regulator {
+        if self.supc.vreg.read().sel().is_buck() {
+            Regulator::Buck
+        } else {
+            Regulator::Ldo
+        }
+    }
+
+    /// Select the voltage reference level
+    #[inline]
+    pub fn set_vref_level(&mut self, level: VrefLevel) {
+        let variant = match level {
+            VrefLevel::V1_0 => SEL_A::_1V0,
+            VrefLevel::V1_1 => SEL_A::_1V1,
+            VrefLevel::V1_2 => SEL_A::_1V2,
+            VrefLevel::V1_25 => SEL_A::_1V25,
+            VrefLevel::V2_0 => SEL_A::_2V0,
+            VrefLevel::V2_2 => SEL_A::_2V2,
+            VrefLevel::V2_4 => SEL_A::_2V4,
+            VrefLevel::V2_5 => SEL_A::_2V5,
+        };
+        self.supc.vref.modify(|_, w| w.sel().variant(variant));
+    }
+
+    /// Output the voltage reference on the VREFA pin
+    #[inline]
+    pub fn set_vref_output(&mut self, enable: bool) {
+        self.supc.vref.modify(|_, w| w.vrefoe().bit(enable));
+    }
+
+    /// Keep the voltage reference running in standby
+    ///
+    /// With `on_demand`, the reference only runs while a peripheral requests
+    /// it.
+    #[inline]
+    pub fn set_vref_standby(&mut self, run_in_standby: bool, on_demand: bool) {
+        self.supc
+            .vref
+            .modify(|_, w| w.runstdby().bit(run_in_standby).ondemand().bit(on_demand));
+    }
+
+    /// Enable the temperature sensors
+    ///
+    /// The two sensors, PTAT and CTAT, are measured through the ADC.
+    #[inline]
+    pub fn set_temperature_sensor(&mut self, enable: bool) {
+        self.supc.vref.modify(|_, w| w.tsen().bit(enable));
+    }
+
+    /// Select when the backup domain is powered from VBAT
+    ///
+    /// With `wake`, switching back to VDD wakes the device from backup sleep.
+    #[inline]
+    pub fn set_battery_switch(&mut self, switch: BatterySwitch, wake: bool) {
+        self.supc.bbps.write(|w| {
+            match switch {
+                BatterySwitch::Bod33 => w.conf().bod33(),
+                BatterySwitch::Forced => w.conf().forced(),
+            };
+            w.wakeen().bit(wake)
+        });
+    }
+
+    /// Enable the backup `output`, driven to `level`
+    pub fn enable_backup_output(&mut self, output: BackupOutput, level: bool) {
+        self.set_backup_output(output, level);
+        self.supc.bkout.modify(|_, w| match output {
+            BackupOutput::Out0 => w.enout0().set_bit(),
+            BackupOutput::Out1 => w.enout1().set_bit(),
+        });
+    }
+
+    /// Disable the backup `output`, releasing the pin
+    pub fn disable_backup_output(&mut self, output: BackupOutput) {
+        self.supc.bkout.modify(|_, w| match output {
+            BackupOutput::Out0 => w.enout0().clear_bit().rtctglout0().clear_bit(),
+            BackupOutput::Out1 => w.enout1().clear_bit().rtctglout1().clear_bit(),
+        });
+    }
+
+    /// Drive the backup `output` to `level`
+    pub fn set_backup_output(&mut self, output: BackupOutput, level: bool) {
+        // SETOUTx and CLROUTx are strobes, read as zero
+        self.supc.bkout.modify(|_, w| match (output, level) {
+            (BackupOutput::Out0, true) => w.setout0().set_bit(),
+            (BackupOutput::Out0, false) => w.clrout0().set_bit(),
+            (BackupOutput::Out1, true) => w.setout1().set_bit(),
+            (BackupOutput::Out1, false) => w.clrout1().set_bit(),
+        });
+    }
+
+    /// Toggle the backup `output` on each RTC event
+    pub fn set_backup_output_rtc_toggle(&mut self, output: BackupOutput, enable: bool) {
+        self.supc.bkout.modify(|_, w| match output {
+            BackupOutput::Out0 => w.rtctglout0().bit(enable),
+            BackupOutput::Out1 => w.rtctglout1().bit(enable),
+        });
+    }
+}
@@ -1,8 +1,8 @@
 //! Working with timer counter hardware
 use crate::ehal::timer::{CountDown, Periodic};
-use crate::pac::tc0::COUNT16;
+use crate::pac::tc0::{COUNT16, COUNT32};
 #[allow(unused)]
-use crate::pac::{MCLK, TC2, TC3};
+use crate::pac::{MCLK, TC0, TC1, TC2, TC3};
 use crate::timer_params::TimerParams;
 // Only the G variants are missing these timers
 #[cfg(feature = "min-samd51j")]
@@ -10,19 +10,23 @@ use crate::pac::{TC4, TC5};
 use crate::timer_traits::InterruptDrivenTimer;
 
 use crate::clock;
-use crate::time::{Hertz, Nanoseconds};
+use crate::time::{Hertz, Microseconds, Nanoseconds};
 use void::Void;
 
+#[cfg(feature = "unproven")]
+pub mod capture;
+
 // Note:
-// TC3 + TC4 can be paired to make a 32-bit counter
-// TC5 + TC6 can be paired to make a 32-bit counter
+// TC0 + TC1 can be paired to make a 32-bit counter
+// TC2 + TC3 can be paired to make a 32-bit counter
+// TC4 + TC5 can be paired to make a 32-bit counter
 
 /// A generic hardware timer counter.
-/// The counters are exposed in 16-bit mode only.
-/// The hardware allows configuring the 8-bit mode
-/// and pairing up some instances to run in 32-bit
-/// mode, but that functionality is not currently
-/// exposed by this hal implementation.
+/// The counters are exposed in 16-bit mode, and pairs of
+/// instances in 32-bit mode through `TimerCounter32`.
+/// The hardware also allows configuring the 8-bit mode,
+/// but that functionality is not currently exposed by
+/// this hal implementation.
 /// TimerCounter implements both the `Periodic` and
 /// the `CountDown` embedded_hal timer traits.
 /// Before a hardware timer can be used, it must first
@@ -39,6 +43,55 @@ pub trait Count16 {
     fn count_16(&self) -> &COUNT16;
 }
 
+/// A pair of hardware timer counters, chained to make a 32-bit counter.
+/// The even-numbered instance is the master and holds the configuration
+/// of the counter, while the odd-numbered instance only provides the upper
+/// 16 bits, and cannot be used on its own until the pair is released.
+/// TimerCounter32 implements both the `Periodic` and the `CountDown`
+/// embedded_hal timer traits. Its timeouts are given in microseconds, and
+/// the prescaler is only used when the timeout doesn't fit in 32 bits at the
+/// full frequency of the clock.
+pub struct TimerCounter32<TC, TCS> {
+    freq: Hertz,
+    tc: TC,
+    slave: TCS,
+}
+
+/// This is the 32-bit counterpart of the [`Count16`] helper trait. It is
+/// only implemented for the master instance of each pair.
+pub trait Count32 {
+    fn count_32(&self) -> &COUNT32;
+}
+
+/// Counting direction of a timer counter
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CountDirection {
+    /// Count up from zero to the top value
+    Up,
+    /// Count down from the top value to zero
+    Down,
+}
+
+/// Waveform generation operation of a timer counter
+///
+/// The waveforms are output on the `WO[0]` and `WO[1]` pins of the timer,
+/// once they are configured in the matching alternate function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaveformMode {
+    /// Normal frequency: the counter wraps at its maximum value, and each
+    /// output toggles when the counter matches its compare value
+    NormalFrequency,
+    /// Match frequency: the counter wraps at the compare value of channel 0,
+    /// and `WO[0]` toggles at each wrap
+    MatchFrequency,
+    /// Normal PWM: the counter wraps at its maximum value, and the duty cycle
+    /// of each output is set by its compare value
+    NormalPwm,
+    /// Match PWM: the counter wraps at the compare value of channel 0, and
+    /// the duty cycle of `WO[1]` is set by the compare value of channel 1
+    MatchPwm,
+}
+
 impl<TC> Periodic for TimerCounter<TC> {}
 impl<TC> CountDown for TimerCounter<TC>
 where
@@ -131,6 +184,209 @@ where
     }
 }
 
+impl<TC, TCS> Periodic for TimerCounter32<TC, TCS> {}
+impl<TC, TCS> CountDown for TimerCounter32<TC, TCS>
+where
+    TC: Count32,
+{
+    type Time = Microseconds;
+
+    fn start<T>(&mut self, timeout: T)
+    where
+        T: Into<Self::Time>,
+    {
+        let params = TimerParams::new_us_32(timeout, self.freq.0);
+        let divider = params.divider;
+        let cycles = params.cycles;
+        let count = self.tc.count_32();
+
+        // Disable the timer while we reconfigure it
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        while count.syncbusy.read().enable().bit_is_set() {}
+
+        count.ctrla.write(|w| w.swrst().set_bit());
+        while count.syncbusy.read().swrst().bit_is_set() {}
+
+        count.ctrlbset.write(|w| {
+            // Count up when the direction bit is zero
+            w.dir().clear_bit();
+            // Periodic
+            w.oneshot().clear_bit()
+        });
+
+        // Set TOP value for mfrq mode
+        count.cc[0].write(|w| unsafe { w.cc().bits(cycles) });
+
+        // Enable Match Frequency Waveform generation
+        count.wave.modify(|_, w| w.wavegen().mfrq());
+
+        count.ctrla.modify(|_, w| {
+            w.mode().count32();
+            match divider {
+                1 => w.prescaler().div1(),
+                2 => w.prescaler().div2(),
+                4 => w.prescaler().div4(),
+                8 => w.prescaler().div8(),
+                16 => w.prescaler().div16(),
+                64 => w.prescaler().div64(),
+                256 => w.prescaler().div256(),
+                1024 => w.prescaler().div1024(),
+                _ => unreachable!(),
+            };
+            w.enable().set_bit();
+            w.runstdby().set_bit()
+        });
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Void> {
+        let count = self.tc.count_32();
+        if count.intflag.read().ovf().bit_is_set() {
+            // Writing a 1 clears the flag
+            count.intflag.modify(|_, w| w.ovf().set_bit());
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+/// `TimerCounter32` doesn't implement [`InterruptDrivenTimer`], whose
+/// timeouts are limited to the `Nanoseconds` range, so its interrupt is
+/// controlled with these inherent methods instead.
+impl<TC, TCS> TimerCounter32<TC, TCS>
+where
+    TC: Count32,
+{
+    /// Enable the interrupt generation for this hardware timer.
+    /// This method only sets the clock configuration to trigger
+    /// the interrupt; it does not configure the interrupt controller
+    /// or define an interrupt handler.
+    pub fn enable_interrupt(&mut self) {
+        self.tc.count_32().intenset.write(|w| w.ovf().set_bit());
+    }
+
+    /// Disables interrupt generation for this hardware timer.
+    /// This method only sets the clock configuration to prevent
+    /// triggering the interrupt; it does not configure the interrupt
+    /// controller.
+    pub fn disable_interrupt(&mut self) {
+        self.tc.count_32().intenclr.write(|w| w.ovf().set_bit());
+    }
+}
+
+impl<TC, TCS> TimerCounter32<TC, TCS> {
+    /// Release the master and slave timer counter instances
+    pub fn free(self) -> (TC, TCS) {
+        (self.tc, self.slave)
+    }
+}
+
+/// Implements the methods shared by the 16-bit and 32-bit timer counters.
+///
+/// Note that starting the timer resets the configuration changed by these
+/// methods, so they should be called after [`CountDown::start`].
+macro_rules! timer_control {
+    (impl<$($T:ident),+> $Timer:ty, $Count:ident::$count:ident, $Beat:ty) => {
+impl<$($T),+> $Timer
+where
+    TC: $Count,
+{
+    /// Enable the overflow event output, so that the timer can be used as
+    /// an event generator through the [`evsys`](crate::evsys) module.
+    ///
+    /// Note that starting the timer resets the event configuration, so this
+    /// method should be called after [`CountDown::start`].
+    pub fn enable_overflow_event(&mut self) {
+        self.tc.$count().evctrl.modify(|_, w| w.ovfeo().set_bit());
+    }
+
+    /// Disable the overflow event output
+    pub fn disable_overflow_event(&mut self) {
+        self.tc.$count().evctrl.modify(|_, w| w.ovfeo().clear_bit());
+    }
+
+    /// Select one-shot operation, where the counter stops at the next
+    /// overflow or underflow until it is retriggered, instead of wrapping
+    pub fn set_one_shot(&mut self, one_shot: bool) {
+        let count = self.tc.$count();
+        if one_shot {
+            count.ctrlbset.write(|w| w.oneshot().set_bit());
+        } else {
+            count.ctrlbclr.write(|w| w.oneshot().set_bit());
+        }
+        while count.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Select the counting direction
+    pub fn set_direction(&mut self, direction: CountDirection) {
+        let count = self.tc.$count();
+        match direction {
+            CountDirection::Up => count.ctrlbclr.write(|w| w.dir().set_bit()),
+            CountDirection::Down => count.ctrlbset.write(|w| w.dir().set_bit()),
+        }
+        while count.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Restart the counter from zero, or from the top value when counting
+    /// down. This also restarts a stopped counter.
+    pub fn retrigger(&mut self) {
+        let count = self.tc.$count();
+        count.ctrlbset.write(|w| w.cmd().retrigger());
+        while count.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Stop the counter, until it is retriggered
+    pub fn stop(&mut self) {
+        let count = self.tc.$count();
+        count.ctrlbset.write(|w| w.cmd().stop());
+        while count.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Check if the counter is stopped, either by [`stop`](Self::stop) or
+    /// at the end of a one-shot period
+    #[inline]
+    pub fn is_stopped(&self) -> bool {
+        self.tc.$count().status.read().stop().bit_is_set()
+    }
+
+    /// Select the waveform generation operation
+    ///
+    /// [`CountDown::start`] selects [`WaveformMode::MatchFrequency`], with the
+    /// timeout as the compare value of channel 0.
+    pub fn set_waveform(&mut self, mode: WaveformMode) {
+        let count = self.tc.$count();
+        let enabled = count.ctrla.read().enable().bit_is_set();
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        while count.syncbusy.read().enable().bit_is_set() {}
+
+        count.wave.write(|w| match mode {
+            WaveformMode::NormalFrequency => w.wavegen().nfrq(),
+            WaveformMode::MatchFrequency => w.wavegen().mfrq(),
+            WaveformMode::NormalPwm => w.wavegen().npwm(),
+            WaveformMode::MatchPwm => w.wavegen().mpwm(),
+        });
+
+        count.ctrla.modify(|_, w| w.enable().bit(enabled));
+        while count.syncbusy.read().enable().bit_is_set() {}
+    }
+
+    /// Set the compare value of channel `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not 0 or 1.
+    #[inline]
+    pub fn set_compare(&mut self, index: usize, value: $Beat) {
+        let count = self.tc.$count();
+        count.cc[index].write(|w| unsafe { w.cc().bits(value) });
+    }
+}
+    };
+}
+
+timer_control!(impl<TC> TimerCounter<TC>, Count16::count_16, u16);
+timer_control!(impl<TC, TCS> TimerCounter32<TC, TCS>, Count32::count_32, u32);
+
 macro_rules! tc {
     ($($TYPE:ident: ($TC:ident, $mclk:ident, $clock:ident, $apmask:ident),)+) => {
         $(
@@ -181,3 +437,53 @@ tc! {
     TimerCounter4: (TC4, tc4_, Tc4Tc5Clock, apbcmask),
     TimerCounter5: (TC5, tc5_, Tc4Tc5Clock, apbcmask),
 }
+
+macro_rules! tc32 {
+    ($($TYPE:ident: ($TC:ident, $TCS:ident, $ctor:ident, $mclk:ident, $mclks:ident, $clock:ident, $apmask:ident),)+) => {
+        $(
+pub type $TYPE = TimerCounter32<$TC, $TCS>;
+
+impl Count32 for $TC {
+    fn count_32(&self) -> &COUNT32 {
+        self.count32()
+    }
+}
+
+impl TimerCounter32<$TC, $TCS>
+{
+    /// Configure this pair of timer counter instances.
+    /// The clock is obtained from the `GenericClockController` instance,
+    /// and is shared by both instances of the pair.
+    /// Its frequency impacts the resolution and maximum range of
+    /// the timeout values that can be passed to the `start` method.
+    pub fn $ctor(clock: &clock::$clock, tc: $TC, slave: $TCS, mclk: &mut MCLK) -> Self {
+        // this is safe because we're constrained to just the bits of the pair
+        mclk.$apmask.modify(|_, w| w.$mclk().set_bit().$mclks().set_bit());
+        {
+            let count = tc.count_32();
+
+            // Disable the timer while we reconfigure it
+            count.ctrla.modify(|_, w| w.enable().clear_bit());
+            while count.syncbusy.read().enable().bit_is_set() {}
+        }
+        Self {
+            freq: clock.freq(),
+            tc,
+            slave,
+        }
+    }
+}
+        )+
+    }
+}
+
+tc32! {
+    TimerCounter0_1: (TC0, TC1, tc0_tc1_, tc0_, tc1_, Tc0Tc1Clock, apbamask),
+    TimerCounter2_3: (TC2, TC3, tc2_tc3_, tc2_, tc3_, Tc2Tc3Clock, apbbmask),
+}
+
+// Only the G variants are missing these timers
+#[cfg(feature = "min-samd51j")]
+tc32! {
+    TimerCounter4_5: (TC4, TC5, tc4_tc5_, tc4_, tc5_, Tc4Tc5Clock, apbcmask),
+}
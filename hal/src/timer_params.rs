@@ -1,5 +1,5 @@
 //! helper struct to calculate divider & cycles settings for timers.
-use crate::time::{Hertz, Nanoseconds};
+use crate::time::{Hertz, Microseconds, Nanoseconds};
 
 /// Helper type for computing cycles and divider given frequency
 #[derive(Debug, Clone, Copy)]
@@ -29,6 +29,24 @@ impl TimerParams {
         Self::new_from_ticks(ticks)
     }
 
+    /// calculates TimerParams from a given period based timeout, for a 32-bit
+    /// counter. The smallest divider whose cycles fit in 32 bits is selected.
+    pub fn new_us_32<T>(timeout: T, src_freq: u32) -> Self
+    where
+        T: Into<Microseconds>,
+    {
+        let ticks = timeout.into().0 as u64 * src_freq as u64 / 1_000_000;
+        let divider = [1, 2, 4, 8, 16, 64, 256, 1024]
+            .into_iter()
+            .find(|&divider| ticks / divider <= u32::MAX as u64)
+            .unwrap_or(1024);
+
+        TimerParams {
+            divider: divider as u16,
+            cycles: (ticks / divider).min(u32::MAX as u64) as u32,
+        }
+    }
+
     fn new_from_ticks(ticks: u32) -> Self {
         let divider = ((ticks >> 16) + 1).next_power_of_two();
         let divider = match divider {
@@ -80,4 +98,18 @@ mod tests {
         assert_eq!(tp_from_hz.divider, tp_from_us.divider);
         assert!((tp_from_hz.cycles as i32 - tp_from_us.cycles as i32).abs() <= 1);
     }
+
+    #[test]
+    fn timer_params_32_bit_prescaler() {
+        let tp = TimerParams::new_us_32(1_u32.s(), 48_000_000_u32);
+        assert_eq!((tp.divider, tp.cycles), (1, 48_000_000));
+
+        // 100 s is 4.8e9 cycles at 48 MHz, which needs a prescaler
+        let tp = TimerParams::new_us_32(100_u32.s(), 48_000_000_u32);
+        assert_eq!((tp.divider, tp.cycles), (2, 2_400_000_000));
+
+        // The longest timeout still fits at 120 MHz
+        let tp = TimerParams::new_us_32(u32::MAX.us(), 120_000_000_u32);
+        assert_eq!(tp.divider, 256);
+    }
 }
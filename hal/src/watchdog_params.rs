@@ -0,0 +1,74 @@
+//! Helper functions to convert times into watchdog period settings.
+use crate::time::{Hertz, Milliseconds};
+
+/// Number of period settings, from 8 to 16384 cycles of the watchdog clock
+const SETTINGS: u8 = 12;
+
+/// Errors of the time-based watchdog API
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Error {
+    /// The watchdog is locked in always-on mode and can't be reconfigured
+    AlwaysOn,
+    /// The watchdog must be disabled for this operation
+    Enabled,
+    /// The requested time is out of the range of the watchdog
+    InvalidTime,
+}
+
+/// Smallest period setting at least `cycles` long
+fn round_up(cycles: u64) -> Result<u8, Error> {
+    (0..SETTINGS)
+        .find(|setting| 8u64 << setting >= cycles)
+        .ok_or(Error::InvalidTime)
+}
+
+/// Convert a time into the smallest period setting at least as long, for a
+/// watchdog clocked at `freq`
+pub(crate) fn timeout_setting(time: Milliseconds, freq: Hertz) -> Result<u8, Error> {
+    round_up((time.0 as u64 * freq.0 as u64 + 999) / 1000)
+}
+
+/// Convert the `closed` and `open` windows into the `WINDOW` and `PER`
+/// settings, for a watchdog clocked at `freq`
+///
+/// The closed window is rounded down, so that a feed after `closed` never
+/// lands inside it. The open window is then rounded up, so that it ends no
+/// earlier than `closed + open`.
+pub(crate) fn window_settings(
+    closed: Milliseconds,
+    open: Milliseconds,
+    freq: Hertz,
+) -> Result<(u8, u8), Error> {
+    let closed_cycles = closed.0 as u64 * freq.0 as u64 / 1000;
+    let window = (0..SETTINGS)
+        .rev()
+        .find(|setting| 8u64 << setting <= closed_cycles)
+        .ok_or(Error::InvalidTime)?;
+    let end_cycles = ((closed.0 as u64 + open.0 as u64) * freq.0 as u64 + 999) / 1000;
+    let per = round_up(end_cycles - (8u64 << window))?;
+    Ok((window, per))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_rounded_to_safe_periods() {
+        let freq = Hertz(1024);
+        let timeout = |ms| timeout_setting(Milliseconds(ms), freq);
+        assert_eq!(timeout(0), Ok(0));
+        assert_eq!(timeout(1000), Ok(7));
+        assert_eq!(timeout(1001), Ok(8));
+        assert_eq!(timeout(16000), Ok(11));
+        assert_eq!(timeout(16001), Err(Error::InvalidTime));
+
+        let window = |closed, open| window_settings(Milliseconds(closed), Milliseconds(open), freq);
+        assert_eq!(window(1000, 1000), Ok((7, 7)));
+        // The closed window is rounded down to 1000 ms, and the open window
+        // extended to end after 2500 ms
+        assert_eq!(window(1500, 1000), Ok((7, 8)));
+        assert_eq!(window(7, 1000), Err(Error::InvalidTime));
+        assert_eq!(window(16000, 16001), Err(Error::InvalidTime));
+    }
+}